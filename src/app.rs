@@ -1,11 +1,12 @@
 use std::sync::Arc;
 use winit::{
     event_loop::EventLoop,
-    window::Window,
+    window::{CursorGrabMode, Window},
 };
 
 use crate::render::WgpuRenderer;
-use crate::input::InputState;
+use crate::input::{InputState, KeyBindings};
+use crate::input::bindings::BINDINGS_PATH;
 use crate::console::Console;
 use crate::audio::events::AudioEventQueue;
 
@@ -15,6 +16,7 @@ pub struct App {
     pub input: InputState,
     pub console: Console,
     pub audio_events: AudioEventQueue,
+    cursor_grabbed: bool,
 }
 
 impl App {
@@ -28,8 +30,9 @@ impl App {
                 .map_err(|e| format!("Failed to create window: {:?}", e))?
         );
 
-        let renderer = WgpuRenderer::new(window.clone()).await?;
-        let input = InputState::new();
+        let renderer = WgpuRenderer::new(window.clone(), 1).await?;
+        let mut input = InputState::new();
+        input.bindings = KeyBindings::load_or_default(BINDINGS_PATH);
         let console = Console::new();
         let audio_events = AudioEventQueue::new();
 
@@ -39,9 +42,38 @@ impl App {
             input,
             console,
             audio_events,
+            cursor_grabbed: false,
         })
     }
 
+    pub fn cursor_grabbed(&self) -> bool {
+        self.cursor_grabbed
+    }
+
+    pub fn set_cursor_grab(&mut self, grabbed: bool) {
+        if grabbed {
+            // Locked isn't supported on every platform; Confined still keeps the pointer in the window
+            let result = self.window.set_cursor_grab(CursorGrabMode::Locked)
+                .or_else(|_| self.window.set_cursor_grab(CursorGrabMode::Confined));
+            if let Err(e) = result {
+                eprintln!("Failed to grab cursor: {:?}", e);
+                return;
+            }
+        } else {
+            let _ = self.window.set_cursor_grab(CursorGrabMode::None);
+        }
+        self.window.set_cursor_visible(!grabbed);
+        self.cursor_grabbed = grabbed;
+    }
+
+    pub fn handle_device_event(&mut self, event: &winit::event::DeviceEvent) {
+        if let winit::event::DeviceEvent::MouseMotion { delta: (dx, dy) } = event {
+            if self.cursor_grabbed {
+                self.input.handle_mouse_motion(*dx, *dy);
+            }
+        }
+    }
+
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
         self.renderer.resize(new_size);
     }
@@ -53,7 +85,11 @@ impl App {
         match event {
             WindowEvent::KeyboardInput { event, .. } => {
                 if let PhysicalKey::Code(keycode) = event.physical_key {
-                    if event.state.is_pressed() {
+                    if keycode == winit::keyboard::KeyCode::Escape {
+                        if event.state.is_pressed() && !event.repeat {
+                            self.set_cursor_grab(!self.cursor_grabbed);
+                        }
+                    } else if event.state.is_pressed() {
                         self.input.handle_key_press(keycode);
                     } else {
                         self.input.handle_key_release(keycode);
@@ -4,6 +4,7 @@ use crate::game::weapon::Weapon;
 #[derive(Clone, Debug)]
 pub enum AudioEvent {
     WeaponFire {
+        player_id: u32,
         weapon: Weapon,
         x: f32,
         has_quad: bool,
@@ -1,81 +1,696 @@
 pub mod events;
+pub mod synth;
 
 use events::AudioEvent;
+use crate::game::events::GameEvent;
 use kira::{
     manager::{AudioManager, AudioManagerSettings, backend::DefaultBackend},
-    sound::static_sound::{StaticSoundData, StaticSoundSettings},
+    sound::static_sound::{StaticSoundData, StaticSoundHandle, StaticSoundSettings},
+    sound::streaming::{StreamingSoundData, StreamingSoundHandle, StreamingSoundSettings},
+    sound::FromFileError,
+    effect::reverb::{ReverbBuilder, ReverbHandle},
+    track::{TrackBuilder, TrackHandle},
+    tween::Tween,
     Volume,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{mpsc::channel, Mutex};
+use std::time::{Duration, Instant};
+use crate::game::weapon::Weapon;
+use crate::resource_path::resolve;
+
+pub type SoundHandle = StaticSoundHandle;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SoundCategory {
+    Weapons,
+    Player,
+    Items,
+    Feedback,
+    Announcer,
+    Music,
+}
+
+impl SoundCategory {
+    pub const ALL: [SoundCategory; 6] = [
+        SoundCategory::Weapons,
+        SoundCategory::Player,
+        SoundCategory::Items,
+        SoundCategory::Feedback,
+        SoundCategory::Announcer,
+        SoundCategory::Music,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SoundCategory::Weapons => "weapons",
+            SoundCategory::Player => "player",
+            SoundCategory::Items => "items",
+            SoundCategory::Feedback => "feedback",
+            SoundCategory::Announcer => "announcer",
+            SoundCategory::Music => "music",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|c| c.name() == name)
+    }
+
+    pub fn of_event(event: &AudioEvent) -> Self {
+        match event {
+            AudioEvent::WeaponFire { .. } | AudioEvent::WeaponSwitch | AudioEvent::Explosion { .. } => SoundCategory::Weapons,
+            AudioEvent::PlayerPain { .. }
+            | AudioEvent::PlayerDeath { .. }
+            | AudioEvent::PlayerGib { .. }
+            | AudioEvent::PlayerJump { .. }
+            | AudioEvent::PlayerLand { .. } => SoundCategory::Player,
+            AudioEvent::ItemPickup { .. }
+            | AudioEvent::ArmorPickup { .. }
+            | AudioEvent::WeaponPickup { .. }
+            | AudioEvent::PowerupPickup { .. }
+            | AudioEvent::QuadDamage => SoundCategory::Items,
+            AudioEvent::PlayerHit { .. } => SoundCategory::Feedback,
+            AudioEvent::Award { .. } => SoundCategory::Announcer,
+        }
+    }
+}
+
+// Settings-level volume groups; `effects` covers weapon, player and item sounds. Applying one
+// writes each group into the per-category table the mix reads, so finer tweaks still work after
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VolumeConfig {
+    pub master: f32,
+    pub effects: f32,
+    pub feedback: f32,
+    pub announcer: f32,
+}
+
+impl Default for VolumeConfig {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            effects: 1.0,
+            feedback: 1.0,
+            announcer: 1.0,
+        }
+    }
+}
+
+impl VolumeConfig {
+    // None for music, which has no group here
+    pub fn category(&self, category: SoundCategory) -> Option<f32> {
+        match category {
+            SoundCategory::Weapons | SoundCategory::Player | SoundCategory::Items => Some(self.effects),
+            SoundCategory::Feedback => Some(self.feedback),
+            SoundCategory::Announcer => Some(self.announcer),
+            SoundCategory::Music => None,
+        }
+    }
+
+    fn set_category(&mut self, category: SoundCategory, volume: f32) {
+        match category {
+            SoundCategory::Weapons | SoundCategory::Player | SoundCategory::Items => self.effects = volume,
+            SoundCategory::Feedback => self.feedback = volume,
+            SoundCategory::Announcer => self.announcer = volume,
+            SoundCategory::Music => {}
+        }
+    }
+}
+
+// Name, q3-resources relative path and the category each sound is mixed under
+const SOUND_FILES: &[(&str, &str, SoundCategory)] = &[
+    ("mg_fire", "q3-resources/sound/weapons/machinegun/machgf1b.wav", SoundCategory::Weapons),
+    ("shotgun_fire", "q3-resources/sound/weapons/shotgun/sshotf1b.wav", SoundCategory::Weapons),
+    ("rocket_fire", "q3-resources/sound/weapons/rocket/rocklf1a.wav", SoundCategory::Weapons),
+    ("rocket_explode", "q3-resources/sound/weapons/rocket/rocklx1a.wav", SoundCategory::Weapons),
+    ("grenade_fire", "q3-resources/sound/weapons/grenade/grenlf1a.wav", SoundCategory::Weapons),
+    ("plasma_fire", "q3-resources/sound/weapons/plasma/hyprbf1a.wav", SoundCategory::Weapons),
+    ("railgun_fire", "q3-resources/sound/weapons/railgun/railgf1a.wav", SoundCategory::Weapons),
+    ("lightning_fire", "q3-resources/sound/weapons/lightning/lg_hum.wav", SoundCategory::Weapons),
+    ("bfg_fire", "q3-resources/sound/weapons/bfg/bfg_fire.wav", SoundCategory::Weapons),
+    ("bfg_hum", "q3-resources/sound/weapons/bfg/bfg_hum.wav", SoundCategory::Weapons),
+    ("gauntlet", "q3-resources/sound/weapons/melee/fstatck.wav", SoundCategory::Weapons),
+    ("land", "q3-resources/sound/player/land1.wav", SoundCategory::Player),
+    ("gib", "q3-resources/sound/player/gibsplt1.wav", SoundCategory::Player),
+    ("weapon_switch", "q3-resources/sound/weapons/change.wav", SoundCategory::Weapons),
+    ("item_pickup", "q3-resources/sound/items/n_health.wav", SoundCategory::Items),
+    ("armor_pickup", "q3-resources/sound/items/s_health.wav", SoundCategory::Items),
+    ("weapon_pickup", "q3-resources/sound/misc/w_pkup.wav", SoundCategory::Items),
+    ("powerup_pickup", "q3-resources/sound/items/protect.wav", SoundCategory::Items),
+    ("quad_damage", "q3-resources/sound/items/quaddamage.wav", SoundCategory::Items),
+    ("quad_fire", "q3-resources/sound/items/quaddamage_fire.wav", SoundCategory::Weapons),
+    ("hit_25", "q3-resources/sound/feedback/hit25.wav", SoundCategory::Feedback),
+    ("hit_50", "q3-resources/sound/feedback/hit50.wav", SoundCategory::Feedback),
+    ("hit_75", "q3-resources/sound/feedback/hit75.wav", SoundCategory::Feedback),
+    ("hit_100", "q3-resources/sound/feedback/hit100.wav", SoundCategory::Feedback),
+    ("excellent", "q3-resources/sound/feedback/excellent.wav", SoundCategory::Announcer),
+    ("impressive", "q3-resources/sound/feedback/impressive.wav", SoundCategory::Announcer),
+    ("humiliation", "q3-resources/sound/feedback/humiliation.wav", SoundCategory::Announcer),
+    ("perfect", "q3-resources/sound/feedback/perfect.wav", SoundCategory::Announcer),
+    ("accuracy", "q3-resources/sound/feedback/accuracy.wav", SoundCategory::Announcer),
+];
+
+// Horizontal offset at which a sound is panned fully to one side
+const PAN_DISTANCE: f32 = 400.0;
+const BEHIND_DAMPING: f32 = 0.3;
+// A weapon loop stops once no fire event has arrived for this long past the weapon's refire time
+const LOOP_RELEASE_TIME: f32 = 0.15;
+const MAX_SOUND_DISTANCE: f32 = 800.0;
+const LOOP_FADE_OUT: Duration = Duration::from_millis(60);
+const SOUND_LOAD_WORKERS: usize = 4;
+const DEFAULT_REVERB_DECAY: f32 = 0.6;
+const DEFAULT_REVERB_MIX: f32 = 0.25;
+// Big, arena-filling sounds get a tail; everything else, announcer and UI included, stays dry
+const REVERB_SOUNDS: &[&str] = &["rocket_explode", "rocket_fire", "bfg_fire", "railgun_fire"];
+const MUSIC_CROSSFADE: Duration = Duration::from_millis(1500);
+// A one-shot can't retrigger faster than this unless given its own interval
+const DEFAULT_SOUND_COOLDOWN: Duration = Duration::from_millis(40);
+
+enum MusicHandle {
+    Static(SoundHandle),
+    Streaming(StreamingSoundHandle<FromFileError>),
+}
+
+impl MusicHandle {
+    fn fade_to(&mut self, volume: f32, tween: Tween) {
+        let volume = Volume::Amplitude(volume as f64);
+        match self {
+            MusicHandle::Static(handle) => {
+                handle.set_volume(volume, tween);
+            }
+            MusicHandle::Streaming(handle) => {
+                handle.set_volume(volume, tween);
+            }
+        }
+    }
+
+    fn fade_out(&mut self, tween: Tween) {
+        match self {
+            MusicHandle::Static(handle) => {
+                handle.stop(tween);
+            }
+            MusicHandle::Streaming(handle) => {
+                handle.stop(tween);
+            }
+        }
+    }
+}
+
+struct MusicTrack {
+    name: String,
+    handle: MusicHandle,
+    volume: f32,
+}
+
+// Sound paths are q3-resources relative; unresolved ones are tried as given so the error names them
+fn sound_path(path: &str) -> PathBuf {
+    resolve(path, &[]).unwrap_or_else(|| PathBuf::from(path))
+}
+
+struct WeaponLoop {
+    handle: SoundHandle,
+    idle: f32,
+    release: f32,
+}
+
+// Held weapons keep one sound running instead of retriggering on every shot
+fn weapon_loop_sound(weapon: Weapon) -> Option<&'static str> {
+    match weapon {
+        Weapon::Lightning => Some("lightning_fire"),
+        Weapon::BFG => Some("bfg_hum"),
+        _ => None,
+    }
+}
+
+// Volume scale and pan for a source at `x`, or None when it's out of earshot
+fn positional_mix(x: f32, listener_x: f32, listener_facing: f32) -> Option<(f32, f32)> {
+    let offset = x - listener_x;
+    let distance = offset.abs();
+    if distance > MAX_SOUND_DISTANCE {
+        return None;
+    }
+
+    let pan = (offset / PAN_DISTANCE).clamp(-1.0, 1.0);
+    let behind = offset * listener_facing < 0.0;
+    let facing_volume = if behind { 1.0 - BEHIND_DAMPING * pan.abs() } else { 1.0 };
+
+    let distance_volume = 1.0 - (distance / MAX_SOUND_DISTANCE).min(1.0);
+    Some((distance_volume * facing_volume, pan))
+}
+
+// kira's panning runs from 0.0 (left) to 1.0 (right)
+fn kira_panning(pan: f32) -> f64 {
+    (pan.clamp(-1.0, 1.0) as f64 + 1.0) * 0.5
+}
 
 pub struct AudioSystem {
-    manager: AudioManager,
+    manager: Option<AudioManager>,
     sounds: HashMap<String, StaticSoundData>,
+    // Long tracks are decoded from disk while playing, so only the path is kept
+    streaming_sounds: HashMap<String, String>,
     enabled: bool,
+    muted: HashSet<SoundCategory>,
+    volume: VolumeConfig,
+    category_volumes: HashMap<SoundCategory, f32>,
+    sound_categories: HashMap<String, SoundCategory>,
+    synth_fallback: bool,
+    // Keyed by player too, so two players on the same weapon don't stop each other's loop
+    weapon_loops: HashMap<(u32, Weapon), WeaponLoop>,
+    reverb: Option<(TrackHandle, ReverbHandle)>,
+    reverb_enabled: bool,
+    reverb_sounds: HashSet<String>,
+    music: Option<MusicTrack>,
+    last_played: HashMap<String, Instant>,
+    sound_cooldowns: HashMap<String, Duration>,
+    play_log: Option<Vec<(String, f32)>>,
 }
 
 impl AudioSystem {
     pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        let manager = AudioManager::<DefaultBackend>::new(AudioManagerSettings::default())?;
-        
+        let mut manager = AudioManager::<DefaultBackend>::new(AudioManagerSettings::default())?;
+        let mut builder = TrackBuilder::new();
+        let reverb = builder.add_effect(
+            ReverbBuilder::new().feedback(DEFAULT_REVERB_DECAY as f64).mix(DEFAULT_REVERB_MIX as f64),
+        );
+        let track = manager.add_sub_track(builder)?;
+
         Ok(Self {
-            manager,
-            sounds: HashMap::new(),
+            manager: Some(manager),
             enabled: true,
+            // The track is ready, but sounds stay dry until set_reverb turns it on
+            reverb: Some((track, reverb)),
+            ..Self::new_silent()
+        })
+    }
+
+    // Falls back to a silent system when no output device can be opened (CI, SSH, headless)
+    pub fn new_or_silent() -> Self {
+        Self::new().unwrap_or_else(|e| {
+            println!("Audio disabled: {}", e);
+            Self::new_silent()
         })
     }
 
-    pub fn load_sound(&mut self, name: &str, path: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let sound_data = StaticSoundData::from_file(path)?;
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    // No output device; every play call is a no-op, so events can be processed headless
+    pub fn new_silent() -> Self {
+        Self {
+            manager: None,
+            sounds: HashMap::new(),
+            streaming_sounds: HashMap::new(),
+            enabled: false,
+            muted: HashSet::new(),
+            volume: VolumeConfig::default(),
+            category_volumes: HashMap::new(),
+            sound_categories: HashMap::new(),
+            synth_fallback: false,
+            weapon_loops: HashMap::new(),
+            reverb: None,
+            reverb_enabled: false,
+            reverb_sounds: REVERB_SOUNDS.iter().map(|name| name.to_string()).collect(),
+            music: None,
+            last_played: HashMap::new(),
+            sound_cooldowns: HashMap::new(),
+            play_log: None,
+        }
+    }
+
+    pub fn load_sound(&mut self, name: &str, path: &str, category: SoundCategory) -> Result<(), Box<dyn std::error::Error>> {
+        let file = sound_path(path);
+        let sound_data = StaticSoundData::from_file(file)?;
         self.sounds.insert(name.to_string(), sound_data);
+        self.sound_categories.insert(name.to_string(), category);
         Ok(())
     }
 
-    pub fn play(&mut self, name: &str, volume: f32) {
-        if !self.enabled {
+    // Opens the file once up front so a bad path is reported now rather than at play time
+    pub fn load_streaming_sound(&mut self, name: &str, path: &str, category: SoundCategory) -> Result<(), Box<dyn std::error::Error>> {
+        let file = sound_path(path);
+        StreamingSoundData::from_file(&file)?;
+        self.streaming_sounds.insert(name.to_string(), file.to_string_lossy().to_string());
+        self.sound_categories.insert(name.to_string(), category);
+        Ok(())
+    }
+
+    // Decoded frames held by all static sounds
+    pub fn static_cache_bytes(&self) -> usize {
+        self.sounds.values().map(|data| std::mem::size_of_val(&*data.frames)).sum()
+    }
+
+    pub fn sound_category(&self, name: &str) -> Option<SoundCategory> {
+        self.sound_categories.get(name).copied()
+    }
+
+    pub fn set_synth_fallback(&mut self, enabled: bool) {
+        self.synth_fallback = enabled;
+    }
+
+    pub fn synth_fallback(&self) -> bool {
+        self.synth_fallback
+    }
+
+    pub fn set_muted(&mut self, category: SoundCategory, muted: bool) {
+        if muted {
+            self.muted.insert(category);
+        } else {
+            self.muted.remove(&category);
+        }
+    }
+
+    pub fn toggle_muted(&mut self, category: SoundCategory) -> bool {
+        let muted = !self.is_muted(category);
+        self.set_muted(category, muted);
+        muted
+    }
+
+    pub fn is_muted(&self, category: SoundCategory) -> bool {
+        self.muted.contains(&category)
+    }
+
+    pub fn set_volume_config(&mut self, config: VolumeConfig) {
+        self.volume.master = config.master.clamp(0.0, 1.0);
+        for category in SoundCategory::ALL {
+            if let Some(volume) = config.category(category) {
+                self.set_category_volume(category, volume);
+            }
+        }
+    }
+
+    // Each group reports the last volume set on any of its categories
+    pub fn volume_config(&self) -> VolumeConfig {
+        self.volume
+    }
+
+    pub fn set_master_volume(&mut self, volume: f32) {
+        self.volume.master = volume.clamp(0.0, 1.0);
+        self.update_music_volume();
+    }
+
+    pub fn master_volume(&self) -> f32 {
+        self.volume.master
+    }
+
+    pub fn set_category_volume(&mut self, category: SoundCategory, volume: f32) {
+        let volume = volume.clamp(0.0, 1.0);
+        self.category_volumes.insert(category, volume);
+        self.volume.set_category(category, volume);
+        self.update_music_volume();
+    }
+
+    pub fn category_volume(&self, category: SoundCategory) -> f32 {
+        self.category_volumes.get(&category).copied().unwrap_or(1.0)
+    }
+
+    // Combined master and category multiplier; muted categories are silent
+    pub fn gain(&self, category: SoundCategory) -> f32 {
+        if self.is_muted(category) {
+            0.0
+        } else {
+            self.volume.master * self.category_volume(category)
+        }
+    }
+
+    // Sounds tagged at load time use their own category; `fallback` covers untagged ones
+    fn sound_gain(&self, name: &str, fallback: SoundCategory) -> f32 {
+        self.gain(self.sound_category(name).unwrap_or(fallback))
+    }
+
+    // `mix` is the wet share of sounds sent to the reverb track and `decay` its feedback, both 0..1
+    pub fn set_reverb(&mut self, enabled: bool, mix: f32, decay: f32) {
+        if let Some((_, ref mut reverb)) = self.reverb {
+            reverb.set_mix(mix.clamp(0.0, 1.0) as f64, Tween::default());
+            reverb.set_feedback(decay.clamp(0.0, 0.99) as f64, Tween::default());
+        }
+        self.reverb_enabled = enabled;
+    }
+
+    pub fn reverb_enabled(&self) -> bool {
+        self.reverb_enabled
+    }
+
+    pub fn set_reverb_send(&mut self, name: &str, wet: bool) {
+        if wet {
+            self.reverb_sounds.insert(name.to_string());
+        } else {
+            self.reverb_sounds.remove(name);
+        }
+    }
+
+    pub fn has_reverb_send(&self, name: &str) -> bool {
+        self.reverb_sounds.contains(name)
+    }
+
+    pub fn play(&mut self, name: &str, volume: f32, category: SoundCategory) {
+        self.play_panned(name, volume, category, 0.0);
+    }
+
+    // `pan` runs from -1.0 (hard left) to 1.0 (hard right)
+    pub fn play_panned(&mut self, name: &str, volume: f32, category: SoundCategory, pan: f32) {
+        let volume = volume * self.sound_gain(name, category);
+        if volume <= 0.0 {
             return;
         }
+        let now = Instant::now();
+        if let Some(last) = self.last_played.get(name) {
+            if now.duration_since(*last) < self.sound_cooldown(name) {
+                return;
+            }
+        }
+        if self.start_sound(name, volume, pan, false).is_some() {
+            self.last_played.insert(name.to_string(), now);
+        }
+    }
+
+    // Logs the name and final volume of every sound started from now on, with or without a device
+    pub fn record_plays(&mut self) {
+        self.play_log.get_or_insert_with(Vec::new);
+    }
+
+    pub fn take_played(&mut self) -> Vec<(String, f32)> {
+        self.play_log.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    pub fn set_sound_cooldown(&mut self, name: &str, cooldown: Duration) {
+        self.sound_cooldowns.insert(name.to_string(), cooldown);
+    }
 
-        if let Some(sound_data) = self.sounds.get(name) {
-            let mut settings = StaticSoundSettings::default();
-            settings.volume = Volume::Amplitude(volume as f64).into();
-            
-            let _ = self.manager.play(sound_data.clone().with_settings(settings));
+    pub fn sound_cooldown(&self, name: &str) -> Duration {
+        self.sound_cooldowns.get(name).copied().unwrap_or(DEFAULT_SOUND_COOLDOWN)
+    }
+
+    pub fn play_loop(&mut self, name: &str, volume: f32, category: SoundCategory) -> Option<SoundHandle> {
+        let volume = volume * self.sound_gain(name, category);
+        if volume <= 0.0 {
+            return None;
         }
+        self.start_sound(name, volume, 0.0, true)
     }
 
-    pub fn play_positional(&mut self, name: &str, volume: f32, x: f32, listener_x: f32) {
-        if !self.enabled {
+    // Crossfades from the current track; asking for the track already playing does nothing
+    pub fn play_music(&mut self, name: &str, looping: bool) {
+        self.play_music_at(name, 1.0, looping);
+    }
+
+    pub fn play_music_at(&mut self, name: &str, volume: f32, looping: bool) {
+        if self.music.as_ref().is_some_and(|music| music.name == name) {
+            return;
+        }
+        self.stop_music(MUSIC_CROSSFADE);
+
+        // Starts silent and fades in while the previous track fades out; streamed data wins over static
+        let handle = if self.streaming_sounds.contains_key(name) {
+            self.start_streaming(name, looping).map(MusicHandle::Streaming)
+        } else {
+            self.start_sound(name, 0.0, 0.0, looping).map(MusicHandle::Static)
+        };
+        let Some(mut handle) = handle else {
             return;
+        };
+        let target = volume * self.sound_gain(name, SoundCategory::Music);
+        handle.fade_to(
+            target,
+            Tween {
+                duration: MUSIC_CROSSFADE,
+                ..Default::default()
+            },
+        );
+        self.music = Some(MusicTrack {
+            name: name.to_string(),
+            handle,
+            volume,
+        });
+    }
+
+    pub fn stop_music(&mut self, fade_out: Duration) {
+        if let Some(MusicTrack { mut handle, .. }) = self.music.take() {
+            handle.fade_out(Tween {
+                duration: fade_out,
+                ..Default::default()
+            });
         }
+    }
 
-        let distance = (x - listener_x).abs();
-        let max_distance = 800.0;
+    pub fn current_music(&self) -> Option<&str> {
+        self.music.as_ref().map(|music| music.name.as_str())
+    }
 
-        if distance > max_distance {
+    fn update_music_volume(&mut self) {
+        let Some(target) = self.music.as_ref().map(|music| music.volume * self.sound_gain(&music.name, SoundCategory::Music)) else {
             return;
+        };
+        if let Some(MusicTrack { ref mut handle, .. }) = self.music {
+            handle.fade_to(target, Tween::default());
+        }
+    }
+
+    fn start_streaming(&mut self, name: &str, looping: bool) -> Option<StreamingSoundHandle<FromFileError>> {
+        if !self.enabled {
+            return None;
         }
+        let path = self.streaming_sounds.get(name)?;
+        let mut data = match StreamingSoundData::from_file(path) {
+            Ok(data) => data,
+            Err(e) => {
+                eprintln!("Failed to stream {}: {}", path, e);
+                return None;
+            }
+        };
+        let mut settings = StreamingSoundSettings::default();
+        settings.volume = Volume::Amplitude(0.0).into();
+        if looping {
+            settings = settings.loop_region(..);
+        }
+        data.settings = settings;
+        self.manager.as_mut()?.play(data).ok()
+    }
 
-        let distance_volume = 1.0 - (distance / max_distance).min(1.0);
-        let final_volume = volume * distance_volume;
+    pub fn stop_loop(&mut self, mut handle: SoundHandle) {
+        handle.stop(Tween {
+            duration: LOOP_FADE_OUT,
+            ..Default::default()
+        });
+    }
+
+    pub fn is_weapon_looping(&self, player_id: u32, weapon: Weapon) -> bool {
+        self.weapon_loops.contains_key(&(player_id, weapon))
+    }
+
+    pub fn stop_weapon_loop(&mut self, player_id: u32, weapon: Weapon) {
+        if let Some(active) = self.weapon_loops.remove(&(player_id, weapon)) {
+            self.stop_loop(active.handle);
+        }
+    }
+
+    // Stops weapon loops whose fire events have dried up
+    pub fn update(&mut self, dt: f32) {
+        let mut released = Vec::new();
+        for (key, active) in self.weapon_loops.iter_mut() {
+            active.idle += dt;
+            if active.idle > active.release {
+                released.push(*key);
+            }
+        }
+        for (player_id, weapon) in released {
+            self.stop_weapon_loop(player_id, weapon);
+        }
+    }
+
+    // With synth_fallback on, a sound that never loaded gets a generated placeholder instead
+    fn sound_data(&mut self, name: &str) -> Option<StaticSoundData> {
+        if self.synth_fallback && !self.sounds.contains_key(name) {
+            self.sounds.insert(name.to_string(), synth::placeholder_sound(name));
+        }
+        self.sounds.get(name).cloned()
+    }
 
+    fn start_sound(&mut self, name: &str, volume: f32, pan: f32, looping: bool) -> Option<SoundHandle> {
+        if let Some(log) = self.play_log.as_mut() {
+            log.push((name.to_string(), volume));
+        }
+        if !self.enabled {
+            return None;
+        }
+
+        let sound_data = self.sound_data(name)?;
+        let mut settings = StaticSoundSettings::default();
+        settings.volume = Volume::Amplitude(volume as f64).into();
+        settings.panning = kira_panning(pan).into();
+        if looping {
+            settings = settings.loop_region(..);
+        }
+        if self.reverb_enabled && self.reverb_sounds.contains(name) {
+            if let Some((ref track, _)) = self.reverb {
+                settings.output_destination = track.into();
+            }
+        }
+
+        self.manager.as_mut()?.play(sound_data.with_settings(settings)).ok()
+    }
+
+    // Starts the loop on the first shot and keeps it alive, following the shooter, on later ones
+    fn fire_weapon_loop(&mut self, player_id: u32, weapon: Weapon, name: &str, volume: f32, mix: Option<(f32, f32)>) {
+        let Some((gain, pan)) = mix else {
+            self.stop_weapon_loop(player_id, weapon);
+            return;
+        };
+        let volume = volume * gain * self.sound_gain(name, SoundCategory::Weapons);
+        if let Some(active) = self.weapon_loops.get_mut(&(player_id, weapon)) {
+            active.idle = 0.0;
+            active.handle.set_volume(Volume::Amplitude(volume as f64), Tween::default());
+            active.handle.set_panning(kira_panning(pan), Tween::default());
+            return;
+        }
+        if volume <= 0.01 {
+            return;
+        }
+        if let Some(handle) = self.start_sound(name, volume, pan, true) {
+            let release = weapon.refire_time_seconds() + LOOP_RELEASE_TIME;
+            self.weapon_loops.insert((player_id, weapon), WeaponLoop { handle, idle: 0.0, release });
+        }
+    }
+
+    // `listener_facing` is the sign of the direction the listener looks along x; sounds behind are dampened
+    pub fn play_positional(
+        &mut self,
+        name: &str,
+        volume: f32,
+        category: SoundCategory,
+        x: f32,
+        listener_x: f32,
+        listener_facing: f32,
+    ) {
+        let Some((gain, pan)) = positional_mix(x, listener_x, listener_facing) else {
+            return;
+        };
+        let final_volume = volume * gain;
         if final_volume > 0.01 {
-            self.play(name, final_volume);
+            self.play_panned(name, final_volume, category, pan);
         }
     }
 
-    pub fn process_event(&mut self, event: &AudioEvent, listener_x: f32) {
-        use crate::game::weapon::Weapon;
+    pub fn process_event(&mut self, event: &GameEvent, listener_x: f32, listener_facing: f32) {
         use crate::game::awards::AwardType;
 
+        let event = match event {
+            GameEvent::Audio(event) => event,
+            _ => return,
+        };
+
+        let category = SoundCategory::of_event(event);
+
         match event {
             AudioEvent::WeaponFire {
+                player_id,
                 weapon,
                 x,
                 has_quad,
             } => {
                 if *has_quad {
-                    self.play("quad_fire", 0.8);
+                    self.play("quad_fire", 0.8, category);
                 }
 
                 let sound_name = match weapon {
@@ -100,11 +715,18 @@ impl AudioSystem {
                     Weapon::Railgun => 0.7,
                     Weapon::BFG => 0.8,
                 };
-                self.play_positional(sound_name, volume, *x, listener_x);
+                if let Some(loop_name) = weapon_loop_sound(*weapon) {
+                    let mix = positional_mix(*x, listener_x, listener_facing);
+                    self.fire_weapon_loop(*player_id, *weapon, loop_name, volume, mix);
+                }
+                // The lightning gun's hum is its firing sound
+                if *weapon != Weapon::Lightning {
+                    self.play_positional(sound_name, volume, category, *x, listener_x, listener_facing);
+                }
             }
-            AudioEvent::WeaponSwitch => self.play("weapon_switch", 0.4),
+            AudioEvent::WeaponSwitch => self.play("weapon_switch", 0.4, category),
             AudioEvent::Explosion { x } => {
-                self.play_positional("rocket_explode", 0.7, *x, listener_x);
+                self.play_positional("rocket_explode", 0.7, category, *x, listener_x, listener_facing);
             }
             AudioEvent::PlayerPain { health, x, model } => {
                 let sound_base = if *health < 25 {
@@ -117,21 +739,21 @@ impl AudioSystem {
                     "pain_100"
                 };
                 let sound_name = format!("{}_{}", sound_base, model);
-                self.play_positional(&sound_name, 0.5, *x, listener_x);
+                self.play_positional(&sound_name, 0.5, category, *x, listener_x, listener_facing);
             }
             AudioEvent::PlayerDeath { x, model } => {
                 let sound_name = format!("death_{}", model);
-                self.play_positional(&sound_name, 0.6, *x, listener_x);
+                self.play_positional(&sound_name, 0.6, category, *x, listener_x, listener_facing);
             }
             AudioEvent::PlayerGib { x } => {
-                self.play_positional("gib", 0.7, *x, listener_x);
+                self.play_positional("gib", 0.7, category, *x, listener_x, listener_facing);
             }
             AudioEvent::PlayerJump { x, model } => {
                 let sound_name = format!("jump_{}", model);
-                self.play_positional(&sound_name, 0.3, *x, listener_x);
+                self.play_positional(&sound_name, 0.3, category, *x, listener_x, listener_facing);
             }
             AudioEvent::PlayerLand { x } => {
-                self.play_positional("land", 0.4, *x, listener_x);
+                self.play_positional("land", 0.4, category, *x, listener_x, listener_facing);
             }
             AudioEvent::PlayerHit { damage } => {
                 let sound_name = if *damage >= 100 {
@@ -143,22 +765,22 @@ impl AudioSystem {
                 } else {
                     "hit_25"
                 };
-                self.play(sound_name, 0.5);
+                self.play(sound_name, 0.5, category);
             }
             AudioEvent::ItemPickup { x } => {
-                self.play_positional("item_pickup", 0.5, *x, listener_x);
+                self.play_positional("item_pickup", 0.5, category, *x, listener_x, listener_facing);
             }
             AudioEvent::ArmorPickup { x } => {
-                self.play_positional("armor_pickup", 0.5, *x, listener_x);
+                self.play_positional("armor_pickup", 0.5, category, *x, listener_x, listener_facing);
             }
             AudioEvent::WeaponPickup { x } => {
-                self.play_positional("weapon_pickup", 0.5, *x, listener_x);
+                self.play_positional("weapon_pickup", 0.5, category, *x, listener_x, listener_facing);
             }
             AudioEvent::PowerupPickup { x } => {
-                self.play_positional("powerup_pickup", 0.6, *x, listener_x);
+                self.play_positional("powerup_pickup", 0.6, category, *x, listener_x, listener_facing);
             }
             AudioEvent::QuadDamage => {
-                self.play("quad_damage", 0.9);
+                self.play("quad_damage", 0.9, category);
             }
             AudioEvent::Award { award_type } => {
                 let sound_name = match award_type {
@@ -168,47 +790,150 @@ impl AudioSystem {
                     AwardType::Perfect => "perfect",
                     AwardType::Accuracy => "accuracy",
                 };
-                self.play(sound_name, 0.8);
-            }
-        }
-    }
-
-    pub fn load_all_sounds(&mut self) {
-        let sounds = vec![
-            ("mg_fire", "q3-resources/sound/weapons/machinegun/machgf1b.wav"),
-            ("shotgun_fire", "q3-resources/sound/weapons/shotgun/sshotf1b.wav"),
-            ("rocket_fire", "q3-resources/sound/weapons/rocket/rocklf1a.wav"),
-            ("rocket_explode", "q3-resources/sound/weapons/rocket/rocklx1a.wav"),
-            ("grenade_fire", "q3-resources/sound/weapons/grenade/grenlf1a.wav"),
-            ("plasma_fire", "q3-resources/sound/weapons/plasma/hyprbf1a.wav"),
-            ("railgun_fire", "q3-resources/sound/weapons/railgun/railgf1a.wav"),
-            ("lightning_fire", "q3-resources/sound/weapons/lightning/lg_hum.wav"),
-            ("bfg_fire", "q3-resources/sound/weapons/bfg/bfg_fire.wav"),
-            ("gauntlet", "q3-resources/sound/weapons/melee/fstatck.wav"),
-            ("land", "q3-resources/sound/player/land1.wav"),
-            ("gib", "q3-resources/sound/player/gibsplt1.wav"),
-            ("weapon_switch", "q3-resources/sound/weapons/change.wav"),
-            ("item_pickup", "q3-resources/sound/items/n_health.wav"),
-            ("armor_pickup", "q3-resources/sound/items/s_health.wav"),
-            ("weapon_pickup", "q3-resources/sound/misc/w_pkup.wav"),
-            ("powerup_pickup", "q3-resources/sound/items/protect.wav"),
-            ("quad_damage", "q3-resources/sound/items/quaddamage.wav"),
-            ("quad_fire", "q3-resources/sound/items/quaddamage_fire.wav"),
-            ("hit_25", "q3-resources/sound/feedback/hit25.wav"),
-            ("hit_50", "q3-resources/sound/feedback/hit50.wav"),
-            ("hit_75", "q3-resources/sound/feedback/hit75.wav"),
-            ("hit_100", "q3-resources/sound/feedback/hit100.wav"),
-            ("excellent", "q3-resources/sound/feedback/excellent.wav"),
-            ("impressive", "q3-resources/sound/feedback/impressive.wav"),
-            ("humiliation", "q3-resources/sound/feedback/humiliation.wav"),
-            ("perfect", "q3-resources/sound/feedback/perfect.wav"),
-            ("accuracy", "q3-resources/sound/feedback/accuracy.wav"),
-        ];
+                self.play(sound_name, 0.8, category);
+            }
+        }
+    }
 
-        for (name, path) in sounds {
-            if let Err(e) = self.load_sound(name, path) {
-                eprintln!("Failed to load sound {}: {}", name, e);
+    // Categories are known before any file decodes, so a failed load still mixes under the right one
+    fn tag_sound_files(&mut self) {
+        for (name, _, category) in SOUND_FILES {
+            self.sound_categories.insert(name.to_string(), *category);
+        }
+    }
+
+    // Decodes on worker threads; returns each sound's outcome in declaration order
+    pub fn load_all_sounds(&mut self) -> Vec<(String, Result<(), String>)> {
+        self.tag_sound_files();
+
+        let jobs = Mutex::new(SOUND_FILES.iter().copied().enumerate());
+        let (result_tx, result_rx) = channel();
+        std::thread::scope(|scope| {
+            for _ in 0..SOUND_LOAD_WORKERS {
+                let jobs = &jobs;
+                let result_tx = result_tx.clone();
+                scope.spawn(move || loop {
+                    let job = match jobs.lock() {
+                        Ok(mut jobs) => jobs.next(),
+                        Err(_) => break,
+                    };
+                    let Some((index, (name, path, _))) = job else {
+                        break;
+                    };
+                    let file = sound_path(path);
+                    let data = StaticSoundData::from_file(file).map_err(|e| format!("{}: {}", path, e));
+                    if result_tx.send((index, name, data)).is_err() {
+                        break;
+                    }
+                });
             }
+        });
+        drop(result_tx);
+
+        let mut loaded: Vec<_> = result_rx.into_iter().collect();
+        loaded.sort_by_key(|(index, _, _)| *index);
+
+        loaded
+            .into_iter()
+            .map(|(_, name, data)| {
+                let result = match data {
+                    Ok(sound_data) => {
+                        self.sounds.insert(name.to_string(), sound_data);
+                        Ok(())
+                    }
+                    Err(e) => {
+                        if self.synth_fallback {
+                            self.sounds.insert(name.to_string(), synth::placeholder_sound(name));
+                        }
+                        Err(e)
+                    }
+                };
+                (name.to_string(), result)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::awards::AwardType;
+
+    #[test]
+    fn volume_config_groups_fill_the_category_table() {
+        let mut audio = AudioSystem::new_silent();
+        audio.set_volume_config(VolumeConfig { master: 0.8, effects: 0.0, feedback: 0.6, announcer: 1.0 });
+        for category in [SoundCategory::Weapons, SoundCategory::Player, SoundCategory::Items] {
+            assert_eq!(audio.gain(category), 0.0);
+        }
+        assert_eq!(audio.category_volume(SoundCategory::Feedback), 0.6);
+        assert_eq!(audio.gain(SoundCategory::Announcer), 0.8);
+        assert_eq!(audio.category_volume(SoundCategory::Music), 1.0);
+
+        audio.set_category_volume(SoundCategory::Announcer, 0.3);
+        assert_eq!(audio.volume_config().announcer, 0.3);
+    }
+
+    #[test]
+    fn master_and_category_volumes_multiply() {
+        let mut audio = AudioSystem::new_silent();
+        audio.tag_sound_files();
+        audio.record_plays();
+        audio.set_master_volume(0.5);
+        audio.set_category_volume(SoundCategory::Feedback, 0.5);
+        audio.set_category_volume(SoundCategory::Weapons, 0.0);
+
+        let events = [
+            AudioEvent::WeaponFire { player_id: 0, weapon: Weapon::RocketLauncher, x: 0.0, has_quad: false },
+            AudioEvent::PlayerHit { damage: 30 },
+            AudioEvent::Award { award_type: AwardType::Excellent },
+        ];
+        for event in events {
+            audio.process_event(&GameEvent::Audio(event), 0.0, 1.0);
         }
+        // Requested volumes are 0.5 for hits and 0.8 for awards
+        assert_eq!(audio.take_played(), [("hit_50".to_string(), 0.125), ("excellent".to_string(), 0.4)]);
+    }
+
+    #[test]
+    fn muted_weapons_drop_gunfire_but_keep_awards() {
+        let mut audio = AudioSystem::new_silent();
+        audio.tag_sound_files();
+        audio.record_plays();
+        assert!(audio.toggle_muted(SoundCategory::Weapons));
+
+        let fire = GameEvent::Audio(AudioEvent::WeaponFire { player_id: 0, weapon: Weapon::Railgun, x: 0.0, has_quad: true });
+        let award = GameEvent::Audio(AudioEvent::Award { award_type: AwardType::Impressive });
+        audio.process_event(&fire, 0.0, 1.0);
+        audio.process_event(&award, 0.0, 1.0);
+        let played: Vec<String> = audio.take_played().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(played, ["impressive"]);
+
+        // Unmuting brings back the level that was set, not full volume
+        audio.set_category_volume(SoundCategory::Weapons, 0.4);
+        audio.set_muted(SoundCategory::Weapons, false);
+        assert_eq!(audio.sound_gain("railgun_fire", SoundCategory::Weapons), 0.4);
+    }
+
+    #[test]
+    fn missing_sounds_use_the_synth_placeholder_only_when_enabled() {
+        let mut audio = AudioSystem::new_silent();
+        assert!(audio.sound_data("mg_fire").is_none());
+
+        audio.set_synth_fallback(true);
+        let expected = synth::placeholder_samples("mg_fire");
+        let data = audio.sound_data("mg_fire").unwrap();
+        assert_eq!(data.frames.len(), expected.len());
+        assert_eq!(data.frames[10].left, expected[10]);
+    }
+
+    #[test]
+    fn sources_pan_toward_their_side_of_the_listener() {
+        let pan = |x: f32| positional_mix(x, 100.0, 1.0).map(|(_, pan)| pan);
+        assert_eq!(pan(100.0 - PAN_DISTANCE), Some(-1.0));
+        assert_eq!(pan(100.0), Some(0.0));
+        assert_eq!(pan(100.0 + PAN_DISTANCE), Some(1.0));
+        assert_eq!(kira_panning(-1.0), 0.0);
+        assert_eq!(kira_panning(1.0), 1.0);
     }
 }
@@ -0,0 +1,52 @@
+use kira::dsp::Frame;
+use kira::sound::static_sound::{StaticSoundData, StaticSoundSettings};
+use std::sync::Arc;
+
+const SAMPLE_RATE: u32 = 22050;
+const DURATION: f32 = 0.15;
+
+fn name_hash(name: &str) -> u32 {
+    name.bytes().fold(2166136261u32, |h, b| (h ^ b as u32).wrapping_mul(16777619))
+}
+
+fn is_noisy(name: &str) -> bool {
+    ["explode", "gib", "shotgun", "land"].iter().any(|k| name.contains(k))
+}
+
+pub fn placeholder_samples(name: &str) -> Vec<f32> {
+    let hash = name_hash(name);
+    let frequency = 220.0 * 2f32.powf((hash % 24) as f32 / 12.0);
+    let noisy = is_noisy(name);
+    let num_samples = (SAMPLE_RATE as f32 * DURATION) as usize;
+
+    let mut seed = hash | 1;
+    (0..num_samples)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let envelope = (1.0 - t / DURATION).max(0.0).powi(2);
+            let sample = if noisy {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                (seed as f32 / u32::MAX as f32) * 2.0 - 1.0
+            } else {
+                (t * frequency * std::f32::consts::TAU).sin()
+            };
+            sample * envelope * 0.5
+        })
+        .collect()
+}
+
+pub fn placeholder_sound(name: &str) -> StaticSoundData {
+    let frames: Vec<Frame> = placeholder_samples(name)
+        .into_iter()
+        .map(Frame::from_mono)
+        .collect();
+
+    StaticSoundData {
+        sample_rate: SAMPLE_RATE,
+        frames: Arc::from(frames),
+        settings: StaticSoundSettings::default(),
+        slice: None,
+    }
+}
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 use std::time::Instant;
 use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 use glam::{Mat3, Mat4, Vec3};
 use pollster::FutureExt;
@@ -10,37 +11,78 @@ use winit::{
     event::WindowEvent,
     event_loop::{ActiveEventLoop, EventLoop},
     keyboard::{KeyCode, PhysicalKey},
-    window::Window,
+    window::{CursorGrabMode, Window},
 };
 
-use sas2::engine::anim::{AnimConfig, AnimRange};
+use sas2::engine::anim::{AnimConfig, AnimState, Animation, ManualFrame};
 use sas2::engine::loader::{
     load_textures_for_model_static,
-    load_weapon_textures_static,
+    load_textures_for_skin_set,
+    list_available_skins,
+    load_weapon,
+    unload_weapon,
+    WeaponModel,
     load_rocket_textures_static,
     load_md3_textures_guess_static,
+    ModelLoadQueue,
 };
-use sas2::engine::math::{axis_from_mat3, attach_rotated_entity, orientation_to_mat4, Orientation, Frustum};
-use sas2::engine::md3::MD3Model;
-use sas2::engine::renderer::{MD3Renderer, WgpuRenderer};
-use sas2::render::TextRenderer;
+use sas2::engine::math::{axis_from_mat3, attach_rotated_entity, attach_rotated_entity_interp, ground_reflection_matrix, orientation_to_mat4, Orientation, Frustum, TagValidator};
+use sas2::engine::md3::{FrameBlend, MD3Model, PoseBlend};
+use sas2::engine::scene::SceneDump;
+use sas2::resource_path::resolve;
+use sas2::skin::SkinSet;
+use sas2::engine::renderer::{MD3Renderer, ModelDrawCall, RenderTarget, SceneView, WgpuRenderer, MUZZLE_FLASH_DURATION};
+use sas2::render::{CrosshairStyle, LoadProgress, LoadingScreen, RenderMode, TextRenderer, MAX_LIGHTS};
+use sas2::render::textures::skybox_face_paths;
+use sas2::audio::{AudioSystem, SoundCategory};
+use sas2::audio::events::AudioEvent;
 
 use sas2::game::world::World;
-use sas2::game::camera::Camera;
+use sas2::game::events::GameEvent;
+use sas2::game::input::PlayerInput;
+use sas2::game::camera::{Camera, SpectatorCamera};
+use sas2::game_loop::GameLoop;
+use sas2::game::hud::HudFeedback;
+use sas2::game::particle::Effects;
 use sas2::game::lighting::{LightingParams, Light};
 // use sas2::game::player::Player;
 use sas2::game::map::ItemType;
+use sas2::game::weapon::Weapon;
+use sas2::console::Console;
+use sas2::input::{Action, InputState, KeyBindings};
+use sas2::input::bindings::BINDINGS_PATH;
+#[cfg(feature = "gamepad")]
+use sas2::input::Gamepad;
+
+const PLAYER2_MODEL_NAME: &str = "orbb";
+const REVERB_MIX: f32 = 0.25;
+const REVERB_DECAY: f32 = 0.6;
+const SKYBOX_NAME: &str = "arena";
+const ARENA_MUSIC: &str = "arena_ambient";
+const ARENA_MUSIC_PATH: &str = "q3-resources/music/sonic1.wav";
+const CROSSHAIR_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const CROSSHAIR_SCALE: f32 = 1.0;
+const SCROLL_PIXELS_PER_LINE: f32 = 40.0;
+const ANISOTROPY_LEVELS: [u16; 3] = [1, 4, 16];
+const RENDER_SCALES: [f64; 3] = [0.5, 0.75, 1.0];
+const SIM_TICK_RATE: u32 = 60;
+const SCREENSHOT_DIR: &str = "screenshots";
+// Standing height of a Q3 player in world units; player models are auto-fit to it
+const PLAYER_MODEL_HEIGHT: f32 = 56.0;
 
 struct PlayerModel {
     lower: Option<MD3Model>,
     upper: Option<MD3Model>,
     head: Option<MD3Model>,
-    weapon: Option<MD3Model>,
+    weapon: Option<WeaponModel>,
+    weapon_kind: Option<Weapon>,
     lower_textures: Vec<Option<String>>,
     upper_textures: Vec<Option<String>>,
     head_textures: Vec<Option<String>>,
-    weapon_textures: Vec<Option<String>>,
     anim_config: Option<AnimConfig>,
+    skin: String,
+    // None fits the model to PLAYER_MODEL_HEIGHT
+    scale_override: Option<f32>,
 }
 
 impl PlayerModel {
@@ -50,13 +92,77 @@ impl PlayerModel {
             upper: None,
             head: None,
             weapon: None,
+            weapon_kind: None,
             lower_textures: Vec::new(),
             upper_textures: Vec::new(),
             head_textures: Vec::new(),
-            weapon_textures: Vec::new(),
             anim_config: None,
+            skin: "default".to_string(),
+            scale_override: None,
         }
     }
+
+    // Feet to the top of the head in the first frame, with the parts stacked on their tags
+    fn standing_height(&self) -> Option<f32> {
+        let (lower, upper, head) = (self.lower.as_ref()?, self.upper.as_ref()?, self.head.as_ref()?);
+        let torso_tag = GameApp::find_tag(lower.tags.first()?, "tag_torso")?;
+        let head_tag = GameApp::find_tag(upper.tags.first()?, "tag_head")?;
+        let (lower_min, _) = lower.frame_bounds(0);
+        let (_, head_max) = head.frame_bounds(0);
+        Some(torso_tag.position[2] - lower_min.z + head_tag.position[2] + head_max.z)
+    }
+
+    fn fit_scale(&self, target_height: f32) -> f32 {
+        match self.standing_height() {
+            Some(height) if height > 0.0 => target_height / height,
+            _ => 1.0,
+        }
+    }
+
+    fn scale(&self) -> f32 {
+        self.scale_override.unwrap_or_else(|| self.fit_scale(PLAYER_MODEL_HEIGHT))
+    }
+
+    fn set_scale(&mut self, scale: Option<f32>) {
+        self.scale_override = scale;
+    }
+
+    fn set_part(&mut self, part: &str, model: Option<MD3Model>, textures: Vec<Option<String>>) {
+        match part {
+            "lower" => {
+                self.lower = model;
+                self.lower_textures = textures;
+            }
+            "upper" => {
+                self.upper = model;
+                self.upper_textures = textures;
+            }
+            "head" => {
+                self.head = model;
+                self.head_textures = textures;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ModelSlot {
+    Local,
+    Opponent,
+}
+
+struct QueuedPart {
+    slot: ModelSlot,
+    model_name: &'static str,
+    part: &'static str,
+}
+
+// A local model part that has loaded but waits for its siblings, so the old model stays up meanwhile
+struct StagedPart {
+    part: &'static str,
+    model: MD3Model,
+    textures: Vec<Option<String>>,
 }
 
 struct StaticModel {
@@ -69,8 +175,9 @@ struct GameApp {
     window: Option<Arc<Window>>,
     wgpu_renderer: Option<WgpuRenderer>,
     md3_renderer: Option<MD3Renderer>,
-    crosshair_renderer: Option<sas2::engine::renderer::crosshair::Crosshair>,
+    crosshair_style: usize,
     text_renderer: Option<TextRenderer>,
+    audio: Option<AudioSystem>,
     player_model: PlayerModel,
     player2_model: PlayerModel,
     rocket_model: Option<MD3Model>,
@@ -81,7 +188,7 @@ struct GameApp {
     depth_texture: Option<Texture>,
     depth_view: Option<wgpu::TextureView>,
     start_time: Instant,
-    last_frame_time: Instant,
+    game_loop: GameLoop,
     last_fps_update: Instant,
     frame_count: u32,
     fps: f32,
@@ -90,19 +197,22 @@ struct GameApp {
     world: World,
     local_player_id: u32,
     
-    move_left: bool,
-    move_right: bool,
-    jump_pressed: bool,
-    crouch_pressed: bool,
-    shoot_pressed: bool,
-    is_shooting: bool,
-    shoot_anim_start_time: f32,
+    // Bound keys, mouse wheel and gamepad merged into one set of actions
+    input: InputState,
+    console: Console,
+    #[cfg(feature = "gamepad")]
+    gamepad: Option<Gamepad>,
     
-    player2_gesture_start_time: f32,
-    player2_is_gesturing: bool,
+    player2_anim: AnimState,
     player2_next_gesture_time: f32,
     
     camera: Camera,
+    spectator_camera: SpectatorCamera,
+    spectator_mode: bool,
+    hud_feedback: HudFeedback,
+    effects: Effects,
+    manual_frame: ManualFrame,
+    sim_time: f32,
     camera_move_z_neg: bool,
     camera_move_z_pos: bool,
     camera_pitch_up: bool,
@@ -113,6 +223,7 @@ struct GameApp {
     aim_x: f32,
     aim_y: f32,
     last_mouse_pos: (f32, f32),
+    cursor_grabbed: bool,
     
     current_legs_yaw: f32,
     player2_legs_yaw: f32,
@@ -120,6 +231,20 @@ struct GameApp {
     available_models: Vec<&'static str>,
     current_model_index: usize,
     shift_pressed: bool,
+    
+    net_client: Option<sas2::net::Client>,
+    snapshot_buffer: sas2::net::SnapshotBuffer,
+    remote_players: Vec<sas2::net::RemotePlayerState>,
+    synth_sfx: bool,
+    model_queue: ModelLoadQueue,
+    queued_model_parts: HashMap<PathBuf, QueuedPart>,
+    staged_parts: Vec<StagedPart>,
+    tag_validator: TagValidator,
+    muzzle_flash_age: Option<f32>,
+    dump_scene: bool,
+    show_debug_lights: bool,
+    show_scene_stats: bool,
+    take_screenshot: bool,
 }
 
 impl GameApp {
@@ -165,12 +290,17 @@ impl GameApp {
         model_path: &str,
         scale: f32,
     ) -> Option<StaticModel> {
-        let model = MD3Model::load(model_path).ok();
-        if model.is_none() {
-            println!("Failed to load static model: {}", model_path);
+        let Some(path) = resolve(model_path, &[]) else {
+            println!("Static model not found: {}", model_path);
             return None;
-        }
-        let model = model.unwrap();
+        };
+        let model = match MD3Model::load(&path) {
+            Ok(model) => model,
+            Err(e) => {
+                println!("Failed to load static model {}: {}", model_path, e);
+                return None;
+            }
+        };
         let textures = load_md3_textures_guess_static(wgpu_renderer, md3_renderer, &model, model_path);
         println!("Loaded static model: {} with {} textures", model_path, textures.len());
         Some(StaticModel { model, textures, scale })
@@ -189,12 +319,34 @@ impl GameApp {
         
         let local_player_id = world.add_player();
         
+        let args: Vec<String> = std::env::args().collect();
+        let net_client = args.iter()
+            .position(|a| a == "--connect")
+            .and_then(|i| args.get(i + 1))
+            .and_then(|addr| match sas2::net::Client::connect(addr.as_str()) {
+                Ok(client) => {
+                    println!("Connecting to {}", addr);
+                    Some(client)
+                }
+                Err(e) => {
+                    println!("Failed to connect to {}: {}", addr, e);
+                    None
+                }
+            });
+        let interpolation_delay = args.iter()
+            .position(|a| a == "--interp-delay")
+            .and_then(|i| args.get(i + 1))
+            .and_then(|v| v.parse::<f32>().ok())
+            .unwrap_or(sas2::net::interpolation::DEFAULT_INTERPOLATION_DELAY);
+
+        
         Self {
             window: None,
             wgpu_renderer: None,
             md3_renderer: None,
-            crosshair_renderer: None,
+            crosshair_style: 0,
             text_renderer: None,
+            audio: None,
             player_model: PlayerModel::new(),
             player2_model: PlayerModel::new(),
             rocket_model: None,
@@ -205,7 +357,7 @@ impl GameApp {
             depth_texture: None,
             depth_view: None,
             start_time: now,
-            last_frame_time: now,
+            game_loop: GameLoop::new(SIM_TICK_RATE),
             last_fps_update: now,
             frame_count: 0,
             fps: 0.0,
@@ -214,19 +366,21 @@ impl GameApp {
             world,
             local_player_id,
             
-            move_left: false,
-            move_right: false,
-            jump_pressed: false,
-            crouch_pressed: false,
-            shoot_pressed: false,
-            is_shooting: false,
-            shoot_anim_start_time: 0.0,
+            input: InputState::new(),
+            console: Console::new(),
+            #[cfg(feature = "gamepad")]
+            gamepad: Gamepad::new().map_err(|e| println!("{}", e)).ok(),
             
-            player2_gesture_start_time: 0.0,
-            player2_is_gesturing: false,
+            player2_anim: AnimState::new(),
             player2_next_gesture_time: 5.0,
             
             camera: Camera::new(),
+            spectator_camera: SpectatorCamera::new(),
+            spectator_mode: false,
+            hud_feedback: HudFeedback::new(0),
+            effects: Effects::new(),
+            manual_frame: ManualFrame::default(),
+            sim_time: 0.0,
             camera_move_z_neg: false,
             camera_move_z_pos: false,
             camera_pitch_up: false,
@@ -237,6 +391,7 @@ impl GameApp {
             aim_x: 1.0,
             aim_y: 0.0,
             last_mouse_pos: (0.0, 0.0),
+            cursor_grabbed: false,
             
             current_legs_yaw: 0.0,
             player2_legs_yaw: 0.0,
@@ -248,42 +403,225 @@ impl GameApp {
             ],
             current_model_index: 0,
             shift_pressed: false,
+            
+            net_client,
+            snapshot_buffer: sas2::net::SnapshotBuffer::with_delay(interpolation_delay),
+            remote_players: Vec::new(),
+            synth_sfx: args.iter().any(|a| a == "--synth-sfx"),
+            model_queue: ModelLoadQueue::new(2),
+            queued_model_parts: HashMap::new(),
+            staged_parts: Vec::new(),
+            tag_validator: TagValidator::new(args.iter().any(|a| a == "--validate-tags")),
+            dump_scene: false,
+            show_debug_lights: false,
+            show_scene_stats: false,
+            take_screenshot: false,
+            muzzle_flash_age: None,
         }
     }
 
     fn create_depth(&mut self) {
         if let Some(ref wgpu_renderer) = self.wgpu_renderer {
-            let (width, height) = wgpu_renderer.get_surface_size();
-            let depth_texture = wgpu_renderer
-                .device
-                .create_texture(&wgpu::TextureDescriptor {
-                    label: Some("Depth Texture"),
-                    size: wgpu::Extent3d {
-                        width,
-                        height,
-                        depth_or_array_layers: 1,
-                    },
-                    mip_level_count: 1,
-                    sample_count: 1,
-                    dimension: wgpu::TextureDimension::D2,
-                    format: wgpu::TextureFormat::Depth24PlusStencil8,
-                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-                    view_formats: &[],
-                });
+            let depth_texture = wgpu_renderer.create_depth_texture();
             let depth_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
             self.depth_texture = Some(depth_texture);
             self.depth_view = Some(depth_view);
         }
     }
 
-    fn load_model_part(paths: &[&str]) -> Option<MD3Model> {
-        paths
-            .iter()
-            .find(|p| std::path::Path::new(p).exists())
-            .and_then(|path| {
-                println!("Loading model: {}", path);
-                MD3Model::load(path).ok()
-            })
+    fn queue_model_part(&mut self, relative: &str, slot: ModelSlot, model_name: &'static str, part: &'static str) {
+        match resolve(relative, &[]) {
+            Some(path) => {
+                println!("Queueing model: {}", path.display());
+                self.queued_model_parts.insert(path.clone(), QueuedPart { slot, model_name, part });
+                self.model_queue.enqueue(path);
+            }
+            None => println!("WARNING: No model file found for {} {}", model_name, part),
+        }
+    }
+
+    fn poll_model_queue(&mut self) {
+        let completed = self.model_queue.poll();
+        if completed.is_empty() {
+            return;
+        }
+
+        let (wgpu_renderer, md3_renderer) = match (self.wgpu_renderer.as_mut(), self.md3_renderer.as_mut()) {
+            (Some(w), Some(m)) => (w, m),
+            _ => return,
+        };
+
+        let mut local_model = None;
+        for loaded in completed {
+            // Loads for a model that was switched away from are no longer tracked
+            let Some(queued) = self.queued_model_parts.remove(&loaded.path) else {
+                continue;
+            };
+            let model = match loaded.result {
+                Ok(model) => model,
+                Err(e) => {
+                    println!("WARNING: Failed to load {}: {}", loaded.path.display(), e);
+                    continue;
+                }
+            };
+            println!("Loaded model: {}", loaded.path.display());
+
+            let textures = load_textures_for_model_static(wgpu_renderer, md3_renderer, &model, queued.model_name, queued.part);
+            match queued.slot {
+                ModelSlot::Opponent => self.player2_model.set_part(queued.part, Some(model), textures),
+                ModelSlot::Local => {
+                    local_model = Some(queued.model_name);
+                    self.staged_parts.push(StagedPart { part: queued.part, model, textures });
+                }
+            }
+        }
+
+        let local_pending = self.queued_model_parts.values().any(|queued| queued.slot == ModelSlot::Local);
+        if let (Some(model_name), false) = (local_model, local_pending) {
+            self.swap_in_staged_parts(model_name);
+        }
+    }
+
+    fn swap_in_staged_parts(&mut self, model_name: &str) {
+        if let Some(md3_renderer) = self.md3_renderer.as_mut() {
+            let parts = [&self.player_model.lower, &self.player_model.upper, &self.player_model.head];
+            for model in parts.into_iter().flatten() {
+                md3_renderer.clear_model_cache(model.id);
+            }
+        }
+
+        // Parts that failed to load stay empty rather than mixing in the previous model's
+        for part in ["lower", "upper", "head"] {
+            self.player_model.set_part(part, None, Vec::new());
+        }
+        for staged in self.staged_parts.drain(..) {
+            self.player_model.set_part(staged.part, Some(staged.model), staged.textures);
+        }
+        for (part, model) in [
+            ("lower", &self.player_model.lower),
+            ("upper", &self.player_model.upper),
+            ("head", &self.player_model.head),
+        ] {
+            if model.is_none() {
+                println!("WARNING: Failed to load {} model for {}", part, model_name);
+            }
+        }
+        self.player_model.anim_config = AnimConfig::load(model_name).ok();
+        self.player_model.skin = "default".to_string();
+        self.player_model.set_scale(None);
+    }
+
+    fn load_model_part(relative: &str) -> Option<MD3Model> {
+        resolve(relative, &[]).and_then(|path| {
+            println!("Loading model: {}", path.display());
+            MD3Model::load(&path)
+                .map_err(|e| println!("Failed to load model {}: {}", path.display(), e))
+                .ok()
+        })
+    }
+
+    fn sync_weapon_model(&mut self) {
+        let weapon = match self.world.player(self.local_player_id) {
+            Some(player) => player.weapon,
+            None => return,
+        };
+        if self.player_model.weapon_kind == Some(weapon) {
+            return;
+        }
+        if let (Some(wgpu_renderer), Some(md3_renderer)) = (self.wgpu_renderer.as_mut(), self.md3_renderer.as_mut()) {
+            if let Some(old) = self.player_model.weapon.take() {
+                unload_weapon(md3_renderer, old);
+            }
+            self.player_model.weapon = match load_weapon(wgpu_renderer, md3_renderer, weapon) {
+                Ok(model) => Some(model),
+                Err(e) => {
+                    println!("Failed to load weapon model: {}", e);
+                    None
+                }
+            };
+            self.player_model.weapon_kind = Some(weapon);
+        }
+    }
+
+    #[cfg(feature = "gamepad")]
+    fn poll_gamepad(&mut self, dt: f32) {
+        if let Some(ref mut gamepad) = self.gamepad {
+            gamepad.poll(dt);
+            self.input.apply_gamepad(gamepad);
+        }
+    }
+
+    // Raw mouse and right stick motion steer the aim, or the camera while spectating
+    fn apply_look(&mut self) {
+        let look = self.input.take_mouse_delta();
+        if look == glam::Vec2::ZERO {
+            return;
+        }
+        if self.spectator_mode {
+            self.camera.set_angles(self.camera.yaw + look.x, self.camera.pitch + look.y);
+            return;
+        }
+        self.aim_x += look.x;
+        self.aim_y += look.y;
+        let len = (self.aim_x * self.aim_x + self.aim_y * self.aim_y).sqrt();
+        if len > 0.0 {
+            self.aim_x /= len;
+            self.aim_y /= len;
+        }
+    }
+
+    // Key binding commands go to the input bindings, renderer toggles are handled here and everything
+    // else goes to the console
+    fn execute_command(&mut self, command: &str) -> String {
+        if KeyBindings::is_bind_command(command) {
+            match self.input.bindings.execute(command) {
+                Ok(()) => String::new(),
+                Err(e) => e,
+            }
+        } else if command.trim() == "r_facewinding" {
+            self.toggle_face_winding();
+            String::new()
+        } else if let Some(args) = command.trim().strip_prefix("model_scale") {
+            self.set_model_scale(args.trim())
+        } else {
+            self.console.execute(command)
+        }
+    }
+
+    // Runs a config file such as bindings.cfg line by line; a missing file is not an error
+    fn exec_config(&mut self, path: &str) {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return;
+        };
+        for line in content.lines() {
+            let line = line.split("//").next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let output = self.execute_command(line);
+            if !output.is_empty() {
+                println!("{}: {}", path, output);
+            }
+        }
+    }
+
+    fn set_cursor_grab(&mut self, grabbed: bool) {
+        let Some(ref window) = self.window else {
+            return;
+        };
+        if grabbed {
+            // Locked isn't supported on every platform; Confined still keeps the pointer in the window
+            let result = window.set_cursor_grab(CursorGrabMode::Locked)
+                .or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined));
+            if let Err(e) = result {
+                println!("Failed to grab cursor: {:?}", e);
+                return;
+            }
+        } else {
+            let _ = window.set_cursor_grab(CursorGrabMode::None);
+        }
+        window.set_cursor_visible(!grabbed);
+        self.cursor_grabbed = grabbed;
     }
 
     fn update_fps_counter(&mut self, now: Instant) {
@@ -294,119 +632,155 @@ impl GameApp {
             self.frame_count = 0;
             self.last_fps_update = now;
             if let Some(ref window) = self.window {
-                let player_x = self.world.players.get(self.local_player_id as usize).map(|p| p.x).unwrap_or(0.0);
-                window.set_title(&format!(
-                    "SAS2 MVP | FPS: {:.0} | X: {:.1}",
-                    self.fps, player_x
-                ));
+                let player_x = self.world.player(self.local_player_id).map(|p| p.x).unwrap_or(0.0);
+                let mut title = match self.manual_frame.frame() {
+                    Some(frame) => format!(
+                        "SAS2 MVP | FPS: {:.0} | X: {:.1} | Frame: {}/{}",
+                        self.fps, player_x, frame, self.manual_frame_count()
+                    ),
+                    None => format!("SAS2 MVP | FPS: {:.0} | X: {:.1}", self.fps, player_x),
+                };
+                if let Some(ref wgpu_renderer) = self.wgpu_renderer {
+                    title.push_str(&format!(" | {}", wgpu_renderer.adapter_summary()));
+                }
+                window.set_title(&title);
             }
         }
     }
 
-    fn frame_for_anim(anim: &AnimRange, time: f32, model: &MD3Model) -> usize {
-        let frames_passed = (time * anim.fps as f32).floor() as usize;
-        let max_index = model.header.num_bone_frames as usize;
-        if max_index == 0 {
-            return 0;
-        }
-        if anim.looping_frames == 0 {
-            let last = anim.num_frames.saturating_sub(1);
-            let frame = anim.first_frame + frames_passed.min(last);
-            return frame.min(max_index - 1);
+    fn manual_frame_count(&self) -> usize {
+        [&self.player_model.lower, &self.player_model.upper]
+            .iter()
+            .filter_map(|m| m.as_ref())
+            .map(|m| m.header.num_bone_frames as usize)
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn step_manual_frame(&mut self, delta: i32) {
+        let count = self.manual_frame_count();
+        self.manual_frame.step(delta, count);
+    }
+
+    fn manual_frame_for(frame: usize, model: &MD3Model) -> usize {
+        let num_frames = model.header.num_bone_frames as usize;
+        if num_frames == 0 {
+            0
+        } else {
+            frame.min(num_frames - 1)
         }
-        let loop_len = anim.looping_frames.min(anim.num_frames).max(1);
-        if frames_passed < anim.num_frames {
-            let frame = anim.first_frame + frames_passed;
-            return frame.min(max_index - 1);
+    }
+
+    fn find_tag<'a>(tags: &'a [sas2::engine::md3::Tag], name: &str) -> Option<&'a sas2::engine::md3::Tag> {
+        tags.iter().find(|t| {
+            let tag_name = std::str::from_utf8(&t.name).unwrap_or("");
+            tag_name.trim_end_matches('\0') == name
+        })
+    }
+
+    fn attach_tag(parent: &Orientation, model: &MD3Model, blend: FrameBlend, name: &str) -> Option<Orientation> {
+        let tag_a = model.tags.get(blend.from).and_then(|tags| Self::find_tag(tags, name))?;
+        let tag_b = model.tags.get(blend.to).and_then(|tags| Self::find_tag(tags, name)).unwrap_or(tag_a);
+        Some(attach_rotated_entity_interp(parent, tag_a, tag_b, blend.t))
+    }
+
+    fn toggle_sound_category(&mut self, category: SoundCategory) {
+        if let Some(ref mut audio) = self.audio {
+            let muted = audio.toggle_muted(category);
+            println!("Sound category '{}' {}", category.name(), if muted { "muted" } else { "unmuted" });
         }
-        let loop_start = anim.first_frame + anim.num_frames.saturating_sub(loop_len);
-        let loop_index = (frames_passed - anim.num_frames) % loop_len;
-        let frame = loop_start + loop_index;
-        frame.min(max_index - 1)
     }
 
-    fn calculate_legs_frame(
-        anim_config: &Option<AnimConfig>,
-        is_moving: bool,
-        is_moving_backward: bool,
-        animation_time: f32,
-        model: &MD3Model,
-        state: sas2::game::player::PlayerState,
-        _is_crouching: bool,
-    ) -> usize {
-        use sas2::game::player::PlayerState;
-        
-        if let Some(ref config) = anim_config {
-            let anim = match state {
-                PlayerState::Air => &config.legs_jump,
-                PlayerState::Crouching => {
-                    if is_moving {
-                        &config.legs_walkcr
-                    } else {
-                        &config.legs_idlecr
-                    }
-                }
-                PlayerState::Ground => {
-                    if is_moving_backward {
-                        &config.legs_back
-                    } else if is_moving {
-                        &config.legs_run
-                    } else {
-                        &config.legs_idle
-                    }
-                }
-            };
-            return Self::frame_for_anim(anim, animation_time, model);
-        } else {
-            0
+    fn toggle_reverb(&mut self) {
+        if let Some(ref mut audio) = self.audio {
+            let enabled = !audio.reverb_enabled();
+            audio.set_reverb(enabled, REVERB_MIX, REVERB_DECAY);
+            println!("Reverb {}", if enabled { "on" } else { "off" });
         }
     }
 
-    fn calculate_torso_frame(
-        anim_config: &Option<AnimConfig>,
-        elapsed_time: f32,
-        model: &MD3Model,
-        is_shooting: bool,
-        shoot_anim_time: f32,
-    ) -> usize {
-        if let Some(ref config) = anim_config {
-            let anim = if is_shooting {
-                &config.torso_attack
+    fn cycle_anisotropy(&mut self) {
+        let (Some(wgpu_renderer), Some(md3_renderer)) = (self.wgpu_renderer.as_mut(), self.md3_renderer.as_mut()) else {
+            return;
+        };
+        let next = ANISOTROPY_LEVELS
+            .iter()
+            .copied()
+            .find(|&level| level > wgpu_renderer.anisotropy())
+            .unwrap_or(ANISOTROPY_LEVELS[0]);
+        let anisotropy = wgpu_renderer.set_anisotropy(next);
+        md3_renderer.set_anisotropy(anisotropy);
+        println!("Anisotropic filtering {}x", anisotropy);
+    }
+
+    fn cycle_render_scale(&mut self) {
+        let Some(wgpu_renderer) = self.wgpu_renderer.as_mut() else {
+            return;
+        };
+        let next = RENDER_SCALES
+            .iter()
+            .copied()
+            .find(|&scale| scale > wgpu_renderer.render_scale())
+            .unwrap_or(RENDER_SCALES[0]);
+        let render_scale = wgpu_renderer.set_render_scale(next);
+        println!("Render scale {}%", (render_scale * 100.0).round());
+        self.create_depth();
+    }
+
+    fn toggle_msaa(&mut self) {
+        let (Some(wgpu_renderer), Some(md3_renderer)) = (self.wgpu_renderer.as_mut(), self.md3_renderer.as_mut()) else {
+            return;
+        };
+        let requested = if wgpu_renderer.sample_count() > 1 { 1 } else { 4 };
+        let sample_count = wgpu_renderer.set_sample_count(requested);
+        md3_renderer.set_sample_count(sample_count);
+        println!("MSAA {}x", sample_count);
+        self.create_depth();
+    }
+
+    fn toggle_fullbright(&mut self) {
+        if let Some(ref mut md3_renderer) = self.md3_renderer {
+            let mode = if md3_renderer.render_mode() == RenderMode::Fullbright {
+                RenderMode::Lit
             } else {
-                &config.torso_stand
+                RenderMode::Fullbright
             };
-            let time = if is_shooting { shoot_anim_time } else { elapsed_time };
-            return Self::frame_for_anim(anim, time, model);
-        } else {
-            0
+            md3_renderer.set_render_mode(mode);
+            println!("Render mode: {:?}", mode);
+        }
+    }
+
+    // "auto" fits the model to PLAYER_MODEL_HEIGHT, a number overrides that, nothing prints the scale
+    fn set_model_scale(&mut self, arg: &str) -> String {
+        match arg {
+            "" => {}
+            "auto" => self.player_model.set_scale(None),
+            value => match value.parse::<f32>() {
+                Ok(scale) if scale > 0.0 => self.player_model.set_scale(Some(scale)),
+                _ => return format!("Invalid model scale: {}", value),
+            },
         }
+        format!("Model scale: {:.3}", self.player_model.scale())
     }
 
-    fn calculate_torso_frame_with_gesture(
-        anim_config: &Option<AnimConfig>,
-        elapsed_time: f32,
-        model: &MD3Model,
-        is_gesturing: bool,
-        gesture_anim_time: f32,
-    ) -> usize {
-        if let Some(ref config) = anim_config {
-            let anim = if is_gesturing {
-                &config.torso_gesture
+    fn toggle_face_winding(&mut self) {
+        if let Some(ref mut md3_renderer) = self.md3_renderer {
+            let mode = if md3_renderer.render_mode() == RenderMode::FaceWinding {
+                RenderMode::Lit
             } else {
-                &config.torso_stand
+                RenderMode::FaceWinding
             };
-            let time = if is_gesturing { gesture_anim_time } else { elapsed_time };
-            return Self::frame_for_anim(anim, time, model);
-        } else {
-            0
+            md3_renderer.set_render_mode(mode);
+            println!("Render mode: {:?}", mode);
         }
     }
 
-    fn find_tag<'a>(tags: &'a [sas2::engine::md3::Tag], name: &str) -> Option<&'a sas2::engine::md3::Tag> {
-        tags.iter().find(|t| {
-            let tag_name = std::str::from_utf8(&t.name).unwrap_or("");
-            tag_name.trim_end_matches('\0') == name
-        })
+    fn toggle_floor_reflection(&mut self) {
+        if let Some(ref mut md3_renderer) = self.md3_renderer {
+            let reflectivity = if md3_renderer.floor_reflectivity() > 0.0 { 0.0 } else { 0.35 };
+            md3_renderer.set_floor_reflectivity(reflectivity);
+            println!("Floor reflection: {}", if reflectivity > 0.0 { "on" } else { "off" });
+        }
     }
 
     fn switch_player_model(&mut self) {
@@ -414,69 +788,106 @@ impl GameApp {
         let model_name = self.available_models[self.current_model_index];
         
         println!("Switching to model: {}", model_name);
-        
-        if let Some(ref mut md3_renderer) = self.md3_renderer.as_mut() {
-            md3_renderer.clear_model_cache();
-        }
-        
-        self.player_model.lower = None;
-        self.player_model.upper = None;
-        self.player_model.head = None;
-        self.player_model.lower_textures.clear();
-        self.player_model.upper_textures.clear();
-        self.player_model.head_textures.clear();
-        
-        self.player_model.lower = Self::load_model_part(&[
-            &format!("q3-resources/models/players/{}/lower.md3", model_name),
-            &format!("../q3-resources/models/players/{}/lower.md3", model_name),
-        ]);
-        self.player_model.upper = Self::load_model_part(&[
-            &format!("q3-resources/models/players/{}/upper.md3", model_name),
-            &format!("../q3-resources/models/players/{}/upper.md3", model_name),
-        ]);
-        self.player_model.head = Self::load_model_part(&[
-            &format!("q3-resources/models/players/{}/head.md3", model_name),
-            &format!("../q3-resources/models/players/{}/head.md3", model_name),
-        ]);
-        
-        if self.player_model.lower.is_none() {
-            println!("WARNING: Failed to load lower model for {}", model_name);
+
+        // The current model keeps drawing as a placeholder until every new part has loaded
+        self.queued_model_parts.retain(|_, queued| queued.slot != ModelSlot::Local);
+        self.staged_parts.clear();
+        for part in ["lower", "upper", "head"] {
+            self.queue_model_part(&format!("models/players/{}/{}.md3", model_name, part), ModelSlot::Local, model_name, part);
         }
-        if self.player_model.upper.is_none() {
-            println!("WARNING: Failed to load upper model for {}", model_name);
+        if !self.queued_model_parts.values().any(|queued| queued.slot == ModelSlot::Local) {
+            self.swap_in_staged_parts(model_name);
         }
-        if self.player_model.head.is_none() {
-            println!("WARNING: Failed to load head model for {}", model_name);
+
+        if let Some(ref window) = self.window {
+            window.set_title(&format!("SAS2 MVP | Model: {}", model_name));
         }
-        
-        self.player_model.anim_config = AnimConfig::load(model_name).ok();
-        
-        if let (Some(ref mut wgpu_renderer), Some(ref mut md3_renderer)) = 
+    }
+
+    fn set_active_skin(&mut self, skin: &str) {
+        let model_name = self.available_models[self.current_model_index];
+
+        // Only the texture map is rebuilt; the loaded meshes are reused
+        let skin_set = SkinSet::load_player(model_name, skin);
+        if let (Some(ref mut wgpu_renderer), Some(ref mut md3_renderer)) =
             (self.wgpu_renderer.as_mut(), self.md3_renderer.as_mut()) {
-            
+
             if let Some(ref lower) = self.player_model.lower {
                 self.player_model.lower_textures =
-                    load_textures_for_model_static(wgpu_renderer, md3_renderer, lower, model_name, "lower");
+                    load_textures_for_skin_set(wgpu_renderer, md3_renderer, lower, model_name, "lower", &skin_set);
             }
             if let Some(ref upper) = self.player_model.upper {
                 self.player_model.upper_textures =
-                    load_textures_for_model_static(wgpu_renderer, md3_renderer, upper, model_name, "upper");
+                    load_textures_for_skin_set(wgpu_renderer, md3_renderer, upper, model_name, "upper", &skin_set);
             }
             if let Some(ref head) = self.player_model.head {
                 self.player_model.head_textures =
-                    load_textures_for_model_static(wgpu_renderer, md3_renderer, head, model_name, "head");
+                    load_textures_for_skin_set(wgpu_renderer, md3_renderer, head, model_name, "head", &skin_set);
             }
         }
-        
-        if let Some(ref window) = self.window {
-            window.set_title(&format!("SAS2 MVP | Model: {}", model_name));
+
+        self.player_model.skin = skin.to_string();
+        println!("Active skin for {}: {}", model_name, skin);
+    }
+
+    fn cycle_skin(&mut self) {
+        let model_name = self.available_models[self.current_model_index];
+        let skins = list_available_skins(model_name);
+        if skins.is_empty() {
+            println!("No skins found for {}", model_name);
+            return;
         }
+
+        let next = skins.iter()
+            .position(|s| *s == self.player_model.skin)
+            .map(|i| (i + 1) % skins.len())
+            .unwrap_or(0);
+        let skin = skins[next].clone();
+        self.set_active_skin(&skin);
+    }
+
+    fn draw_loading_screen(
+        wgpu_renderer: &mut WgpuRenderer,
+        loading_screen: &LoadingScreen,
+        text_renderer: &TextRenderer,
+        progress: f32,
+        label: &str,
+    ) {
+        let frame = match wgpu_renderer.begin_frame() {
+            Ok(Some(f)) => f,
+            Ok(None) => return,
+            Err(e) => {
+                eprintln!("{}", e);
+                return;
+            }
+        };
+        let view = frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let (width, height) = wgpu_renderer.get_viewport_size();
+
+        let mut encoder = wgpu_renderer.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Loading Screen Encoder"),
+        });
+        loading_screen.render(&mut encoder, &view, &wgpu_renderer.queue, progress, width, height);
+        text_renderer.render_text(
+            &mut encoder,
+            &view,
+            label,
+            width as f32 * 0.2,
+            height as f32 * 0.5 - 48.0,
+            24.0,
+            [0.9, 0.9, 0.9, 1.0],
+            width,
+            height,
+        );
+
+        wgpu_renderer.queue.submit(Some(encoder.finish()));
+        wgpu_renderer.end_frame(frame);
     }
 
     fn calculate_model_bottom_offset(lower_model: Option<&MD3Model>, frame: usize) -> f32 {
         if let Some(model) = lower_model {
-            let min_z = model.get_min_z(frame);
-            -min_z
+            let (min_bounds, _) = model.frame_bounds(frame);
+            -min_bounds.z
         } else {
             0.0
         }
@@ -484,27 +895,27 @@ impl GameApp {
 
     fn render_player<'a>(
         encoder: &mut wgpu::CommandEncoder,
-        view: &wgpu::TextureView,
-        depth_view: &wgpu::TextureView,
+        target: RenderTarget,
         md3_renderer: &mut MD3Renderer,
-        surface_format: wgpu::TextureFormat,
         player_model: &'a PlayerModel,
         game_transform: Mat4,
         scale_mat: Mat4,
         lower_orientation: Orientation,
-        lower_frame: usize,
-        upper_frame: usize,
-        view_proj: Mat4,
-        camera_pos: Vec3,
-        lights: &[(Vec3, Vec3, f32)],
-        ambient: f32,
+        lower_frame: PoseBlend,
+        upper_frame: FrameBlend,
+        scene: SceneView,
         include_weapon: bool,
         aim_angle: f32,
         flip_x: bool,
         current_legs_yaw: &mut f32,
+        tag_validator: &mut TagValidator,
         dt: f32,
+        mut scene_dump: Option<&mut SceneDump>,
     ) -> (Option<Orientation>, Vec<(&'a MD3Model, usize, &'a [Option<String>], Mat4)>) {
         let mut shadow_models = Vec::new();
+        let mut draws: Vec<ModelDrawCall> = Vec::new();
+        let lower_key_frame = lower_frame.key_frame();
+        let upper_key_frame = upper_frame.key_frame();
         
         let pitch = if flip_x {
             std::f32::consts::PI - aim_angle
@@ -563,101 +974,74 @@ impl GameApp {
         let mut upper_orientation = lower_orientation_rotated;
         let mut head_orientation: Option<Orientation> = None;
         let mut weapon_orientation_result: Option<Orientation> = None;
+        let mut lower_node: Option<usize> = None;
+        let mut upper_node: Option<usize> = None;
 
         if let Some(ref lower) = player_model.lower {
             let md3_model_mat = scale_mat * orientation_to_mat4(&lower_orientation_rotated);
             let model_mat = game_transform * md3_model_mat;
-            md3_renderer.render_model(
-                encoder,
-                view,
-                depth_view,
-                surface_format,
-                lower,
-                lower_frame,
-                &player_model.lower_textures,
-                model_mat,
-                view_proj,
-                camera_pos,
-                lights,
-                ambient,
-                false,
-            );
-            shadow_models.push((lower, lower_frame, player_model.lower_textures.as_slice(), model_mat));
-
-            if let Some(tags) = lower.tags.get(lower_frame) {
-                if let Some(torso_tag) = Self::find_tag(tags, "tag_torso") {
-                    upper_orientation = attach_rotated_entity(&lower_orientation_rotated, torso_tag);
-                    
-                    // Apply Torso Twist in MD3 coordinates
-                    // torso_yaw around Z (vertical in MD3)
-                    // torso_pitch around Y (left in MD3) - follows aim up/down
-                    // torso_roll around X (forward in MD3)
-                    let twist = Mat3::from_rotation_z(torso_yaw);
-                    let pitch_rot = Mat3::from_rotation_y(torso_pitch);
-                    let roll = Mat3::from_rotation_x(torso_roll_extra);
-                    
-                    let torso_local_rot = twist * pitch_rot * roll;
-                    
-                    let base_mat = Mat3::from_cols(upper_orientation.axis[0], upper_orientation.axis[1], upper_orientation.axis[2]);
-                    let new_mat = base_mat * torso_local_rot;
-                    upper_orientation.axis = [new_mat.x_axis, new_mat.y_axis, new_mat.z_axis];
-                }
+            draws.push(ModelDrawCall { model: lower, pose: lower_frame, textures: player_model.lower_textures.as_slice(), matrix: model_mat });
+            shadow_models.push((lower, lower_key_frame, player_model.lower_textures.as_slice(), model_mat));
+            lower_node = scene_dump.as_deref_mut()
+                .map(|dump| dump.add("lower", None, None, lower, lower_key_frame, model_mat));
+
+            if let Some(torso_orientation) = Self::attach_tag(&lower_orientation_rotated, lower, lower_frame.dominant(), "tag_torso") {
+                upper_orientation = torso_orientation;
+                tag_validator.check("tag_torso", lower_key_frame, &upper_orientation);
+                
+                // Apply Torso Twist in MD3 coordinates
+                // torso_yaw around Z (vertical in MD3)
+                // torso_pitch around Y (left in MD3) - follows aim up/down
+                // torso_roll around X (forward in MD3)
+                let twist = Mat3::from_rotation_z(torso_yaw);
+                let pitch_rot = Mat3::from_rotation_y(torso_pitch);
+                let roll = Mat3::from_rotation_x(torso_roll_extra);
+                
+                let torso_local_rot = twist * pitch_rot * roll;
+                
+                let base_mat = Mat3::from_cols(upper_orientation.axis[0], upper_orientation.axis[1], upper_orientation.axis[2]);
+                let new_mat = base_mat * torso_local_rot;
+                upper_orientation.axis = [new_mat.x_axis, new_mat.y_axis, new_mat.z_axis];
             }
         }
 
         if let Some(ref upper) = player_model.upper {
             let md3_model_mat = scale_mat * orientation_to_mat4(&upper_orientation);
             let model_mat = game_transform * md3_model_mat;
-            md3_renderer.render_model(
-                encoder,
-                view,
-                depth_view,
-                surface_format,
-                upper,
-                upper_frame,
-                &player_model.upper_textures,
-                model_mat,
-                view_proj,
-                camera_pos,
-                lights,
-                ambient,
-                false,
-            );
-            shadow_models.push((upper, upper_frame, player_model.upper_textures.as_slice(), model_mat));
-
-            if let Some(tags) = upper.tags.get(upper_frame) {
-                if let Some(head_tag) = Self::find_tag(tags, "tag_head") {
-                    head_orientation = Some(attach_rotated_entity(&upper_orientation, head_tag));
-                    
-                    // Apply Head Rotation for aiming in MD3 coordinates
-                    // In MD3: Z is up, X is forward, Y is left
-                    // Pitch (looking up/down) rotates around Y axis
+            draws.push(ModelDrawCall { model: upper, pose: upper_frame.into(), textures: player_model.upper_textures.as_slice(), matrix: model_mat });
+            shadow_models.push((upper, upper_key_frame, player_model.upper_textures.as_slice(), model_mat));
+            upper_node = scene_dump.as_deref_mut()
+                .map(|dump| dump.add("upper", lower_node, Some("tag_torso"), upper, upper_key_frame, model_mat));
+
+            head_orientation = Self::attach_tag(&upper_orientation, upper, upper_frame, "tag_head");
+            if let Some(ref mut orient) = head_orientation {
+                tag_validator.check("tag_head", upper_key_frame, orient);
+                
+                // Apply Head Rotation for aiming in MD3 coordinates
+                // In MD3: Z is up, X is forward, Y is left
+                // Pitch (looking up/down) rotates around Y axis
+                
+                let head_pitch = pitch.clamp(-1.2, 1.2);
+                let head_rot = Mat3::from_rotation_y(head_pitch);
+                
+                let base = Mat3::from_cols(orient.axis[0], orient.axis[1], orient.axis[2]);
+                let new_mat = base * head_rot;
+                orient.axis = [new_mat.x_axis, new_mat.y_axis, new_mat.z_axis];
+            }
+            if include_weapon {
+                weapon_orientation_result = Self::attach_tag(&upper_orientation, upper, upper_frame, "tag_weapon");
+                if let Some(ref mut orient) = weapon_orientation_result {
+                    tag_validator.check("tag_weapon", upper_key_frame, orient);
                     
-                    let head_pitch = pitch.clamp(-1.2, 1.2);
-                    let head_rot = Mat3::from_rotation_y(head_pitch);
+                    // Apply Weapon Rotation (Pitch) in MD3 coordinates
+                    // Rotate around Y axis for pitch
+                    // Limit weapon pitch to avoid excessive rotation
+                    let weapon_pitch = (pitch * 0.7).clamp(-1.0, 1.0);
+                    let weapon_rot = Mat3::from_rotation_y(weapon_pitch);
                     
-                    if let Some(ref mut orient) = head_orientation {
-                         let base = Mat3::from_cols(orient.axis[0], orient.axis[1], orient.axis[2]);
-                         let new_mat = base * head_rot;
-                         orient.axis = [new_mat.x_axis, new_mat.y_axis, new_mat.z_axis];
-                    }
-                }
-                if include_weapon {
-                    if let Some(weapon_tag) = Self::find_tag(tags, "tag_weapon") {
-                        weapon_orientation_result = Some(attach_rotated_entity(&upper_orientation, weapon_tag));
-                        
-                        // Apply Weapon Rotation (Pitch) in MD3 coordinates
-                        // Rotate around Y axis for pitch
-                        // Limit weapon pitch to avoid excessive rotation
-                        let weapon_pitch = (pitch * 0.7).clamp(-1.0, 1.0);
-                        let weapon_rot = Mat3::from_rotation_y(weapon_pitch);
-                        
-                        if let Some(ref mut orient) = weapon_orientation_result {
-                             let base = Mat3::from_cols(orient.axis[0], orient.axis[1], orient.axis[2]);
-                             let new_mat = base * weapon_rot;
-                             orient.axis = [new_mat.x_axis, new_mat.y_axis, new_mat.z_axis];
-                        }
-                    }
+                    let base = Mat3::from_cols(orient.axis[0], orient.axis[1], orient.axis[2]);
+                    let new_mat = base * weapon_rot;
+                    orient.axis = [new_mat.x_axis, new_mat.y_axis, new_mat.z_axis];
                 }
             }
         }
@@ -665,47 +1049,38 @@ impl GameApp {
         if let (Some(ref head), Some(head_orient)) = (&player_model.head, head_orientation) {
             let md3_model_mat = scale_mat * orientation_to_mat4(&head_orient);
             let model_mat = game_transform * md3_model_mat;
-            md3_renderer.render_model(
-                encoder,
-                view,
-                depth_view,
-                surface_format,
-                head,
-                0,
-                &player_model.head_textures,
-                model_mat,
-                view_proj,
-                camera_pos,
-                lights,
-                ambient,
-                false,
-            );
+            draws.push(ModelDrawCall { model: head, pose: FrameBlend::fixed(0).into(), textures: player_model.head_textures.as_slice(), matrix: model_mat });
             shadow_models.push((head, 0, player_model.head_textures.as_slice(), model_mat));
+            if let Some(dump) = scene_dump.as_deref_mut() {
+                dump.add("head", upper_node, Some("tag_head"), head, 0, model_mat);
+            }
         }
 
         if include_weapon {
             if let (Some(ref weapon), Some(weapon_orient)) = (&player_model.weapon, weapon_orientation_result) {
                 let md3_model_mat = scale_mat * orientation_to_mat4(&weapon_orient);
                 let model_mat = game_transform * md3_model_mat;
-                md3_renderer.render_model(
-                    encoder,
-                    view,
-                    depth_view,
-                    surface_format,
-                    weapon,
-                    0,
-                    &player_model.weapon_textures,
-                    model_mat,
-                    view_proj,
-                    camera_pos,
-                    lights,
-                    ambient,
-                    false,
-                );
-                shadow_models.push((weapon, 0, player_model.weapon_textures.as_slice(), model_mat));
+                draws.push(ModelDrawCall { model: &weapon.main.model, pose: FrameBlend::fixed(0).into(), textures: weapon.main.textures.as_slice(), matrix: model_mat });
+                shadow_models.push((&weapon.main.model, 0, weapon.main.textures.as_slice(), model_mat));
+                let weapon_node = scene_dump.as_deref_mut()
+                    .map(|dump| dump.add("weapon", upper_node, Some("tag_weapon"), &weapon.main.model, 0, model_mat));
+
+                let barrel_tag = weapon.main.model.tags.first()
+                    .and_then(|tags| Self::find_tag(tags, "tag_barrel"));
+                if let (Some(barrel), Some(barrel_tag)) = (&weapon.barrel, barrel_tag) {
+                    let barrel_orient = attach_rotated_entity(&weapon_orient, barrel_tag);
+                    let model_mat = game_transform * scale_mat * orientation_to_mat4(&barrel_orient);
+                    draws.push(ModelDrawCall { model: &barrel.model, pose: FrameBlend::fixed(0).into(), textures: barrel.textures.as_slice(), matrix: model_mat });
+                    shadow_models.push((&barrel.model, 0, barrel.textures.as_slice(), model_mat));
+                    if let Some(dump) = scene_dump {
+                        dump.add("barrel", weapon_node, Some("tag_barrel"), &barrel.model, 0, model_mat);
+                    }
+                }
             }
         }
 
+        md3_renderer.render_models(encoder, target, &draws, scene, false);
+
         (weapon_orientation_result, shadow_models)
     }
 }
@@ -721,97 +1096,59 @@ impl ApplicationHandler for GameApp {
             .with_inner_size(winit::dpi::LogicalSize::new(1280.0, 720.0));
         let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
 
-        let mut wgpu_renderer = WgpuRenderer::new(window.clone()).block_on().unwrap();
+        let mut wgpu_renderer = match WgpuRenderer::new(window.clone(), 4).block_on() {
+            Ok(renderer) => renderer,
+            Err(e) => {
+                eprintln!("{}", e);
+                event_loop.exit();
+                return;
+            }
+        };
         let mut md3_renderer =
             MD3Renderer::new(wgpu_renderer.device.clone(), wgpu_renderer.queue.clone());
+        md3_renderer.set_shadow_support(wgpu_renderer.shadow_support());
+        md3_renderer.set_sample_count(wgpu_renderer.sample_count());
+        md3_renderer.set_anisotropy(wgpu_renderer.anisotropy());
         
         md3_renderer.load_map_tiles(&self.world.map);
+        let skybox_faces = skybox_face_paths(SKYBOX_NAME);
+        md3_renderer.set_skybox(skybox_faces.each_ref().map(String::as_str));
         
-        let crosshair_renderer = sas2::engine::renderer::crosshair::Crosshair::new(
-            &wgpu_renderer.device,
-            wgpu_renderer.surface_config.format,
-        );
         let text_renderer = TextRenderer::new(
             wgpu_renderer.device.clone(),
             wgpu_renderer.queue.clone(),
             wgpu_renderer.surface_config.format,
         );
+        let loading_screen = LoadingScreen::new(&wgpu_renderer.device, wgpu_renderer.surface_config.format);
 
-        self.player_model.lower = Self::load_model_part(&[
-            "q3-resources/models/players/sarge/lower.md3",
-            "../q3-resources/models/players/sarge/lower.md3",
-        ]);
-        if let Some(ref lower) = self.player_model.lower {
-            let (min_x, max_x, min_y, max_y, min_z, max_z) = lower.get_bounds(0);
-            let height = max_z - min_z;
-            let width = max_y - min_y;
-            println!("Lower model bounds - Height (Z): {:.2}, Width (Y): {:.2}, Depth (X): {:.2}", height, width, max_x - min_x);
+        let mut unique_item_types = HashSet::new();
+        for item in &self.world.map.items {
+            unique_item_types.insert(item.item_type);
         }
+        // Player and rocket models, their textures plus the weapon, items with the two markers, and sounds
+        let mut progress = LoadProgress::new(4 + 5 + unique_item_types.len() + 2 + 1);
+        Self::draw_loading_screen(&mut wgpu_renderer, &loading_screen, &text_renderer, progress.fraction(), "Loading models...");
+
+        self.player_model.lower = Self::load_model_part("models/players/sarge/lower.md3");
         
-        self.player_model.upper = Self::load_model_part(&[
-            "q3-resources/models/players/sarge/upper.md3",
-            "../q3-resources/models/players/sarge/upper.md3",
-        ]);
-        if let Some(ref upper) = self.player_model.upper {
-            let (min_x, max_x, min_y, max_y, min_z, max_z) = upper.get_bounds(0);
-            let height = max_z - min_z;
-            let width = max_y - min_y;
-            println!("Upper model bounds - Height (Z): {:.2}, Width (Y): {:.2}, Depth (X): {:.2}", height, width, max_x - min_x);
-        }
+        self.player_model.upper = Self::load_model_part("models/players/sarge/upper.md3");
         
-        self.player_model.head = Self::load_model_part(&[
-            "q3-resources/models/players/sarge/head.md3",
-            "../q3-resources/models/players/sarge/head.md3",
-        ]);
-        if let Some(ref head) = self.player_model.head {
-            let (min_x, max_x, min_y, max_y, min_z, max_z) = head.get_bounds(0);
-            let height = max_z - min_z;
-            let width = max_y - min_y;
-            println!("Head model bounds - Height (Z): {:.2}, Width (Y): {:.2}, Depth (X): {:.2}", height, width, max_x - min_x);
-        }
+        self.player_model.head = Self::load_model_part("models/players/sarge/head.md3");
         
-        if let (Some(ref lower), Some(ref upper), Some(ref head)) = (&self.player_model.lower, &self.player_model.upper, &self.player_model.head) {
-            let (lower_min_x, lower_max_x, lower_min_y, lower_max_y, lower_min_z, lower_max_z) = lower.get_bounds(0);
-            let (upper_min_x, upper_max_x, upper_min_y, upper_max_y, upper_min_z, upper_max_z) = upper.get_bounds(0);
-            let (head_min_x, head_max_x, head_min_y, head_max_y, head_min_z, head_max_z) = head.get_bounds(0);
-            
-            let total_min_z = lower_min_z.min(upper_min_z).min(head_min_z);
-            let total_max_z = lower_max_z.max(upper_max_z).max(head_max_z);
-            let total_min_y = lower_min_y.min(upper_min_y).min(head_min_y);
-            let total_max_y = lower_max_y.max(upper_max_y).max(head_max_y);
-            
-            let total_height = total_max_z - total_min_z;
-            let total_width = total_max_y - total_min_y;
-            println!("Total player model - Height: {:.2}, Width: {:.2}", total_height, total_width);
-        }
-        self.player_model.weapon = Self::load_model_part(&[
-            "q3-resources/models/weapons2/rocketl/rocketl.md3",
-            "../q3-resources/models/weapons2/rocketl/rocketl.md3",
-        ]);
-
-        self.player2_model.lower = Self::load_model_part(&[
-            "q3-resources/models/players/orbb/lower.md3",
-            "../q3-resources/models/players/orbb/lower.md3",
-        ]);
-        self.player2_model.upper = Self::load_model_part(&[
-            "q3-resources/models/players/orbb/upper.md3",
-            "../q3-resources/models/players/orbb/upper.md3",
-        ]);
-        self.player2_model.head = Self::load_model_part(&[
-            "q3-resources/models/players/orbb/head.md3",
-            "../q3-resources/models/players/orbb/head.md3",
-        ]);
-
-        self.rocket_model = Self::load_model_part(&[
-            "q3-resources/models/ammo/rocket/rocket.md3",
-            "../q3-resources/models/ammo/rocket/rocket.md3",
-        ]);
+        self.queue_model_part("models/players/orbb/lower.md3", ModelSlot::Opponent, PLAYER2_MODEL_NAME, "lower");
+        self.queue_model_part("models/players/orbb/upper.md3", ModelSlot::Opponent, PLAYER2_MODEL_NAME, "upper");
+        self.queue_model_part("models/players/orbb/head.md3", ModelSlot::Opponent, PLAYER2_MODEL_NAME, "head");
+        progress.add_total(self.model_queue.pending());
+
+        self.rocket_model = Self::load_model_part("models/ammo/rocket/rocket.md3");
 
         self.player_model.anim_config = AnimConfig::load("sarge").ok();
         self.player2_model.anim_config = AnimConfig::load("orbb").ok();
 
         let surface_format = wgpu_renderer.surface_config.format;
         md3_renderer.create_pipeline(surface_format);
+        progress.advance(4);
+        Self::draw_loading_screen(&mut wgpu_renderer, &loading_screen, &text_renderer, progress.fraction(), "Loading player textures...");
 
         if let Some(ref lower) = self.player_model.lower {
             self.player_model.lower_textures =
@@ -825,39 +1162,33 @@ impl ApplicationHandler for GameApp {
             self.player_model.head_textures =
                 load_textures_for_model_static(&mut wgpu_renderer, &mut md3_renderer, head, "sarge", "head");
         }
-        if let Some(ref weapon) = self.player_model.weapon {
-            self.player_model.weapon_textures =
-                load_weapon_textures_static(&mut wgpu_renderer, &mut md3_renderer, weapon);
-        }
-
-        if let Some(ref lower) = self.player2_model.lower {
-            self.player2_model.lower_textures =
-                load_textures_for_model_static(&mut wgpu_renderer, &mut md3_renderer, lower, "orbb", "lower");
-        }
-        if let Some(ref upper) = self.player2_model.upper {
-            self.player2_model.upper_textures =
-                load_textures_for_model_static(&mut wgpu_renderer, &mut md3_renderer, upper, "orbb", "upper");
-        }
-        if let Some(ref head) = self.player2_model.head {
-            self.player2_model.head_textures =
-                load_textures_for_model_static(&mut wgpu_renderer, &mut md3_renderer, head, "orbb", "head");
-        }
+        let initial_weapon = self.world
+            .player(self.local_player_id)
+            .map(|player| player.weapon)
+            .unwrap_or(Weapon::RocketLauncher);
+        self.player_model.weapon_kind = Some(initial_weapon);
+        self.player_model.weapon = load_weapon(&mut wgpu_renderer, &mut md3_renderer, initial_weapon)
+            .map_err(|e| println!("Failed to load weapon model: {}", e))
+            .ok();
+
+        progress.advance(4);
+        Self::draw_loading_screen(&mut wgpu_renderer, &loading_screen, &text_renderer, progress.fraction(), "Loading projectile textures...");
 
         if let Some(ref rocket) = self.rocket_model {
             self.rocket_textures =
                 load_rocket_textures_static(&mut wgpu_renderer, &mut md3_renderer, rocket);
         }
 
-        let mut unique_item_types = HashSet::new();
-        for item in &self.world.map.items {
-            unique_item_types.insert(item.item_type);
-        }
+        progress.advance(1);
+        Self::draw_loading_screen(&mut wgpu_renderer, &loading_screen, &text_renderer, progress.fraction(), "Loading items...");
+
         for item_type in unique_item_types {
             let model_path = Self::item_model_path(item_type);
             let scale = Self::item_model_scale(item_type);
             if let Some(model) = Self::load_static_model(&mut wgpu_renderer, &mut md3_renderer, model_path, scale) {
                 self.item_models.insert(item_type, model);
             }
+            progress.advance(1);
         }
 
         self.teleporter_marker = Self::load_static_model(
@@ -874,17 +1205,66 @@ impl ApplicationHandler for GameApp {
             0.6,
         );
 
+        progress.advance(2);
+        Self::draw_loading_screen(&mut wgpu_renderer, &loading_screen, &text_renderer, progress.fraction(), "Loading sounds...");
+
         self.window = Some(window.clone());
         self.wgpu_renderer = Some(wgpu_renderer);
         self.md3_renderer = Some(md3_renderer);
-        self.crosshair_renderer = Some(crosshair_renderer);
         self.text_renderer = Some(text_renderer);
+
+        let mut audio = AudioSystem::new_or_silent();
+        // Nothing would be heard without a device, so skip decoding
+        if audio.is_enabled() {
+            audio.set_synth_fallback(self.synth_sfx);
+            let missing: Vec<_> = audio
+                .load_all_sounds()
+                .into_iter()
+                .filter_map(|(name, result)| result.err().map(|e| (name, e)))
+                .collect();
+            if !missing.is_empty() {
+                eprintln!("{} sounds missing:", missing.len());
+                for (name, e) in &missing {
+                    eprintln!("  {}: {}", name, e);
+                }
+            }
+            match audio.load_streaming_sound(ARENA_MUSIC, ARENA_MUSIC_PATH, SoundCategory::Music) {
+                Ok(()) => audio.play_music(ARENA_MUSIC, true),
+                Err(e) => eprintln!("Failed to load music {}: {}", ARENA_MUSIC_PATH, e),
+            }
+        }
+        self.audio = Some(audio);
+        progress.advance(1);
+
+        // Keep the loading screen up until the background model loads have landed
+        while !self.model_queue.is_idle() {
+            let pending = self.model_queue.pending();
+            self.poll_model_queue();
+            progress.advance(pending - self.model_queue.pending());
+            if let (Some(wgpu_renderer), Some(text_renderer)) = (self.wgpu_renderer.as_mut(), self.text_renderer.as_ref()) {
+                Self::draw_loading_screen(wgpu_renderer, &loading_screen, text_renderer, progress.fraction(), "Loading models...");
+            }
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
         self.create_depth();
-        self.last_frame_time = Instant::now();
+        self.game_loop.reset();
 
         window.request_redraw();
     }
 
+    fn device_event(
+        &mut self,
+        _event_loop: &ActiveEventLoop,
+        _device_id: winit::event::DeviceId,
+        event: winit::event::DeviceEvent,
+    ) {
+        if let winit::event::DeviceEvent::MouseMotion { delta: (dx, dy) } = event {
+            if self.cursor_grabbed {
+                self.input.handle_mouse_motion(dx, dy);
+            }
+        }
+    }
+
     fn window_event(
         &mut self,
         event_loop: &ActiveEventLoop,
@@ -901,37 +1281,139 @@ impl ApplicationHandler for GameApp {
                     self.create_depth();
                 }
             }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                if let Some(ref mut wgpu_renderer) = self.wgpu_renderer {
+                    wgpu_renderer.set_scale_factor(scale_factor);
+                    self.create_depth();
+                }
+            }
             WindowEvent::KeyboardInput { event, .. } => {
                 let pressed = event.state == winit::event::ElementState::Pressed;
                 if let PhysicalKey::Code(code) = event.physical_key {
+                    // Bound keys take precedence over the debug keys below
+                    if let Some(action) = self.input.bindings.action(code) {
+                        if pressed {
+                            self.input.handle_key_press(code);
+                            if action == Action::SwitchModel && !event.repeat {
+                                self.switch_player_model();
+                            }
+                        } else {
+                            self.input.handle_key_release(code);
+                        }
+                        return;
+                    }
                     match code {
-                        KeyCode::KeyA => self.move_left = pressed,
-                        KeyCode::KeyD => self.move_right = pressed,
-                        KeyCode::KeyW => self.jump_pressed = pressed,
-                        KeyCode::KeyS => self.crouch_pressed = pressed,
                         KeyCode::KeyR => self.camera_move_z_neg = pressed,
                         KeyCode::KeyF => self.camera_move_z_pos = pressed,
                         KeyCode::ArrowUp => self.camera_pitch_up = pressed,
                         KeyCode::ArrowDown => self.camera_pitch_down = pressed,
                         KeyCode::ArrowLeft => self.camera_yaw_left = pressed,
                         KeyCode::ArrowRight => self.camera_yaw_right = pressed,
-                        KeyCode::Space => {
-                            self.shoot_pressed = pressed;
-                        }
                         KeyCode::ShiftLeft | KeyCode::ShiftRight => {
                             self.shift_pressed = pressed;
                         }
+                        KeyCode::BracketLeft if pressed => self.step_manual_frame(-1),
+                        KeyCode::BracketRight if pressed => self.step_manual_frame(1),
+                        KeyCode::KeyN if pressed => self.manual_frame.toggle(),
+                        KeyCode::KeyP if pressed => {
+                            let paused = self.game_loop.toggle_pause();
+                            println!("Simulation {}", if paused { "frozen" } else { "resumed" });
+                        }
+                        KeyCode::Minus if pressed && self.shift_pressed => {
+                            let scale = (self.player_model.scale() / 1.1).max(0.01);
+                            self.player_model.set_scale(Some(scale));
+                            println!("Model scale: {:.3}", scale);
+                        }
+                        KeyCode::Equal if pressed && self.shift_pressed => {
+                            let scale = (self.player_model.scale() * 1.1).min(100.0);
+                            self.player_model.set_scale(Some(scale));
+                            println!("Model scale: {:.3}", scale);
+                        }
+                        KeyCode::Minus if pressed => {
+                            let time_scale = (self.world.time_scale() * 0.5).max(0.125);
+                            self.world.set_time_scale(time_scale);
+                            println!("Time scale: {:.3}", time_scale);
+                        }
+                        KeyCode::Equal if pressed => {
+                            let time_scale = (self.world.time_scale() * 2.0).min(4.0);
+                            self.world.set_time_scale(time_scale);
+                            println!("Time scale: {:.3}", time_scale);
+                        }
+                        KeyCode::KeyC if pressed => {
+                            if let Some(ref md3_renderer) = self.md3_renderer {
+                                self.crosshair_style = (self.crosshair_style + 1) % md3_renderer.crosshair_style_count().max(1);
+                                println!("Crosshair style: {}", self.crosshair_style);
+                            }
+                        }
+                        KeyCode::F3 if pressed && self.shift_pressed => {
+                            self.show_debug_lights = !self.show_debug_lights;
+                            println!("Light debug: {}", if self.show_debug_lights { "on" } else { "off" });
+                        }
+                        KeyCode::F3 if pressed => self.dump_scene = true,
+                        KeyCode::F4 if pressed => {
+                            self.cycle_skin();
+                        }
                         KeyCode::F5 if pressed && self.shift_pressed => {
                             self.switch_player_model();
                         }
-                        KeyCode::Escape if pressed => event_loop.exit(),
+                        KeyCode::F6 if pressed => {
+                            self.spectator_mode = !self.spectator_mode;
+                            if self.spectator_mode {
+                                self.spectator_camera.camera.x = self.camera.x;
+                                self.spectator_camera.camera.y = self.camera.y;
+                                self.spectator_camera.camera.z = self.camera.z;
+                            }
+                        }
+                        KeyCode::F7 if pressed => self.toggle_sound_category(SoundCategory::Weapons),
+                        KeyCode::F8 if pressed => self.toggle_sound_category(SoundCategory::Announcer),
+                        KeyCode::F9 if pressed => self.toggle_sound_category(SoundCategory::Player),
+                        KeyCode::F10 if pressed && self.shift_pressed => self.toggle_face_winding(),
+                        KeyCode::F10 if pressed => self.toggle_reverb(),
+                        KeyCode::F2 if pressed && self.shift_pressed => self.cycle_render_scale(),
+                        KeyCode::F2 if pressed => self.cycle_anisotropy(),
+                        KeyCode::F1 if pressed && self.shift_pressed => self.show_scene_stats = !self.show_scene_stats,
+                        KeyCode::F1 if pressed => self.toggle_msaa(),
+                        KeyCode::F11 if pressed && self.shift_pressed => self.toggle_floor_reflection(),
+                        KeyCode::F11 if pressed => self.toggle_fullbright(),
+                        KeyCode::F12 if pressed => self.take_screenshot = true,
+                        KeyCode::Escape if pressed && !event.repeat => {
+                            self.set_cursor_grab(!self.cursor_grabbed);
+                        }
                         _ => {}
                     }
                 }
             }
+            WindowEvent::MouseInput { state, button: winit::event::MouseButton::Left, .. } => {
+                // The first click only grabs the cursor so it doesn't also fire
+                if !self.cursor_grabbed {
+                    if state.is_pressed() {
+                        self.set_cursor_grab(true);
+                    }
+                } else if state.is_pressed() {
+                    self.input.handle_mouse_button_press();
+                } else {
+                    self.input.handle_mouse_button_release();
+                }
+            }
+            WindowEvent::Focused(false) => {
+                self.input.handle_mouse_button_release();
+                self.set_cursor_grab(false);
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let lines = match delta {
+                    winit::event::MouseScrollDelta::LineDelta(_, y) => y,
+                    winit::event::MouseScrollDelta::PixelDelta(pos) => pos.y as f32 / SCROLL_PIXELS_PER_LINE,
+                };
+                self.input.handle_scroll(lines);
+            }
             WindowEvent::CursorMoved { position, .. } => {
                 // SAS-style aiming: mouse movement rotates aim direction
                 let current_pos = (position.x as f32, position.y as f32);
+                if self.cursor_grabbed {
+                    // Raw motion from device_event aims instead; the pointer position is meaningless
+                    self.last_mouse_pos = current_pos;
+                    return;
+                }
                 let mouse_delta = (
                     current_pos.0 - self.last_mouse_pos.0,
                     current_pos.1 - self.last_mouse_pos.1,
@@ -958,15 +1440,40 @@ impl ApplicationHandler for GameApp {
             }
             WindowEvent::RedrawRequested => {
                 let now = Instant::now();
-                let dt = now.duration_since(self.last_frame_time).as_secs_f32();
-                self.last_frame_time = now;
+                let steps = self.game_loop.advance();
+                let dt = self.game_loop.frame_time();
+                // Render-only state (animation phase, spins, flashes) follows frame time so it stays
+                // smooth between fixed steps; the world itself only moves in fixed World::step increments
+                let render_dt = self.world.scaled_dt(self.game_loop.sim_frame_time());
+                self.sim_time += render_dt;
+                self.muzzle_flash_age = self.muzzle_flash_age
+                    .map(|age| age + render_dt)
+                    .filter(|&age| age < MUZZLE_FLASH_DURATION);
 
                 self.update_fps_counter(now);
-
-                if let Some(player) = self.world.players.get(self.local_player_id as usize) {
-                    self.camera.follow(player.x, player.y);
+                #[cfg(feature = "gamepad")]
+                self.poll_gamepad(dt);
+                self.apply_look();
+                if let Some(player) = self.world.player_mut(self.local_player_id) {
+                    self.input.apply_weapon_cycle(player.weapon, &player.owned_weapons());
+                    if let Some(weapon) = self.input.take_weapon_switch() {
+                        player.switch_weapon(weapon);
+                    }
+                }
+                self.poll_model_queue();
+                self.sync_weapon_model();
+
+                if self.spectator_mode {
+                    self.spectator_camera.update(&self.world.players, dt);
+                    self.camera.x = self.spectator_camera.camera.x;
+                    self.camera.y = self.spectator_camera.camera.y;
+                    self.camera.z = self.spectator_camera.camera.z;
+                } else {
+                    if let Some(player) = self.world.player(self.local_player_id) {
+                        self.camera.follow(player.x, player.y);
+                    }
+                    self.camera.update(dt, &self.world.map);
                 }
-                self.camera.update(dt, &self.world.map);
 
                 let camera_speed = 20.0;
                 if self.camera_move_z_neg {
@@ -993,26 +1500,91 @@ impl ApplicationHandler for GameApp {
                 self.camera.yaw = self.camera.yaw.clamp(-1.5, 1.5);
 
                 // Update World
-                let (width, height) = if let Some(ref wgpu_renderer) = self.wgpu_renderer {
-                    wgpu_renderer.get_viewport_size()
-                } else {
-                    return;
-                };
-                let aspect = width as f32 / height as f32;
-                let (view_proj, _camera_pos) = self.camera.get_view_proj(aspect);
-                let frustum = Frustum::from_view_proj(view_proj);
+                let mut sim_events = Vec::new();
+                if !self.game_loop.is_paused() {
+                    if let Some(ref mut client) = self.net_client {
+                        let input = sas2::net::PlayerInput {
+                            player_id: self.local_player_id,
+                            move_left: self.input.move_left,
+                            move_right: self.input.move_right,
+                            move_axis: if self.input.gamepad_active() { self.input.move_axis.x } else { 0.0 },
+                            jump: self.input.jump,
+                            crouch: self.input.crouch,
+                            fire: self.input.fire,
+                            aim_angle: self.aim_y.atan2(self.aim_x),
+                            weapon_switch: None,
+                        };
+                        if let Err(e) = client.send_input(&input) {
+                            println!("{}", e);
+                        }
+                        let local_time = self.start_time.elapsed().as_secs_f32();
+                        match client.poll() {
+                            Ok(Some(snapshot)) => {
+                                snapshot.apply(&mut self.world);
+                                self.snapshot_buffer.push(snapshot, local_time);
+                            }
+                            Ok(None) => {}
+                            Err(e) => println!("{}", e),
+                        }
+                        if let Some(player_id) = client.player_id {
+                            self.local_player_id = player_id;
+                        }
 
-                if let Some(player) = self.world.players.get_mut(self.local_player_id as usize) {
-                    let aim_angle = self.aim_y.atan2(self.aim_x);
-                    
-                    player.update(dt, self.move_left, self.move_right, self.jump_pressed, self.crouch_pressed, &mut self.world.map, aim_angle);
+                        let local_player_id = self.local_player_id;
+                        self.remote_players = self.snapshot_buffer.sample(local_time);
+                        self.remote_players.retain(|r| r.id != local_player_id);
+                        for remote in &self.remote_players {
+                            if let Some(player) = self.world.players.iter_mut().find(|p| p.id == remote.id) {
+                                player.x = remote.x;
+                                player.y = remote.y;
+                                player.aim_angle = remote.aim_angle;
+                            }
+                        }
+                    } else {
+                        let aim_angle = self.aim_y.atan2(self.aim_x);
+                        let mut input = PlayerInput::from_input_state(self.local_player_id, &self.input, aim_angle);
+                        // A new shot waits for the previous attack animation to finish
+                        input.fire &= self.world.player(self.local_player_id)
+                            .is_some_and(|p| p.anim.torso() != Animation::TorsoAttack);
+                        for _ in 0..steps {
+                            sim_events.extend(self.world.step(std::slice::from_ref(&input), self.game_loop.delta_time()));
+                        }
+                    }
+                }
+
+                let local_player_id = self.local_player_id;
+                let local_fired = sim_events.iter().any(|event| matches!(
+                    event,
+                    GameEvent::Audio(AudioEvent::WeaponFire { player_id, .. }) if *player_id == local_player_id
+                ));
+                if local_fired {
+                    self.muzzle_flash_age = Some(0.0);
+                    if let Some(player) = self.world.player_mut(self.local_player_id) {
+                        player.anim.set_torso(Animation::TorsoAttack);
+                    }
                 }
-                
-                self.world.update(dt, &frustum);
+
+                self.hud_feedback.player_id = self.local_player_id;
+                let (listener_x, listener_facing) = self.world.player(self.local_player_id)
+                    .map(|p| (p.x, if p.facing_right { 1.0 } else { -1.0 }))
+                    .unwrap_or((0.0, 1.0));
+                sim_events.extend(self.world.events.drain());
+                for event in sim_events {
+                    if let Some(ref mut audio) = self.audio {
+                        audio.process_event(&event, listener_x, listener_facing);
+                    }
+                    self.hud_feedback.handle_event(&event);
+                    self.effects.handle_event(&event);
+                }
+                if let Some(ref mut audio) = self.audio {
+                    audio.update(dt);
+                }
+                self.hud_feedback.update(dt);
+                self.effects.update(render_dt);
 
                 let now_debug = Instant::now();
                 if now_debug.duration_since(self.last_debug_log).as_secs_f32() >= 1.0 {
-                    if let Some(player) = self.world.players.get(self.local_player_id as usize) {
+                    if let Some(player) = self.world.player(self.local_player_id) {
                         println!("=== DEBUG: Player pos=({:.2}, {:.2}), Teleporters count={}", 
                             player.x, player.y, self.world.map.teleporters.len());
                         for (i, tp) in self.world.map.teleporters.iter().enumerate() {
@@ -1027,13 +1599,21 @@ impl ApplicationHandler for GameApp {
                     self.last_debug_log = now_debug;
                 }
 
+                let (legs_frame, torso_frame) = match (
+                    self.world.player_mut(self.local_player_id),
+                    self.player_model.anim_config.as_ref(),
+                ) {
+                    (Some(player), Some(config)) => player.anim.advance(render_dt, config),
+                    _ => (PoseBlend::default(), FrameBlend::default()),
+                };
+
                 // Rendering
-                let player = match self.world.players.get(self.local_player_id as usize) {
+                let player = match self.world.player(self.local_player_id) {
                     Some(p) => p,
                     None => return,
                 };
-                let player_x = player.x;
-                let player_y = player.y;
+                let alpha = self.game_loop.alpha();
+                let (player_x, player_y) = player.interpolated_position(alpha);
                 let player_aim_angle = player.aim_angle;
                 // Calculate facing from aim_angle
                 let normalized_angle = if player.aim_angle > std::f32::consts::PI {
@@ -1043,83 +1623,31 @@ impl ApplicationHandler for GameApp {
                 };
                 let player_facing_right = normalized_angle.abs() < std::f32::consts::FRAC_PI_2;
 
-                let player_is_moving = player.is_moving;
-                let player_is_moving_backward = player.is_moving_backward;
-                let player_animation_time = player.animation_time;
-                let player_state = player.state;
-                let player_is_crouching = player.is_crouching;
-
-                let elapsed_time = self.start_time.elapsed().as_secs_f32();
-                let lower_frame = self.player_model.lower.as_ref()
-                    .map(|lower| Self::calculate_legs_frame(
-                        &self.player_model.anim_config,
-                        player_is_moving,
-                        player_is_moving_backward,
-                        player_animation_time,
-                        lower,
-                        player_state,
-                        player_is_crouching
-                    ))
-                    .unwrap_or(0);
-
-                let shoot_anim_time = elapsed_time - self.shoot_anim_start_time;
-                if self.is_shooting {
-                    if let Some(ref config) = self.player_model.anim_config {
-                        let anim_duration = config.torso_attack.num_frames as f32 / config.torso_attack.fps as f32;
-                        if shoot_anim_time >= anim_duration {
-                            self.is_shooting = false;
-                        }
-                    }
-                }
+                let elapsed_time = self.sim_time;
 
-                let upper_frame = self.player_model.upper.as_ref()
-                    .map(|upper| Self::calculate_torso_frame(
-                        &self.player_model.anim_config,
-                        elapsed_time,
-                        upper,
-                        self.is_shooting,
-                        shoot_anim_time
-                    ))
-                    .unwrap_or(0);
-
-                if elapsed_time >= self.player2_next_gesture_time && !self.player2_is_gesturing {
-                    self.player2_is_gesturing = true;
-                    self.player2_gesture_start_time = elapsed_time;
-                    self.player2_next_gesture_time = elapsed_time + 5.0 + (elapsed_time.sin() * 3.0).abs();
-                }
+                let lower_frame = match (self.manual_frame.frame(), self.player_model.lower.as_ref()) {
+                    (Some(frame), Some(lower)) => FrameBlend::fixed(Self::manual_frame_for(frame, lower)).into(),
+                    (None, Some(lower)) => legs_frame.clamped(lower.num_frames()),
+                    _ => PoseBlend::default(),
+                };
+                let upper_frame = match (self.manual_frame.frame(), self.player_model.upper.as_ref()) {
+                    (Some(frame), Some(upper)) => FrameBlend::fixed(Self::manual_frame_for(frame, upper)),
+                    (None, Some(upper)) => torso_frame.clamped(upper.num_frames()),
+                    _ => FrameBlend::default(),
+                };
 
-                if self.player2_is_gesturing {
-                    if let Some(ref config) = self.player2_model.anim_config {
-                        let gesture_time = elapsed_time - self.player2_gesture_start_time;
-                        let gesture_duration = config.torso_gesture.num_frames as f32 / config.torso_gesture.fps as f32;
-                        if gesture_time >= gesture_duration {
-                            self.player2_is_gesturing = false;
-                        }
-                    }
+                if elapsed_time >= self.player2_next_gesture_time && self.player2_anim.torso() != Animation::TorsoGesture {
+                    self.player2_anim.set_torso(Animation::TorsoGesture);
+                    self.player2_next_gesture_time = elapsed_time + 5.0 + (elapsed_time.sin() * 3.0).abs();
                 }
 
-                let player2_lower_frame = self.player2_model.lower.as_ref()
-                    .map(|lower| Self::calculate_legs_frame(
-                        &self.player2_model.anim_config,
-                        false,
-                        false,
-                        elapsed_time,
-                        lower,
-                        sas2::game::player::PlayerState::Ground,
-                        false
-                    ))
-                    .unwrap_or(0);
-
-                let player2_gesture_time = elapsed_time - self.player2_gesture_start_time;
+                let (_, player2_torso_frame) = match self.player2_model.anim_config.as_ref() {
+                    Some(config) => self.player2_anim.advance(render_dt, config),
+                    None => (PoseBlend::default(), FrameBlend::default()),
+                };
                 let player2_upper_frame = self.player2_model.upper.as_ref()
-                    .map(|upper| Self::calculate_torso_frame_with_gesture(
-                        &self.player2_model.anim_config,
-                        elapsed_time,
-                        upper,
-                        self.player2_is_gesturing,
-                        player2_gesture_time
-                    ))
-                    .unwrap_or(0);
+                    .map(|upper| player2_torso_frame.clamped(upper.num_frames()))
+                    .unwrap_or_default();
 
                 let player_model = &self.player_model;
                 let player2_model = &self.player2_model;
@@ -1132,8 +1660,13 @@ impl ApplicationHandler for GameApp {
                     };
 
                 let frame = match wgpu_renderer.begin_frame() {
-                    Some(f) => f,
-                    None => {
+                    Ok(Some(f)) => f,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        event_loop.exit();
+                        return;
+                    }
+                    Ok(None) => {
                         if let Some(ref window) = self.window {
                             window.request_redraw();
                         }
@@ -1143,6 +1676,8 @@ impl ApplicationHandler for GameApp {
                 let view = frame
                     .texture
                     .create_view(&wgpu::TextureViewDescriptor::default());
+                let msaa_view = wgpu_renderer.create_msaa_view();
+                let scene_view = msaa_view.as_ref().unwrap_or(&view);
 
                 let mut encoder =
                     wgpu_renderer
@@ -1156,7 +1691,7 @@ impl ApplicationHandler for GameApp {
                     let _pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                         label: Some("Clear Pass"),
                         color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                            view: &view,
+                            view: scene_view,
                             resolve_target: None,
                             ops: wgpu::Operations {
                                 load: wgpu::LoadOp::Clear(wgpu::Color {
@@ -1180,6 +1715,7 @@ impl ApplicationHandler for GameApp {
                     });
                 }
 
+                md3_renderer.begin_frame();
                 let frame_start = Instant::now();
                 
                 let (width, height) = wgpu_renderer.get_viewport_size();
@@ -1194,7 +1730,7 @@ impl ApplicationHandler for GameApp {
                 } else {
                     LightingParams::new()
                 };
-                let time = self.start_time.elapsed().as_secs_f32();
+                let time = self.sim_time;
                 
                 let mut dynamic_lights = Vec::new();
                 
@@ -1202,10 +1738,11 @@ impl ApplicationHandler for GameApp {
                     if !rocket.is_visible(&frustum) {
                         continue;
                     }
+                    let rocket_position = rocket.interpolated_position(alpha);
                     
                     let flame_color = Vec3::new(3.5, 2.0, 0.8);
                     dynamic_lights.push(Light::with_randomized_flicker(
-                        rocket.position,
+                        rocket_position,
                         flame_color,
                         250.0,
                         41.0,
@@ -1213,7 +1750,7 @@ impl ApplicationHandler for GameApp {
                     ));
                     
                     let flame_offset = if rocket.velocity.x > 0.0 { -20.0 } else { 20.0 };
-                    let flame_pos = rocket.position + Vec3::new(flame_offset, 0.0, 0.0);
+                    let flame_pos = rocket_position + Vec3::new(flame_offset, 0.0, 0.0);
                     let flash_color = Vec3::new(4.0, 2.5, 1.0);
                     dynamic_lights.push(Light::with_randomized_flicker(
                         flame_pos,
@@ -1235,11 +1772,28 @@ impl ApplicationHandler for GameApp {
                 let mut all_lights = static_lights.clone();
                 all_lights.extend(dynamic_lights_data.iter().copied());
 
+                let particle_light_budget = MAX_LIGHTS.saturating_sub(all_lights.len());
+                all_lights.extend(
+                    self.effects.lights(particle_light_budget).iter()
+                        .map(|l| (l.position, l.color, l.radius)),
+                );
+
                 let surface_format = wgpu_renderer.surface_config.format;
+                let target = RenderTarget { color: scene_view, depth: depth_view, format: surface_format };
+                let scene = SceneView { view_proj, camera_pos, lights: &all_lights, ambient_light: lighting.ambient };
+
+                md3_renderer.render_skybox(
+                    &mut encoder,
+                    scene_view,
+                    depth_view,
+                    view_proj,
+                    camera_pos,
+                    surface_format,
+                );
 
                 md3_renderer.render_tiles(
                     &mut encoder,
-                    &view,
+                    scene_view,
                     depth_view,
                     view_proj,
                     camera_pos,
@@ -1252,6 +1806,8 @@ impl ApplicationHandler for GameApp {
                 let item_spin = Mat3::from_rotation_y(time * 1.2);
                 let item_rotation = Mat4::from_mat3(item_spin * md3_correction_items);
 
+                // Items and map markers share one pass
+                let mut prop_draws = Vec::new();
                 for item in &self.world.map.items {
                     if !item.active {
                         continue;
@@ -1265,21 +1821,7 @@ impl ApplicationHandler for GameApp {
                     let scale_mat = Mat4::from_scale(Vec3::splat(model.scale));
                     let model_mat = translation * item_rotation * scale_mat;
 
-                    md3_renderer.render_model(
-                        &mut encoder,
-                        &view,
-                        depth_view,
-                        surface_format,
-                        &model.model,
-                        0,
-                        &model.textures,
-                        model_mat,
-                        view_proj,
-                        camera_pos,
-                        &all_lights,
-                        lighting.ambient,
-                        false,
-                    );
+                    prop_draws.push(ModelDrawCall::at_frame(&model.model, 0.0, &model.textures, model_mat));
                 }
 
                 if let Some(marker) = self.teleporter_marker.as_ref() {
@@ -1289,21 +1831,7 @@ impl ApplicationHandler for GameApp {
                         let scale_mat = Mat4::from_scale(Vec3::splat(marker.scale));
                         let model_mat = translation * spin * scale_mat;
 
-                        md3_renderer.render_model(
-                            &mut encoder,
-                            &view,
-                            depth_view,
-                            surface_format,
-                            &marker.model,
-                            0,
-                            &marker.textures,
-                            model_mat,
-                            view_proj,
-                            camera_pos,
-                            &all_lights,
-                            lighting.ambient,
-                            false,
-                        );
+                        prop_draws.push(ModelDrawCall::at_frame(&marker.model, 0.0, &marker.textures, model_mat));
                     }
                 }
 
@@ -1317,26 +1845,10 @@ impl ApplicationHandler for GameApp {
                         let scale_mat = Mat4::from_scale(Vec3::splat(marker.scale));
                         let model_mat = translation * spin * scale_mat;
 
-                        md3_renderer.render_model(
-                            &mut encoder,
-                            &view,
-                            depth_view,
-                            surface_format,
-                            &marker.model,
-                            0,
-                            &marker.textures,
-                            model_mat,
-                            view_proj,
-                            camera_pos,
-                            &all_lights,
-                            lighting.ambient,
-                            false,
-                        );
+                        prop_draws.push(ModelDrawCall::at_frame(&marker.model, 0.0, &marker.textures, model_mat));
                     }
                 }
-
-                let scale = 1.0;
-                let scale_mat = Mat4::from_scale(Vec3::splat(scale));
+                md3_renderer.render_models(&mut encoder, target, &prop_draws, scene, false);
 
                 // Render Player
                 
@@ -1362,33 +1874,32 @@ impl ApplicationHandler for GameApp {
                 let combined_rotation = facing_rotation * md3_correction;
                 
                 let ground_y = self.world.map.ground_y;
-                let model_bottom_offset = Self::calculate_model_bottom_offset(self.player_model.lower.as_ref(), lower_frame);
+                let player_scale = self.player_model.scale();
+                let model_bottom_offset = Self::calculate_model_bottom_offset(self.player_model.lower.as_ref(), lower_frame.key_frame()) * player_scale;
                 let render_y = ground_y + model_bottom_offset + player_y;
                 let game_translation = Mat4::from_translation(Vec3::new(player_x, render_y, 50.0));
                 let game_rotation = Mat4::from_mat3(combined_rotation);
                 let game_transform = game_translation * game_rotation;
 
-                let (_weapon_orientation, mut shadow_models) = Self::render_player(
+                let mut scene_dump = self.dump_scene.then(SceneDump::new);
+                let (weapon_orientation, mut shadow_models) = Self::render_player(
                     &mut encoder,
-                    &view,
-                    depth_view,
+                    target,
                     md3_renderer,
-                    surface_format,
                     player_model,
                     game_transform,
-                    Mat4::from_scale(Vec3::splat(1.0)),
+                    Mat4::from_scale(Vec3::splat(player_scale)),
                     lower_orientation,
                     lower_frame,
                     upper_frame,
-                    view_proj,
-                    camera_pos,
-                    &all_lights,
-                    lighting.ambient,
+                    scene,
                     true,
                     player_aim_angle,
                     flip_x,
                     &mut self.current_legs_yaw,
-                    dt,
+                    &mut self.tag_validator,
+                    render_dt,
+                    scene_dump.as_mut(),
                 );
 
 
@@ -1396,43 +1907,86 @@ impl ApplicationHandler for GameApp {
                 // For MVP refactor, keeping it as static dummy
                 let ground_y = self.world.map.ground_y;
                 let player2_lower_frame = 0;
-                let model_bottom_offset = Self::calculate_model_bottom_offset(self.player2_model.lower.as_ref(), player2_lower_frame);
-                let player2_y = ground_y + model_bottom_offset;
-                let player2_game_translation = Mat4::from_translation(Vec3::new(250.0, player2_y, 50.0));
+                let player2_scale = self.player2_model.scale();
+                let model_bottom_offset = Self::calculate_model_bottom_offset(self.player2_model.lower.as_ref(), player2_lower_frame) * player2_scale;
+                let (player2_x, player2_y, player2_yaw) = match self.remote_players.first() {
+                    Some(remote) => {
+                        let yaw = if remote.aim_angle > std::f32::consts::PI {
+                            remote.aim_angle - 2.0 * std::f32::consts::PI
+                        } else {
+                            remote.aim_angle
+                        };
+                        (remote.x, ground_y + model_bottom_offset + remote.y, yaw)
+                    }
+                    None => (250.0, ground_y + model_bottom_offset, std::f32::consts::PI),
+                };
+                let player2_game_translation = Mat4::from_translation(Vec3::new(player2_x, player2_y, 50.0));
                 let md3_correction = Mat3::from_rotation_x(-std::f32::consts::FRAC_PI_2);
-                let facing_rotation = Mat3::from_rotation_y(std::f32::consts::PI);
+                let facing_rotation = Mat3::from_rotation_y(player2_yaw);
                 let player2_combined_rotation = facing_rotation * md3_correction;
                 let player2_game_rotation = Mat4::from_mat3(player2_combined_rotation);
                 let player2_game_transform = player2_game_translation * player2_game_rotation;
 
                 let (_player2_weapon_orientation, player2_shadow_models) = Self::render_player(
                     &mut encoder,
-                    &view,
-                    depth_view,
+                    target,
                     md3_renderer,
-                    surface_format,
                     player2_model,
                     player2_game_transform,
-                    Mat4::from_scale(Vec3::splat(1.0)),
+                    Mat4::from_scale(Vec3::splat(player2_scale)),
                     lower_orientation,
-                    player2_lower_frame,
+                    FrameBlend::fixed(player2_lower_frame).into(),
                     player2_upper_frame,
-                    view_proj,
-                    camera_pos,
-                    &all_lights,
-                    lighting.ambient,
+                    scene,
                     false,
                     0.0,
                     true,
                     &mut self.player2_legs_yaw,
-                    dt,
+                    &mut self.tag_validator,
+                    render_dt,
+                    scene_dump.as_mut(),
                 );
+                if let Some(dump) = scene_dump.take() {
+                    self.dump_scene = false;
+                    print!("=== Scene graph ===\n{}", dump);
+                    if let Err(e) = dump.write_to("scene_dump.txt") {
+                        println!("{}", e);
+                    }
+                }
                 shadow_models.extend(player2_shadow_models);
 
-                let should_shoot = self.shoot_pressed && !self.is_shooting;
+                // Players mirrored under the floor first, so the floor can blend them in
+                let (surface_width, surface_height) = wgpu_renderer.get_surface_size();
+                if let Some((reflection_view, reflection_depth)) =
+                    md3_renderer.begin_floor_reflection(&mut encoder, surface_format, surface_width, surface_height)
+                {
+                    let mirror = ground_reflection_matrix();
+                    let reflected: Vec<ModelDrawCall> = shadow_models.iter()
+                        .map(|&(model, frame, textures, matrix)| ModelDrawCall {
+                            model,
+                            pose: FrameBlend::fixed(frame).into(),
+                            textures,
+                            matrix: mirror * matrix,
+                        })
+                        .collect();
+                    let reflection_target = RenderTarget { color: &reflection_view, depth: &reflection_depth, format: surface_format };
+                    md3_renderer.render_models(&mut encoder, reflection_target, &reflected, scene, false);
+                    md3_renderer.end_floor_reflection(&mut encoder);
+                    md3_renderer.render_ground(
+                        &mut encoder,
+                        scene_view,
+                        depth_view,
+                        view_proj,
+                        camera_pos,
+                        &all_lights,
+                        lighting.ambient,
+                    );
+                }
+
 
                 // Render Rockets
                 if let Some(rocket_model) = rocket_model {
+                    let mut rocket_draws = Vec::new();
                     for rocket in &self.world.rockets {
                         if !rocket.active || !rocket.is_visible(&frustum) {
                             continue;
@@ -1445,36 +1999,24 @@ impl ApplicationHandler for GameApp {
                         );
                         let rocket_rotation = facing_rotation * md3_correction;
                         
-                        let translation = Mat4::from_translation(rocket.position);
+                        let translation = Mat4::from_translation(rocket.interpolated_position(alpha));
                         let rotation = Mat4::from_mat3(rocket_rotation);
                         let scale_mat = Mat4::from_scale(Vec3::splat(rocket_scale));
                         let model_mat = translation * rotation * scale_mat;
                         
-                        md3_renderer.render_model(
-                            &mut encoder,
-                            &view,
-                            depth_view,
-                            surface_format,
-                            rocket_model,
-                            0,
-                            &self.rocket_textures,
-                            model_mat,
-                            view_proj,
-                            camera_pos,
-                            &all_lights,
-                            lighting.ambient,
-                            false,
-                        );
+                        rocket_draws.push(ModelDrawCall::at_frame(rocket_model, 0.0, &self.rocket_textures, model_mat));
                     }
+                    md3_renderer.render_models(&mut encoder, target, &rocket_draws, scene, false);
                 }
 
                 let smoke_particles: Vec<(Vec3, f32, f32)> = self.world.smoke_particles.iter()
                     .map(|p| (p.position, p.size, p.get_alpha()))
+                    .chain(self.effects.instances().copied())
                     .collect();
                 
                 md3_renderer.render_particles(
                     &mut encoder,
-                    &view,
+                    scene_view,
                     depth_view,
                     view_proj,
                     camera_pos,
@@ -1487,83 +2029,66 @@ impl ApplicationHandler for GameApp {
                 
                 md3_renderer.render_flames(
                     &mut encoder,
-                    &view,
+                    scene_view,
                     depth_view,
                     view_proj,
                     camera_pos,
                     &flame_particles,
                 );
 
+                let flash_tag = player_model.weapon.as_ref()
+                    .and_then(|weapon| weapon.main.model.tags.first())
+                    .and_then(|tags| Self::find_tag(tags, "tag_flash"));
+                if let (Some(age), Some(weapon_orient), Some(flash_tag)) = (self.muzzle_flash_age, weapon_orientation, flash_tag) {
+                    let flash_orient = attach_rotated_entity(&weapon_orient, flash_tag);
+                    md3_renderer.render_muzzle_flash(
+                        &mut encoder,
+                        target,
+                        view_proj,
+                        camera_pos,
+                        game_transform * orientation_to_mat4(&flash_orient),
+                        age,
+                    );
+                }
+
                 let shadow_volume_models: Vec<(&MD3Model, usize, Mat4)> = shadow_models.iter()
                     .map(|(model, frame, _textures, matrix)| (*model, *frame, *matrix))
                     .collect();
 
                 md3_renderer.render_planar_shadows(
                     &mut encoder,
-                    &view,
-                    depth_view,
+                    target,
                     view_proj,
                     &shadow_volume_models,
                     &all_lights,
                 );
 
-                // md3_renderer.render_debug_lights(
-                //     &mut encoder,
-                //     &view,
-                //     depth_view,
-                //     view_proj,
-                //     camera_pos,
-                //     &all_lights,
-                //     surface_format,
-                // );
-
-                // md3_renderer.render_debug_light_rays(
-                //     &mut encoder,
-                //     &view,
-                //     depth_view,
-                //     view_proj,
-                //     &all_lights,
-                //     surface_format,
-                // );
+                if self.show_debug_lights {
+                    md3_renderer.render_debug_lights(
+                        &mut encoder,
+                        scene_view,
+                        depth_view,
+                        view_proj,
+                        camera_pos,
+                        &all_lights,
+                        surface_format,
+                    );
+                    md3_renderer.render_debug_light_rays(
+                        &mut encoder,
+                        target,
+                        view_proj,
+                        (surface_width, surface_height),
+                        &all_lights,
+                    );
+                }
+
+                wgpu_renderer.resolve_msaa(&mut encoder, &view);
 
                 let render_time = frame_start.elapsed();
+                let scene_stats = md3_renderer.scene_stats();
                 
                 wgpu_renderer.queue.submit(Some(encoder.finish()));
                 
-                if let Some(crosshair_renderer) = &self.crosshair_renderer {
-                    const CROSSHAIR_DISTANCE: f32 = 4.0;
-                    
-                    let lower_frame = 0;
-                    let model_bottom_offset = Self::calculate_model_bottom_offset(self.player_model.lower.as_ref(), lower_frame);
-                    let player_center_y = ground_y + model_bottom_offset + player_y + 0.5;
-                    let player_center = Vec3::new(player_x, player_center_y, 50.0);
-                    
-                    let crosshair_world_x = player_center.x + self.aim_x * CROSSHAIR_DISTANCE;
-                    let crosshair_world_y = player_center.y + self.aim_y * CROSSHAIR_DISTANCE;
-                    
-                    let crosshair_world_pos = Vec3::new(crosshair_world_x, crosshair_world_y, 0.0);
-                    let clip_pos = view_proj * glam::Vec4::new(crosshair_world_pos.x, crosshair_world_pos.y, crosshair_world_pos.z, 1.0);
-                    let ndc = Vec3::new(clip_pos.x, clip_pos.y, clip_pos.z) / clip_pos.w;
-                    let screen_x = (ndc.x * 0.5 + 0.5) * width as f32;
-                    let screen_y = (1.0 - (ndc.y * 0.5 + 0.5)) * height as f32;
-                    
-                    let mut encoder = wgpu_renderer.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                        label: Some("Crosshair Encoder"),
-                    });
-                    
-                    crosshair_renderer.render(
-                        &mut encoder,
-                        &view,
-                        &wgpu_renderer.queue,
-                        screen_x,
-                        screen_y,
-                        width,
-                        height,
-                    );
-                    
-                    wgpu_renderer.queue.submit(Some(encoder.finish()));
-                }
-
                 if let Some(ref text_renderer) = self.text_renderer {
                     let mut text_encoder = wgpu_renderer.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
                         label: Some("Text Encoder"),
@@ -1595,17 +2120,122 @@ impl ApplicationHandler for GameApp {
                         }
                     }
 
+                    if self.hud_feedback.hit_marker > 0.0 {
+                        text_renderer.render_text(
+                            &mut text_encoder,
+                            &view,
+                            &format!("-{}", self.hud_feedback.last_damage_dealt),
+                            width as f32 * 0.5 + 24.0,
+                            height as f32 * 0.5 - 24.0,
+                            24.0,
+                            [1.0, 0.3, 0.2, self.hud_feedback.hit_marker / 0.25],
+                            width,
+                            height,
+                        );
+                    }
+
+                    if self.show_scene_stats {
+                        let lines = [
+                            format!("{} tris in {} meshes", scene_stats.visible_triangles, scene_stats.meshes),
+                            format!("{} models culled", scene_stats.culled_models),
+                            format!("{} shadow volume tris", scene_stats.shadow_volume_triangles),
+                            format!("{} particles", scene_stats.particles),
+                            format!("{} uniform buffers allocated", scene_stats.uniform_buffers),
+                        ];
+                        for (i, line) in lines.iter().enumerate() {
+                            text_renderer.render_text(
+                                &mut text_encoder,
+                                &view,
+                                line,
+                                20.0,
+                                20.0 + i as f32 * 22.0,
+                                18.0,
+                                [0.8, 0.9, 1.0, 1.0],
+                                width,
+                                height,
+                            );
+                        }
+                    }
+
+                    if !self.model_queue.is_idle() {
+                        text_renderer.render_text(
+                            &mut text_encoder,
+                            &view,
+                            &format!("Loading models ({} pending)...", self.model_queue.pending()),
+                            20.0,
+                            height as f32 - 30.0,
+                            20.0,
+                            [0.8, 0.8, 0.8, 1.0],
+                            width,
+                            height,
+                        );
+                    }
+
                     wgpu_renderer.queue.submit(Some(text_encoder.finish()));
                 }
                 
-                wgpu_renderer.end_frame(frame);
-                
-                if should_shoot {
-                    if self.world.try_fire(self.local_player_id, player_aim_angle, &frustum) {
-                        self.is_shooting = true;
-                        self.shoot_anim_start_time = elapsed_time;
+                // Last so it stays on top of the HUD text
+                if self.spectator_mode {
+                    // The free camera has no aim, so mark the middle of the screen
+                    let mut encoder = wgpu_renderer.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                        label: Some("Crosshair Encoder"),
+                    });
+                    md3_renderer.render_crosshair(
+                        &mut encoder,
+                        &view,
+                        (width, height),
+                        self.crosshair_style,
+                        CROSSHAIR_COLOR,
+                        CROSSHAIR_SCALE,
+                    );
+                    wgpu_renderer.queue.submit(Some(encoder.finish()));
+                } else if let Some(crosshair_renderer) = md3_renderer.crosshair() {
+                    const CROSSHAIR_DISTANCE: f32 = 4.0;
+                    
+                    let lower_frame = 0;
+                    let model_bottom_offset = Self::calculate_model_bottom_offset(self.player_model.lower.as_ref(), lower_frame);
+                    let player_center_y = ground_y + model_bottom_offset + player_y + 0.5;
+                    let player_center = Vec3::new(player_x, player_center_y, 50.0);
+                    
+                    let crosshair_world_x = player_center.x + self.aim_x * CROSSHAIR_DISTANCE;
+                    let crosshair_world_y = player_center.y + self.aim_y * CROSSHAIR_DISTANCE;
+                    
+                    let crosshair_world_pos = Vec3::new(crosshair_world_x, crosshair_world_y, 0.0);
+                    let clip_pos = view_proj * glam::Vec4::new(crosshair_world_pos.x, crosshair_world_pos.y, crosshair_world_pos.z, 1.0);
+                    let ndc = Vec3::new(clip_pos.x, clip_pos.y, clip_pos.z) / clip_pos.w;
+                    let screen_x = (ndc.x * 0.5 + 0.5) * width as f32;
+                    let screen_y = (1.0 - (ndc.y * 0.5 + 0.5)) * height as f32;
+                    
+                    let mut encoder = wgpu_renderer.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                        label: Some("Crosshair Encoder"),
+                    });
+                    
+                    crosshair_renderer.render(
+                        &mut encoder,
+                        &view,
+                        &wgpu_renderer.queue,
+                        [screen_x, screen_y],
+                        (width, height),
+                        CrosshairStyle {
+                            index: self.crosshair_style,
+                            color: CROSSHAIR_COLOR,
+                            scale: CROSSHAIR_SCALE,
+                        },
+                    );
+                    
+                    wgpu_renderer.queue.submit(Some(encoder.finish()));
+                }
+
+                if self.take_screenshot {
+                    self.take_screenshot = false;
+                    let saved = wgpu_renderer.capture_frame(&frame.texture).and_then(|img| save_screenshot(&img));
+                    match saved {
+                        Ok(path) => println!("Screenshot saved to {}", path.display()),
+                        Err(e) => println!("{}", e),
                     }
                 }
+
+                wgpu_renderer.end_frame(frame);
                 
                 let total_time = frame_start.elapsed();
                 if self.frame_count % 60 == 0 {
@@ -1624,8 +2254,23 @@ impl ApplicationHandler for GameApp {
     }
 }
 
+// Named by Unix time in milliseconds so shots taken in quick succession don't collide
+fn save_screenshot(img: &image::RgbaImage) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(SCREENSHOT_DIR)
+        .map_err(|e| format!("Failed to create {}: {}", SCREENSHOT_DIR, e))?;
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let path = Path::new(SCREENSHOT_DIR).join(format!("shot_{}.png", millis));
+    img.save(&path)
+        .map_err(|e| format!("Failed to write screenshot {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
 fn main() {
     let event_loop = EventLoop::new().unwrap();
     let mut app = GameApp::new();
+    app.exec_config(BINDINGS_PATH);
     event_loop.run_app(&mut app).unwrap();
 }
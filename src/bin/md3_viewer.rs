@@ -15,20 +15,15 @@ use winit::{
 };
 
 use sas2::engine::loader::load_md3_textures_guess_static;
-use sas2::engine::md3::MD3Model;
+use sas2::engine::md3::{LoadOptions, MD3Model};
 use sas2::engine::renderer::{MD3Renderer, WgpuRenderer};
-use sas2::render::TextRenderer;
+use sas2::render::{FloorGrid, RenderTarget, TextRenderer, TextureViewer};
+use sas2::render::benchmark::{self, BenchmarkConfig};
+use sas2::resource_path::resource_paths;
 
 fn find_all_md3_files() -> Vec<PathBuf> {
     let mut files = Vec::new();
-    let search_paths = [
-        "q3-resources",
-        "../q3-resources",
-        "../../q3-resources",
-    ];
-    
-    for base_path in &search_paths {
-        let path = Path::new(base_path);
+    for path in resource_paths().roots() {
         if path.exists() {
             if let Ok(entries) = std::fs::read_dir(path) {
                 for entry in entries.flatten() {
@@ -60,11 +55,14 @@ fn collect_md3_files_recursive(dir: &Path, files: &mut Vec<PathBuf>) {
     }
 }
 
+const DEFAULT_TARGET_HEIGHT: f32 = 56.0;
+
 struct MD3ViewerApp {
     window: Option<Arc<Window>>,
     wgpu_renderer: Option<WgpuRenderer>,
     md3_renderer: Option<MD3Renderer>,
     text_renderer: Option<TextRenderer>,
+    texture_viewer: Option<TextureViewer>,
     depth_texture: Option<Texture>,
     depth_view: Option<wgpu::TextureView>,
     
@@ -73,13 +71,25 @@ struct MD3ViewerApp {
     current_model: Option<MD3Model>,
     current_textures: Vec<Option<String>>,
     
+    model_scale: f32,
+    target_height: f32,
+    load_options: LoadOptions,
+    
     camera_distance: f32,
     camera_yaw: f32,
     camera_pitch: f32,
+    camera_roll: f32,
+    roll_locked: bool,
+    
+    show_grid: bool,
+    floor_grid: FloorGrid,
     
     show_file_list: bool,
     scroll_offset: usize,
     
+    show_texture_viewer: bool,
+    texture_view_index: usize,
+    
     start_time: Instant,
     last_frame_time: Instant,
 }
@@ -94,17 +104,27 @@ impl MD3ViewerApp {
             wgpu_renderer: None,
             md3_renderer: None,
             text_renderer: None,
+            texture_viewer: None,
             depth_texture: None,
             depth_view: None,
             md3_files,
             current_file_index: 0,
             current_model: None,
             current_textures: Vec::new(),
+            model_scale: 1.0,
+            target_height: DEFAULT_TARGET_HEIGHT,
+            load_options: LoadOptions::default(),
             camera_distance: 100.0,
             camera_yaw: 0.0,
             camera_pitch: 0.3,
+            camera_roll: 0.0,
+            roll_locked: false,
+            show_grid: true,
+            floor_grid: FloorGrid::default(),
             show_file_list: true,
             scroll_offset: 0,
+            show_texture_viewer: false,
+            texture_view_index: 0,
             start_time: Instant::now(),
             last_frame_time: Instant::now(),
         }
@@ -118,24 +138,32 @@ impl MD3ViewerApp {
         let file_path = &self.md3_files[self.current_file_index];
         println!("Loading: {}", file_path.display());
         
-        if let Some(ref mut md3_renderer) = self.md3_renderer.as_mut() {
-            md3_renderer.clear_model_cache();
+        if let (Some(md3_renderer), Some(model)) = (self.md3_renderer.as_mut(), self.current_model.as_ref()) {
+            md3_renderer.clear_model_cache(model.id);
         }
         
-        match MD3Model::load(file_path) {
+        match MD3Model::load_with(file_path, self.load_options) {
             Ok(model) => {
-                println!("Model loaded: {} meshes, {} frames", model.meshes.len(), model.header.num_bone_frames);
-                let (min_x, max_x, min_y, max_y, min_z, max_z) = model.get_bounds(0);
-                let size_x = max_x - min_x;
-                let size_y = max_y - min_y;
-                let size_z = max_z - min_z;
-                let max_size = size_x.max(size_y).max(size_z);
-                println!("Model bounds: {:.2} x {:.2} x {:.2}", size_x, size_y, size_z);
+                println!(
+                    "Model loaded: {} meshes, {} frames, {} LODs",
+                    model.meshes.len(),
+                    model.header.num_bone_frames,
+                    model.num_lods()
+                );
+                let (min_bounds, max_bounds) = model.overall_bounds();
+                let size = max_bounds - min_bounds;
+                let max_size = size.max_element();
+                println!("Model bounds: {:.2} x {:.2} x {:.2}", size.x, size.y, size.z);
+                
+                self.model_scale = model.fit_scale(0, self.target_height);
+                println!("Model scale: {:.3}", self.model_scale);
                 
                 self.current_model = Some(model.clone());
                 
                 if max_size > 0.0 {
-                    self.camera_distance = max_size * 2.5;
+                    let scaled_size = max_size * self.model_scale;
+                    self.camera_distance = (scaled_size * 2.5).clamp(10.0, 500.0);
+                    self.floor_grid.extent = (scaled_size * 1.5).max(self.floor_grid.spacing);
                 }
                 
                 if let (Some(ref mut wgpu_renderer), Some(ref mut md3_renderer)) = 
@@ -153,7 +181,10 @@ impl MD3ViewerApp {
                     let file_name = file_path.file_name()
                         .and_then(|n| n.to_str())
                         .unwrap_or("unknown");
-                    window.set_title(&format!("MD3 Viewer - {}", file_name));
+                    let adapter = self.wgpu_renderer.as_ref()
+                        .map(|r| format!(" | {}", r.adapter_summary()))
+                        .unwrap_or_default();
+                    window.set_title(&format!("MD3 Viewer - {}{}", file_name, adapter));
                 }
             }
             Err(e) => {
@@ -166,29 +197,20 @@ impl MD3ViewerApp {
     
     fn create_depth(&mut self) {
         if let Some(ref wgpu_renderer) = self.wgpu_renderer {
-            let (width, height) = wgpu_renderer.get_surface_size();
-            let depth_texture = wgpu_renderer
-                .device
-                .create_texture(&wgpu::TextureDescriptor {
-                    label: Some("Depth Texture"),
-                    size: wgpu::Extent3d {
-                        width,
-                        height,
-                        depth_or_array_layers: 1,
-                    },
-                    mip_level_count: 1,
-                    sample_count: 1,
-                    dimension: wgpu::TextureDimension::D2,
-                    format: wgpu::TextureFormat::Depth24PlusStencil8,
-                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-                    view_formats: &[],
-                });
+            let depth_texture = wgpu_renderer.create_depth_texture();
             let depth_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
             self.depth_texture = Some(depth_texture);
             self.depth_view = Some(depth_view);
         }
     }
     
+    fn lock_roll(&mut self, locked: bool) {
+        self.roll_locked = locked;
+        if locked {
+            self.camera_roll = 0.0;
+        }
+    }
+
     fn get_camera_matrix(&self, aspect: f32) -> (Mat4, Vec3) {
         let camera_pos = Vec3::new(
             self.camera_distance * self.camera_yaw.cos() * self.camera_pitch.cos(),
@@ -199,7 +221,8 @@ impl MD3ViewerApp {
         let target = Vec3::ZERO;
         let up = Vec3::new(0.0, 0.0, 1.0);
         
-        let view = Mat4::look_at_rh(camera_pos, target, up);
+        let roll = if self.roll_locked { 0.0 } else { self.camera_roll };
+        let view = Mat4::from_rotation_z(roll) * Mat4::look_at_rh(camera_pos, target, up);
         let proj = Mat4::perspective_rh(std::f32::consts::PI / 4.0, aspect, 0.1, 1000.0);
         let view_proj = proj * view;
         
@@ -218,11 +241,20 @@ impl ApplicationHandler for MD3ViewerApp {
             .with_inner_size(winit::dpi::LogicalSize::new(1280.0, 720.0));
         let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
         
-        let mut wgpu_renderer = WgpuRenderer::new(window.clone()).block_on().unwrap();
+        let mut wgpu_renderer = match WgpuRenderer::new(window.clone(), 4).block_on() {
+            Ok(renderer) => renderer,
+            Err(e) => {
+                eprintln!("{}", e);
+                event_loop.exit();
+                return;
+            }
+        };
         let mut md3_renderer = MD3Renderer::new(
             wgpu_renderer.device.clone(),
             wgpu_renderer.queue.clone(),
         );
+        md3_renderer.set_sample_count(wgpu_renderer.sample_count());
+        md3_renderer.set_anisotropy(wgpu_renderer.anisotropy());
         
         md3_renderer.create_pipeline(wgpu_renderer.surface_config.format);
         
@@ -236,6 +268,10 @@ impl ApplicationHandler for MD3ViewerApp {
         self.wgpu_renderer = Some(wgpu_renderer);
         self.md3_renderer = Some(md3_renderer);
         self.text_renderer = Some(text_renderer);
+        self.texture_viewer = Some(TextureViewer::new(
+            &self.wgpu_renderer.as_ref().unwrap().device,
+            self.wgpu_renderer.as_ref().unwrap().surface_config.format,
+        ));
         self.create_depth();
         self.last_frame_time = Instant::now();
         
@@ -262,6 +298,12 @@ impl ApplicationHandler for MD3ViewerApp {
                     self.create_depth();
                 }
             }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                if let Some(ref mut wgpu_renderer) = self.wgpu_renderer {
+                    wgpu_renderer.set_scale_factor(scale_factor);
+                    self.create_depth();
+                }
+            }
             WindowEvent::KeyboardInput { event, .. } => {
                 let pressed = event.state == winit::event::ElementState::Pressed;
                 if !pressed {
@@ -320,6 +362,67 @@ impl ApplicationHandler for MD3ViewerApp {
                         KeyCode::KeyE => {
                             self.camera_distance = (self.camera_distance / 1.1).max(10.0);
                         }
+                        KeyCode::KeyZ if !self.roll_locked => {
+                            self.camera_roll -= 0.1;
+                        }
+                        KeyCode::KeyC if !self.roll_locked => {
+                            self.camera_roll += 0.1;
+                        }
+                        KeyCode::KeyR => {
+                            self.lock_roll(!self.roll_locked);
+                            println!("Camera roll {}", if self.roll_locked { "locked" } else { "unlocked" });
+                        }
+                        KeyCode::KeyG => {
+                            self.show_grid = !self.show_grid;
+                        }
+                        KeyCode::Equal => {
+                            self.model_scale = (self.model_scale * 1.1).min(100.0);
+                        }
+                        KeyCode::Minus => {
+                            self.model_scale = (self.model_scale / 1.1).max(0.01);
+                        }
+                        KeyCode::Digit0 => {
+                            if let Some(ref model) = self.current_model {
+                                self.model_scale = model.fit_scale(0, self.target_height);
+                            }
+                        }
+                        KeyCode::KeyW => {
+                            self.load_options.flip_winding = !self.load_options.flip_winding;
+                            println!("Flip winding: {}", self.load_options.flip_winding);
+                            self.load_current_model();
+                        }
+                        KeyCode::KeyT => {
+                            self.show_texture_viewer = !self.show_texture_viewer;
+                        }
+                        KeyCode::Comma if self.show_texture_viewer => {
+                            self.texture_view_index = self.texture_view_index.saturating_sub(1);
+                        }
+                        KeyCode::Period if self.show_texture_viewer => {
+                            self.texture_view_index += 1;
+                        }
+                        KeyCode::KeyF => {
+                            if let Some(ref mut md3_renderer) = self.md3_renderer {
+                                let mode = md3_renderer.render_mode().next();
+                                md3_renderer.set_render_mode(mode);
+                                println!("Render mode: {:?}", mode);
+                            }
+                        }
+                        KeyCode::KeyO => {
+                            if let Some(ref mut md3_renderer) = self.md3_renderer {
+                                let overlay = match md3_renderer.wire_overlay() {
+                                    Some(_) => None,
+                                    None => Some([0.0, 1.0, 0.3, 1.0]),
+                                };
+                                md3_renderer.set_wire_overlay(overlay);
+                                println!("Wire overlay: {}", overlay.is_some());
+                            }
+                        }
+                        KeyCode::BracketLeft => {
+                            self.floor_grid.spacing = (self.floor_grid.spacing * 0.5).max(1.0);
+                        }
+                        KeyCode::BracketRight => {
+                            self.floor_grid.spacing = (self.floor_grid.spacing * 2.0).min(64.0);
+                        }
                         _ => {}
                     }
                 }
@@ -358,18 +461,26 @@ impl ApplicationHandler for MD3ViewerApp {
                 };
                 
                 let frame = match wgpu_renderer.begin_frame() {
-                    Some(f) => f,
-                    None => {
+                    Ok(Some(f)) => f,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        event_loop.exit();
+                        return;
+                    }
+                    Ok(None) => {
                         if let Some(ref window) = self.window {
                             window.request_redraw();
                         }
                         return;
                     }
                 };
+                md3_renderer.begin_frame();
                 
                 let view = frame
                     .texture
                     .create_view(&wgpu::TextureViewDescriptor::default());
+                let msaa_view = wgpu_renderer.create_msaa_view();
+                let scene_view = msaa_view.as_ref().unwrap_or(&view);
                 
                 let mut encoder = wgpu_renderer
                     .device
@@ -383,7 +494,7 @@ impl ApplicationHandler for MD3ViewerApp {
                     let _pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                         label: Some("Clear Pass"),
                         color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                            view: &view,
+                            view: scene_view,
                             resolve_target: None,
                             ops: wgpu::Operations {
                                 load: wgpu::LoadOp::Clear(wgpu::Color {
@@ -415,32 +526,32 @@ impl ApplicationHandler for MD3ViewerApp {
                 let ambient = 0.3;
                 
                 if let Some(ref model) = self.current_model {
-                    let (min_x, max_x, min_y, max_y, min_z, max_z) = model.get_bounds(0);
-                    let center_x = (min_x + max_x) * 0.5;
-                    let center_y = (min_y + max_y) * 0.5;
-                    let center_z = (min_z + max_z) * 0.5;
-                    
-                    let size_x = max_x - min_x;
-                    let size_y = max_y - min_y;
-                    let size_z = max_z - min_z;
-                    let max_size = size_x.max(size_y).max(size_z);
-                    
-                    if max_size > 0.0 && self.camera_distance == 100.0 {
-                        self.camera_distance = max_size * 2.5;
-                    }
+                    let (min_bounds, max_bounds) = model.frame_bounds(0);
+                    let center = (min_bounds + max_bounds) * 0.5;
                     
                     let md3_correction = Mat3::from_rotation_x(-std::f32::consts::FRAC_PI_2);
-                    let translation = Mat4::from_translation(Vec3::new(-center_x, -center_y, -center_z));
+                    let translation = Mat4::from_translation(-center);
                     let rotation = Mat4::from_mat3(md3_correction);
-                    let model_mat = rotation * translation;
+                    let scale_mat = Mat4::from_scale(Vec3::splat(self.model_scale));
+                    let model_mat = rotation * scale_mat * translation;
+                    
+                    if self.show_grid {
+                        let grid_mat = Mat4::from_translation(Vec3::new(0.0, (min_bounds.z - center.z) * self.model_scale, 0.0));
+                        let target = RenderTarget {
+                            color: scene_view,
+                            depth: depth_view,
+                            format: wgpu_renderer.surface_config.format,
+                        };
+                        md3_renderer.render_floor_grid(&mut encoder, target, &self.floor_grid, grid_mat, view_proj);
+                    }
                     
                     md3_renderer.render_model(
                         &mut encoder,
-                        &view,
+                        scene_view,
                         depth_view,
                         wgpu_renderer.surface_config.format,
                         model,
-                        0,
+                        0.0,
                         &self.current_textures,
                         model_mat,
                         view_proj,
@@ -451,6 +562,40 @@ impl ApplicationHandler for MD3ViewerApp {
                     );
                 }
                 
+                wgpu_renderer.resolve_msaa(&mut encoder, &view);
+                
+                if self.show_texture_viewer {
+                    let keys = md3_renderer.texture_keys();
+                    if !keys.is_empty() {
+                        self.texture_view_index = self.texture_view_index.min(keys.len() - 1);
+                        let key = &keys[self.texture_view_index];
+                        if let (Some(ref texture_viewer), Some(texture)) = (&self.texture_viewer, md3_renderer.texture(key)) {
+                            texture_viewer.render(
+                                &wgpu_renderer.device,
+                                &mut encoder,
+                                &view,
+                                &wgpu_renderer.queue,
+                                texture,
+                                (width, height),
+                            );
+                        }
+                        if let Some(ref text_renderer) = self.text_renderer {
+                            let (tex_w, tex_h) = md3_renderer.texture_size(key).unwrap_or((0, 0));
+                            text_renderer.render_text(
+                                &mut encoder,
+                                &view,
+                                &format!("[{}/{}] {} ({}x{})  ,/.: Cycle | T: Close", self.texture_view_index + 1, keys.len(), key, tex_w, tex_h),
+                                20.0,
+                                30.0,
+                                20.0,
+                                [1.0, 1.0, 0.0, 1.0],
+                                width,
+                                height,
+                            );
+                        }
+                    }
+                }
+                
                 if let Some(ref text_renderer) = self.text_renderer {
                     let mut text_encoder = wgpu_renderer.device.create_command_encoder(
                         &wgpu::CommandEncoderDescriptor {
@@ -541,12 +686,28 @@ impl ApplicationHandler for MD3ViewerApp {
                                 width,
                                 height,
                             );
+
+                            if !self.show_texture_viewer {
+                                for (i, info) in model.mesh_info().iter().enumerate() {
+                                    text_renderer.render_text(
+                                        &mut text_encoder,
+                                        &view,
+                                        &format!("{}: {} verts, {} tris", info.name, info.num_vertices, info.num_triangles),
+                                        20.0,
+                                        60.0 + i as f32 * 22.0,
+                                        18.0,
+                                        [0.8, 0.8, 0.8, 1.0],
+                                        width,
+                                        height,
+                                    );
+                                }
+                            }
                         }
                         
                         text_renderer.render_text(
                             &mut text_encoder,
                             &view,
-                            "Arrow Keys: Rotate Camera | Q/E: Zoom | Tab: Show List | ESC: Exit",
+                            "Arrow Keys: Rotate Camera | Q/E: Zoom | Z/C: Roll | R: Lock Roll | G: Grid | [/]: Grid Spacing | F: Render Mode | O: Wire Overlay | W: Flip Winding | T: Textures | +/-/0: Scale | Tab: Show List | ESC: Exit",
                             20.0,
                             height as f32 - 30.0,
                             20.0,
@@ -571,7 +732,59 @@ impl ApplicationHandler for MD3ViewerApp {
     }
 }
 
+fn run_benchmark(args: &[String]) {
+    let config = match BenchmarkConfig::from_args(args) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("Usage: md3_viewer --benchmark <frames> --model <path.md3> [--lights N] [--shadows none|planar|volumes] [--size WxH]");
+            std::process::exit(2);
+        }
+    };
+    match benchmark::run_benchmark(&config) {
+        Ok(report) => report.print(),
+        Err(e) => {
+            eprintln!("Benchmark failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run_buffer_build_benchmark(args: &[String]) {
+    let value = |flag: &str| args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1));
+    let usage = "Usage: md3_viewer --buffer-benchmark <iterations> --model <path.md3>";
+    let Some(model_path) = value("--model") else {
+        eprintln!("{}", usage);
+        std::process::exit(2);
+    };
+    let iterations = match value("--buffer-benchmark").map(|v| v.parse::<usize>()) {
+        Some(Ok(iterations)) => iterations,
+        None => 100,
+        Some(Err(_)) => {
+            eprintln!("{}", usage);
+            std::process::exit(2);
+        }
+    };
+    match benchmark::run_buffer_build_benchmark(Path::new(model_path), iterations) {
+        Ok(report) => report.print(),
+        Err(e) => {
+            eprintln!("Benchmark failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.iter().any(|a| a == "--benchmark") {
+        run_benchmark(&args);
+        return;
+    }
+    if args.iter().any(|a| a == "--buffer-benchmark") {
+        run_buffer_build_benchmark(&args);
+        return;
+    }
+
     let event_loop = EventLoop::new().unwrap();
     let mut app = MD3ViewerApp::new();
     event_loop.run_app(&mut app).unwrap();
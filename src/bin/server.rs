@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use sas2::game::map::Map;
+use sas2::game::world::World;
+use sas2::game_loop::GameLoop;
+use sas2::net::protocol::DEFAULT_PORT;
+use sas2::net::Server;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let port = args.get(1).and_then(|p| p.parse::<u16>().ok()).unwrap_or(DEFAULT_PORT);
+    let map_name = args.get(2).map(|s| s.as_str()).unwrap_or("0-arena");
+
+    let mut world = World::new();
+    match Map::load_from_file(map_name) {
+        Ok(map) => {
+            println!("Loaded map: {}x{} tiles", map.width, map.height);
+            world.map = map;
+        }
+        Err(e) => println!("Failed to load map {}: {}, using default", map_name, e),
+    }
+
+    let mut server = match Server::bind(("0.0.0.0", port), world) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    println!("Server listening on {}", server.local_addr().unwrap());
+
+    let mut game_loop = GameLoop::new(60);
+    loop {
+        if let Err(e) = server.poll() {
+            eprintln!("{}", e);
+        }
+
+        let start_tick = server.tick();
+        game_loop.tick(|dt| server.step(dt));
+
+        if server.tick() != start_tick {
+            if let Err(e) = server.broadcast_snapshot() {
+                eprintln!("{}", e);
+            }
+        }
+
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
@@ -1,70 +1,241 @@
-#[derive(Clone, Debug)]
-pub struct AnimRange {
+use std::collections::HashMap;
+use glam::Vec3;
+use crate::engine::md3::{FrameBlend, PoseBlend};
+use crate::resource_path::resolve;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Animation {
+    BothDeath1,
+    BothDead1,
+    BothDeath2,
+    BothDead2,
+    BothDeath3,
+    BothDead3,
+    TorsoGesture,
+    TorsoAttack,
+    TorsoAttack2,
+    TorsoDrop,
+    TorsoRaise,
+    TorsoStand,
+    TorsoStand2,
+    LegsWalkcr,
+    LegsWalk,
+    LegsRun,
+    LegsBack,
+    LegsSwim,
+    LegsJump,
+    LegsLand,
+    LegsJumpb,
+    LegsLandb,
+    LegsIdle,
+    LegsIdlecr,
+    LegsTurn,
+}
+
+pub const NUM_ANIMATIONS: usize = 25;
+
+impl Animation {
+    pub const ALL: [Animation; NUM_ANIMATIONS] = [
+        Animation::BothDeath1,
+        Animation::BothDead1,
+        Animation::BothDeath2,
+        Animation::BothDead2,
+        Animation::BothDeath3,
+        Animation::BothDead3,
+        Animation::TorsoGesture,
+        Animation::TorsoAttack,
+        Animation::TorsoAttack2,
+        Animation::TorsoDrop,
+        Animation::TorsoRaise,
+        Animation::TorsoStand,
+        Animation::TorsoStand2,
+        Animation::LegsWalkcr,
+        Animation::LegsWalk,
+        Animation::LegsRun,
+        Animation::LegsBack,
+        Animation::LegsSwim,
+        Animation::LegsJump,
+        Animation::LegsLand,
+        Animation::LegsJumpb,
+        Animation::LegsLandb,
+        Animation::LegsIdle,
+        Animation::LegsIdlecr,
+        Animation::LegsTurn,
+    ];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Animation::BothDeath1 => "BOTH_DEATH1",
+            Animation::BothDead1 => "BOTH_DEAD1",
+            Animation::BothDeath2 => "BOTH_DEATH2",
+            Animation::BothDead2 => "BOTH_DEAD2",
+            Animation::BothDeath3 => "BOTH_DEATH3",
+            Animation::BothDead3 => "BOTH_DEAD3",
+            Animation::TorsoGesture => "TORSO_GESTURE",
+            Animation::TorsoAttack => "TORSO_ATTACK",
+            Animation::TorsoAttack2 => "TORSO_ATTACK2",
+            Animation::TorsoDrop => "TORSO_DROP",
+            Animation::TorsoRaise => "TORSO_RAISE",
+            Animation::TorsoStand => "TORSO_STAND",
+            Animation::TorsoStand2 => "TORSO_STAND2",
+            Animation::LegsWalkcr => "LEGS_WALKCR",
+            Animation::LegsWalk => "LEGS_WALK",
+            Animation::LegsRun => "LEGS_RUN",
+            Animation::LegsBack => "LEGS_BACK",
+            Animation::LegsSwim => "LEGS_SWIM",
+            Animation::LegsJump => "LEGS_JUMP",
+            Animation::LegsLand => "LEGS_LAND",
+            Animation::LegsJumpb => "LEGS_JUMPB",
+            Animation::LegsLandb => "LEGS_LANDB",
+            Animation::LegsIdle => "LEGS_IDLE",
+            Animation::LegsIdlecr => "LEGS_IDLECR",
+            Animation::LegsTurn => "LEGS_TURN",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|anim| anim.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn fallback(self) -> Option<Self> {
+        match self {
+            Animation::TorsoGesture
+            | Animation::TorsoAttack
+            | Animation::TorsoAttack2
+            | Animation::TorsoDrop
+            | Animation::TorsoRaise => Some(Animation::TorsoStand),
+            Animation::LegsLand | Animation::LegsLandb | Animation::LegsTurn => Some(Animation::LegsIdle),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnimClip {
     pub first_frame: usize,
     pub num_frames: usize,
     pub looping_frames: usize,
     pub fps: usize,
 }
 
-#[derive(Clone, Debug)]
-pub struct AnimEntry {
-    pub name: String,
-    pub range: AnimRange,
+impl Default for AnimClip {
+    fn default() -> Self {
+        Self {
+            first_frame: 0,
+            num_frames: 1,
+            looping_frames: 0,
+            fps: 10,
+        }
+    }
+}
+
+impl AnimClip {
+    pub fn is_looping(&self) -> bool {
+        self.looping_frames > 0
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.num_frames as f32 / self.fps.max(1) as f32
+    }
+
+    pub fn blend_at(&self, time: f32) -> FrameBlend {
+        let frames_passed = (time * self.fps as f32).max(0.0);
+        let num_frames = self.num_frames.max(1);
+        if !self.is_looping() {
+            let last = num_frames - 1;
+            let offset = frames_passed.min(last as f32);
+            let index = offset.floor() as usize;
+            let next = (index + 1).min(last);
+            return FrameBlend::new(self.first_frame + index, self.first_frame + next, offset.fract());
+        }
+
+        let loop_len = self.looping_frames.min(num_frames);
+        let loop_start = self.first_frame + num_frames - loop_len;
+        let (base, offset, span) = if frames_passed < num_frames as f32 {
+            (self.first_frame, frames_passed, num_frames)
+        } else {
+            let loop_offset = (frames_passed - num_frames as f32) % loop_len as f32;
+            (loop_start, loop_offset, loop_len)
+        };
+        let index = (offset.floor() as usize).min(span - 1);
+        // The last frame of a looping clip blends back into the start of its loop
+        let to = if index + 1 < span { base + index + 1 } else { loop_start };
+        FrameBlend::new(base + index, to, offset.fract())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Sex {
+    #[default]
+    Male,
+    Female,
+    Neuter,
 }
 
 #[derive(Clone, Debug)]
 pub struct AnimConfig {
-    pub entries: Vec<AnimEntry>,
-    pub both_death1: AnimRange,
-    pub both_dead1: AnimRange,
-    pub both_death2: AnimRange,
-    pub both_dead2: AnimRange,
-    pub both_death3: AnimRange,
-    pub both_dead3: AnimRange,
-    pub both_dead3_2: AnimRange,
-    pub torso_gesture: AnimRange,
-    pub torso_attack: AnimRange,
-    pub torso_attack2: AnimRange,
-    pub torso_drop: AnimRange,
-    pub torso_raise: AnimRange,
-    pub torso_stand: AnimRange,
-    pub torso_stand2: AnimRange,
-    pub legs_walkcr: AnimRange,
-    pub legs_walk: AnimRange,
-    pub legs_run: AnimRange,
-    pub legs_back: AnimRange,
-    pub legs_swim: AnimRange,
-    pub legs_jump: AnimRange,
-    pub legs_land: AnimRange,
-    pub legs_jumpb: AnimRange,
-    pub legs_landb: AnimRange,
-    pub legs_idle: AnimRange,
-    pub legs_idlecr: AnimRange,
-    pub legs_turn: AnimRange,
+    pub sex: Sex,
+    pub footsteps: String,
+    pub head_offset: [f32; 3],
+    pub clips: Vec<AnimClip>,
+    // Every entry in the file keyed by upper-case name, including ones past the standard set
+    pub named: HashMap<String, AnimClip>,
 }
 
 impl AnimConfig {
     pub fn load(model_name: &str) -> Result<Self, String> {
-        let path = format!("q3-resources/models/players/{}/animation.cfg", model_name);
-        let alt_path = format!("../q3-resources/models/players/{}/animation.cfg", model_name);
-        
+        let relative = format!("models/players/{}/animation.cfg", model_name);
+        let path = resolve(&relative, &[]).ok_or_else(|| format!("animation.cfg not found: {}", relative))?;
         let content = std::fs::read_to_string(&path)
-            .or_else(|_| std::fs::read_to_string(&alt_path))
             .map_err(|e| format!("Failed to read animation.cfg: {}", e))?;
-        
+
         Self::parse_content(&content)
     }
 
     pub fn parse_content(content: &str) -> Result<Self, String> {
-        let mut entries: Vec<AnimEntry> = Vec::new();
+        let mut sex = Sex::default();
+        let mut footsteps = "default".to_string();
+        let mut head_offset = [0.0; 3];
+        let mut clips: Vec<AnimClip> = Vec::with_capacity(NUM_ANIMATIONS);
+        let mut extra: Vec<(String, AnimClip)> = Vec::new();
 
         for line in content.lines() {
-            let line = line.trim();
-            if line.is_empty() || line.starts_with("//") || line.starts_with("sex") {
+            let mut split = line.splitn(2, "//");
+            let line = split.next().unwrap_or("").trim();
+            let comment = split.next().and_then(|c| c.split_whitespace().next());
+            if line.is_empty() {
                 continue;
             }
 
             let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts[0].to_ascii_lowercase().as_str() {
+                "sex" => {
+                    sex = match parts.get(1).and_then(|s| s.chars().next()) {
+                        Some('f') | Some('F') => Sex::Female,
+                        Some('n') | Some('N') => Sex::Neuter,
+                        _ => Sex::Male,
+                    };
+                    continue;
+                }
+                "footsteps" => {
+                    if let Some(value) = parts.get(1) {
+                        footsteps = value.to_string();
+                    }
+                    continue;
+                }
+                "headoffset" => {
+                    for (i, value) in parts.iter().skip(1).take(3).enumerate() {
+                        head_offset[i] = value.parse().unwrap_or(0.0);
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
             if parts.len() < 4 {
                 continue;
             }
@@ -74,85 +245,345 @@ impl AnimConfig {
                 parts[2].parse::<usize>(),
                 parts[3].parse::<usize>(),
             );
-            if let (Ok(first), Ok(num), Ok(loop_frames), Ok(fps)) = parsed {
-                let name = line
-                    .split("//")
-                    .nth(1)
-                    .map(|s| s.trim().to_string())
-                    .unwrap_or_else(|| format!("ANIM_{}", entries.len()));
-                let range = AnimRange {
-                    first_frame: first,
-                    num_frames: num,
-                    looping_frames: loop_frames,
-                    fps,
+            if let (Ok(first_frame), Ok(num_frames), Ok(looping_frames), Ok(fps)) = parsed {
+                let clip = AnimClip {
+                    first_frame,
+                    num_frames,
+                    looping_frames,
+                    fps: fps.max(1),
                 };
-                entries.push(AnimEntry { name, range });
+                if clips.len() < NUM_ANIMATIONS {
+                    clips.push(clip);
+                } else if let Some(name) = comment {
+                    extra.push((name.to_ascii_uppercase(), clip));
+                }
             }
         }
 
-        let mut ranges: Vec<AnimRange> = entries.iter().map(|e| e.range.clone()).collect();
+        if clips.len() < Animation::LegsWalkcr.index() + 1 {
+            return Err(format!("animation.cfg has only {} animations", clips.len()));
+        }
 
-        let skip = if ranges.len() > 13 {
-            if ranges[13].first_frame > ranges[6].first_frame {
-                ranges[13].first_frame - ranges[6].first_frame
-            } else {
-                0
+        // Legs frames are numbered after the torso frames in the file, but lower.md3
+        // only contains the shared death frames followed by the legs frames
+        let skip = clips[Animation::LegsWalkcr.index()]
+            .first_frame
+            .saturating_sub(clips[Animation::TorsoGesture.index()].first_frame);
+        for clip in clips.iter_mut().skip(Animation::LegsWalkcr.index()) {
+            clip.first_frame = clip.first_frame.saturating_sub(skip);
+        }
+        for (name, clip) in extra.iter_mut() {
+            if name.starts_with("LEGS_") {
+                clip.first_frame = clip.first_frame.saturating_sub(skip);
             }
-        } else {
-            0
-        };
+        }
+
+        clips.resize(NUM_ANIMATIONS, AnimClip::default());
+
+        let mut named: HashMap<String, AnimClip> = Animation::ALL
+            .iter()
+            .map(|anim| (anim.name().to_string(), clips[anim.index()].clone()))
+            .collect();
+        named.extend(extra);
+
+        Ok(AnimConfig {
+            sex,
+            footsteps,
+            head_offset,
+            clips,
+            named,
+        })
+    }
+
+    pub fn get(&self, anim: Animation) -> &AnimClip {
+        &self.clips[anim.index()]
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<&AnimClip> {
+        self.named.get(&name.to_ascii_uppercase())
+    }
+
+    pub fn head_offset(&self) -> Vec3 {
+        Vec3::from(self.head_offset)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AnimState {
+    legs: Animation,
+    legs_time: f32,
+    // Second legs animation mixed over `legs` by weight, phase-synced to it
+    legs_blend: Option<(Animation, f32)>,
+    torso: Animation,
+    torso_time: f32,
+}
+
+impl Default for AnimState {
+    fn default() -> Self {
+        Self {
+            legs: Animation::LegsIdle,
+            legs_time: 0.0,
+            legs_blend: None,
+            torso: Animation::TorsoStand,
+            torso_time: 0.0,
+        }
+    }
+}
+
+impl AnimState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn legs(&self) -> Animation {
+        self.legs
+    }
+
+    pub fn torso(&self) -> Animation {
+        self.torso
+    }
+
+    pub fn legs_time(&self) -> f32 {
+        self.legs_time
+    }
+
+    pub fn torso_time(&self) -> f32 {
+        self.torso_time
+    }
+
+    pub fn blend(&self) -> Option<(Animation, f32)> {
+        self.legs_blend
+    }
+
+    pub fn set_legs(&mut self, anim: Animation) {
+        self.legs_blend = None;
+        if self.legs != anim {
+            self.legs = anim;
+            self.legs_time = 0.0;
+        }
+    }
+
+    pub fn set_blend(&mut self, anim_a: Animation, anim_b: Animation, weight: f32) {
+        self.set_legs(anim_a);
+        self.legs_blend = Some((anim_b, weight.clamp(0.0, 1.0)));
+    }
 
-        for i in 13..ranges.len() {
-            ranges[i].first_frame = ranges[i].first_frame.saturating_sub(skip);
+    pub fn set_torso(&mut self, anim: Animation) {
+        if self.torso != anim {
+            self.torso = anim;
+            self.torso_time = 0.0;
         }
+    }
 
-        for (entry, range) in entries.iter_mut().zip(ranges.iter()) {
-            entry.range = range.clone();
+    pub fn advance(&mut self, dt: f32, config: &AnimConfig) -> (PoseBlend, FrameBlend) {
+        self.legs_time += dt;
+        self.torso_time += dt;
+        let legs = self.legs;
+        let legs_frame = Self::settle(&mut self.legs, &mut self.legs_time, config);
+        let torso_frame = Self::settle(&mut self.torso, &mut self.torso_time, config);
+        if self.legs != legs {
+            self.legs_blend = None;
         }
+        (self.legs_pose(legs_frame, config), torso_frame)
+    }
 
-        let get = |i: usize| -> AnimRange {
-            ranges
-                .get(i)
-                .cloned()
-                .unwrap_or(AnimRange { first_frame: 0, num_frames: 1, looping_frames: 0, fps: 10 })
+    fn legs_pose(&self, legs_frame: FrameBlend, config: &AnimConfig) -> PoseBlend {
+        let Some((other, weight)) = self.legs_blend else {
+            return legs_frame.into();
         };
+        let phase = self.legs_time / config.get(self.legs).duration().max(f32::EPSILON);
+        let other_clip = config.get(other);
+        PoseBlend::new(legs_frame, other_clip.blend_at(phase * other_clip.duration()), weight)
+    }
 
-        Ok(AnimConfig {
-            entries,
-            both_death1: get(0),
-            both_dead1: get(1),
-            both_death2: get(2),
-            both_dead2: get(3),
-            both_death3: get(4),
-            both_dead3: get(5),
-            both_dead3_2: get(5), // Reuse or placeholder
-            torso_gesture: get(6),
-            torso_attack: get(7),
-            torso_attack2: get(8),
-            torso_drop: get(9),
-            torso_raise: get(10),
-            torso_stand: get(11),
-            torso_stand2: get(12),
-            legs_walkcr: get(13),
-            legs_walk: get(14),
-            legs_run: get(15),
-            legs_back: get(16),
-            legs_swim: get(17),
-            legs_jump: get(18),
-            legs_land: get(19),
-            legs_jumpb: get(20),
-            legs_landb: get(21),
-            legs_idle: get(22),
-            legs_idlecr: get(23),
-            legs_turn: get(24),
-        })
+    fn settle(anim: &mut Animation, time: &mut f32, config: &AnimConfig) -> FrameBlend {
+        let clip = config.get(*anim);
+        if !clip.is_looping() && *time >= clip.duration() {
+            if let Some(next) = anim.fallback() {
+                *time -= clip.duration();
+                *anim = next;
+            }
+        }
+        config.get(*anim).blend_at(*time)
+    }
+}
+
+// A frame picked by hand for inspecting a model; while unset the animation runs as normal
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ManualFrame {
+    frame: Option<usize>,
+}
+
+impl ManualFrame {
+    pub fn frame(&self) -> Option<usize> {
+        self.frame
     }
 
-    pub fn by_name(&self, name: &str) -> Option<&AnimRange> {
-        self.entries
-            .iter()
-            .find(|e| e.name.eq_ignore_ascii_case(name))
-            .map(|e| &e.range)
+    pub fn toggle(&mut self) {
+        self.frame = match self.frame {
+            Some(_) => None,
+            None => Some(0),
+        };
+    }
+
+    // Stepping also turns the mode on, and wraps at either end of `num_frames`
+    pub fn step(&mut self, delta: i32, num_frames: usize) {
+        if num_frames == 0 {
+            return;
+        }
+        let current = self.frame.unwrap_or(0) as i32;
+        self.frame = Some((current + delta).rem_euclid(num_frames as i32) as usize);
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn walk_run_config() -> AnimConfig {
+        let mut clips = vec![AnimClip::default(); NUM_ANIMATIONS];
+        clips[Animation::LegsWalk.index()] = AnimClip { first_frame: 10, num_frames: 10, looping_frames: 10, fps: 10 };
+        clips[Animation::LegsRun.index()] = AnimClip { first_frame: 30, num_frames: 10, looping_frames: 10, fps: 10 };
+        AnimConfig {
+            sex: Sex::Male,
+            footsteps: "default".to_string(),
+            head_offset: [0.0; 3],
+            clips,
+            named: HashMap::new(),
+        }
+    }
+
+    fn blended_legs(weight: f32) -> PoseBlend {
+        let config = walk_run_config();
+        let mut state = AnimState::new();
+        state.set_blend(Animation::LegsWalk, Animation::LegsRun, weight);
+        state.advance(0.25, &config).0
+    }
+
+    #[test]
+    fn blend_weights_select_and_mix_the_two_clips() {
+        let walk = FrameBlend::new(12, 13, 0.5);
+        let run = FrameBlend::new(32, 33, 0.5);
+
+        let pure_walk = blended_legs(0.0);
+        assert_eq!((pure_walk.a, pure_walk.weight), (walk, 0.0));
+        assert_eq!(pure_walk.dominant(), walk);
+
+        let pure_run = blended_legs(1.0);
+        assert_eq!((pure_run.b, pure_run.weight), (run, 1.0));
+        assert_eq!(pure_run.dominant(), run);
+
+        let half = blended_legs(0.5);
+        assert_eq!((half.a, half.b, half.weight), (walk, run, 0.5));
+    }
+
+    #[test]
+    fn looping_clip_blends_from_the_last_frame_back_to_the_loop_start() {
+        let clip = AnimClip { first_frame: 10, num_frames: 10, looping_frames: 10, fps: 10 };
+        assert_eq!(clip.blend_at(0.95), FrameBlend::new(19, 10, 0.5));
+        assert_eq!(clip.blend_at(1.25), FrameBlend::new(12, 13, 0.5));
+
+        // Only the last four frames loop, so the wrap goes back to frame 16
+        let clip = AnimClip { first_frame: 10, num_frames: 10, looping_frames: 4, fps: 10 };
+        assert_eq!(clip.blend_at(1.35), FrameBlend::new(19, 16, 0.5));
+        assert_eq!(clip.blend_at(1.45), FrameBlend::new(16, 17, 0.5));
+    }
+
+    #[test]
+    fn one_shot_clip_holds_its_last_frame() {
+        let clip = AnimClip { first_frame: 10, num_frames: 5, looping_frames: 0, fps: 10 };
+        assert_eq!(clip.blend_at(0.45), FrameBlend::new(14, 14, 0.0));
+        assert_eq!(clip.blend_at(3.0), FrameBlend::new(14, 14, 0.0));
+    }
+
+    #[test]
+    fn switching_legs_drops_the_blend() {
+        let mut state = AnimState::new();
+        state.set_blend(Animation::LegsWalk, Animation::LegsRun, 0.5);
+        state.set_legs(Animation::LegsJump);
+        assert_eq!(state.blend(), None);
+    }
+
+    const ANIMATION_CFG: &str = "
+// trimmed from a stock player model
+sex f
+footsteps boot
+headoffset -2 0 1.5
+
+0   30  0   25  // BOTH_DEATH1
+29  1   0   25  // BOTH_DEAD1
+30  30  0   25  // BOTH_DEATH2
+59  1   0   25  // BOTH_DEAD2
+60  30  0   25  // BOTH_DEATH3
+89  1   0   25  // BOTH_DEAD3
+90  40  0   20  // TORSO_GESTURE
+130 6   0   20  // TORSO_ATTACK
+136 6   0   15  // TORSO_ATTACK2
+142 5   0   20  // TORSO_DROP
+147 4   0   20  // TORSO_RAISE
+151 1   0   15  // TORSO_STAND
+152 1   0   15  // TORSO_STAND2
+153 8   8   20  // LEGS_WALKCR
+161 12  12  20  // LEGS_WALK
+173 9   9   20  // LEGS_RUN
+182 10  10  20  // LEGS_BACK
+192 10  10  15  // LEGS_SWIM
+202 8   0   15  // LEGS_JUMP
+210 1   0   15  // LEGS_LAND
+211 8   0   15  // LEGS_JUMPB
+219 1   0   15  // LEGS_LANDB
+220 10  10  15  // LEGS_IDLE
+230 10  10  15  // LEGS_IDLECR
+240 7   7   15  // LEGS_TURN
+";
+
+    #[test]
+    fn parses_header_and_shifts_legs_frames_past_the_torso_block() {
+        let config = AnimConfig::parse_content(ANIMATION_CFG).unwrap();
+        assert_eq!(config.sex, Sex::Female);
+        assert_eq!(config.footsteps, "boot");
+        assert_eq!(config.head_offset(), Vec3::new(-2.0, 0.0, 1.5));
+
+        // The 63 torso-only frames are missing from lower.md3, so legs clips start right after the deaths
+        assert_eq!(config.get(Animation::TorsoAttack).first_frame, 130);
+        assert_eq!(config.get(Animation::LegsWalkcr).first_frame, 90);
+        assert_eq!(config.get(Animation::LegsRun).first_frame, 110);
+        assert_eq!(config.get(Animation::LegsTurn).first_frame, 177);
+        assert_eq!(config.by_name("legs_run"), Some(config.get(Animation::LegsRun)));
+    }
+
+    #[test]
+    fn parsed_run_and_attack_advance_through_their_own_frames() {
+        let config = AnimConfig::parse_content(ANIMATION_CFG).unwrap();
+        let mut state = AnimState::new();
+        state.set_legs(Animation::LegsRun);
+        state.set_torso(Animation::TorsoAttack);
+
+        let (legs, torso) = state.advance(0.125, &config);
+        assert_eq!(legs.dominant(), FrameBlend::new(112, 113, 0.5));
+        assert_eq!(torso, FrameBlend::new(132, 133, 0.5));
+
+        // Ten frames into the nine-frame run it has looped once; the one-shot attack hands over to TORSO_STAND
+        let (legs, torso) = state.advance(0.375, &config);
+        assert_eq!(legs.dominant(), FrameBlend::new(111, 112, 0.0));
+        assert_eq!(state.torso(), Animation::TorsoStand);
+        assert_eq!(torso, FrameBlend::new(151, 151, 0.0));
+    }
+
+    #[test]
+    fn manual_frames_wrap_and_release_the_animation() {
+        let mut manual = ManualFrame::default();
+        manual.step(1, 3);
+        manual.step(1, 3);
+        assert_eq!(manual.frame(), Some(2));
+        manual.step(1, 3);
+        assert_eq!(manual.frame(), Some(0));
+        manual.step(-1, 3);
+        assert_eq!(manual.frame(), Some(2));
+
+        manual.toggle();
+        assert_eq!(manual.frame(), None);
+        manual.toggle();
+        assert_eq!(manual.frame(), Some(0));
+    }
+}
@@ -1,7 +1,15 @@
-use wgpu::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, ImageCopyTexture, Origin3d, TextureAspect, ImageDataLayout, TextureViewDescriptor, SamplerDescriptor, FilterMode, AddressMode};
+use wgpu::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, ImageCopyTexture, Origin3d, TextureAspect, ImageDataLayout, TextureViewDescriptor};
+use crate::render::textures::create_model_sampler;
+use crate::render::texture_loader::load_rgba;
 use crate::engine::renderer::{WgpuRenderer, MD3Renderer, WgpuTexture};
-use crate::engine::md3::MD3Model;
-use std::path::Path;
+use crate::engine::md3::{MD3Model, ModelId};
+use crate::game::weapon::Weapon;
+use crate::resource_path::{resolve, resource_paths, ResourcePaths, IMAGE_EXTENSIONS};
+use crate::skin::SkinSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
 
 pub fn load_textures_for_model_static(
     wgpu_renderer: &mut WgpuRenderer,
@@ -10,218 +18,52 @@ pub fn load_textures_for_model_static(
     model_name: &str,
     part: &str,
 ) -> Vec<Option<String>> {
-    let mut texture_paths = Vec::new();
-    let mut mesh_texture_map = std::collections::HashMap::new();
-    
-    let skin_candidates = vec![
-        format!("q3-resources/models/players/{}/{}_default.skin", model_name, part),
-        format!("../q3-resources/models/players/{}/{}_default.skin", model_name, part),
-        format!("q3-resources/models/players/{}/{}.skin", model_name, part),
-        format!("../q3-resources/models/players/{}/{}.skin", model_name, part),
-    ];
-    
-    for skin_path in skin_candidates {
-        if let Ok(content) = std::fs::read_to_string(&skin_path) {
-            println!("Loaded skin file: {}", skin_path);
-            for line in content.lines() {
-                let line = line.trim();
-                if line.is_empty() || line.starts_with("//") {
-                    continue;
-                }
-                let parts: Vec<&str> = line.split(',').collect();
-                if parts.len() == 2 {
-                    let mesh_name = parts[0].trim().to_string();
-                    let mut texture_path = parts[1].trim().to_string();
-                    if !texture_path.is_empty() {
-                        if !texture_path.starts_with("q3-resources/") {
-                            texture_path = format!("q3-resources/{}", texture_path);
-                        }
-                        let texture_path_clone = texture_path.clone();
-                        mesh_texture_map.insert(mesh_name.clone(), texture_path);
-                        println!("  Mesh '{}' -> texture '{}'", mesh_name, texture_path_clone);
-                    }
-                }
-            }
-            break;
-        }
-    }
-    
-    for (_mesh_idx, mesh) in model.meshes.iter().enumerate() {
-        let mesh_name = std::str::from_utf8(&mesh.header.name)
-            .unwrap_or("")
-            .trim_end_matches('\0')
-            .to_string();
-        
-        let texture_path = mesh_texture_map.get(&mesh_name)
-            .cloned()
-            .or_else(|| {
-                let candidates = vec![
-                    format!("q3-resources/models/players/{}/{}_{}.tga", model_name, part, mesh_name),
-                    format!("q3-resources/models/players/{}/{}_{}.png", model_name, part, mesh_name),
-                    format!("q3-resources/models/players/{}/{}_{}.jpg", model_name, part, mesh_name),
-                    format!("../q3-resources/models/players/{}/{}_{}.tga", model_name, part, mesh_name),
-                    format!("../q3-resources/models/players/{}/{}_{}.png", model_name, part, mesh_name),
-                    format!("../q3-resources/models/players/{}/{}_{}.jpg", model_name, part, mesh_name),
-                    format!("q3-resources/models/players/{}/{}.tga", model_name, mesh_name),
-                    format!("q3-resources/models/players/{}/{}.png", model_name, mesh_name),
-                    format!("q3-resources/models/players/{}/{}.jpg", model_name, mesh_name),
-                    format!("../q3-resources/models/players/{}/{}.tga", model_name, mesh_name),
-                    format!("../q3-resources/models/players/{}/{}.png", model_name, mesh_name),
-                    format!("../q3-resources/models/players/{}/{}.jpg", model_name, mesh_name),
-                ];
-                candidates.iter()
-                    .find(|p| std::path::Path::new(p).exists())
-                    .map(|s| s.to_string())
-            });
-        
-        let mut texture_loaded = false;
-        if let Some(ref path) = texture_path {
-            let mut alt_paths = vec![];
-            
-            if path.ends_with(".TGA") {
-                let png_path = path.replace(".TGA", ".png");
-                alt_paths.push(png_path.clone());
-                alt_paths.push(format!("../{}", png_path));
-            }
-            
-            alt_paths.push(path.clone());
-            alt_paths.push(format!("../{}", path));
-            
-            if path.ends_with(".png") {
-                alt_paths.push(path.replace(".png", ".jpg"));
-                alt_paths.push(path.replace(".png", ".tga"));
-                alt_paths.push(format!("../{}", path.replace(".png", ".jpg")));
-                alt_paths.push(format!("../{}", path.replace(".png", ".tga")));
-            } else if path.ends_with(".jpg") {
-                alt_paths.push(path.replace(".jpg", ".png"));
-                alt_paths.push(path.replace(".jpg", ".tga"));
-                alt_paths.push(format!("../{}", path.replace(".jpg", ".png")));
-                alt_paths.push(format!("../{}", path.replace(".jpg", ".tga")));
-            } else if path.ends_with(".tga") {
-                alt_paths.push(path.replace(".tga", ".png"));
-                alt_paths.push(path.replace(".tga", ".jpg"));
-                alt_paths.push(format!("../{}", path.replace(".tga", ".png")));
-                alt_paths.push(format!("../{}", path.replace(".tga", ".jpg")));
-            } else if path.ends_with(".TGA") {
-                alt_paths.push(path.replace(".TGA", ".jpg"));
-                alt_paths.push(format!("../{}", path.replace(".TGA", ".jpg")));
-            }
-            
-            for alt_path in alt_paths {
-                if std::path::Path::new(&alt_path).exists() {
-                    if let Ok(data) = std::fs::read(&alt_path) {
-                        if let Ok(img) = image::load_from_memory(&data) {
-                            let img = img.to_rgba8();
-                            let size = Extent3d {
-                                width: img.width(),
-                                height: img.height(),
-                                depth_or_array_layers: 1,
-                            };
-                            let texture = wgpu_renderer.device.create_texture(&TextureDescriptor {
-                                label: Some("MD3 Texture"),
-                                size,
-                                mip_level_count: 1,
-                                sample_count: 1,
-                                dimension: TextureDimension::D2,
-                                format: TextureFormat::Rgba8UnormSrgb,
-                                usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
-                                view_formats: &[],
-                            });
-
-                            wgpu_renderer.queue.write_texture(
-                                ImageCopyTexture {
-                                    texture: &texture,
-                                    mip_level: 0,
-                                    origin: Origin3d::ZERO,
-                                    aspect: TextureAspect::All,
-                                },
-                                &img,
-                                ImageDataLayout {
-                                    offset: 0,
-                                    bytes_per_row: Some(4 * img.width()),
-                                    rows_per_image: Some(img.height()),
-                                },
-                                size,
-                            );
-
-                            let view = texture.create_view(&TextureViewDescriptor::default());
-                            let sampler = wgpu_renderer.device.create_sampler(&SamplerDescriptor {
-                                address_mode_u: AddressMode::Repeat,
-                                address_mode_v: AddressMode::Repeat,
-                                address_mode_w: AddressMode::Repeat,
-                                mag_filter: FilterMode::Linear,
-                                min_filter: FilterMode::Linear,
-                                mipmap_filter: FilterMode::Linear,
-                                ..Default::default()
-                            });
-
-                            let wgpu_tex = WgpuTexture {
-                                texture,
-                                view,
-                                sampler,
-                            };
-
-                            md3_renderer.load_texture(path, wgpu_tex);
-                            println!("Loaded texture: {} for mesh: {} (from file: {})", path, mesh_name, alt_path);
-                            texture_loaded = true;
-                            break;
-                        }
-                    }
-                }
-            }
-            if !texture_loaded {
-                println!("Warning: texture not found for mesh: {} (path: {:?})", mesh_name, path);
-            }
-        } else {
-            println!("Warning: no texture path for mesh: {}", mesh_name);
-        }
-        
-        texture_paths.push(texture_path);
-    }
-    
-    println!("Total textures loaded: {}/{}", texture_paths.iter().filter(|p| p.is_some()).count(), texture_paths.len());
-    texture_paths
+    load_textures_for_model_skin_static(wgpu_renderer, md3_renderer, model, model_name, part, "default")
 }
 
-pub fn load_weapon_textures_static(
+pub fn list_available_skins(model_name: &str) -> Vec<String> {
+    SkinSet::player_dir(model_name)
+        .map(|dir| SkinSet::available_skins(&dir))
+        .unwrap_or_default()
+}
+
+pub fn load_textures_for_model_skin_static(
+    wgpu_renderer: &mut WgpuRenderer,
+    md3_renderer: &mut MD3Renderer,
+    model: &MD3Model,
+    model_name: &str,
+    part: &str,
+    skin: &str,
+) -> Vec<Option<String>> {
+    let skin_set = SkinSet::load_player(model_name, skin);
+    load_textures_for_skin_set(wgpu_renderer, md3_renderer, model, model_name, part, &skin_set)
+}
+
+pub fn load_textures_for_skin_set(
     wgpu_renderer: &mut WgpuRenderer,
     md3_renderer: &mut MD3Renderer,
     model: &MD3Model,
+    model_name: &str,
+    part: &str,
+    skin_set: &SkinSet,
 ) -> Vec<Option<String>> {
     let mut texture_paths = Vec::new();
     
-    let weapon_candidates: Vec<Vec<&str>> = vec![
-        vec![
-            "q3-resources/models/weapons2/rocketl/rocketl.png",
-            "q3-resources/models/weapons2/rocketl/rocketl.jpg",
-            "../q3-resources/models/weapons2/rocketl/rocketl.png",
-            "../q3-resources/models/weapons2/rocketl/rocketl.jpg",
-        ],
-        vec![
-            "q3-resources/models/weapons2/rocketl/rocketl2.png",
-            "q3-resources/models/weapons2/rocketl/rocketl2.jpg",
-            "../q3-resources/models/weapons2/rocketl/rocketl2.png",
-            "../q3-resources/models/weapons2/rocketl/rocketl2.jpg",
-        ],
-    ];
-
-    for (_mesh_idx, candidates) in weapon_candidates.iter().take(model.meshes.len()).enumerate() {
-        let texture_path = candidates
-            .iter()
-            .find(|p| std::path::Path::new(p).exists())
-            .map(|s| s.to_string());
-
+    for mesh in &model.meshes {
+        let mesh_name = mesh.name().to_string();
+        let texture_path = skin_mesh_texture(resource_paths(), model_name, part, &mesh_name, skin_set);
+        
+        let mut texture_loaded = false;
         if let Some(ref path) = texture_path {
-            if let Ok(data) = std::fs::read(path) {
-                if let Ok(img) = image::load_from_memory(&data) {
-                    let img = img.to_rgba8();
+            if let Some(alt_path) = resolve(path, IMAGE_EXTENSIONS) {
+                if let Ok(img) = load_rgba(&alt_path) {
                     let size = Extent3d {
                         width: img.width(),
                         height: img.height(),
                         depth_or_array_layers: 1,
                     };
                     let texture = wgpu_renderer.device.create_texture(&TextureDescriptor {
-                        label: Some("Weapon Texture"),
+                        label: Some("MD3 Texture"),
                         size,
                         mip_level_count: 1,
                         sample_count: 1,
@@ -248,15 +90,7 @@ pub fn load_weapon_textures_static(
                     );
 
                     let view = texture.create_view(&TextureViewDescriptor::default());
-                    let sampler = wgpu_renderer.device.create_sampler(&SamplerDescriptor {
-                        address_mode_u: AddressMode::Repeat,
-                        address_mode_v: AddressMode::Repeat,
-                        address_mode_w: AddressMode::Repeat,
-                        mag_filter: FilterMode::Linear,
-                        min_filter: FilterMode::Linear,
-                        mipmap_filter: FilterMode::Linear,
-                        ..Default::default()
-                    });
+                    let sampler = create_model_sampler(&wgpu_renderer.device, wgpu_renderer.anisotropy());
 
                     let wgpu_tex = WgpuTexture {
                         texture,
@@ -265,8 +99,185 @@ pub fn load_weapon_textures_static(
                     };
 
                     md3_renderer.load_texture(path, wgpu_tex);
+                    println!("Loaded texture: {} for mesh: {} (from file: {})", path, mesh_name, alt_path.display());
+                    texture_loaded = true;
                 }
             }
+            if !texture_loaded {
+                println!("Warning: texture not found for mesh: {} (path: {:?})", mesh_name, path);
+            }
+        } else {
+            println!("Warning: no texture path for mesh: {}", mesh_name);
+        }
+        
+        texture_paths.push(texture_path);
+    }
+    
+    println!("Total textures loaded: {}/{}", texture_paths.iter().filter(|p| p.is_some()).count(), texture_paths.len());
+    texture_paths
+}
+
+// The skin's texture for the mesh when it exists on disk, else one named after the part or mesh
+pub fn skin_mesh_texture(
+    resources: &ResourcePaths,
+    model_name: &str,
+    part: &str,
+    mesh_name: &str,
+    skin_set: &SkinSet,
+) -> Option<String> {
+    let skin_texture = skin_set
+        .texture(part, mesh_name)
+        .filter(|path| resources.resolve(path, IMAGE_EXTENSIONS).is_some());
+    if skin_texture.is_none() && skin_set.has_part(part) {
+        println!("  Skin '{}' has no usable texture for mesh '{}', searching by mesh name", skin_set.name, mesh_name);
+    }
+
+    skin_texture.cloned().or_else(|| {
+        [format!("{}_{}", part, mesh_name), mesh_name.to_string()]
+            .iter()
+            .find_map(|name| {
+                resources.resolve(&format!("models/players/{}/{}.tga", model_name, name), &["tga", "png", "jpg"])
+            })
+            .map(|path| path.to_string_lossy().to_string())
+    })
+}
+
+pub struct WeaponPart {
+    pub model: MD3Model,
+    pub textures: Vec<Option<String>>,
+}
+
+pub struct WeaponModel {
+    pub weapon: Weapon,
+    pub main: WeaponPart,
+    pub barrel: Option<WeaponPart>,
+    pub flash: Option<WeaponPart>,
+}
+
+impl WeaponModel {
+    pub fn model_ids(&self) -> Vec<ModelId> {
+        std::iter::once(&self.main)
+            .chain(self.barrel.as_ref())
+            .chain(self.flash.as_ref())
+            .map(|part| part.model.id)
+            .collect()
+    }
+}
+
+fn load_weapon_part(
+    wgpu_renderer: &mut WgpuRenderer,
+    md3_renderer: &mut MD3Renderer,
+    model_path: &str,
+) -> Result<WeaponPart, String> {
+    let resolved = resolve(model_path, &[])
+        .ok_or_else(|| format!("Weapon model not found: {}", model_path))?;
+    let model = MD3Model::load(&resolved).map_err(|e| format!("{}: {}", model_path, e))?;
+    let textures = load_weapon_textures_static(wgpu_renderer, md3_renderer, &model, model_path);
+    Ok(WeaponPart { model, textures })
+}
+
+pub fn load_weapon(
+    wgpu_renderer: &mut WgpuRenderer,
+    md3_renderer: &mut MD3Renderer,
+    weapon: Weapon,
+) -> Result<WeaponModel, String> {
+    let main = load_weapon_part(wgpu_renderer, md3_renderer, &weapon.model_path())?;
+    let barrel = load_weapon_part(wgpu_renderer, md3_renderer, &weapon.part_model_path("_barrel")).ok();
+    let flash = load_weapon_part(wgpu_renderer, md3_renderer, &weapon.part_model_path("_flash")).ok();
+    println!(
+        "Loaded weapon {}: {} meshes, barrel: {}, flash: {}",
+        weapon.name(),
+        main.model.meshes.len(),
+        barrel.is_some(),
+        flash.is_some()
+    );
+    Ok(WeaponModel { weapon, main, barrel, flash })
+}
+
+// Drops a replaced weapon along with the renderer's caches for its parts, leaving other models' alone
+pub fn unload_weapon(md3_renderer: &mut MD3Renderer, weapon: WeaponModel) {
+    for id in weapon.model_ids() {
+        md3_renderer.clear_model_cache(id);
+    }
+}
+
+// Texture files to try for a weapon mesh, best first: the mesh's own name, then the model's name
+// with the mesh number, then the bare model name. Extensions are swapped by `resolve`
+pub fn weapon_texture_candidates(model_path: &str, mesh_name: &str, mesh_idx: usize) -> Vec<String> {
+    let path = Path::new(model_path.trim_start_matches("../"));
+    let dir = path.parent().and_then(|p| p.to_str()).unwrap_or("");
+    let base_name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+
+    let mut names = Vec::new();
+    if !mesh_name.is_empty() {
+        names.push(mesh_name.to_string());
+    }
+    if mesh_idx > 0 {
+        names.push(format!("{}{}", base_name, mesh_idx + 1));
+    }
+    names.push(base_name.to_string());
+    names.iter().map(|name| format!("{}/{}.png", dir, name)).collect()
+}
+
+pub fn load_weapon_textures_static(
+    wgpu_renderer: &mut WgpuRenderer,
+    md3_renderer: &mut MD3Renderer,
+    model: &MD3Model,
+    model_path: &str,
+) -> Vec<Option<String>> {
+    let mut texture_paths = Vec::new();
+
+    for (mesh_idx, mesh) in model.meshes.iter().enumerate() {
+        let texture_path = weapon_texture_candidates(model_path, mesh.name(), mesh_idx)
+            .iter()
+            .find_map(|candidate| resolve(candidate, &["png", "jpg", "tga"]))
+            .map(|path| path.to_string_lossy().to_string());
+
+        if let Some(ref path) = texture_path {
+            if let Ok(img) = load_rgba(path) {
+                let size = Extent3d {
+                    width: img.width(),
+                    height: img.height(),
+                    depth_or_array_layers: 1,
+                };
+                let texture = wgpu_renderer.device.create_texture(&TextureDescriptor {
+                    label: Some("Weapon Texture"),
+                    size,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: TextureDimension::D2,
+                    format: TextureFormat::Rgba8UnormSrgb,
+                    usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+                    view_formats: &[],
+                });
+
+                wgpu_renderer.queue.write_texture(
+                    ImageCopyTexture {
+                        texture: &texture,
+                        mip_level: 0,
+                        origin: Origin3d::ZERO,
+                        aspect: TextureAspect::All,
+                    },
+                    &img,
+                    ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(4 * img.width()),
+                        rows_per_image: Some(img.height()),
+                    },
+                    size,
+                );
+
+                let view = texture.create_view(&TextureViewDescriptor::default());
+                let sampler = create_model_sampler(&wgpu_renderer.device, wgpu_renderer.anisotropy());
+
+                let wgpu_tex = WgpuTexture {
+                    texture,
+                    view,
+                    sampler,
+                };
+
+                md3_renderer.load_texture(path, wgpu_tex);
+            }
         }
 
         texture_paths.push(texture_path);
@@ -283,84 +294,60 @@ pub fn load_rocket_textures_static(
     let mut texture_paths = Vec::new();
     
     for mesh in &model.meshes {
-        let raw_name = std::str::from_utf8(&mesh.header.name)
-            .unwrap_or("")
-            .trim_end_matches('\0');
+        let raw_name = mesh.name();
         let shader_name = if raw_name.is_empty() || raw_name == "default" {
             "rocket"
         } else {
             raw_name
         };
         
-        let candidates = vec![
-            format!("q3-resources/models/ammo/rocket/{}.png", shader_name),
-            format!("q3-resources/models/ammo/rocket/{}.jpg", shader_name),
-            format!("q3-resources/models/ammo/rocket/{}.tga", shader_name),
-            format!("../q3-resources/models/ammo/rocket/{}.png", shader_name),
-            format!("../q3-resources/models/ammo/rocket/{}.jpg", shader_name),
-            format!("../q3-resources/models/ammo/rocket/{}.tga", shader_name),
-        ];
-        
-        let texture_path = candidates
-            .iter()
-            .find(|p| std::path::Path::new(p).exists())
-            .map(|s| s.to_string());
+        let texture_path = resolve(&format!("models/ammo/rocket/{}.png", shader_name), &["png", "jpg", "tga"])
+            .map(|path| path.to_string_lossy().to_string());
 
         if let Some(ref path) = texture_path {
-            if let Ok(data) = std::fs::read(path) {
-                if let Ok(img) = image::load_from_memory(&data) {
-                    let img = img.to_rgba8();
-                    let size = Extent3d {
-                        width: img.width(),
-                        height: img.height(),
-                        depth_or_array_layers: 1,
-                    };
-                    let texture = wgpu_renderer.device.create_texture(&TextureDescriptor {
-                        label: Some("Rocket Texture"),
-                        size,
-                        mip_level_count: 1,
-                        sample_count: 1,
-                        dimension: TextureDimension::D2,
-                        format: TextureFormat::Rgba8UnormSrgb,
-                        usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
-                        view_formats: &[],
-                    });
-
-                    wgpu_renderer.queue.write_texture(
-                        ImageCopyTexture {
-                            texture: &texture,
-                            mip_level: 0,
-                            origin: Origin3d::ZERO,
-                            aspect: TextureAspect::All,
-                        },
-                        &img,
-                        ImageDataLayout {
-                            offset: 0,
-                            bytes_per_row: Some(4 * img.width()),
-                            rows_per_image: Some(img.height()),
-                        },
-                        size,
-                    );
-
-                    let view = texture.create_view(&TextureViewDescriptor::default());
-                    let sampler = wgpu_renderer.device.create_sampler(&SamplerDescriptor {
-                        address_mode_u: AddressMode::Repeat,
-                        address_mode_v: AddressMode::Repeat,
-                        address_mode_w: AddressMode::Repeat,
-                        mag_filter: FilterMode::Linear,
-                        min_filter: FilterMode::Linear,
-                        mipmap_filter: FilterMode::Linear,
-                        ..Default::default()
-                    });
-
-                    let wgpu_tex = WgpuTexture {
-                        texture,
-                        view,
-                        sampler,
-                    };
-
-                    md3_renderer.load_texture(path, wgpu_tex);
-                }
+            if let Ok(img) = load_rgba(path) {
+                let size = Extent3d {
+                    width: img.width(),
+                    height: img.height(),
+                    depth_or_array_layers: 1,
+                };
+                let texture = wgpu_renderer.device.create_texture(&TextureDescriptor {
+                    label: Some("Rocket Texture"),
+                    size,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: TextureDimension::D2,
+                    format: TextureFormat::Rgba8UnormSrgb,
+                    usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+                    view_formats: &[],
+                });
+
+                wgpu_renderer.queue.write_texture(
+                    ImageCopyTexture {
+                        texture: &texture,
+                        mip_level: 0,
+                        origin: Origin3d::ZERO,
+                        aspect: TextureAspect::All,
+                    },
+                    &img,
+                    ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(4 * img.width()),
+                        rows_per_image: Some(img.height()),
+                    },
+                    size,
+                );
+
+                let view = texture.create_view(&TextureViewDescriptor::default());
+                let sampler = create_model_sampler(&wgpu_renderer.device, wgpu_renderer.anisotropy());
+
+                let wgpu_tex = WgpuTexture {
+                    texture,
+                    view,
+                    sampler,
+                };
+
+                md3_renderer.load_texture(path, wgpu_tex);
             }
         }
 
@@ -386,7 +373,7 @@ pub fn load_md3_textures_guess_static(
     let mut texture_paths = Vec::new();
 
     for (mesh_idx, mesh) in model.meshes.iter().enumerate() {
-        let raw_name = std::str::from_utf8(&mesh.header.name).unwrap_or("").trim_end_matches('\0');
+        let raw_name = mesh.name();
         let mesh_name = if raw_name.is_empty() || raw_name == "default" {
             base_name
         } else {
@@ -412,74 +399,50 @@ pub fn load_md3_textures_guess_static(
 
         let mut found: Option<String> = None;
         for name in candidate_names {
-            let candidates = [
-                format!("q3-resources/{}/{}.png", base_dir, name),
-                format!("q3-resources/{}/{}.jpg", base_dir, name),
-                format!("q3-resources/{}/{}.tga", base_dir, name),
-                format!("q3-resources/{}/{}.TGA", base_dir, name),
-                format!("../q3-resources/{}/{}.png", base_dir, name),
-                format!("../q3-resources/{}/{}.jpg", base_dir, name),
-                format!("../q3-resources/{}/{}.tga", base_dir, name),
-                format!("../q3-resources/{}/{}.TGA", base_dir, name),
-            ];
-
-            for candidate in candidates {
-                if !Path::new(&candidate).exists() {
-                    continue;
-                }
-                if let Ok(data) = std::fs::read(&candidate) {
-                    if let Ok(img) = image::load_from_memory(&data) {
-                        let img = img.to_rgba8();
-                        let size = Extent3d {
-                            width: img.width(),
-                            height: img.height(),
-                            depth_or_array_layers: 1,
-                        };
-                        let texture = wgpu_renderer.device.create_texture(&TextureDescriptor {
-                            label: Some("MD3 Guess Texture"),
-                            size,
-                            mip_level_count: 1,
-                            sample_count: 1,
-                            dimension: TextureDimension::D2,
-                            format: TextureFormat::Rgba8UnormSrgb,
-                            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
-                            view_formats: &[],
-                        });
-
-                        wgpu_renderer.queue.write_texture(
-                            ImageCopyTexture {
-                                texture: &texture,
-                                mip_level: 0,
-                                origin: Origin3d::ZERO,
-                                aspect: TextureAspect::All,
-                            },
-                            &img,
-                            ImageDataLayout {
-                                offset: 0,
-                                bytes_per_row: Some(4 * img.width()),
-                                rows_per_image: Some(img.height()),
-                            },
-                            size,
-                        );
-
-                        let view = texture.create_view(&TextureViewDescriptor::default());
-                        let sampler = wgpu_renderer.device.create_sampler(&SamplerDescriptor {
-                            address_mode_u: AddressMode::Repeat,
-                            address_mode_v: AddressMode::Repeat,
-                            address_mode_w: AddressMode::Repeat,
-                            mag_filter: FilterMode::Linear,
-                            min_filter: FilterMode::Linear,
-                            mipmap_filter: FilterMode::Linear,
-                            ..Default::default()
-                        });
-
-                        let wgpu_tex = WgpuTexture { texture, view, sampler };
-
-                        let key = candidate.trim_start_matches("../").to_string();
-                        md3_renderer.load_texture(&key, wgpu_tex);
-                        found = Some(key);
-                        break;
-                    }
+            let relative = format!("{}/{}.png", base_dir, name);
+            if let Some(candidate) = resolve(&relative, &["png", "jpg", "tga", "TGA"]) {
+                if let Ok(img) = load_rgba(&candidate) {
+                    let size = Extent3d {
+                        width: img.width(),
+                        height: img.height(),
+                        depth_or_array_layers: 1,
+                    };
+                    let texture = wgpu_renderer.device.create_texture(&TextureDescriptor {
+                        label: Some("MD3 Guess Texture"),
+                        size,
+                        mip_level_count: 1,
+                        sample_count: 1,
+                        dimension: TextureDimension::D2,
+                        format: TextureFormat::Rgba8UnormSrgb,
+                        usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+                        view_formats: &[],
+                    });
+
+                    wgpu_renderer.queue.write_texture(
+                        ImageCopyTexture {
+                            texture: &texture,
+                            mip_level: 0,
+                            origin: Origin3d::ZERO,
+                            aspect: TextureAspect::All,
+                        },
+                        &img,
+                        ImageDataLayout {
+                            offset: 0,
+                            bytes_per_row: Some(4 * img.width()),
+                            rows_per_image: Some(img.height()),
+                        },
+                        size,
+                    );
+
+                    let view = texture.create_view(&TextureViewDescriptor::default());
+                    let sampler = create_model_sampler(&wgpu_renderer.device, wgpu_renderer.anisotropy());
+
+                    let wgpu_tex = WgpuTexture { texture, view, sampler };
+
+                    let ext = candidate.extension().and_then(|ext| ext.to_str()).unwrap_or("png");
+                    let key = format!("q3-resources/{}/{}.{}", base_dir, name, ext);
+                    md3_renderer.load_texture(&key, wgpu_tex);
+                    found = Some(key);
                 }
             }
 
@@ -492,4 +455,144 @@ pub fn load_md3_textures_guess_static(
     }
 
     texture_paths
-}
\ No newline at end of file
+}
+pub struct ModelLoadResult {
+    pub path: PathBuf,
+    pub result: Result<MD3Model, String>,
+}
+
+pub struct ModelLoadQueue {
+    jobs: Option<Sender<PathBuf>>,
+    results: Receiver<ModelLoadResult>,
+    workers: Vec<JoinHandle<()>>,
+    pending: usize,
+}
+
+impl ModelLoadQueue {
+    pub fn new(num_workers: usize) -> Self {
+        let (job_tx, job_rx) = channel::<PathBuf>();
+        let (result_tx, result_rx) = channel();
+        let job_rx = Arc::new(Mutex::new(job_rx));
+
+        let workers = (0..num_workers.max(1))
+            .map(|_| {
+                let job_rx = Arc::clone(&job_rx);
+                let result_tx = result_tx.clone();
+                std::thread::spawn(move || loop {
+                    let job = match job_rx.lock() {
+                        Ok(rx) => rx.recv(),
+                        Err(_) => break,
+                    };
+                    let path = match job {
+                        Ok(path) => path,
+                        Err(_) => break,
+                    };
+                    let result = std::panic::catch_unwind(|| MD3Model::load(&path).map_err(|e| e.to_string()))
+                        .unwrap_or_else(|_| Err("Loader panicked".to_string()));
+                    if result_tx.send(ModelLoadResult { path, result }).is_err() {
+                        break;
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            jobs: Some(job_tx),
+            results: result_rx,
+            workers,
+            pending: 0,
+        }
+    }
+
+    pub fn enqueue<P: Into<PathBuf>>(&mut self, path: P) {
+        if let Some(ref jobs) = self.jobs {
+            if jobs.send(path.into()).is_ok() {
+                self.pending += 1;
+            }
+        }
+    }
+
+    pub fn enqueue_batch<P: Into<PathBuf>, I: IntoIterator<Item = P>>(&mut self, paths: I) {
+        for path in paths {
+            self.enqueue(path);
+        }
+    }
+
+    pub fn poll(&mut self) -> Vec<ModelLoadResult> {
+        let mut completed = Vec::new();
+        while let Ok(result) = self.results.try_recv() {
+            self.pending = self.pending.saturating_sub(1);
+            completed.push(result);
+        }
+        completed
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.pending == 0
+    }
+}
+
+impl Drop for ModelLoadQueue {
+    fn drop(&mut self) {
+        self.jobs = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switching_skins_changes_the_mesh_texture_paths() {
+        let root = std::env::temp_dir().join(format!("sas2_skin_swap_{}", std::process::id()));
+        let model_dir = root.join("models/players/test");
+        std::fs::create_dir_all(&model_dir).unwrap();
+        std::fs::write(model_dir.join("lower_default.skin"), "l_legs,models/players/test/legs.tga\n").unwrap();
+        std::fs::write(model_dir.join("lower_red.skin"), "l_legs,models/players/test/legs_red.tga\n").unwrap();
+        std::fs::write(model_dir.join("legs.tga"), []).unwrap();
+        std::fs::write(model_dir.join("legs_red.tga"), []).unwrap();
+
+        let resources = ResourcePaths::new(vec![root.clone()]);
+        let texture = |skin: &str| {
+            skin_mesh_texture(&resources, "test", "lower", "l_legs", &SkinSet::load(&model_dir, skin))
+        };
+        let default = texture("default");
+        let red = texture("red");
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(default.as_deref(), Some("q3-resources/models/players/test/legs.tga"));
+        assert_eq!(red.as_deref(), Some("q3-resources/models/players/test/legs_red.tga"));
+    }
+
+    #[test]
+    fn shotgun_resolves_its_model_and_texture_paths() {
+        let model_path = Weapon::Shotgun.model_path();
+        assert_eq!(model_path, "q3-resources/models/weapons2/shotgun/shotgun.md3");
+        assert_eq!(
+            Weapon::Shotgun.part_model_path("_flash"),
+            "q3-resources/models/weapons2/shotgun/shotgun_flash.md3"
+        );
+
+        assert_eq!(
+            weapon_texture_candidates(&model_path, "w_shotgun", 0),
+            vec![
+                "q3-resources/models/weapons2/shotgun/w_shotgun.png",
+                "q3-resources/models/weapons2/shotgun/shotgun.png",
+            ]
+        );
+        assert_eq!(
+            weapon_texture_candidates(&model_path, "", 1),
+            vec![
+                "q3-resources/models/weapons2/shotgun/shotgun2.png",
+                "q3-resources/models/weapons2/shotgun/shotgun.png",
+            ]
+        );
+    }
+}
@@ -1,4 +1,5 @@
-use glam::{Mat3, Mat4, Vec3, Vec4};
+use std::collections::HashSet;
+use glam::{Mat3, Mat4, Quat, Vec3, Vec4};
 use crate::engine::md3::Tag;
 
 #[derive(Clone, Copy)]
@@ -77,6 +78,15 @@ impl Frustum {
         true
     }
     
+    // False only when the box lies entirely behind one plane, so boxes near frustum corners can pass
+    pub fn intersects_aabb(&self, min: Vec3, max: Vec3) -> bool {
+        self.planes.iter().all(|plane| {
+            let normal = plane.truncate();
+            let farthest = Vec3::select(normal.cmpge(Vec3::ZERO), max, min);
+            normal.dot(farthest) + plane.w >= 0.0
+        })
+    }
+    
     pub fn estimate_visibility_time(&self, start_pos: Vec3, velocity: Vec3, radius: f32) -> f32 {
         if self.contains_sphere(start_pos, radius) {
             let mut min_exit_time = f32::INFINITY;
@@ -145,12 +155,26 @@ impl Frustum {
     }
 }
 
+// Axis-aligned box enclosing a local box after `matrix` is applied
+pub fn transform_aabb(matrix: Mat4, min: Vec3, max: Vec3) -> (Vec3, Vec3) {
+    let center = matrix.transform_point3((min + max) * 0.5);
+    let half = (max - min) * 0.5;
+    let extent = matrix.x_axis.truncate().abs() * half.x
+        + matrix.y_axis.truncate().abs() * half.y
+        + matrix.z_axis.truncate().abs() * half.z;
+    (center - extent, center + extent)
+}
+
 #[derive(Clone, Copy)]
 pub struct Orientation {
     pub origin: Vec3,
     pub axis: [Vec3; 3],
 }
 
+pub fn ground_reflection_matrix() -> Mat4 {
+    Mat4::from_scale(Vec3::new(1.0, -1.0, 1.0))
+}
+
 pub fn axis_from_mat3(m: Mat3) -> [Vec3; 3] {
     let cols = m.to_cols_array();
     [
@@ -197,3 +221,156 @@ pub fn attach_rotated_entity(parent: &Orientation, tag: &Tag) -> Orientation {
     Orientation { origin, axis }
 }
 
+pub fn attach_rotated_entity_interp(parent: &Orientation, tag_a: &Tag, tag_b: &Tag, t: f32) -> Orientation {
+    let lerp = |a: [f32; 3], b: [f32; 3]| Vec3::from(a).lerp(Vec3::from(b), t).to_array();
+    let tag = Tag {
+        name: tag_a.name,
+        position: lerp(tag_a.position, tag_b.position),
+        axis: slerp_axis(&tag_a.axis, &tag_b.axis, t),
+    };
+    attach_rotated_entity(parent, &tag)
+}
+
+pub fn slerp_axis(a: &[[f32; 3]; 3], b: &[[f32; 3]; 3], t: f32) -> [[f32; 3]; 3] {
+    let qa = Quat::from_mat3(&Mat3::from_cols_array_2d(a)).normalize();
+    let qb = Quat::from_mat3(&Mat3::from_cols_array_2d(b)).normalize();
+    Mat3::from_quat(qa.slerp(qb, t)).to_cols_array_2d()
+}
+
+pub const ORIENTATION_TOLERANCE: f32 = 1e-3;
+
+pub fn orientation_error(orientation: &Orientation, tolerance: f32) -> Option<String> {
+    if !orientation.origin.is_finite() {
+        return Some(format!("origin is not finite: {:?}", orientation.origin));
+    }
+    for (i, axis) in orientation.axis.iter().enumerate() {
+        if !axis.is_finite() {
+            return Some(format!("axis {} is not finite: {:?}", i, axis));
+        }
+        let length = axis.length();
+        if (length - 1.0).abs() > tolerance {
+            return Some(format!("axis {} has length {:.4}", i, length));
+        }
+    }
+    for (a, b) in [(0, 1), (0, 2), (1, 2)] {
+        let dot = orientation.axis[a].dot(orientation.axis[b]);
+        if dot.abs() > tolerance {
+            return Some(format!("axes {} and {} are not orthogonal (dot {:.4})", a, b, dot));
+        }
+    }
+    None
+}
+
+#[derive(Debug, Default)]
+pub struct TagValidator {
+    pub enabled: bool,
+    reported: HashSet<(String, usize)>,
+}
+
+impl TagValidator {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            reported: HashSet::new(),
+        }
+    }
+
+    pub fn check(&mut self, tag_name: &str, frame: usize, orientation: &Orientation) -> bool {
+        if !self.enabled {
+            return true;
+        }
+        match orientation_error(orientation, ORIENTATION_TOLERANCE) {
+            Some(error) => {
+                if self.reported.insert((tag_name.to_string(), frame)) {
+                    println!("WARNING: invalid attachment for {} at frame {}: {}", tag_name, frame, error);
+                }
+                false
+            }
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ground_reflection_mirrors_across_the_floor() {
+        let mirror = ground_reflection_matrix();
+        assert_eq!(mirror.transform_point3(Vec3::new(3.0, 40.0, -7.0)), Vec3::new(3.0, -40.0, -7.0));
+        assert_eq!(mirror.transform_point3(Vec3::new(12.0, 0.0, 5.0)), Vec3::new(12.0, 0.0, 5.0));
+        // Mirroring twice is the identity, and the flip reverses triangle winding
+        assert_eq!(mirror * mirror, Mat4::IDENTITY);
+        assert!(mirror.determinant() < 0.0);
+    }
+
+    fn test_frustum() -> Frustum {
+        let view = Mat4::look_at_rh(Vec3::ZERO, Vec3::NEG_Z, Vec3::Y);
+        let proj = Mat4::perspective_rh(std::f32::consts::FRAC_PI_2, 1.0, 0.1, 100.0);
+        Frustum::from_view_proj(proj * view)
+    }
+
+    #[test]
+    fn box_in_front_of_the_camera_intersects() {
+        let frustum = test_frustum();
+        assert!(frustum.intersects_aabb(Vec3::new(-1.0, -1.0, -11.0), Vec3::new(1.0, 1.0, -9.0)));
+    }
+
+    #[test]
+    fn boxes_outside_the_frustum_are_culled() {
+        let frustum = test_frustum();
+        // Behind the camera, off to the side, and past the far plane
+        assert!(!frustum.intersects_aabb(Vec3::new(-1.0, -1.0, 9.0), Vec3::new(1.0, 1.0, 11.0)));
+        assert!(!frustum.intersects_aabb(Vec3::new(20.0, -1.0, -11.0), Vec3::new(22.0, 1.0, -9.0)));
+        assert!(!frustum.intersects_aabb(Vec3::new(-1.0, -1.0, -210.0), Vec3::new(1.0, 1.0, -200.0)));
+    }
+
+    #[test]
+    fn box_straddling_a_plane_intersects() {
+        let frustum = test_frustum();
+        // At z = -10 the right plane sits at x = 10
+        assert!(frustum.intersects_aabb(Vec3::new(9.0, -1.0, -11.0), Vec3::new(12.0, 1.0, -9.0)));
+        assert!(frustum.intersects_aabb(Vec3::new(-1.0, -1.0, -105.0), Vec3::new(1.0, 1.0, -95.0)));
+    }
+
+    #[test]
+    fn skewed_tag_axis_is_reported_once() {
+        let valid = Orientation { origin: Vec3::ZERO, axis: [Vec3::X, Vec3::Y, Vec3::Z] };
+        let skewed = Orientation { origin: Vec3::ZERO, axis: [Vec3::X, Vec3::new(0.6, 0.8, 0.0), Vec3::Z] };
+        assert!(orientation_error(&valid, ORIENTATION_TOLERANCE).is_none());
+        assert!(orientation_error(&skewed, ORIENTATION_TOLERANCE).unwrap().contains("not orthogonal"));
+
+        let mut validator = TagValidator::new(true);
+        assert!(validator.check("tag_torso", 3, &valid));
+        assert!(!validator.check("tag_torso", 3, &skewed));
+        assert!(!validator.check("tag_torso", 3, &skewed));
+        assert_eq!(validator.reported.len(), 1);
+
+        assert!(TagValidator::new(false).check("tag_torso", 3, &skewed));
+    }
+
+    fn assert_axis_eq(a: [[f32; 3]; 3], b: [[f32; 3]; 3]) {
+        for (x, y) in a.iter().flatten().zip(b.iter().flatten()) {
+            assert!((x - y).abs() < 1e-5, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn slerped_tag_axes_follow_the_rotation() {
+        let rotation_z = |degrees: f32| Mat3::from_rotation_z(degrees.to_radians()).to_cols_array_2d();
+        let tilted = Mat3::from_euler(glam::EulerRot::XYZ, 0.3, -0.7, 1.1).to_cols_array_2d();
+        assert_axis_eq(slerp_axis(&tilted, &tilted, 0.37), tilted);
+        assert_axis_eq(slerp_axis(&rotation_z(0.0), &rotation_z(90.0), 0.5), rotation_z(45.0));
+
+        let tag = |position: [f32; 3], axis| Tag { name: [0; 64], position, axis };
+        let parent = Orientation { origin: Vec3::ZERO, axis: [Vec3::X, Vec3::Y, Vec3::Z] };
+        let child = attach_rotated_entity_interp(
+            &parent,
+            &tag([0.0, 0.0, 0.0], rotation_z(0.0)),
+            &tag([10.0, 0.0, 4.0], rotation_z(90.0)),
+            0.5,
+        );
+        assert_eq!(child.origin, Vec3::new(5.0, 0.0, 2.0));
+    }
+}
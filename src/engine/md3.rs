@@ -1,6 +1,9 @@
-use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
+use std::fmt;
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread::JoinHandle;
+use glam::Vec3;
+use crate::resource_path::resource_paths;
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -19,6 +22,15 @@ pub struct MD3Header {
     pub file_size: i32,
 }
 
+#[derive(Debug, Clone)]
+pub struct BoneFrame {
+    pub min_bounds: [f32; 3],
+    pub max_bounds: [f32; 3],
+    pub local_origin: [f32; 3],
+    pub radius: f32,
+    pub name: [u8; 16],
+}
+
 #[derive(Debug, Clone)]
 pub struct Tag {
     pub name: [u8; 64],
@@ -40,6 +52,15 @@ pub struct TexCoord {
 pub struct Vertex {
     pub vertex: [i16; 3],
     pub normal: u16,
+    pub normal_f32: [f32; 3],
+}
+
+// Both angles step by 2*PI/256, as in Quake 3. The loader runs this once per vertex into
+// `Vertex::normal_f32`, so buffer builds only copy floats
+pub fn decode_normal(packed: u16) -> [f32; 3] {
+    let lat = ((packed >> 8) & 0xFF) as f32 * 2.0 * std::f32::consts::PI / 256.0;
+    let lng = (packed & 0xFF) as f32 * 2.0 * std::f32::consts::PI / 256.0;
+    [lat.cos() * lng.sin(), lat.sin() * lng.sin(), lng.cos()]
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -64,13 +85,125 @@ pub struct Mesh {
     pub triangles: Vec<Triangle>,
     pub tex_coords: Vec<TexCoord>,
     pub vertices: Vec<Vec<Vertex>>,
+    // Per-frame bounding spheres, computed once at load
+    pub bounds: Vec<Option<(Vec3, f32)>>,
+}
+
+// Sphere around one frame's vertices, or None for an empty frame
+fn frame_bounding_sphere(frame: &[Vertex]) -> Option<(Vec3, f32)> {
+    let position = |v: &Vertex| Vec3::new(v.vertex[0] as f32, v.vertex[1] as f32, v.vertex[2] as f32) / 64.0;
+    let first = position(frame.first()?);
+    let (min, max) = frame.iter().map(position).fold((first, first), |(min, max), p| (min.min(p), max.max(p)));
+    let center = (min + max) * 0.5;
+    let radius = frame.iter().map(|v| position(v).distance(center)).fold(0.0, f32::max);
+    Some((center, radius))
+}
+
+impl Mesh {
+    pub fn name(&self) -> &str {
+        let end = self.header.name.iter().position(|&b| b == 0).unwrap_or(self.header.name.len());
+        std::str::from_utf8(&self.header.name[..end]).unwrap_or("")
+    }
+
+    // Sphere around this surface's own vertices; the frame radius covers the whole model
+    pub fn bounding_sphere(&self, frame_idx: usize) -> Option<(Vec3, f32)> {
+        self.bounds.get(frame_idx).copied().flatten()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MeshInfo {
+    pub name: String,
+    pub num_vertices: usize,
+    pub num_triangles: usize,
+    pub num_frames: usize,
 }
 
 #[derive(Debug, Clone)]
 pub struct MD3Model {
+    pub id: ModelId,
     pub header: MD3Header,
+    pub frames: Vec<BoneFrame>,
     pub tags: Vec<Vec<Tag>>,
     pub meshes: Vec<Mesh>,
+    // Lower detail surfaces from sibling `*_1.md3`/`*_2.md3` files; `meshes` is LOD 0
+    pub lods: Vec<Vec<Mesh>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FrameBlend {
+    pub from: usize,
+    pub to: usize,
+    pub t: f32,
+}
+
+impl FrameBlend {
+    pub fn new(from: usize, to: usize, t: f32) -> Self {
+        Self { from, to, t: t.clamp(0.0, 1.0) }
+    }
+
+    pub fn fixed(frame: usize) -> Self {
+        Self::new(frame, frame, 0.0)
+    }
+
+    // Splits a fractional frame into the pair it falls between, without wrapping past the last frame
+    pub fn at(frame: f32, num_frames: usize) -> Self {
+        if num_frames == 0 {
+            return Self::fixed(0);
+        }
+        let last = num_frames - 1;
+        let frame = frame.max(0.0);
+        let from = (frame.floor() as usize).min(last);
+        let to = (from + 1).min(last);
+        let t = if from == to { 0.0 } else { frame.fract() };
+        Self::new(from, to, t)
+    }
+
+    pub fn clamped(self, num_frames: usize) -> Self {
+        let last = num_frames.saturating_sub(1);
+        Self::new(self.from.min(last), self.to.min(last), self.t)
+    }
+
+    pub fn key_frame(&self) -> usize {
+        self.from
+    }
+}
+
+// Two independently interpolated poses mixed by weight, e.g. walk and run legs
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PoseBlend {
+    pub a: FrameBlend,
+    pub b: FrameBlend,
+    pub weight: f32,
+}
+
+impl PoseBlend {
+    pub fn new(a: FrameBlend, b: FrameBlend, weight: f32) -> Self {
+        Self { a, b, weight: weight.clamp(0.0, 1.0) }
+    }
+
+    pub fn dominant(&self) -> FrameBlend {
+        if self.weight < 0.5 { self.a } else { self.b }
+    }
+
+    pub fn key_frame(&self) -> usize {
+        self.dominant().key_frame()
+    }
+
+    pub fn clamped(self, num_frames: usize) -> Self {
+        Self::new(self.a.clamped(num_frames), self.b.clamped(num_frames), self.weight)
+    }
+}
+
+impl From<FrameBlend> for PoseBlend {
+    fn from(blend: FrameBlend) -> Self {
+        Self::new(blend, blend, 0.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoadOptions {
+    pub flip_winding: bool,
 }
 
 trait CopyFromSlice {
@@ -96,176 +229,363 @@ impl<const N: usize> CopySlice for [u8; N] {
     }
 }
 
+#[derive(Debug)]
+pub enum Md3Error {
+    Io(std::io::Error),
+    BadMagic([u8; 4]),
+    BadVersion(i32),
+    UnexpectedEof { offset: usize, needed: usize },
+    InvalidOffset { lump: &'static str, offset: i64 },
+    InvalidCount { lump: &'static str, count: i32 },
+    InvalidIndex { index: i32, num_vertices: usize },
+}
+
+impl fmt::Display for Md3Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Md3Error::Io(e) => write!(f, "I/O error: {}", e),
+            Md3Error::BadMagic(id) => write!(f, "Invalid MD3 magic {:?}", String::from_utf8_lossy(id)),
+            Md3Error::BadVersion(v) => write!(f, "Unsupported MD3 version {} (expected {})", v, MD3_VERSION),
+            Md3Error::UnexpectedEof { offset, needed } => {
+                write!(f, "Unexpected end of MD3 file: needed {} bytes at offset {}", needed, offset)
+            }
+            Md3Error::InvalidOffset { lump, offset } => write!(f, "Invalid {} offset {} in MD3 file", lump, offset),
+            Md3Error::InvalidCount { lump, count } => write!(f, "Invalid {} {} in MD3 file", lump, count),
+            Md3Error::InvalidIndex { index, num_vertices } => {
+                write!(f, "Triangle index {} out of range for {} vertices", index, num_vertices)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Md3Error {}
+
+impl From<std::io::Error> for Md3Error {
+    fn from(e: std::io::Error) -> Self {
+        Md3Error::Io(e)
+    }
+}
+
+const MD3_VERSION: i32 = 15;
+const MAX_LODS: usize = 3;
+pub const LOD_DISTANCE: f32 = 120.0;
+const HEADER_SIZE: usize = 108;
+const FRAME_SIZE: usize = 56;
+const TAG_SIZE: usize = 112;
+const MESH_HEADER_SIZE: usize = 108;
+
+static NEXT_MODEL_ID: AtomicU64 = AtomicU64::new(1);
+
+// Assigned once per load and shared by clones; renderer caches key on it instead of the
+// model's address, which can be reused after a drop or change when the model moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ModelId(u64);
+
+impl ModelId {
+    fn next() -> Self {
+        ModelId(NEXT_MODEL_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+fn slice_at(data: &[u8], offset: usize, needed: usize) -> Result<&[u8], Md3Error> {
+    offset
+        .checked_add(needed)
+        .and_then(|end| data.get(offset..end))
+        .ok_or(Md3Error::UnexpectedEof { offset, needed })
+}
+
+fn read_i32(bytes: &[u8], start: usize) -> i32 {
+    i32::from_le_bytes(bytes[start..start + 4].try_into().unwrap())
+}
+
+fn read_f32(bytes: &[u8], start: usize) -> f32 {
+    f32::from_le_bytes(bytes[start..start + 4].try_into().unwrap())
+}
+
+fn read_vec3(bytes: &[u8], start: usize) -> [f32; 3] {
+    [read_f32(bytes, start), read_f32(bytes, start + 4), read_f32(bytes, start + 8)]
+}
+
+fn count(lump: &'static str, value: i32) -> Result<usize, Md3Error> {
+    usize::try_from(value).map_err(|_| Md3Error::InvalidCount { lump, count: value })
+}
+
+// Resolves an offset relative to `base` and checks it lies within the file
+fn lump_offset(data: &[u8], lump: &'static str, base: usize, relative: i32) -> Result<usize, Md3Error> {
+    let offset = base as i64 + relative as i64;
+    if relative < 0 || offset > data.len() as i64 {
+        return Err(Md3Error::InvalidOffset { lump, offset });
+    }
+    Ok(offset as usize)
+}
+
+// Like `lump_offset`, but also requires `len` bytes to be readable from there
+fn mesh_offset(
+    data: &[u8],
+    lump: &'static str,
+    mesh_start: usize,
+    relative: i32,
+    len: usize,
+) -> Result<usize, Md3Error> {
+    let offset = lump_offset(data, lump, mesh_start, relative)?;
+    slice_at(data, offset, len)?;
+    Ok(offset)
+}
+
 impl MD3Model {
-    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
-        let mut file = File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Md3Error> {
+        Self::load_with(path, LoadOptions::default())
+    }
 
-        let mut header_bytes = [0u8; 108];
-        file.read_exact(&mut header_bytes)
-            .map_err(|e| format!("Failed to read header: {}", e))?;
+    pub fn load_async<P: AsRef<Path>>(path: P) -> JoinHandle<Result<Self, Md3Error>> {
+        let path = path.as_ref().to_path_buf();
+        std::thread::spawn(move || Self::load(path))
+    }
 
-        let header: MD3Header = unsafe { std::ptr::read(header_bytes.as_ptr() as *const _) };
+    pub fn load_with<P: AsRef<Path>>(path: P, opts: LoadOptions) -> Result<Self, Md3Error> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)?;
+        let mut model = Self::from_bytes(&data, opts)?;
+        model.load_lods(path, opts);
+        Ok(model)
+    }
 
-        if &header.id != b"IDP3" {
-            return Err("Invalid MD3 file format".to_string());
+    fn load_lods(&mut self, path: &Path, opts: LoadOptions) {
+        let stem = match path.file_stem().and_then(|s| s.to_str()) {
+            Some(stem) => stem,
+            None => return,
+        };
+        for level in 1..MAX_LODS {
+            let lod_path = path.with_file_name(format!("{}_{}.md3", stem, level));
+            if !lod_path.exists() {
+                break;
+            }
+            let lod = match std::fs::read(&lod_path)
+                .map_err(Md3Error::from)
+                .and_then(|data| Self::from_bytes(&data, opts))
+            {
+                Ok(lod) => lod,
+                Err(e) => {
+                    println!("Skipping LOD {}: {}", lod_path.display(), e);
+                    break;
+                }
+            };
+            // Textures are assigned by mesh index, so every level has to keep the same surfaces
+            if lod.meshes.len() != self.meshes.len() {
+                println!(
+                    "Skipping LOD {}: {} meshes, expected {}",
+                    lod_path.display(),
+                    lod.meshes.len(),
+                    self.meshes.len()
+                );
+                break;
+            }
+            self.lods.push(lod.meshes);
         }
+    }
 
-        for _ in 0..header.num_bone_frames {
-            let mut frame_bytes = [0u8; 56];
-            file.read_exact(&mut frame_bytes)
-                .map_err(|e| format!("Failed to read bone frame: {}", e))?;
+    pub fn num_frames(&self) -> usize {
+        self.meshes.first().map(|mesh| mesh.vertices.len()).unwrap_or(self.frames.len())
+    }
+
+    pub fn num_lods(&self) -> usize {
+        1 + self.lods.len()
+    }
+
+    pub fn lod_meshes(&self, lod: usize) -> &[Mesh] {
+        match lod.min(self.lods.len()) {
+            0 => &self.meshes,
+            level => &self.lods[level - 1],
         }
+    }
 
-        let mut tags = vec![Vec::new(); header.num_bone_frames as usize];
-        for frame_idx in 0..header.num_bone_frames as usize {
-            for _ in 0..header.num_tags {
-                let mut tag_bytes = [0u8; 112];
-                file.read_exact(&mut tag_bytes)
-                    .map_err(|e| format!("Failed to read tag: {}", e))?;
-
-                let mut name = [0u8; 64];
-                name.copy_from_slice(&tag_bytes[0..64]);
-
-                let mut position = [0f32; 3];
-                for i in 0..3 {
-                    let start = 64 + i * 4;
-                    position[i] = f32::from_le_bytes([
-                        tag_bytes[start],
-                        tag_bytes[start + 1],
-                        tag_bytes[start + 2],
-                        tag_bytes[start + 3],
-                    ]);
-                }
+    pub fn select_lod(&self, distance: f32) -> usize {
+        let level = (distance.max(0.0) / LOD_DISTANCE) as usize;
+        level.min(self.num_lods() - 1)
+    }
 
-                let mut axis = [[0f32; 3]; 3];
-                for row in 0..3 {
-                    for col in 0..3 {
-                        let start = 76 + (row * 3 + col) * 4;
-                        axis[row][col] = f32::from_le_bytes([
-                            tag_bytes[start],
-                            tag_bytes[start + 1],
-                            tag_bytes[start + 2],
-                            tag_bytes[start + 3],
-                        ]);
-                    }
-                }
+    pub fn from_bytes(data: &[u8], opts: LoadOptions) -> Result<Self, Md3Error> {
+        let header_bytes = slice_at(data, 0, HEADER_SIZE)?;
 
-                tags[frame_idx].push(Tag {
-                    name,
-                    position,
-                    axis,
-                });
-            }
+        let mut id = [0u8; 4];
+        id.copy_from_slice(&header_bytes[0..4]);
+        if &id != b"IDP3" {
+            return Err(Md3Error::BadMagic(id));
+        }
+
+        let version = read_i32(header_bytes, 4);
+        if version != MD3_VERSION {
+            return Err(Md3Error::BadVersion(version));
+        }
+
+        let mut filename = [0u8; 64];
+        filename.copy_from_slice(&header_bytes[8..72]);
+        let header = MD3Header {
+            id,
+            version,
+            filename,
+            flags: read_i32(header_bytes, 72),
+            num_bone_frames: read_i32(header_bytes, 76),
+            num_tags: read_i32(header_bytes, 80),
+            num_meshes: read_i32(header_bytes, 84),
+            num_max_skins: read_i32(header_bytes, 88),
+            header_length: read_i32(header_bytes, 92),
+            tag_start: read_i32(header_bytes, 96),
+            tag_end: read_i32(header_bytes, 100),
+            file_size: read_i32(header_bytes, 104),
+        };
+
+        let num_frames = count("frame count", header.num_bone_frames)?;
+        let num_tags = count("tag count", header.num_tags)?;
+        let num_meshes = count("mesh count", header.num_meshes)?;
+
+        let frames_start = lump_offset(data, "frames", 0, header.header_length)?;
+        let tags_start = lump_offset(data, "tags", 0, header.tag_start)?;
+        let meshes_start = lump_offset(data, "meshes", 0, header.tag_end)?;
+        // A file shorter than its declared size has been cut off
+        let file_size = usize::try_from(header.file_size)
+            .map_err(|_| Md3Error::InvalidOffset { lump: "end", offset: header.file_size as i64 })?;
+        slice_at(data, 0, file_size)?;
+
+        let frames_len = num_frames.saturating_mul(FRAME_SIZE);
+        let frame_data = slice_at(data, frames_start, frames_len)?;
+        let mut frames = Vec::with_capacity(num_frames);
+        for frame_bytes in frame_data.chunks_exact(FRAME_SIZE) {
+            let mut name = [0u8; 16];
+            name.copy_from_slice(&frame_bytes[40..56]);
+            frames.push(BoneFrame {
+                min_bounds: read_vec3(frame_bytes, 0),
+                max_bounds: read_vec3(frame_bytes, 12),
+                local_origin: read_vec3(frame_bytes, 24),
+                radius: read_f32(frame_bytes, 36),
+                name,
+            });
         }
 
-        let mut meshes = Vec::with_capacity(header.num_meshes as usize);
-        for _ in 0..header.num_meshes {
-            let mesh_start =
-                file.stream_position()
-                    .map_err(|e| format!("Failed to get position: {}", e))? as i64;
+        let tags_len = num_frames.saturating_mul(num_tags).saturating_mul(TAG_SIZE);
+        let tag_data = slice_at(data, tags_start, tags_len)?;
+        let mut tags = vec![Vec::with_capacity(num_tags); num_frames];
+        for (i, tag_bytes) in tag_data.chunks_exact(TAG_SIZE).enumerate() {
+            let mut name = [0u8; 64];
+            name.copy_from_slice(&tag_bytes[0..64]);
 
-            let mut mesh_header_bytes = [0u8; 108];
-            file.read_exact(&mut mesh_header_bytes)
-                .map_err(|e| format!("Failed to read mesh header: {}", e))?;
+            let position = read_vec3(tag_bytes, 64);
+            let axis = [
+                read_vec3(tag_bytes, 76),
+                read_vec3(tag_bytes, 88),
+                read_vec3(tag_bytes, 100),
+            ];
+
+            tags[i / num_tags].push(Tag {
+                name,
+                position,
+                axis,
+            });
+        }
+
+        let mut meshes = Vec::with_capacity(num_meshes);
+        let mut mesh_start = meshes_start;
+        for _ in 0..num_meshes {
+            let mesh_header_bytes = slice_at(data, mesh_start, MESH_HEADER_SIZE)?;
 
             let mut id = [0u8; 4];
             id.copy_from_slice(&mesh_header_bytes[0..4]);
             let mut name = [0u8; 64];
             name.copy_from_slice(&mesh_header_bytes[4..68]);
-            let flags = i32::from_le_bytes(mesh_header_bytes[68..72].try_into().unwrap());
-            let num_mesh_frames = i32::from_le_bytes(mesh_header_bytes[72..76].try_into().unwrap());
-            let num_shaders = i32::from_le_bytes(mesh_header_bytes[76..80].try_into().unwrap());
-            let num_vertices = i32::from_le_bytes(mesh_header_bytes[80..84].try_into().unwrap());
-            let num_triangles = i32::from_le_bytes(mesh_header_bytes[84..88].try_into().unwrap());
-            let tri_start = i32::from_le_bytes(mesh_header_bytes[88..92].try_into().unwrap());
-            let shaders_start = i32::from_le_bytes(mesh_header_bytes[92..96].try_into().unwrap());
-            let tex_vector_start = i32::from_le_bytes(mesh_header_bytes[96..100].try_into().unwrap());
-            let vertex_start = i32::from_le_bytes(mesh_header_bytes[100..104].try_into().unwrap());
-            let mesh_size = i32::from_le_bytes(mesh_header_bytes[104..108].try_into().unwrap());
 
             let mesh_header = MeshHeader {
                 id,
                 name,
-                flags,
-                num_mesh_frames,
-                num_shaders,
-                num_vertices,
-                num_triangles,
-                tri_start,
-                shaders_start,
-                tex_vector_start,
-                vertex_start,
-                mesh_size,
+                flags: read_i32(mesh_header_bytes, 68),
+                num_mesh_frames: read_i32(mesh_header_bytes, 72),
+                num_shaders: read_i32(mesh_header_bytes, 76),
+                num_vertices: read_i32(mesh_header_bytes, 80),
+                num_triangles: read_i32(mesh_header_bytes, 84),
+                tri_start: read_i32(mesh_header_bytes, 88),
+                shaders_start: read_i32(mesh_header_bytes, 92),
+                tex_vector_start: read_i32(mesh_header_bytes, 96),
+                vertex_start: read_i32(mesh_header_bytes, 100),
+                mesh_size: read_i32(mesh_header_bytes, 104),
             };
 
-            file.seek(SeekFrom::Start(
-                (mesh_start + mesh_header.tri_start as i64) as u64,
-            ))
-            .map_err(|e| format!("Failed to seek: {}", e))?;
-
-            let mut triangles = Vec::with_capacity(mesh_header.num_triangles as usize);
-            for _ in 0..mesh_header.num_triangles {
-                let mut tri_bytes = [0u8; 12];
-                file.read_exact(&mut tri_bytes)
-                    .map_err(|e| format!("Failed to read triangle: {}", e))?;
-                let tri = unsafe { std::ptr::read(tri_bytes.as_ptr() as *const Triangle) };
-                triangles.push(tri);
-            }
-
-            file.seek(SeekFrom::Start(
-                (mesh_start + mesh_header.tex_vector_start as i64) as u64,
-            ))
-            .map_err(|e| format!("Failed to seek: {}", e))?;
-
-            let mut tex_coords = Vec::with_capacity(mesh_header.num_vertices as usize);
-            for _ in 0..mesh_header.num_vertices {
-                let mut tc_bytes = [0u8; 8];
-                file.read_exact(&mut tc_bytes)
-                    .map_err(|e| format!("Failed to read tex coord: {}", e))?;
-                let tc = unsafe { std::ptr::read(tc_bytes.as_ptr() as *const TexCoord) };
-                tex_coords.push(tc);
+            let num_mesh_frames = count("mesh frame count", mesh_header.num_mesh_frames)?;
+            let num_vertices = count("vertex count", mesh_header.num_vertices)?;
+            let num_triangles = count("triangle count", mesh_header.num_triangles)?;
+
+            let tri_len = num_triangles.saturating_mul(12);
+            let tri_offset = mesh_offset(data, "triangles", mesh_start, mesh_header.tri_start, tri_len)?;
+            let mut triangles = Vec::with_capacity(num_triangles);
+            for tri_bytes in data[tri_offset..tri_offset + tri_len].chunks_exact(12) {
+                let mut vertex = [read_i32(tri_bytes, 0), read_i32(tri_bytes, 4), read_i32(tri_bytes, 8)];
+                if let Some(&index) = vertex.iter().find(|&&i| i < 0 || i as usize >= num_vertices) {
+                    return Err(Md3Error::InvalidIndex { index, num_vertices });
+                }
+                if opts.flip_winding {
+                    vertex.reverse();
+                }
+                triangles.push(Triangle { vertex });
             }
 
-            file.seek(SeekFrom::Start(
-                (mesh_start + mesh_header.vertex_start as i64) as u64,
-            ))
-            .map_err(|e| format!("Failed to seek: {}", e))?;
-
-            let mut vertices = Vec::with_capacity(mesh_header.num_mesh_frames as usize);
-            for _ in 0..mesh_header.num_mesh_frames {
-                let mut frame_verts = Vec::with_capacity(mesh_header.num_vertices as usize);
-                for _ in 0..mesh_header.num_vertices {
-                    let mut vert_bytes = [0u8; 8];
-                    file.read_exact(&mut vert_bytes)
-                        .map_err(|e| format!("Failed to read vertex: {}", e))?;
-                    let vertex = [
-                        i16::from_le_bytes([vert_bytes[0], vert_bytes[1]]),
-                        i16::from_le_bytes([vert_bytes[2], vert_bytes[3]]),
-                        i16::from_le_bytes([vert_bytes[4], vert_bytes[5]]),
-                    ];
-                    let normal = u16::from_le_bytes([vert_bytes[6], vert_bytes[7]]);
-                    frame_verts.push(Vertex { vertex, normal });
+            let tc_len = num_vertices.saturating_mul(8);
+            let tc_offset = mesh_offset(data, "tex coords", mesh_start, mesh_header.tex_vector_start, tc_len)?;
+            let tex_coords = data[tc_offset..tc_offset + tc_len]
+                .chunks_exact(8)
+                .map(|tc_bytes| TexCoord {
+                    coord: [read_f32(tc_bytes, 0), read_f32(tc_bytes, 4)],
+                })
+                .collect();
+
+            let vert_len = num_mesh_frames.saturating_mul(num_vertices).saturating_mul(8);
+            let vert_offset = mesh_offset(data, "vertices", mesh_start, mesh_header.vertex_start, vert_len)?;
+            let mut vertices = Vec::with_capacity(num_mesh_frames);
+            if num_vertices > 0 {
+                for frame_bytes in data[vert_offset..vert_offset + vert_len].chunks_exact(num_vertices * 8) {
+                    let frame_verts = frame_bytes
+                        .chunks_exact(8)
+                        .map(|vert_bytes| {
+                            let normal = u16::from_le_bytes([vert_bytes[6], vert_bytes[7]]);
+                            Vertex {
+                                vertex: [
+                                    i16::from_le_bytes([vert_bytes[0], vert_bytes[1]]),
+                                    i16::from_le_bytes([vert_bytes[2], vert_bytes[3]]),
+                                    i16::from_le_bytes([vert_bytes[4], vert_bytes[5]]),
+                                ],
+                                normal,
+                                normal_f32: decode_normal(normal),
+                            }
+                        })
+                        .collect();
+                    vertices.push(frame_verts);
                 }
-                vertices.push(frame_verts);
+            } else {
+                vertices.resize(num_mesh_frames, Vec::new());
             }
 
+            let bounds = vertices.iter().map(|frame| frame_bounding_sphere(frame)).collect();
             meshes.push(Mesh {
                 header: mesh_header,
                 triangles,
                 tex_coords,
                 vertices,
+                bounds,
             });
 
-            file.seek(SeekFrom::Start(
-                (mesh_start + mesh_header.mesh_size as i64) as u64,
-            ))
-            .map_err(|e| format!("Failed to seek: {}", e))?;
+            if mesh_header.mesh_size <= 0 {
+                return Err(Md3Error::InvalidOffset {
+                    lump: "mesh size",
+                    offset: mesh_header.mesh_size as i64,
+                });
+            }
+            mesh_start = lump_offset(data, "next mesh", mesh_start, mesh_header.mesh_size)?;
         }
 
         Ok(MD3Model {
+            id: ModelId::next(),
             header,
+            frames,
             tags,
             meshes,
+            lods: Vec::new(),
         })
     }
 
@@ -318,4 +638,340 @@ impl MD3Model {
             (min_x, max_x, min_y, max_y, min_z, max_z)
         }
     }
+
+    pub fn list_skins(model_name: &str, part: &str) -> Vec<String> {
+        let prefix = format!("{}_", part);
+        let mut skins: Vec<String> = Vec::new();
+
+        for dir in resource_paths().resolve_all(&format!("models/players/{}", model_name)) {
+            let entries = match std::fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            for entry in entries.flatten() {
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                let skin = file_name
+                    .strip_suffix(".skin")
+                    .and_then(|stem| stem.strip_prefix(&prefix));
+                if let Some(skin) = skin {
+                    if !skin.is_empty() && !skins.iter().any(|s| s == skin) {
+                        skins.push(skin.to_string());
+                    }
+                }
+            }
+        }
+
+        skins.sort();
+        skins
+    }
+
+    pub fn frame_bounds(&self, frame_idx: usize) -> (Vec3, Vec3) {
+        if let Some(frame) = self.frames.get(frame_idx) {
+            let min = Vec3::from(frame.min_bounds);
+            let max = Vec3::from(frame.max_bounds);
+            if min.cmple(max).all() && min != max {
+                return (min, max);
+            }
+        }
+        let (min_x, max_x, min_y, max_y, min_z, max_z) = self.get_bounds(frame_idx);
+        (Vec3::new(min_x, min_y, min_z), Vec3::new(max_x, max_y, max_z))
+    }
+
+    pub fn overall_bounds(&self) -> (Vec3, Vec3) {
+        let num_frames = self.frames.len().max(1);
+        (0..num_frames)
+            .map(|frame_idx| self.frame_bounds(frame_idx))
+            .reduce(|(min_a, max_a), (min_b, max_b)| (min_a.min(min_b), max_a.max(max_b)))
+            .unwrap_or((Vec3::ZERO, Vec3::ZERO))
+    }
+
+    pub fn mesh_info(&self) -> Vec<MeshInfo> {
+        self.meshes
+            .iter()
+            .map(|mesh| MeshInfo {
+                name: mesh.name().to_string(),
+                num_vertices: mesh.vertices.first().map(|frame| frame.len()).unwrap_or(0),
+                num_triangles: mesh.triangles.len(),
+                num_frames: mesh.vertices.len(),
+            })
+            .collect()
+    }
+
+    pub fn get_height(&self, frame: usize) -> f32 {
+        let (_, _, _, _, min_z, max_z) = self.get_bounds(frame);
+        max_z - min_z
+    }
+
+    pub fn fit_scale(&self, frame: usize, target_height: f32) -> f32 {
+        let height = self.get_height(frame);
+        if height > 0.0 {
+            target_height / height
+        } else {
+            1.0
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    fn empty_header() -> MD3Header {
+        MD3Header {
+            id: *b"IDP3",
+            version: MD3_VERSION,
+            filename: [0; 64],
+            flags: 0,
+            num_bone_frames: 0,
+            num_tags: 0,
+            num_meshes: 0,
+            num_max_skins: 0,
+            header_length: 0,
+            tag_start: 0,
+            tag_end: 0,
+            file_size: 0,
+        }
+    }
+
+    // One mesh with a single frame at the given positions, in model units
+    fn model_with_positions(positions: &[[f32; 3]]) -> MD3Model {
+        let frame: Vec<Vertex> = positions
+            .iter()
+            .map(|p| Vertex {
+                vertex: p.map(|c| (c * 64.0) as i16),
+                normal: 0,
+                normal_f32: decode_normal(0),
+            })
+            .collect();
+        let mesh_header = MeshHeader {
+            id: *b"IDP3",
+            name: [0; 64],
+            flags: 0,
+            num_mesh_frames: 1,
+            num_shaders: 0,
+            num_vertices: frame.len() as i32,
+            num_triangles: 0,
+            tri_start: 0,
+            shaders_start: 0,
+            tex_vector_start: 0,
+            vertex_start: 0,
+            mesh_size: 0,
+        };
+        MD3Model {
+            id: ModelId::next(),
+            header: empty_header(),
+            frames: Vec::new(),
+            tags: Vec::new(),
+            meshes: vec![Mesh {
+                header: mesh_header,
+                triangles: Vec::new(),
+                tex_coords: Vec::new(),
+                bounds: vec![frame_bounding_sphere(&frame)],
+                vertices: vec![frame],
+            }],
+            lods: Vec::new(),
+        }
+    }
+
+    // A minimal valid file: one frame, no tags and one mesh with `num_verts` vertices
+    pub(crate) fn md3_bytes(num_verts: usize, triangles: &[[i32; 3]]) -> Vec<u8> {
+        let mut data = vec![0u8; HEADER_SIZE];
+        data[0..4].copy_from_slice(b"IDP3");
+        data[4..8].copy_from_slice(&MD3_VERSION.to_le_bytes());
+        let frames_start = HEADER_SIZE;
+        let meshes_start = frames_start + FRAME_SIZE;
+        let tri_start = MESH_HEADER_SIZE;
+        let tc_start = tri_start + triangles.len() * 12;
+        let vert_start = tc_start + num_verts * 8;
+        let mesh_size = vert_start + num_verts * 8;
+        for (offset, value) in [
+            (76, 1),
+            (80, 0),
+            (84, 1),
+            (92, frames_start),
+            (96, meshes_start),
+            (100, meshes_start),
+            (104, meshes_start + mesh_size),
+        ] {
+            data[offset..offset + 4].copy_from_slice(&(value as i32).to_le_bytes());
+        }
+        data.resize(meshes_start, 0);
+
+        let mut mesh = vec![0u8; MESH_HEADER_SIZE];
+        mesh[0..4].copy_from_slice(b"IDP3");
+        for (offset, value) in [
+            (72, 1),
+            (80, num_verts),
+            (84, triangles.len()),
+            (88, tri_start),
+            (96, tc_start),
+            (100, vert_start),
+            (104, mesh_size),
+        ] {
+            mesh[offset..offset + 4].copy_from_slice(&(value as i32).to_le_bytes());
+        }
+        for index in triangles.iter().flatten() {
+            mesh.extend_from_slice(&index.to_le_bytes());
+        }
+        mesh.resize(mesh_size, 0);
+        data.extend_from_slice(&mesh);
+        data
+    }
+
+    fn set_i32(data: &mut [u8], offset: usize, value: i32) {
+        data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    #[test]
+    fn valid_file_parses() {
+        let model = MD3Model::from_bytes(&md3_bytes(3, &[[0, 1, 2]]), LoadOptions::default()).unwrap();
+        assert_eq!(model.meshes.len(), 1);
+        assert_eq!(model.meshes[0].triangles[0].vertex, [0, 1, 2]);
+        assert_eq!(model.meshes[0].vertices[0].len(), 3);
+    }
+
+    #[test]
+    fn reloaded_models_do_not_alias_cache_entries() {
+        use crate::render::buffers::{BufferCacheKey, FrameCache};
+        use std::sync::Arc;
+
+        let key = |id| BufferCacheKey { model_id: id, lod: 0, mesh_idx: 0, frame_idx: 0 };
+        let mut cache = FrameCache::new(8);
+        let bytes = md3_bytes(3, &[[0, 1, 2]]);
+
+        // Boxed and dropped in turn, so the allocator is free to hand back the same address
+        let first = Box::new(MD3Model::from_bytes(&bytes, LoadOptions::default()).unwrap());
+        let first_id = first.id;
+        cache.insert(key(first_id), Arc::new(Vec::new()));
+        drop(first);
+
+        let second = Box::new(MD3Model::from_bytes(&bytes, LoadOptions::default()).unwrap());
+        assert_ne!(second.id, first_id);
+        assert!(cache.get(&key(second.id)).is_none());
+        assert_eq!(second.clone().id, second.id);
+    }
+
+    #[test]
+    fn flip_winding_reverses_triangle_indices() {
+        let bytes = md3_bytes(4, &[[0, 1, 2], [1, 3, 2]]);
+        let flipped = MD3Model::from_bytes(&bytes, LoadOptions { flip_winding: true }).unwrap();
+        let triangles: Vec<[i32; 3]> = flipped.meshes[0].triangles.iter().map(|t| t.vertex).collect();
+        assert_eq!(triangles, vec![[2, 1, 0], [2, 3, 1]]);
+    }
+
+    #[test]
+    fn mesh_info_matches_the_parsed_mesh() {
+        let mut bytes = md3_bytes(4, &[[0, 1, 2], [0, 2, 3]]);
+        let name_offset = HEADER_SIZE + FRAME_SIZE + 4;
+        bytes[name_offset..name_offset + 6].copy_from_slice(b"l_legs");
+        let model = MD3Model::from_bytes(&bytes, LoadOptions::default()).unwrap();
+
+        let info = model.mesh_info();
+        assert_eq!(info.len(), 1);
+        assert_eq!(info[0].name, "l_legs");
+        assert_eq!(info[0].num_vertices, model.meshes[0].vertices[0].len());
+        assert_eq!(info[0].num_vertices, 4);
+        assert_eq!(info[0].num_triangles, 2);
+        assert_eq!(info[0].num_frames, 1);
+    }
+
+    #[test]
+    fn zero_length_buffer_is_truncated() {
+        let err = MD3Model::from_bytes(&[], LoadOptions::default()).unwrap_err();
+        assert!(matches!(err, Md3Error::UnexpectedEof { offset: 0, needed: HEADER_SIZE }));
+    }
+
+    #[test]
+    fn truncated_mesh_data_is_an_unexpected_eof() {
+        let data = md3_bytes(3, &[[0, 1, 2]]);
+        let err = MD3Model::from_bytes(&data[..data.len() - 4], LoadOptions::default()).unwrap_err();
+        assert!(matches!(err, Md3Error::UnexpectedEof { offset: 0, needed } if needed == data.len()));
+    }
+
+    #[test]
+    fn negative_counts_are_count_errors() {
+        let mut data = md3_bytes(3, &[[0, 1, 2]]);
+        set_i32(&mut data, 84, -1);
+        let err = MD3Model::from_bytes(&data, LoadOptions::default()).unwrap_err();
+        assert!(matches!(err, Md3Error::InvalidCount { lump: "mesh count", count: -1 }));
+
+        let mut data = md3_bytes(3, &[[0, 1, 2]]);
+        set_i32(&mut data, HEADER_SIZE + FRAME_SIZE + 80, -5);
+        let err = MD3Model::from_bytes(&data, LoadOptions::default()).unwrap_err();
+        assert!(matches!(err, Md3Error::InvalidCount { lump: "vertex count", count: -5 }));
+    }
+
+    #[test]
+    fn zero_byte_file_is_an_unexpected_eof() {
+        let path = std::env::temp_dir().join(format!("sas2_empty_{}.md3", std::process::id()));
+        std::fs::write(&path, []).unwrap();
+        let err = MD3Model::load(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(err, Md3Error::UnexpectedEof { .. }));
+    }
+
+    #[test]
+    fn wrong_version_is_rejected() {
+        let mut data = md3_bytes(3, &[[0, 1, 2]]);
+        set_i32(&mut data, 4, 16);
+        let err = MD3Model::from_bytes(&data, LoadOptions::default()).unwrap_err();
+        assert!(matches!(err, Md3Error::BadVersion(16)));
+    }
+
+    #[test]
+    fn offset_past_eof_is_rejected() {
+        let mut data = md3_bytes(3, &[[0, 1, 2]]);
+        let past_end = data.len() as i32 + 1;
+        set_i32(&mut data, 96, past_end);
+        let err = MD3Model::from_bytes(&data, LoadOptions::default()).unwrap_err();
+        assert!(matches!(err, Md3Error::InvalidOffset { lump: "tags", .. }));
+    }
+
+    #[test]
+    fn out_of_range_triangle_index_is_rejected() {
+        let data = md3_bytes(3, &[[0, 1, 3]]);
+        let err = MD3Model::from_bytes(&data, LoadOptions::default()).unwrap_err();
+        assert!(matches!(err, Md3Error::InvalidIndex { index: 3, num_vertices: 3 }));
+
+        let data = md3_bytes(3, &[[0, -1, 2]]);
+        let err = MD3Model::from_bytes(&data, LoadOptions::default()).unwrap_err();
+        assert!(matches!(err, Md3Error::InvalidIndex { index: -1, .. }));
+    }
+
+    fn assert_normal(packed: u16, expected: [f32; 3]) {
+        let normal = decode_normal(packed);
+        for (got, want) in normal.iter().zip(expected) {
+            assert!((got - want).abs() < 1e-6, "{:#06x}: {:?} != {:?}", packed, normal, expected);
+        }
+    }
+
+    #[test]
+    fn decoded_normals_match_quake3() {
+        // High byte is latitude, low byte longitude, each in 256 steps around the circle
+        assert_normal(0x0000, [0.0, 0.0, 1.0]);
+        assert_normal(0x0040, [1.0, 0.0, 0.0]);
+        assert_normal(0x4040, [0.0, 1.0, 0.0]);
+        assert_normal(0x8040, [-1.0, 0.0, 0.0]);
+        assert_normal(0x0080, [0.0, 0.0, -1.0]);
+
+        let packed = 0x2010u16;
+        let lat = 0x20 as f32 * std::f32::consts::TAU / 256.0;
+        let lng = 0x10 as f32 * std::f32::consts::TAU / 256.0;
+        assert_normal(packed, [lat.cos() * lng.sin(), lat.sin() * lng.sin(), lng.cos()]);
+    }
+
+    #[test]
+    fn fit_scale_matches_the_target_height() {
+        let model = model_with_positions(&[[0.0, 0.0, -10.0], [4.0, 2.0, 30.0]]);
+        assert_eq!(model.get_height(0), 40.0);
+
+        let scale = model.fit_scale(0, 56.0);
+        assert!((model.get_height(0) * scale - 56.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn flat_model_keeps_unit_scale() {
+        let model = model_with_positions(&[[0.0, 0.0, 5.0], [8.0, 8.0, 5.0]]);
+        assert_eq!(model.fit_scale(0, 56.0), 1.0);
+    }
 }
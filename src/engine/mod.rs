@@ -3,4 +3,5 @@ pub mod loader;
 pub mod math;
 pub mod md3;
 pub mod renderer;
+pub mod scene;
 pub mod shaders;
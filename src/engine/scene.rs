@@ -0,0 +1,142 @@
+use std::fmt;
+use glam::{Mat4, Vec3};
+use crate::engine::md3::MD3Model;
+
+#[derive(Clone, Debug)]
+pub struct SceneNode {
+    pub name: String,
+    pub parent: Option<usize>,
+    pub parent_tag: Option<String>,
+    pub frame: usize,
+    pub world: Mat4,
+    pub bounds: (Vec3, Vec3),
+    pub tags: Vec<String>,
+}
+
+// Resolved model hierarchy for one frame, collected while rendering and printed on demand
+#[derive(Clone, Debug, Default)]
+pub struct SceneDump {
+    nodes: Vec<SceneNode>,
+}
+
+impl SceneDump {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(
+        &mut self,
+        name: &str,
+        parent: Option<usize>,
+        parent_tag: Option<&str>,
+        model: &MD3Model,
+        frame: usize,
+        world: Mat4,
+    ) -> usize {
+        let tags = model
+            .tags
+            .get(frame)
+            .map(|tags| {
+                tags.iter()
+                    .map(|tag| String::from_utf8_lossy(&tag.name).trim_end_matches('\0').to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.nodes.push(SceneNode {
+            name: name.to_string(),
+            parent,
+            parent_tag: parent_tag.map(|tag| tag.to_string()),
+            frame,
+            world,
+            bounds: model.frame_bounds(frame),
+            tags,
+        });
+        self.nodes.len() - 1
+    }
+
+    pub fn nodes(&self) -> &[SceneNode] {
+        &self.nodes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn write_to(&self, path: &str) -> Result<(), String> {
+        std::fs::write(path, self.to_string())
+            .map_err(|e| format!("Failed to write scene dump {}: {}", path, e))
+    }
+
+    fn write_node(&self, f: &mut fmt::Formatter<'_>, index: usize, depth: usize) -> fmt::Result {
+        let node = &self.nodes[index];
+        let indent = "  ".repeat(depth);
+        match node.parent_tag {
+            Some(ref tag) => writeln!(f, "{}{} @ {} (frame {})", indent, node.name, tag, node.frame)?,
+            None => writeln!(f, "{}{} (frame {})", indent, node.name, node.frame)?,
+        }
+
+        let (scale, rotation, translation) = node.world.to_scale_rotation_translation();
+        let (axis, angle) = rotation.to_axis_angle();
+        writeln!(f, "{}  origin   {:.2} {:.2} {:.2}", indent, translation.x, translation.y, translation.z)?;
+        writeln!(
+            f,
+            "{}  rotation {:.1} deg about {:.3} {:.3} {:.3}",
+            indent,
+            angle.to_degrees(),
+            axis.x,
+            axis.y,
+            axis.z
+        )?;
+        writeln!(f, "{}  scale    {:.3} {:.3} {:.3}", indent, scale.x, scale.y, scale.z)?;
+        let (min, max) = node.bounds;
+        writeln!(
+            f,
+            "{}  bounds   ({:.2} {:.2} {:.2}) - ({:.2} {:.2} {:.2})",
+            indent, min.x, min.y, min.z, max.x, max.y, max.z
+        )?;
+        if !node.tags.is_empty() {
+            writeln!(f, "{}  tags     {}", indent, node.tags.join(", "))?;
+        }
+
+        for child in (0..self.nodes.len()).filter(|&i| self.nodes[i].parent == Some(index)) {
+            self.write_node(f, child, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SceneDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for root in (0..self.nodes.len()).filter(|&i| self.nodes[i].parent.is_none()) {
+            self.write_node(f, root, 0)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::md3::tests::md3_bytes;
+    use crate::engine::md3::LoadOptions;
+
+    #[test]
+    fn player_chain_lists_every_part_with_its_transform() {
+        let model = MD3Model::from_bytes(&md3_bytes(3, &[[0, 1, 2]]), LoadOptions::default()).unwrap();
+        let mut dump = SceneDump::new();
+        let lower = dump.add("lower", None, None, &model, 0, Mat4::from_translation(Vec3::new(1.0, 2.0, 3.0)));
+        let upper = dump.add("upper", Some(lower), Some("tag_torso"), &model, 0, Mat4::from_translation(Vec3::new(1.0, 2.0, 27.0)));
+        dump.add("head", Some(upper), Some("tag_head"), &model, 0, Mat4::from_translation(Vec3::new(1.0, 2.0, 51.0)));
+
+        let report = dump.to_string();
+        let lines: Vec<&str> = report.lines().collect();
+        let line = |start: &str| lines.iter().position(|line| line.starts_with(start)).unwrap();
+        let lower_line = line("lower (frame 0)");
+        let upper_line = line("  upper @ tag_torso");
+        let head_line = line("    head @ tag_head");
+        assert!(lower_line < upper_line && upper_line < head_line);
+        assert_eq!(lines[lower_line + 1].trim(), "origin   1.00 2.00 3.00");
+        assert_eq!(lines[upper_line + 1].trim(), "origin   1.00 2.00 27.00");
+        assert_eq!(lines[head_line + 1].trim(), "origin   1.00 2.00 51.00");
+    }
+}
@@ -90,7 +90,7 @@ struct Uniforms {
     lights: array<LightData, 8>,
     num_lights: i32,
     ambient_light: f32,
-    _padding0: f32,
+    render_mode: u32,
     _padding1: f32,
 }
 
@@ -124,8 +124,21 @@ fn saturate_color(color: vec3<f32>, amount: f32) -> vec3<f32> {
     return mix(vec3<f32>(gray), color, amount);
 }
 
-@fragment
-fn fs_main(input: VertexOutput, @builtin(front_facing) is_front: bool) -> @location(0) vec4<f32> {
+const CUTOUT_ALPHA_REF: f32 = 0.5;
+
+fn shade(input: VertexOutput, is_front: bool) -> vec4<f32> {
+    if (uniforms.render_mode == 1u) {
+        if (is_front) {
+            return vec4<f32>(0.0, 1.0, 0.0, 1.0);
+        }
+        return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+    }
+
+    if (uniforms.render_mode == 2u) {
+        let tex_color = textureSample(model_texture, model_sampler, input.uv).rgb;
+        return vec4<f32>(tex_color * input.color.rgb, input.color.a);
+    }
+
     var total_light = vec3<f32>(uniforms.ambient_light);
 
     for (var i = 0; i < uniforms.num_lights; i++) {
@@ -174,6 +187,30 @@ fn fs_main(input: VertexOutput, @builtin(front_facing) is_front: bool) -> @locat
     
     return vec4<f32>(final_color, input.color.a);
 }
+
+@fragment
+fn fs_main(input: VertexOutput, @builtin(front_facing) is_front: bool) -> @location(0) vec4<f32> {
+    return shade(input, is_front);
+}
+
+@fragment
+fn fs_cutout(input: VertexOutput, @builtin(front_facing) is_front: bool) -> @location(0) vec4<f32> {
+    let alpha = textureSample(model_texture, model_sampler, input.uv).a;
+    let color = shade(input, is_front);
+    if (alpha < CUTOUT_ALPHA_REF) {
+        discard;
+    }
+    return vec4<f32>(color.rgb, 1.0);
+}
+
+@fragment
+fn fs_coverage(input: VertexOutput, @builtin(front_facing) is_front: bool) -> @location(0) vec4<f32> {
+    let alpha = textureSample(model_texture, model_sampler, input.uv).a;
+    let color = shade(input, is_front);
+    // Sharpen the alpha ramp to roughly one pixel so coverage only softens the edge
+    let coverage = (alpha - CUTOUT_ALPHA_REF) / max(fwidth(alpha), 0.0001) + 0.5;
+    return vec4<f32>(color.rgb, clamp(coverage, 0.0, 1.0));
+}
 "#;
 
 pub const GROUND_SHADER: &str = r#"
@@ -207,8 +244,8 @@ struct Uniforms {
     lights: array<LightData, 8>,
     num_lights: i32,
     ambient_light: f32,
-    _padding0: f32,
-    _padding1: f32,
+    render_mode: u32,
+    reflectivity: f32,
 }
 
 @group(0) @binding(0)
@@ -220,6 +257,12 @@ var ground_texture: texture_2d<f32>;
 @group(0) @binding(2)
 var ground_sampler: sampler;
 
+@group(0) @binding(3)
+var reflection_texture: texture_2d<f32>;
+
+@group(0) @binding(4)
+var reflection_sampler: sampler;
+
 @vertex
 fn vs_main(input: VertexInput) -> VertexOutput {
     var output: VertexOutput;
@@ -279,7 +322,18 @@ fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
         lighting += contribution;
     }
     
-    return vec4<f32>(tex_color * lighting, 1.0);
+    var color = tex_color * lighting;
+
+    if (uniforms.reflectivity > 0.0) {
+        let screen_uv = input.clip_position.xy / vec2<f32>(textureDimensions(reflection_texture));
+        let reflection = textureSample(reflection_texture, reflection_sampler, screen_uv);
+        let view_dir = normalize(uniforms.camera_pos.xyz - input.world_pos);
+        let cos_theta = clamp(dot(input.normal, view_dir), 0.0, 1.0);
+        let fresnel = uniforms.reflectivity + (1.0 - uniforms.reflectivity) * pow(1.0 - cos_theta, 5.0);
+        color = mix(color, reflection.rgb, fresnel * reflection.a);
+    }
+    
+    return vec4<f32>(color, 1.0);
 }
 "#;
 
@@ -799,6 +853,14 @@ fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
     let color = tex_color.rgb;
     return vec4<f32>(color, alpha);
 }
+
+@fragment
+fn fs_main_premultiplied(input: VertexOutput) -> @location(0) vec4<f32> {
+    let tex_color = textureSample(smoke_texture, smoke_sampler, input.uv);
+    let dist = distance(input.uv, vec2<f32>(0.5, 0.5));
+    let edge = smoothstep(0.5, 0.2, dist);
+    return tex_color * (input.alpha * edge);
+}
 "#;
 
 pub const FLAME_SHADER: &str = r#"
@@ -811,11 +873,13 @@ struct VertexInput {
 
 struct InstanceInput {
     @location(4) position_size: vec4<f32>,
+    @location(5) alpha: f32,
 }
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) uv: vec2<f32>,
+    @location(1) alpha: f32,
 }
 
 struct Uniforms {
@@ -851,13 +915,14 @@ fn vs_main(input: VertexInput, instance: InstanceInput) -> VertexOutput {
     
     output.clip_position = uniforms.view_proj * vec4<f32>(billboard_pos, 1.0);
     output.uv = input.uv;
+    output.alpha = instance.alpha;
     return output;
 }
 
 @fragment
 fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
     let tex_color = textureSample(flame_texture, flame_sampler, input.uv);
-    return vec4<f32>(tex_color.rgb, tex_color.a);
+    return vec4<f32>(tex_color.rgb, tex_color.a * input.alpha);
 }
 "#;
 
@@ -912,17 +977,21 @@ fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
 
 pub const DEBUG_LIGHT_RAY_SHADER: &str = r#"
 struct VertexInput {
-    @location(0) position: vec3<f32>,
-    @location(1) color: vec4<f32>,
+    @location(0) start: vec3<f32>,
+    @location(1) end: vec3<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) t_side: vec2<f32>,
 }
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) color: vec4<f32>,
+    @location(1) edge_px: f32,
 }
 
 struct Uniforms {
     view_proj: mat4x4<f32>,
+    viewport_line_width: vec4<f32>,
 }
 
 @group(0) @binding(0)
@@ -931,14 +1000,35 @@ var<uniform> uniforms: Uniforms;
 @vertex
 fn vs_main(input: VertexInput) -> VertexOutput {
     var output: VertexOutput;
-    output.clip_position = uniforms.view_proj * vec4<f32>(input.position, 1.0);
+    let viewport = uniforms.viewport_line_width.xy;
+    let half_width = uniforms.viewport_line_width.z * 0.5 + 1.0;
+
+    let clip_start = uniforms.view_proj * vec4<f32>(input.start, 1.0);
+    let clip_end = uniforms.view_proj * vec4<f32>(input.end, 1.0);
+    let screen_start = clip_start.xy / clip_start.w * viewport;
+    let screen_end = clip_end.xy / clip_end.w * viewport;
+
+    var dir = screen_end - screen_start;
+    if (dot(dir, dir) < 1e-8) {
+        dir = vec2<f32>(1.0, 0.0);
+    }
+    dir = normalize(dir);
+    let normal = vec2<f32>(-dir.y, dir.x);
+
+    let clip = mix(clip_start, clip_end, input.t_side.x);
+    let offset = normal * input.t_side.y * half_width * 2.0 / viewport * clip.w;
+
+    output.clip_position = vec4<f32>(clip.xy + offset, clip.zw);
     output.color = input.color;
+    output.edge_px = input.t_side.y * half_width;
     return output;
 }
 
 @fragment
 fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
-    return input.color;
+    let half_width = uniforms.viewport_line_width.z * 0.5;
+    let coverage = clamp(half_width + 0.5 - abs(input.edge_px), 0.0, 1.0);
+    return vec4<f32>(input.color.rgb, input.color.a * coverage);
 }
 "#;
 
@@ -1043,6 +1133,38 @@ fn fs_main(_input: VertexOutput) -> @location(0) vec4<f32> {
 }
 "#;
 
+pub const WIRE_OVERLAY_SHADER: &str = r#"
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) normal: vec3<f32>,
+}
+
+struct Uniforms {
+    view_proj: mat4x4<f32>,
+    model: mat4x4<f32>,
+    color: vec4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+@vertex
+fn vs_main(input: VertexInput) -> @builtin(position) vec4<f32> {
+    let world_pos = uniforms.model * vec4<f32>(input.position, 1.0);
+    var clip_position = uniforms.view_proj * world_pos;
+    // Pull lines slightly towards the camera so they win the depth test against their own faces
+    clip_position.z = clip_position.z - 0.0005 * clip_position.w;
+    return clip_position;
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return uniforms.color;
+}
+"#;
+
 pub const COORDINATE_GRID_SHADER: &str = r#"
 struct VertexInput {
     @location(0) position: vec3<f32>,
@@ -1078,3 +1200,40 @@ fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
     return input.color;
 }
 "#;
+
+pub const SKYBOX_SHADER: &str = r#"
+struct SkyboxUniforms {
+    inv_view_proj: mat4x4<f32>,
+    camera_pos: vec4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> uniforms: SkyboxUniforms;
+@group(0) @binding(1)
+var sky_texture: texture_cube<f32>;
+@group(0) @binding(2)
+var sky_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) ndc: vec2<f32>,
+}
+
+// One oversized triangle covering the screen, pinned to the far plane
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    let ndc = uv * 2.0 - 1.0;
+    var output: VertexOutput;
+    output.clip_position = vec4<f32>(ndc, 1.0, 1.0);
+    output.ndc = ndc;
+    return output;
+}
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    let far = uniforms.inv_view_proj * vec4<f32>(input.ndc, 1.0, 1.0);
+    let dir = normalize(far.xyz / far.w - uniforms.camera_pos.xyz);
+    return textureSample(sky_texture, sky_sampler, dir);
+}
+"#;
@@ -1,5 +1,7 @@
 use glam::{Mat4, Vec3};
 use super::map::Map;
+use super::player::Player;
+use super::constants::PLAYER_HITBOX_HEIGHT;
 
 pub struct Camera {
     pub x: f32,
@@ -9,6 +11,8 @@ pub struct Camera {
     pub target_y: f32,
     pub pitch: f32,
     pub yaw: f32,
+    pub roll: f32,
+    pub roll_locked: bool,
 }
 
 impl Camera {
@@ -21,6 +25,28 @@ impl Camera {
             target_y: 59.0,
             pitch: 0.0,
             yaw: 0.0,
+            roll: 0.0,
+            roll_locked: true,
+        }
+    }
+
+    pub fn set_angles(&mut self, yaw: f32, pitch: f32) {
+        self.yaw = yaw;
+        self.pitch = pitch;
+    }
+
+    pub fn lock_roll(&mut self, locked: bool) {
+        self.roll_locked = locked;
+        if locked {
+            self.roll = 0.0;
+        }
+    }
+
+    pub fn effective_roll(&self) -> f32 {
+        if self.roll_locked {
+            0.0
+        } else {
+            self.roll
         }
     }
 
@@ -58,8 +84,126 @@ impl Camera {
         let yaw_offset = self.yaw * 50.0;
         let camera_target = Vec3::new(self.x + yaw_offset, self.y + pitch_offset, 0.0);
         
-        let view_matrix = Mat4::look_at_rh(camera_pos, camera_target, Vec3::Y);
+        let view_matrix = Mat4::from_rotation_z(self.effective_roll())
+            * Mat4::look_at_rh(camera_pos, camera_target, Vec3::Y);
         let proj_matrix = Mat4::perspective_rh(std::f32::consts::PI / 4.0, aspect, 0.1, 1000.0);
         (proj_matrix * view_matrix, camera_pos)
     }
 }
+
+pub struct SpectatorCamera {
+    pub camera: Camera,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    pub padding: f32,
+    pub smoothness: f32,
+    pub target_z: f32,
+}
+
+impl SpectatorCamera {
+    pub fn new() -> Self {
+        let camera = Camera::new();
+        let target_z = camera.z;
+        Self {
+            camera,
+            min_distance: 200.0,
+            max_distance: 950.0,
+            padding: 60.0,
+            smoothness: 3.0,
+            target_z,
+        }
+    }
+
+    pub fn bounding_sphere(players: &[Player]) -> Option<(Vec3, f32)> {
+        let points: Vec<Vec3> = players.iter()
+            .filter(|p| !p.dead)
+            .map(|p| Vec3::new(p.x, p.y + PLAYER_HITBOX_HEIGHT * 0.5, 0.0))
+            .collect();
+
+        if points.is_empty() {
+            return None;
+        }
+
+        let mut min = points[0];
+        let mut max = points[0];
+        for p in &points[1..] {
+            min = min.min(*p);
+            max = max.max(*p);
+        }
+
+        let center = (min + max) * 0.5;
+        let radius = points.iter()
+            .map(|p| (*p - center).length())
+            .fold(0.0f32, f32::max);
+
+        Some((center, radius))
+    }
+
+    pub fn update(&mut self, players: &[Player], dt: f32) {
+        if let Some((center, radius)) = Self::bounding_sphere(players) {
+            let half_fov = std::f32::consts::PI / 8.0;
+            let distance = (radius + self.padding) / half_fov.sin();
+
+            self.camera.target_x = center.x;
+            self.camera.target_y = center.y;
+            self.target_z = distance.clamp(self.min_distance, self.max_distance);
+        }
+
+        let t = (self.smoothness * dt).min(1.0);
+        self.camera.x += (self.camera.target_x - self.camera.x) * t;
+        self.camera.y += (self.camera.target_y - self.camera.y) * t;
+        self.camera.z += (self.target_z - self.camera.z) * t;
+    }
+
+    pub fn get_view_proj(&self, aspect: f32) -> (Mat4, Vec3) {
+        self.camera.get_view_proj(aspect)
+    }
+}
+
+impl Default for SpectatorCamera {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player_at(id: u32, x: f32) -> Player {
+        let mut player = Player::new(id);
+        player.x = x;
+        player.y = 0.0;
+        player
+    }
+
+    #[test]
+    fn distant_player_widens_the_spectator_framing() {
+        let mut spectator = SpectatorCamera::new();
+        let mut players = vec![player_at(0, 0.0), player_at(1, 100.0)];
+        // One step of at least 1 / smoothness seconds lands exactly on the target
+        spectator.update(&players, 1.0);
+        let close = spectator.camera.z;
+
+        players.push(player_at(2, 500.0));
+        spectator.update(&players, 1.0);
+        assert!(spectator.camera.z > close);
+        assert!(spectator.camera.z <= spectator.max_distance);
+        assert_eq!(spectator.camera.x, 250.0);
+    }
+
+    #[test]
+    fn locked_roll_ignores_roll_input() {
+        let mut camera = Camera::new();
+        let (upright, _) = camera.get_view_proj(1.5);
+
+        camera.roll = 0.4;
+        let (locked, _) = camera.get_view_proj(1.5);
+        assert_eq!(locked, upright);
+
+        camera.lock_roll(false);
+        camera.roll = 0.4;
+        let (rolled, _) = camera.get_view_proj(1.5);
+        assert_ne!(rolled, upright);
+    }
+}
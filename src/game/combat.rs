@@ -15,6 +15,7 @@ pub struct CombatResult {
     pub killed: bool,
     pub gibbed: bool,
     pub final_health: i32,
+    pub damage: i32,
 }
 
 pub fn apply_damage(
@@ -41,6 +42,7 @@ pub fn apply_damage(
         killed,
         gibbed: player.gibbed,
         final_health: player.health,
+        damage: final_damage,
     }
 }
 
@@ -63,6 +65,7 @@ pub fn apply_self_damage(
         killed,
         gibbed: player.gibbed,
         final_health: player.health,
+        damage: final_damage,
     }
 }
 
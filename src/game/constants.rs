@@ -6,6 +6,8 @@ pub const MAX_FALL_SPEED: f32 = 14.285714285714286;
 pub const MAX_SPEED: f32 = 9.142857142857142;
 pub const MAX_SPEED_GROUND: f32 = 9.142857142857142;
 pub const MAX_SPEED_AIR: f32 = 9.142857142857142;
+// Legs play the walk cycle up to this speed and blend fully into the run at MAX_SPEED_GROUND
+pub const WALK_SPEED: f32 = 3.0;
 
 pub const GROUND_ACCEL: f32 = 2.857142857142857;
 pub const AIR_ACCEL: f32 = 0.2857142857142857;
@@ -64,3 +66,5 @@ pub const PLASMA_SPLASH_RADIUS: f32 = 0.5714285714285714;
 pub const BFG_SPEED: f32 = 57.142857142857146;
 pub const BFG_SPLASH_RADIUS: f32 = 5.714285714285714;
 
+pub const MAX_PARTICLE_LIGHTS: usize = 4;
+
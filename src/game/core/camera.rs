@@ -4,6 +4,8 @@ pub struct Camera {
     pub x: f32,
     pub y: f32,
     pub z: f32,
+    pub yaw: f32,
+    pub pitch: f32,
 }
 
 impl Camera {
@@ -12,13 +14,26 @@ impl Camera {
             x: 0.0,
             y: 5.0,
             z: 35.0,
+            yaw: 0.0,
+            pitch: 0.0,
         }
     }
 
+    pub fn set_angles(&mut self, yaw: f32, pitch: f32) {
+        self.yaw = yaw;
+        self.pitch = pitch;
+    }
+
+    // Zero yaw and pitch look down -Z; positive yaw turns right, positive pitch looks up
+    pub fn forward(&self) -> Vec3 {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        Vec3::new(cos_pitch * sin_yaw, sin_pitch, -cos_pitch * cos_yaw)
+    }
+
     pub fn get_view_proj(&self, aspect: f32) -> (Mat4, Vec3) {
         let camera_pos = Vec3::new(self.x, self.y, self.z);
-        let camera_target = Vec3::new(self.x, self.y, 0.0);
-        let view_matrix = Mat4::look_at_rh(camera_pos, camera_target, Vec3::Y);
+        let view_matrix = Mat4::look_to_rh(camera_pos, self.forward(), Vec3::Y);
         let proj_matrix = Mat4::perspective_rh(std::f32::consts::PI / 4.0, aspect, 0.1, 1000.0);
         (proj_matrix * view_matrix, camera_pos)
     }
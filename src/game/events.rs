@@ -0,0 +1,70 @@
+use glam::Vec3;
+use crate::audio::events::AudioEvent;
+use super::particle::EmitterConfig;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParticleEffect {
+    Smoke,
+    Sparks,
+}
+
+impl ParticleEffect {
+    pub fn config(&self) -> EmitterConfig {
+        match self {
+            ParticleEffect::Smoke => EmitterConfig::smoke(),
+            ParticleEffect::Sparks => EmitterConfig::sparks(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum GameEvent {
+    Audio(AudioEvent),
+    ParticleBurst {
+        effect: ParticleEffect,
+        position: Vec3,
+        count: u32,
+        speed: f32,
+    },
+    LightFlash {
+        position: Vec3,
+        radius: f32,
+    },
+    PlayerDamaged {
+        player_id: u32,
+        attacker_id: u32,
+        damage: i32,
+        health: i32,
+        killed: bool,
+    },
+}
+
+impl From<AudioEvent> for GameEvent {
+    fn from(event: AudioEvent) -> Self {
+        GameEvent::Audio(event)
+    }
+}
+
+pub struct GameEventQueue {
+    pub events: Vec<GameEvent>,
+}
+
+impl GameEventQueue {
+    pub fn new() -> Self {
+        Self { events: Vec::new() }
+    }
+
+    pub fn push<E: Into<GameEvent>>(&mut self, event: E) {
+        self.events.push(event.into());
+    }
+
+    pub fn drain(&mut self) -> Vec<GameEvent> {
+        self.events.drain(..).collect()
+    }
+}
+
+impl Default for GameEventQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
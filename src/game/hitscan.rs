@@ -1,4 +1,5 @@
 use glam::Vec3;
+use rand::Rng;
 use crate::game::weapon::Weapon;
 use crate::game::player::Player;
 
@@ -62,6 +63,7 @@ pub fn hitscan_trace(
     shooter_id: u32,
     players: &[Player],
     weapon: Weapon,
+    rng: &mut impl Rng,
 ) -> HitResult {
     let normalized_dir = direction.normalize();
     let ray_end = origin + normalized_dir * max_distance;
@@ -98,7 +100,7 @@ pub fn hitscan_trace(
         let mut damage = weapon.damage();
         
         if matches!(weapon, Weapon::Shotgun) {
-            let spread_factor = rand::random::<f32>();
+            let spread_factor = rng.gen::<f32>();
             damage = (damage as f32 * (0.5 + spread_factor * 0.5)) as i32;
         }
 
@@ -123,17 +125,18 @@ pub fn shotgun_trace(
     direction: Vec3,
     shooter_id: u32,
     players: &[Player],
+    rng: &mut impl Rng,
 ) -> Vec<HitResult> {
     let mut results = Vec::new();
     let pellet_count = 10;
     let spread = 0.1;
 
     for _ in 0..pellet_count {
-        let spread_x = (rand::random::<f32>() - 0.5) * spread;
-        let spread_y = (rand::random::<f32>() - 0.5) * spread;
+        let spread_x = (rng.gen::<f32>() - 0.5) * spread;
+        let spread_y = (rng.gen::<f32>() - 0.5) * spread;
         
         let spread_dir = direction + Vec3::new(spread_x, spread_y, 0.0);
-        let result = hitscan_trace(origin, spread_dir, 57.142857142857146, shooter_id, players, Weapon::Shotgun);
+        let result = hitscan_trace(origin, spread_dir, 57.142857142857146, shooter_id, players, Weapon::Shotgun, rng);
         results.push(result);
     }
 
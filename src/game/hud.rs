@@ -0,0 +1,40 @@
+use super::events::GameEvent;
+
+pub struct HudFeedback {
+    pub player_id: u32,
+    pub hit_marker: f32,
+    pub damage_flash: f32,
+    pub last_damage_dealt: i32,
+    pub frags: u32,
+}
+
+impl HudFeedback {
+    pub fn new(player_id: u32) -> Self {
+        Self {
+            player_id,
+            hit_marker: 0.0,
+            damage_flash: 0.0,
+            last_damage_dealt: 0,
+            frags: 0,
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &GameEvent) {
+        if let GameEvent::PlayerDamaged { player_id, attacker_id, damage, killed, .. } = event {
+            if *player_id == self.player_id {
+                self.damage_flash = 0.3;
+            } else if *attacker_id == self.player_id {
+                self.hit_marker = 0.25;
+                self.last_damage_dealt = *damage;
+                if *killed {
+                    self.frags += 1;
+                }
+            }
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.hit_marker = (self.hit_marker - dt).max(0.0);
+        self.damage_flash = (self.damage_flash - dt).max(0.0);
+    }
+}
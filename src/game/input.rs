@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use crate::game::weapon::Weapon;
+use crate::input::InputState;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerInput {
+    pub player_id: u32,
+    pub move_left: bool,
+    pub move_right: bool,
+    // Analog horizontal input in -1..1; zero means use the digital buttons
+    #[serde(default)]
+    pub move_axis: f32,
+    pub jump: bool,
+    pub crouch: bool,
+    pub fire: bool,
+    pub aim_angle: f32,
+    pub weapon_switch: Option<Weapon>,
+}
+
+impl PlayerInput {
+    pub fn from_input_state(player_id: u32, input: &InputState, aim_angle: f32) -> Self {
+        Self {
+            player_id,
+            move_left: input.move_left,
+            move_right: input.move_right,
+            move_axis: if input.gamepad_active() { input.move_axis.x } else { 0.0 },
+            jump: input.jump,
+            crouch: input.crouch,
+            fire: input.fire,
+            aim_angle,
+            weapon_switch: input.weapon_switch,
+        }
+    }
+
+    pub fn horizontal_axis(&self) -> f32 {
+        if self.move_axis != 0.0 {
+            self.move_axis.clamp(-1.0, 1.0)
+        } else {
+            (self.move_right as i32 - self.move_left as i32) as f32
+        }
+    }
+}
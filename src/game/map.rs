@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use glam::Mat4;
+use crate::engine::math::Frustum;
 use std::hash::Hash;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -225,6 +227,15 @@ impl Map {
         self.is_solid(self.world_to_tile_x(world_x), self.world_to_tile_y(world_y))
     }
 
+    pub fn simulation_frustum(&self) -> Frustum {
+        let margin = 200.0;
+        let left = self.origin_x() - margin;
+        let right = self.origin_x() + self.width as f32 * self.tile_width + margin;
+        let bottom = self.ground_y - margin;
+        let top = self.height as f32 * self.tile_height + margin;
+        Frustum::from_view_proj(Mat4::orthographic_rh(left, right, bottom, top, -1000.0, 1000.0))
+    }
+
     pub fn map_width(&self) -> usize {
         self.width
     }
@@ -7,14 +7,18 @@ pub mod awards;
 pub mod camera;
 pub mod combat;
 pub mod constants;
+pub mod events;
 pub mod game_state;
 pub mod hitscan;
+pub mod hud;
+pub mod input;
 pub mod items;
 pub mod lighting;
 pub mod menu;
 pub mod particle;
 pub mod weapon;
 pub mod player;
+pub mod snapshot;
 pub mod map;
 pub mod map_loader;
 pub mod world;
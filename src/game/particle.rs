@@ -1,4 +1,8 @@
+use std::sync::OnceLock;
 use glam::Vec3;
+use super::lighting::Light;
+use super::events::{GameEvent, ParticleEffect};
+use super::constants::{MAX_PARTICLE_LIGHTS, ROCKET_SPLASH_RADIUS};
 
 pub struct SmokeParticle {
     pub position: Vec3,
@@ -32,9 +36,7 @@ impl SmokeParticle {
             return false;
         }
         
-        // Quad scaling? 
-        let size_growth = 1.0 + life_ratio * 1.5;
-        self.size = self.initial_size * size_growth;
+        self.size = self.initial_size * smoke_config().size_curve.sample(life_ratio);
         
         true
     }
@@ -44,19 +46,48 @@ impl SmokeParticle {
         if life_ratio >= 1.0 {
             return 0.0;
         }
-        
-        if life_ratio < 0.1 {
-            life_ratio / 0.1 * 0.33
-        } else {
-            let fade_start = 0.7;
-            let fade_end = 1.0;
-            if life_ratio < fade_start {
-                0.33
-            } else {
-                0.33 * (1.0 - (life_ratio - fade_start) / (fade_end - fade_start)).max(0.0)
-            }
+        smoke_config().alpha_curve.sample(life_ratio)
+    }
+}
+
+// Rocket trails follow the same size and fade curves as smoke bursts
+fn smoke_config() -> &'static EmitterConfig {
+    static CONFIG: OnceLock<EmitterConfig> = OnceLock::new();
+    CONFIG.get_or_init(EmitterConfig::smoke)
+}
+
+pub struct ExplosionFlash {
+    pub position: Vec3,
+    pub color: Vec3,
+    pub radius: f32,
+    pub lifetime: f32,
+    pub max_lifetime: f32,
+}
+
+impl ExplosionFlash {
+    pub fn new(position: Vec3, splash_radius: f32) -> Self {
+        Self {
+            position,
+            color: Vec3::new(4.0, 2.6, 1.2),
+            radius: 300.0 * splash_radius / ROCKET_SPLASH_RADIUS,
+            lifetime: 0.0,
+            max_lifetime: 0.35,
         }
     }
+
+    pub fn update(&mut self, dt: f32) -> bool {
+        self.lifetime += dt;
+        self.lifetime < self.max_lifetime
+    }
+
+    pub fn get_alpha(&self) -> f32 {
+        let remaining = (1.0 - self.lifetime / self.max_lifetime).clamp(0.0, 1.0);
+        remaining * remaining
+    }
+
+    pub fn to_light(&self) -> Light {
+        Light::new(self.position, self.color * self.get_alpha(), self.radius)
+    }
 }
 
 pub struct FlameParticle {
@@ -97,3 +128,334 @@ impl FlameParticle {
         self.lifetime < self.max_lifetime
     }
 }
+
+#[derive(Clone, Debug)]
+pub struct Curve {
+    pub points: Vec<(f32, f32)>,
+}
+
+impl Curve {
+    // What an empty curve samples to; curves scale size and alpha, so it leaves them unchanged
+    pub const EMPTY_VALUE: f32 = 1.0;
+
+    pub fn constant(value: f32) -> Self {
+        Self { points: vec![(0.0, value)] }
+    }
+
+    pub fn linear(start: f32, end: f32) -> Self {
+        Self { points: vec![(0.0, start), (1.0, end)] }
+    }
+
+    pub fn sample(&self, t: f32) -> f32 {
+        let first = match self.points.first() {
+            Some(p) => *p,
+            None => return Self::EMPTY_VALUE,
+        };
+        if t <= first.0 {
+            return first.1;
+        }
+        for pair in self.points.windows(2) {
+            let (t0, v0) = pair[0];
+            let (t1, v1) = pair[1];
+            if t <= t1 {
+                let span = t1 - t0;
+                if span <= 0.0 {
+                    return v1;
+                }
+                return v0 + (v1 - v0) * ((t - t0) / span);
+            }
+        }
+        self.points[self.points.len() - 1].1
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EmitterConfig {
+    pub gravity: f32,
+    pub wind: Vec3,
+    pub drag: f32,
+    pub lifetime: f32,
+    pub spawn_rate: f32,
+    pub size: f32,
+    pub size_curve: Curve,
+    pub alpha_curve: Curve,
+}
+
+impl EmitterConfig {
+    pub fn smoke() -> Self {
+        Self {
+            gravity: -0.5,
+            wind: Vec3::ZERO,
+            drag: 1.5,
+            lifetime: 2.0,
+            spawn_rate: 20.0,
+            size: 3.6,
+            size_curve: Curve::linear(1.0, 2.5),
+            alpha_curve: Curve {
+                points: vec![(0.0, 0.0), (0.1, 0.33), (0.7, 0.33), (1.0, 0.0)],
+            },
+        }
+    }
+
+    pub fn sparks() -> Self {
+        Self {
+            gravity: 30.0,
+            wind: Vec3::ZERO,
+            drag: 0.2,
+            lifetime: 0.6,
+            spawn_rate: 60.0,
+            size: 0.3,
+            size_curve: Curve::linear(1.0, 0.5),
+            alpha_curve: Curve::linear(1.0, 0.0),
+        }
+    }
+}
+
+pub struct Particle {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub age: f32,
+}
+
+pub struct ParticleSystem {
+    pub config: EmitterConfig,
+    pub effect: Option<ParticleEffect>,
+    pub particles: Vec<Particle>,
+    spawn_accumulator: f32,
+    output: Vec<(Vec3, f32, f32)>,
+}
+
+impl ParticleSystem {
+    pub fn new(config: EmitterConfig) -> Self {
+        Self {
+            config,
+            effect: None,
+            particles: Vec::new(),
+            spawn_accumulator: 0.0,
+            output: Vec::new(),
+        }
+    }
+
+    pub fn for_effect(effect: ParticleEffect) -> Self {
+        let mut system = Self::new(effect.config());
+        system.effect = Some(effect);
+        system
+    }
+
+    pub fn handle_event(&mut self, event: &GameEvent) {
+        if let GameEvent::ParticleBurst { effect, position, count, speed } = event {
+            if self.effect != Some(*effect) {
+                return;
+            }
+            for i in 0..*count {
+                let angle = i as f32 / *count as f32 * std::f32::consts::TAU;
+                let velocity = Vec3::new(angle.cos(), angle.sin(), 0.0) * *speed;
+                self.emit(*position, velocity);
+            }
+        }
+    }
+
+    pub fn emit(&mut self, position: Vec3, velocity: Vec3) {
+        self.particles.push(Particle {
+            position,
+            velocity,
+            age: 0.0,
+        });
+    }
+
+    pub fn spawn(&mut self, dt: f32, position: Vec3, velocity: Vec3) {
+        self.spawn_accumulator += dt * self.config.spawn_rate;
+        while self.spawn_accumulator >= 1.0 {
+            self.spawn_accumulator -= 1.0;
+            self.emit(position, velocity);
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) -> &[(Vec3, f32, f32)] {
+        let config = &self.config;
+        let drag = (config.drag * dt).min(1.0);
+
+        for particle in &mut self.particles {
+            particle.age += dt;
+            particle.velocity.y -= config.gravity * dt;
+            particle.velocity += (config.wind - particle.velocity) * drag;
+            particle.position += particle.velocity * dt;
+        }
+
+        let lifetime = config.lifetime;
+        self.particles.retain(|p| p.age < lifetime);
+
+        self.output.clear();
+        for particle in &self.particles {
+            let t = particle.age / lifetime;
+            self.output.push((
+                particle.position,
+                config.size * config.size_curve.sample(t),
+                config.alpha_curve.sample(t),
+            ));
+        }
+
+        &self.output
+    }
+
+    pub fn instances(&self) -> &[(Vec3, f32, f32)] {
+        &self.output
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.output.clear();
+        self.spawn_accumulator = 0.0;
+    }
+}
+
+// Presentation-side reactions to world events: particle bursts and the brief lights explosions cast
+pub struct Effects {
+    pub systems: Vec<ParticleSystem>,
+    pub flashes: Vec<ExplosionFlash>,
+    pub emissive: bool,
+}
+
+impl Effects {
+    pub fn new() -> Self {
+        Self {
+            systems: vec![
+                ParticleSystem::for_effect(ParticleEffect::Smoke),
+                ParticleSystem::for_effect(ParticleEffect::Sparks),
+            ],
+            flashes: Vec::new(),
+            emissive: true,
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &GameEvent) {
+        for system in &mut self.systems {
+            system.handle_event(event);
+        }
+        if let GameEvent::LightFlash { position, radius } = event {
+            if self.emissive {
+                self.flashes.push(ExplosionFlash::new(*position, *radius));
+            }
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for system in &mut self.systems {
+            system.update(dt);
+        }
+        self.flashes.retain_mut(|flash| flash.update(dt));
+    }
+
+    pub fn instances(&self) -> impl Iterator<Item = &(Vec3, f32, f32)> {
+        self.systems.iter().flat_map(|system| system.instances())
+    }
+
+    // Brightest flashes first, so the budget keeps the ones that matter
+    pub fn lights(&self, max_lights: usize) -> Vec<Light> {
+        if !self.emissive {
+            return Vec::new();
+        }
+
+        let mut flashes: Vec<&ExplosionFlash> = self.flashes.iter()
+            .filter(|f| f.get_alpha() > 0.01)
+            .collect();
+        flashes.sort_by(|a, b| b.get_alpha().total_cmp(&a.get_alpha()));
+
+        flashes.iter()
+            .take(max_lights.min(MAX_PARTICLE_LIGHTS))
+            .map(|f| f.to_light())
+            .collect()
+    }
+}
+
+impl Default for Effects {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curves_interpolate_and_hold_their_ends() {
+        let curve = Curve { points: vec![(0.0, 0.0), (0.5, 1.0), (1.0, 0.0)] };
+        assert_eq!(curve.sample(-1.0), 0.0);
+        assert_eq!(curve.sample(0.25), 0.5);
+        assert_eq!(curve.sample(0.5), 1.0);
+        assert_eq!(curve.sample(0.75), 0.5);
+        assert_eq!(curve.sample(2.0), 0.0);
+        assert_eq!(Curve::constant(0.3).sample(0.9), 0.3);
+        assert_eq!(Curve { points: Vec::new() }.sample(0.5), Curve::EMPTY_VALUE);
+    }
+
+    #[test]
+    fn trail_smoke_follows_the_smoke_curves() {
+        let mut smoke = SmokeParticle::new(Vec3::ZERO, 0.0);
+        smoke.update(0.0, 1.0);
+        let config = EmitterConfig::smoke();
+        assert_eq!(smoke.get_alpha(), config.alpha_curve.sample(0.5));
+        assert_eq!(smoke.size, smoke.initial_size * config.size_curve.sample(0.5));
+    }
+
+    #[test]
+    fn emitter_applies_gravity_and_expires_particles() {
+        let mut falling = ParticleSystem::new(EmitterConfig { drag: 0.0, ..EmitterConfig::sparks() });
+        let mut floating = ParticleSystem::new(EmitterConfig { drag: 0.0, gravity: 0.0, ..EmitterConfig::sparks() });
+        for system in [&mut falling, &mut floating] {
+            system.emit(Vec3::ZERO, Vec3::X);
+            system.update(0.1);
+            system.update(0.1);
+        }
+        // Identical emitters apart from gravity only drift apart vertically
+        let (fell, floated) = (falling.particles[0].position, floating.particles[0].position);
+        assert_eq!(floated.y, 0.0);
+        assert!(fell.y < floated.y);
+        assert_eq!(fell.x, floated.x);
+
+        falling.update(1.0);
+        assert!(falling.particles.is_empty());
+    }
+
+    #[test]
+    fn effects_turn_events_into_particles_and_lights() {
+        let mut effects = Effects::new();
+        let position = Vec3::new(10.0, 5.0, 0.0);
+        effects.handle_event(&GameEvent::ParticleBurst {
+            effect: ParticleEffect::Sparks,
+            position,
+            count: 8,
+            speed: 4.0,
+        });
+        effects.handle_event(&GameEvent::LightFlash { position, radius: ROCKET_SPLASH_RADIUS });
+        effects.update(0.016);
+
+        assert_eq!(effects.instances().count(), 8);
+        let lights = effects.lights(MAX_PARTICLE_LIGHTS);
+        assert_eq!(lights.len(), 1);
+        assert_eq!(lights[0].position, position);
+
+        // Flashes fade out and drop from the light list
+        effects.update(1.0);
+        assert!(effects.lights(MAX_PARTICLE_LIGHTS).is_empty());
+    }
+
+    #[test]
+    fn explosion_light_fades_out_and_respects_the_light_cap() {
+        let mut effects = Effects::new();
+        for i in 0..MAX_PARTICLE_LIGHTS + 2 {
+            let position = Vec3::new(i as f32 * 50.0, 0.0, 0.0);
+            effects.handle_event(&GameEvent::LightFlash { position, radius: ROCKET_SPLASH_RADIUS });
+        }
+        assert_eq!(effects.lights(usize::MAX).len(), MAX_PARTICLE_LIGHTS);
+
+        let mut brightness = effects.lights(1)[0].color.length();
+        effects.update(0.05);
+        while let Some(light) = effects.lights(1).first() {
+            assert!(light.color.length() < brightness);
+            brightness = light.color.length();
+            effects.update(0.05);
+        }
+    }
+}
@@ -64,6 +64,8 @@ pub fn pmove(state: &PmoveState, cmd: &PmoveCmd, dt: f32, map: &mut Map) -> Pmov
     } else {
         base_max_speed
     };
+    // Partial stick deflection moves proportionally slower
+    let max_speed = max_speed * cmd.move_right.abs().min(1.0);
 
     let accel_tick = if on_ground {
         GROUND_ACCEL_TICK
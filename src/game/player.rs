@@ -2,15 +2,17 @@ use super::constants::*;
 use super::map::Map;
 use super::physics::pmove::{self, PmoveCmd, PmoveState};
 use super::weapon::Weapon;
+use crate::engine::anim::{AnimState, Animation};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum PlayerState {
     Ground,
     Air,
     Crouching,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct PowerUps {
     pub quad: u16,
     pub regen: u16,
@@ -47,6 +49,7 @@ pub struct Player {
     pub is_moving: bool,
     pub is_moving_backward: bool,
     pub animation_time: f32,
+    pub anim: AnimState,
     pub state: PlayerState,
     pub is_crouching: bool,
     pub crouch_time: f32,
@@ -107,6 +110,7 @@ impl Player {
             is_moving: false,
             is_moving_backward: false,
             animation_time: 0.0,
+            anim: AnimState::new(),
             state: PlayerState::Ground,
             is_crouching: false,
             crouch_time: 0.0,
@@ -155,6 +159,8 @@ impl Player {
     pub fn spawn(&mut self, x: f32, y: f32) {
         self.x = x;
         self.y = y;
+        self.prev_x = x;
+        self.prev_y = y;
         self.vx = 0.0;
         self.vy = 0.0;
         self.health = STARTING_HEALTH;
@@ -260,7 +266,15 @@ impl Player {
         }
     }
 
-    pub fn update(&mut self, dt: f32, move_left: bool, move_right: bool, jump: bool, crouch: bool, map: &mut Map, aim_angle: f32) -> Vec<crate::audio::events::AudioEvent> {
+    // Between the previous and latest update; alpha 1 is the latest position
+    pub fn interpolated_position(&self, alpha: f32) -> (f32, f32) {
+        (
+            self.prev_x + (self.x - self.prev_x) * alpha,
+            self.prev_y + (self.y - self.prev_y) * alpha,
+        )
+    }
+
+    pub fn update(&mut self, dt: f32, move_axis: f32, jump: bool, crouch: bool, map: &mut Map, aim_angle: f32) -> Vec<crate::audio::events::AudioEvent> {
         let mut audio_events = Vec::new();
         let was_moving = self.is_moving;
         let was_state = self.state;
@@ -315,12 +329,6 @@ impl Player {
             self.model_yaw += 2.0 * std::f32::consts::PI;
         }
 
-        let move_axis = match (move_left, move_right) {
-            (true, false) => -1.0,
-            (false, true) => 1.0,
-            _ => 0.0,
-        };
-
         let state = PmoveState {
             x: self.x,
             y: self.y,
@@ -374,6 +382,9 @@ impl Player {
                     i, self.x, self.y, teleporter.dest_x, teleporter.dest_y);
                 self.x = teleporter.dest_x;
                 self.y = teleporter.dest_y;
+                // Don't interpolate across the jump
+                self.prev_x = self.x;
+                self.prev_y = self.y;
                 break;
             }
         }
@@ -429,10 +440,39 @@ impl Player {
             self.animation_time = 0.0;
         }
         self.animation_time += dt;
+        match self.legs_animation() {
+            Animation::LegsRun => {
+                let weight = (self.vx.abs() - WALK_SPEED) / (MAX_SPEED_GROUND - WALK_SPEED);
+                self.anim.set_blend(Animation::LegsWalk, Animation::LegsRun, weight);
+            }
+            legs => self.anim.set_legs(legs),
+        }
         
         audio_events
     }
 
+    pub fn legs_animation(&self) -> Animation {
+        match self.state {
+            PlayerState::Air => Animation::LegsJump,
+            PlayerState::Crouching => {
+                if self.is_moving {
+                    Animation::LegsWalkcr
+                } else {
+                    Animation::LegsIdlecr
+                }
+            }
+            PlayerState::Ground => {
+                if self.is_moving_backward {
+                    Animation::LegsBack
+                } else if self.is_moving {
+                    Animation::LegsRun
+                } else {
+                    Animation::LegsIdle
+                }
+            }
+        }
+    }
+
     pub fn damage(&mut self, amount: i32) -> bool {
         if self.dead {
             return false;
@@ -468,6 +508,13 @@ impl Player {
         !self.dead && self.refire <= 0.0 && self.weapon_switch_time <= 0.0
     }
 
+    pub fn owned_weapons(&self) -> Vec<Weapon> {
+        (0..self.has_weapon.len())
+            .filter(|&i| self.has_weapon[i])
+            .filter_map(Weapon::from_index)
+            .collect()
+    }
+
     pub fn switch_weapon(&mut self, weapon: Weapon) -> bool {
         if self.dead || self.weapon_switch_time > 0.0 {
             return false;
@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use super::player::{Player, PlayerState, PowerUps};
+use super::weapon::Weapon;
+use super::world::World;
+
+pub const SNAPSHOT_VERSION: u16 = 1;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PlayerSnapshot {
+    pub id: u32,
+    pub x: f32,
+    pub y: f32,
+    pub vx: f32,
+    pub vy: f32,
+    pub aim_angle: f32,
+    pub state: PlayerState,
+    pub facing_right: bool,
+    pub is_crouching: bool,
+    pub was_in_air: bool,
+    pub health: i32,
+    pub armor: i32,
+    pub frags: i32,
+    pub deaths: i32,
+    pub dead: bool,
+    pub respawn_timer: f32,
+    pub weapon: Weapon,
+    pub has_weapon: [bool; 9],
+    pub ammo: [u8; 9],
+    pub refire: f32,
+    pub weapon_switch_time: f32,
+    pub powerups: PowerUps,
+}
+
+impl PlayerSnapshot {
+    pub fn from_player(player: &Player) -> Self {
+        Self {
+            id: player.id,
+            x: player.x,
+            y: player.y,
+            vx: player.vx,
+            vy: player.vy,
+            aim_angle: player.aim_angle,
+            state: player.state,
+            facing_right: player.facing_right,
+            is_crouching: player.is_crouching,
+            was_in_air: player.was_in_air,
+            health: player.health,
+            armor: player.armor,
+            frags: player.frags,
+            deaths: player.deaths,
+            dead: player.dead,
+            respawn_timer: player.respawn_timer,
+            weapon: player.weapon,
+            has_weapon: player.has_weapon,
+            ammo: player.ammo,
+            refire: player.refire,
+            weapon_switch_time: player.weapon_switch_time,
+            powerups: player.powerups.clone(),
+        }
+    }
+
+    pub fn apply_to(&self, player: &mut Player) {
+        player.prev_x = player.x;
+        player.prev_y = player.y;
+        player.x = self.x;
+        player.y = self.y;
+        player.vx = self.vx;
+        player.vy = self.vy;
+        player.aim_angle = self.aim_angle;
+        player.state = self.state;
+        player.facing_right = self.facing_right;
+        player.is_crouching = self.is_crouching;
+        player.was_in_air = self.was_in_air;
+        player.health = self.health;
+        player.armor = self.armor;
+        player.frags = self.frags;
+        player.deaths = self.deaths;
+        player.dead = self.dead;
+        player.respawn_timer = self.respawn_timer;
+        player.weapon = self.weapon;
+        player.has_weapon = self.has_weapon;
+        player.ammo = self.ammo;
+        player.refire = self.refire;
+        player.weapon_switch_time = self.weapon_switch_time;
+        player.powerups = self.powerups.clone();
+    }
+
+    pub fn to_player(&self) -> Player {
+        let mut player = Player::new(self.id);
+        self.apply_to(&mut player);
+        player.prev_x = self.x;
+        player.prev_y = self.y;
+        player
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ItemSnapshot {
+    pub active: bool,
+    pub respawn_time: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WorldSnapshot {
+    pub version: u16,
+    pub tick: u32,
+    pub time: f32,
+    pub players: Vec<PlayerSnapshot>,
+    pub items: Vec<ItemSnapshot>,
+}
+
+impl WorldSnapshot {
+    pub fn capture(world: &World, tick: u32) -> Self {
+        Self {
+            version: SNAPSHOT_VERSION,
+            tick,
+            time: world.time,
+            players: world.players.iter().map(PlayerSnapshot::from_player).collect(),
+            items: world.map.items.iter()
+                .map(|item| ItemSnapshot {
+                    active: item.active,
+                    respawn_time: item.respawn_time,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn apply(&self, world: &mut World) {
+        world.time = self.time;
+
+        for snapshot in &self.players {
+            match world.players.iter_mut().find(|p| p.id == snapshot.id) {
+                Some(player) => snapshot.apply_to(player),
+                None => world.players.push(snapshot.to_player()),
+            }
+        }
+        world.players.retain(|p| self.players.iter().any(|s| s.id == p.id));
+        world.players.sort_by_key(|p| p.id);
+
+        for (item, snapshot) in world.map.items.iter_mut().zip(&self.items) {
+            item.active = snapshot.active;
+            item.respawn_time = snapshot.respawn_time;
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        bincode::serialize(self).map_err(|e| format!("Failed to serialize snapshot: {}", e))
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, String> {
+        // The version leads the encoding, so check it before trusting the rest of the layout
+        let version: u16 = bincode::deserialize(data)
+            .map_err(|e| format!("Failed to parse snapshot: {}", e))?;
+        if version != SNAPSHOT_VERSION {
+            return Err(format!(
+                "Unsupported snapshot version {} (expected {})",
+                version, SNAPSHOT_VERSION
+            ));
+        }
+
+        bincode::deserialize(data).map_err(|e| format!("Failed to parse snapshot: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_preserves_player_state() {
+        let mut world = World::new();
+        let mut player = Player::new(3);
+        player.x = 120.5;
+        player.y = -48.25;
+        player.vx = 7.5;
+        player.vy = -2.0;
+        player.health = 42;
+        player.weapon = Weapon::RocketLauncher;
+        world.players.push(player);
+
+        let snapshot = WorldSnapshot::capture(&world, 17);
+        let bytes = snapshot.to_bytes().unwrap();
+        let decoded = WorldSnapshot::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, snapshot);
+
+        let mut restored = World::new();
+        decoded.apply(&mut restored);
+        let p = &restored.players[0];
+        assert_eq!((p.x, p.y, p.vx, p.vy), (120.5, -48.25, 7.5, -2.0));
+        assert_eq!(p.health, 42);
+        assert_eq!(p.weapon, Weapon::RocketLauncher);
+    }
+
+    #[test]
+    fn other_versions_are_rejected() {
+        let mut snapshot = WorldSnapshot::capture(&World::new(), 0);
+        snapshot.version = SNAPSHOT_VERSION + 1;
+        let bytes = snapshot.to_bytes().unwrap();
+        assert!(WorldSnapshot::from_bytes(&bytes).unwrap_err().contains("version"));
+    }
+}
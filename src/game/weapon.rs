@@ -1,6 +1,7 @@
 use super::constants::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Weapon {
     Gauntlet = 0,
     MachineGun = 1,
@@ -74,6 +75,28 @@ impl Weapon {
         }
     }
 
+    pub fn model_dir(&self) -> &'static str {
+        match self {
+            Weapon::Gauntlet => "gauntlet",
+            Weapon::MachineGun => "machinegun",
+            Weapon::Shotgun => "shotgun",
+            Weapon::GrenadeLauncher => "grenadel",
+            Weapon::RocketLauncher => "rocketl",
+            Weapon::Lightning => "lightning",
+            Weapon::Railgun => "railgun",
+            Weapon::Plasmagun => "plasma",
+            Weapon::BFG => "bfg",
+        }
+    }
+
+    pub fn model_path(&self) -> String {
+        self.part_model_path("")
+    }
+
+    pub fn part_model_path(&self, suffix: &str) -> String {
+        format!("q3-resources/models/weapons2/{0}/{0}{1}.md3", self.model_dir(), suffix)
+    }
+
     pub fn from_index(index: usize) -> Option<Self> {
         match index {
             0 => Some(Weapon::Gauntlet),
@@ -54,6 +54,11 @@ impl Rocket {
     pub fn is_visible(&self, frustum: &Frustum) -> bool {
         frustum.contains_sphere(self.position, 0.014285714285714285)
     }
+
+    // Position between the last two fixed steps, `alpha` being GameLoop::alpha
+    pub fn interpolated_position(&self, alpha: f32) -> Vec3 {
+        self.previous_position.lerp(self.position, alpha)
+    }
 }
 
 pub struct Grenade {
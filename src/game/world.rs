@@ -1,5 +1,5 @@
 use crate::engine::math::Frustum;
-use crate::audio::events::{AudioEvent, AudioEventQueue};
+use crate::audio::events::AudioEvent;
 use super::player::Player;
 use super::weapons::{Rocket, Grenade, Plasma, BFGBall};
 use super::particle::{SmokeParticle, FlameParticle};
@@ -11,7 +11,11 @@ use super::weapon::Weapon;
 use super::physics::collision;
 use super::combat;
 use super::constants::*;
+use super::events::{GameEvent, GameEventQueue, ParticleEffect};
+use super::input::PlayerInput;
 use glam::Vec3;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
 pub struct World {
     pub players: Vec<Player>,
@@ -26,12 +30,20 @@ pub struct World {
     pub map: Map,
     pub lighting: LightingParams,
     pub time: f32,
-    pub audio_events: AudioEventQueue,
+    pub events: GameEventQueue,
     pub awards: AwardTracker,
+    pub seed: u64,
+    rng: StdRng,
+    time_scale: f32,
+    next_player_id: u32,
 }
 
 impl World {
     pub fn new() -> Self {
+        Self::with_seed(rand::random())
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
         Self {
             players: Vec::new(),
             rockets: Vec::new(),
@@ -45,13 +57,37 @@ impl World {
             map: Map::new(),
             lighting: LightingParams::new(),
             time: 0.0,
-            audio_events: AudioEventQueue::new(),
+            events: GameEventQueue::new(),
             awards: AwardTracker::new(),
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            time_scale: 1.0,
+            next_player_id: 0,
         }
     }
 
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    // Slows down or speeds up the simulation; 0.5 runs at half speed, 2.0 at double speed
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale.max(0.0);
+    }
+
+    pub fn scaled_dt(&self, dt: f32) -> f32 {
+        dt * self.time_scale
+    }
+
+    // Ids are never reused, so a removed player's id can't end up on someone who joins later
     pub fn add_player(&mut self) -> u32 {
-        let id = self.players.len() as u32;
+        let id = self.next_player_id;
+        self.next_player_id += 1;
         let mut player = Player::new(id);
         
         let spawn_idx = (id as usize) % self.map.spawn_points.len().max(1);
@@ -67,6 +103,43 @@ impl World {
         id
     }
 
+    pub fn remove_player(&mut self, id: u32) {
+        self.players.retain(|p| p.id != id);
+    }
+
+    // Look players up by id; their position in `players` shifts as others leave
+    pub fn player(&self, id: u32) -> Option<&Player> {
+        self.players.iter().find(|p| p.id == id)
+    }
+
+    pub fn player_mut(&mut self, id: u32) -> Option<&mut Player> {
+        self.players.iter_mut().find(|p| p.id == id)
+    }
+
+    pub fn step(&mut self, inputs: &[PlayerInput], dt: f32) -> Vec<GameEvent> {
+        let frustum = self.map.simulation_frustum();
+        let dt = self.scaled_dt(dt);
+
+        for input in inputs {
+            let World { players, map, events, .. } = self;
+            if let Some(player) = players.iter_mut().find(|p| p.id == input.player_id) {
+                if let Some(weapon) = input.weapon_switch {
+                    player.switch_weapon(weapon);
+                }
+                for event in player.update(dt, input.horizontal_axis(), input.jump, input.crouch, map, input.aim_angle) {
+                    events.push(event);
+                }
+            }
+
+            if input.fire {
+                self.try_fire(input.player_id, input.aim_angle, &frustum);
+            }
+        }
+
+        self.update(dt, &frustum);
+        self.events.drain()
+    }
+
     pub fn update(&mut self, dt: f32, frustum: &Frustum) {
         self.time += dt;
 
@@ -211,18 +284,18 @@ impl World {
             if collision.collided {
                 rocket.active = false;
                 explosions.push((rocket.position, ROCKET_SPLASH_RADIUS, rocket.owner_id));
-                self.audio_events.push(AudioEvent::Explosion { x: rocket.position.x });
+                self.events.push(AudioEvent::Explosion { x: rocket.position.x });
             } else if collision::check_projectile_ground_collision(rocket.position, self.map.ground_y) {
                 rocket.active = false;
                 explosions.push((rocket.position, ROCKET_SPLASH_RADIUS, rocket.owner_id));
-                self.audio_events.push(AudioEvent::Explosion { x: rocket.position.x });
+                self.events.push(AudioEvent::Explosion { x: rocket.position.x });
             } else {
                 let tile_x = self.map.world_to_tile_x(rocket.position.x);
                 let tile_y = self.map.world_to_tile_y(rocket.position.y);
                 if self.map.is_solid(tile_x, tile_y) {
                     rocket.active = false;
                     explosions.push((rocket.position, ROCKET_SPLASH_RADIUS, rocket.owner_id));
-                    self.audio_events.push(AudioEvent::Explosion { x: rocket.position.x });
+                    self.events.push(AudioEvent::Explosion { x: rocket.position.x });
                 }
             }
         }
@@ -242,13 +315,13 @@ impl World {
             if collision.collided {
                 grenade.active = false;
                 explosions.push((grenade.position, GRENADE_SPLASH_RADIUS, grenade.owner_id));
-                self.audio_events.push(AudioEvent::Explosion { x: grenade.position.x });
+                self.events.push(AudioEvent::Explosion { x: grenade.position.x });
             }
 
             if grenade.lifetime >= grenade.fuse_time {
                 grenade.active = false;
                 explosions.push((grenade.position, GRENADE_SPLASH_RADIUS, grenade.owner_id));
-                self.audio_events.push(AudioEvent::Explosion { x: grenade.position.x });
+                self.events.push(AudioEvent::Explosion { x: grenade.position.x });
             }
         }
 
@@ -274,14 +347,21 @@ impl World {
 
                     if let Some(player) = self.players.iter_mut().find(|p| p.id == player_id) {
                         let result = combat::apply_damage(player, DAMAGE_PLASMA, attacker_has_quad, None);
+                        self.events.push(GameEvent::PlayerDamaged {
+                            player_id: player.id,
+                            attacker_id: plasma.owner_id,
+                            damage: result.damage,
+                            health: result.final_health,
+                            killed: result.killed,
+                        });
                         
                         if result.killed {
-                            self.audio_events.push(AudioEvent::PlayerDeath {
+                            self.events.push(AudioEvent::PlayerDeath {
                                 x: player.x,
                                 model: player.model.clone(),
                             });
                         } else {
-                            self.audio_events.push(AudioEvent::PlayerPain {
+                            self.events.push(AudioEvent::PlayerPain {
                                 health: result.final_health,
                                 x: player.x,
                                 model: player.model.clone(),
@@ -314,19 +394,36 @@ impl World {
             if collision.collided {
                 bfg.active = false;
                 explosions.push((bfg.position, BFG_SPLASH_RADIUS, bfg.owner_id));
-                self.audio_events.push(AudioEvent::Explosion { x: bfg.position.x });
+                self.events.push(AudioEvent::Explosion { x: bfg.position.x });
             } else {
                 let tile_x = self.map.world_to_tile_x(bfg.position.x);
                 let tile_y = self.map.world_to_tile_y(bfg.position.y);
                 if self.map.is_solid(tile_x, tile_y) {
                     bfg.active = false;
                     explosions.push((bfg.position, BFG_SPLASH_RADIUS, bfg.owner_id));
-                    self.audio_events.push(AudioEvent::Explosion { x: bfg.position.x });
+                    self.events.push(AudioEvent::Explosion { x: bfg.position.x });
                 }
             }
         }
 
         for (explosion_pos, radius, owner_id) in explosions {
+            self.events.push(GameEvent::ParticleBurst {
+                effect: ParticleEffect::Smoke,
+                position: explosion_pos,
+                count: 12,
+                speed: 2.0,
+            });
+            self.events.push(GameEvent::ParticleBurst {
+                effect: ParticleEffect::Sparks,
+                position: explosion_pos,
+                count: 16,
+                speed: 8.0,
+            });
+            self.events.push(GameEvent::LightFlash {
+                position: explosion_pos,
+                radius,
+            });
+
             let damages = collision::check_all_explosion_damage(
                 explosion_pos,
                 radius,
@@ -342,14 +439,21 @@ impl World {
             for (player_id, damage, knockback) in damages {
                 if let Some(player) = self.players.iter_mut().find(|p| p.id == player_id) {
                     let result = combat::apply_damage(player, damage, attacker_has_quad, Some(knockback));
+                    self.events.push(GameEvent::PlayerDamaged {
+                        player_id: player.id,
+                        attacker_id: owner_id,
+                        damage: result.damage,
+                        health: result.final_health,
+                        killed: result.killed,
+                    });
                     
                     if result.killed {
-                        self.audio_events.push(AudioEvent::PlayerDeath {
+                        self.events.push(AudioEvent::PlayerDeath {
                             x: player.x,
                             model: player.model.clone(),
                         });
                     } else {
-                        self.audio_events.push(AudioEvent::PlayerPain {
+                        self.events.push(AudioEvent::PlayerPain {
                             health: result.final_health,
                             x: player.x,
                             model: player.model.clone(),
@@ -399,108 +503,108 @@ impl World {
                             if player.health < 100 {
                                 player.health = (player.health + 25).min(100);
                                 picked_up = true;
-                                self.audio_events.push(AudioEvent::ItemPickup { x: item.x });
+                                self.events.push(AudioEvent::ItemPickup { x: item.x });
                             }
                         }
                         ItemType::Health50 => {
                             if player.health < 100 {
                                 player.health = (player.health + 50).min(100);
                                 picked_up = true;
-                                self.audio_events.push(AudioEvent::ItemPickup { x: item.x });
+                                self.events.push(AudioEvent::ItemPickup { x: item.x });
                             }
                         }
                         ItemType::Health100 => {
                             if player.health < 200 {
                                 player.health = (player.health + 100).min(200);
                                 picked_up = true;
-                                self.audio_events.push(AudioEvent::ItemPickup { x: item.x });
+                                self.events.push(AudioEvent::ItemPickup { x: item.x });
                             }
                         }
                         ItemType::Armor50 => {
                             if player.armor < 100 {
                                 player.armor = (player.armor + 50).min(100);
                                 picked_up = true;
-                                self.audio_events.push(AudioEvent::ArmorPickup { x: item.x });
+                                self.events.push(AudioEvent::ArmorPickup { x: item.x });
                             }
                         }
                         ItemType::Armor100 => {
                             if player.armor < 200 {
                                 player.armor = (player.armor + 100).min(200);
                                 picked_up = true;
-                                self.audio_events.push(AudioEvent::ArmorPickup { x: item.x });
+                                self.events.push(AudioEvent::ArmorPickup { x: item.x });
                             }
                         }
                         ItemType::RocketLauncher => {
                             player.has_weapon[4] = true;
                             player.ammo[4] = (player.ammo[4] + 10).min(100);
                             picked_up = true;
-                            self.audio_events.push(AudioEvent::WeaponPickup { x: item.x });
+                            self.events.push(AudioEvent::WeaponPickup { x: item.x });
                         }
                         ItemType::LightningGun => {
                             player.has_weapon[5] = true;
                             player.ammo[5] = (player.ammo[5].saturating_add(100)).min(200);
                             picked_up = true;
-                            self.audio_events.push(AudioEvent::WeaponPickup { x: item.x });
+                            self.events.push(AudioEvent::WeaponPickup { x: item.x });
                         }
                         ItemType::Railgun => {
                             player.has_weapon[6] = true;
                             player.ammo[6] = (player.ammo[6] + 10).min(100);
                             picked_up = true;
-                            self.audio_events.push(AudioEvent::WeaponPickup { x: item.x });
+                            self.events.push(AudioEvent::WeaponPickup { x: item.x });
                         }
                         ItemType::Plasmagun => {
                             player.has_weapon[7] = true;
                             player.ammo[7] = (player.ammo[7] + 50).min(200);
                             picked_up = true;
-                            self.audio_events.push(AudioEvent::WeaponPickup { x: item.x });
+                            self.events.push(AudioEvent::WeaponPickup { x: item.x });
                         }
                         ItemType::Shotgun => {
                             player.has_weapon[2] = true;
                             player.ammo[2] = (player.ammo[2] + 10).min(100);
                             picked_up = true;
-                            self.audio_events.push(AudioEvent::WeaponPickup { x: item.x });
+                            self.events.push(AudioEvent::WeaponPickup { x: item.x });
                         }
                         ItemType::GrenadeLauncher => {
                             player.has_weapon[3] = true;
                             player.ammo[3] = (player.ammo[3] + 10).min(100);
                             picked_up = true;
-                            self.audio_events.push(AudioEvent::WeaponPickup { x: item.x });
+                            self.events.push(AudioEvent::WeaponPickup { x: item.x });
                         }
                         ItemType::BFG => {
                             player.has_weapon[8] = true;
                             player.ammo[8] = (player.ammo[8] + 15).min(200);
                             picked_up = true;
-                            self.audio_events.push(AudioEvent::WeaponPickup { x: item.x });
+                            self.events.push(AudioEvent::WeaponPickup { x: item.x });
                         }
                         ItemType::Quad => {
                             player.powerups.quad = POWERUP_DURATION_QUAD;
                             picked_up = true;
-                            self.audio_events.push(AudioEvent::PowerupPickup { x: item.x });
+                            self.events.push(AudioEvent::PowerupPickup { x: item.x });
                         }
                         ItemType::Regen => {
                             player.powerups.regen = POWERUP_DURATION_REGEN;
                             picked_up = true;
-                            self.audio_events.push(AudioEvent::PowerupPickup { x: item.x });
+                            self.events.push(AudioEvent::PowerupPickup { x: item.x });
                         }
                         ItemType::Battle => {
                             player.powerups.battle = POWERUP_DURATION_BATTLE;
                             picked_up = true;
-                            self.audio_events.push(AudioEvent::PowerupPickup { x: item.x });
+                            self.events.push(AudioEvent::PowerupPickup { x: item.x });
                         }
                         ItemType::Flight => {
                             player.powerups.flight = POWERUP_DURATION_FLIGHT;
                             picked_up = true;
-                            self.audio_events.push(AudioEvent::PowerupPickup { x: item.x });
+                            self.events.push(AudioEvent::PowerupPickup { x: item.x });
                         }
                         ItemType::Haste => {
                             player.powerups.haste = POWERUP_DURATION_HASTE;
                             picked_up = true;
-                            self.audio_events.push(AudioEvent::PowerupPickup { x: item.x });
+                            self.events.push(AudioEvent::PowerupPickup { x: item.x });
                         }
                         ItemType::Invis => {
                             player.powerups.invis = POWERUP_DURATION_INVIS;
                             picked_up = true;
-                            self.audio_events.push(AudioEvent::PowerupPickup { x: item.x });
+                            self.events.push(AudioEvent::PowerupPickup { x: item.x });
                         }
                     }
 
@@ -541,6 +645,9 @@ impl World {
         let player_y = player.y;
         let player_vx = player.vx;
         let player_vy = player.vy;
+        let has_quad = player.powerups.quad > 0;
+
+        self.events.push(AudioEvent::WeaponFire { player_id, weapon, x: player_x, has_quad });
 
         if weapon.is_projectile() {
             let direction = Vec3::new(aim_angle.cos(), aim_angle.sin(), 0.0);
@@ -577,7 +684,7 @@ impl World {
 
             match weapon {
                 Weapon::Shotgun => {
-                    let hits = shotgun_trace(origin, direction, player_id, &self.players);
+                    let hits = shotgun_trace(origin, direction, player_id, &self.players, &mut self.rng);
                     for hit in hits {
                         if hit.hit {
                             if let Some(victim_id) = hit.hit_player_id {
@@ -588,14 +695,21 @@ impl World {
 
                                 if let Some(victim) = self.players.iter_mut().find(|p| p.id == victim_id) {
                                     let result = combat::apply_damage(victim, hit.damage, attacker_has_quad, None);
+                                    self.events.push(GameEvent::PlayerDamaged {
+                                        player_id: victim.id,
+                                        attacker_id: player_id,
+                                        damage: result.damage,
+                                        health: result.final_health,
+                                        killed: result.killed,
+                                    });
                                     
                                     if result.killed {
-                                        self.audio_events.push(AudioEvent::PlayerDeath {
+                                        self.events.push(AudioEvent::PlayerDeath {
                                             x: victim.x,
                                             model: victim.model.clone(),
                                         });
                                     } else {
-                                        self.audio_events.push(AudioEvent::PlayerPain {
+                                        self.events.push(AudioEvent::PlayerPain {
                                             health: result.final_health,
                                             x: victim.x,
                                             model: victim.model.clone(),
@@ -608,7 +722,7 @@ impl World {
                 }
                 Weapon::MachineGun | Weapon::Lightning => {
                     let max_distance = 57.142857142857146;
-                    let hit = hitscan_trace(origin, direction, max_distance, player_id, &self.players, weapon);
+                    let hit = hitscan_trace(origin, direction, max_distance, player_id, &self.players, weapon, &mut self.rng);
                     
                     if hit.hit {
                         if let Some(victim_id) = hit.hit_player_id {
@@ -619,14 +733,21 @@ impl World {
 
                             if let Some(victim) = self.players.iter_mut().find(|p| p.id == victim_id) {
                                 let result = combat::apply_damage(victim, hit.damage, attacker_has_quad, None);
+                                self.events.push(GameEvent::PlayerDamaged {
+                                    player_id: victim.id,
+                                    attacker_id: player_id,
+                                    damage: result.damage,
+                                    health: result.final_health,
+                                    killed: result.killed,
+                                });
                                 
                                 if result.killed {
-                                    self.audio_events.push(AudioEvent::PlayerDeath {
+                                    self.events.push(AudioEvent::PlayerDeath {
                                         x: victim.x,
                                         model: victim.model.clone(),
                                     });
                                 } else {
-                                    self.audio_events.push(AudioEvent::PlayerPain {
+                                    self.events.push(AudioEvent::PlayerPain {
                                         health: result.final_health,
                                         x: victim.x,
                                         model: victim.model.clone(),
@@ -643,7 +764,7 @@ impl World {
                 }
                 Weapon::Railgun => {
                     let max_distance = 285.71428571428567;
-                    let hit = hitscan_trace(origin, direction, max_distance, player_id, &self.players, weapon);
+                    let hit = hitscan_trace(origin, direction, max_distance, player_id, &self.players, weapon, &mut self.rng);
                     
                     if hit.hit {
                         if let Some(victim_id) = hit.hit_player_id {
@@ -654,14 +775,21 @@ impl World {
 
                             if let Some(victim) = self.players.iter_mut().find(|p| p.id == victim_id) {
                                 let result = combat::apply_damage(victim, hit.damage, attacker_has_quad, None);
+                                self.events.push(GameEvent::PlayerDamaged {
+                                    player_id: victim.id,
+                                    attacker_id: player_id,
+                                    damage: result.damage,
+                                    health: result.final_health,
+                                    killed: result.killed,
+                                });
                                 
                                 if result.killed {
-                                    self.audio_events.push(AudioEvent::PlayerDeath {
+                                    self.events.push(AudioEvent::PlayerDeath {
                                         x: victim.x,
                                         model: victim.model.clone(),
                                     });
                                 } else {
-                                    self.audio_events.push(AudioEvent::PlayerPain {
+                                    self.events.push(AudioEvent::PlayerPain {
                                         health: result.final_health,
                                         x: victim.x,
                                         model: victim.model.clone(),
@@ -676,7 +804,7 @@ impl World {
                 }
                 Weapon::Gauntlet => {
                     let max_distance = 1.1428571428571428;
-                    let hit = hitscan_trace(origin, direction, max_distance, player_id, &self.players, weapon);
+                    let hit = hitscan_trace(origin, direction, max_distance, player_id, &self.players, weapon, &mut self.rng);
                     
                     if hit.hit {
                         if let Some(victim_id) = hit.hit_player_id {
@@ -687,14 +815,21 @@ impl World {
 
                             if let Some(victim) = self.players.iter_mut().find(|p| p.id == victim_id) {
                                 let result = combat::apply_damage(victim, hit.damage, attacker_has_quad, None);
+                                self.events.push(GameEvent::PlayerDamaged {
+                                    player_id: victim.id,
+                                    attacker_id: player_id,
+                                    damage: result.damage,
+                                    health: result.final_health,
+                                    killed: result.killed,
+                                });
                                 
                                 if result.killed {
-                                    self.audio_events.push(AudioEvent::PlayerDeath {
+                                    self.events.push(AudioEvent::PlayerDeath {
                                         x: victim.x,
                                         model: victim.model.clone(),
                                     });
                                 } else {
-                                    self.audio_events.push(AudioEvent::PlayerPain {
+                                    self.events.push(AudioEvent::PlayerPain {
                                         health: result.final_health,
                                         x: victim.x,
                                         model: victim.model.clone(),
@@ -711,3 +846,74 @@ impl World {
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(seed: u64) -> World {
+        let mut world = World::with_seed(seed);
+        let id = world.add_player();
+        for tick in 0..180 {
+            let input = PlayerInput {
+                player_id: id,
+                move_right: tick < 90,
+                move_left: tick >= 90,
+                jump: tick % 40 == 0,
+                fire: tick % 20 == 0,
+                aim_angle: 0.3,
+                ..Default::default()
+            };
+            world.step(&[input], 1.0 / 60.0);
+        }
+        world
+    }
+
+    #[test]
+    fn identical_inputs_and_seed_give_identical_worlds() {
+        let a = run(7);
+        let b = run(7);
+        let player_a = &a.players[0];
+        let player_b = &b.players[0];
+        assert_eq!((player_a.x, player_a.y), (player_b.x, player_b.y));
+        assert_eq!((player_a.vx, player_a.vy), (player_b.vx, player_b.vy));
+        assert_eq!(a.rockets.len(), b.rockets.len());
+        for (ra, rb) in a.rockets.iter().zip(&b.rockets) {
+            assert_eq!(ra.position, rb.position);
+        }
+        assert_eq!(a.time, b.time);
+    }
+
+    #[test]
+    fn rocket_impact_emits_audio_particle_and_light_events() {
+        let mut world = World::with_seed(1);
+        let shooter = world.add_player();
+        world.add_player();
+        let (x, y) = (world.players[1].x, world.players[1].y);
+        let frustum = world.map.simulation_frustum();
+        world.rockets.push(Rocket::new(Vec3::new(x, y, 0.0), Vec3::X, ROCKET_SPEED, &frustum, shooter));
+
+        let events = world.step(&[], 1.0 / 60.0);
+        assert!(events.iter().any(|e| matches!(e, GameEvent::Audio(AudioEvent::Explosion { .. }))));
+        assert!(events.iter().any(|e| matches!(e, GameEvent::ParticleBurst { .. })));
+        assert!(events.iter().any(|e| matches!(e, GameEvent::LightFlash { .. })));
+    }
+
+    #[test]
+    fn half_time_scale_halves_projectile_travel() {
+        let travel = |time_scale: f32| {
+            let mut world = World::with_seed(1);
+            world.set_time_scale(time_scale);
+            let frustum = world.map.simulation_frustum();
+            let start = Vec3::new(world.map.origin_x() + 5.0, 5.0, 0.0);
+            world.rockets.push(Rocket::new(start, Vec3::Y, ROCKET_SPEED, &frustum, 0));
+            for _ in 0..60 {
+                world.step(&[], 1.0 / 60.0);
+            }
+            assert_eq!(world.rockets.len(), 1);
+            world.rockets[0].position.distance(start)
+        };
+        let full = travel(1.0);
+        assert!((travel(0.5) - full * 0.5).abs() < 1e-3);
+    }
+}
@@ -1,9 +1,14 @@
 use std::time::{Duration, Instant};
 
+// Longer stalls are dropped rather than simulated, so a hitch can't snowball into more catch-up steps
+pub const MAX_FRAME_TIME: Duration = Duration::from_millis(100);
+
 pub struct GameLoop {
     last_update: Instant,
     accumulator: Duration,
     fixed_timestep: Duration,
+    frame_time: Duration,
+    paused: bool,
 }
 
 impl GameLoop {
@@ -12,6 +17,8 @@ impl GameLoop {
             last_update: Instant::now(),
             accumulator: Duration::ZERO,
             fixed_timestep: Duration::from_secs_f64(1.0 / fps as f64),
+            frame_time: Duration::ZERO,
+            paused: false,
         }
     }
 
@@ -19,25 +26,75 @@ impl GameLoop {
     where
         F: FnMut(f32),
     {
+        let dt = self.fixed_timestep.as_secs_f32();
+        for _ in 0..self.advance() {
+            update_fn(dt);
+        }
+        dt
+    }
+
+    // Adds the wall time since the last call, clamped to MAX_FRAME_TIME, and returns how many
+    // fixed steps are now due. Run that many delta_time() updates before rendering
+    pub fn advance(&mut self) -> u32 {
         let now = Instant::now();
-        let frame_time = now.duration_since(self.last_update);
+        self.frame_time = now.duration_since(self.last_update).min(MAX_FRAME_TIME);
         self.last_update = now;
+        if self.paused {
+            return 0;
+        }
 
-        self.accumulator += frame_time;
-
-        let dt = self.fixed_timestep.as_secs_f32();
-        
+        self.accumulator += self.frame_time;
+        let mut steps = 0;
         while self.accumulator >= self.fixed_timestep {
-            update_fn(dt);
             self.accumulator -= self.fixed_timestep;
+            steps += 1;
         }
+        steps
+    }
 
-        dt
+    // Forgets time spent outside the loop, e.g. while loading
+    pub fn reset(&mut self) {
+        self.last_update = Instant::now();
+        self.accumulator = Duration::ZERO;
+    }
+
+    // While paused no fixed steps come due and sim_frame_time() is zero; frame_time() keeps
+    // running so the camera and menus still move
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn toggle_pause(&mut self) -> bool {
+        self.paused = !self.paused;
+        self.paused
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
     }
 
     pub fn delta_time(&self) -> f32 {
         self.fixed_timestep.as_secs_f32()
     }
+
+    // Clamped wall time covered by the last advance, for things that don't need fixed steps
+    pub fn frame_time(&self) -> f32 {
+        self.frame_time.as_secs_f32()
+    }
+
+    // Frame time for render-side state that should freeze along with the simulation
+    pub fn sim_frame_time(&self) -> f32 {
+        if self.paused { 0.0 } else { self.frame_time() }
+    }
+
+    // How far between the last two fixed steps the current frame falls, for interpolating state.
+    // Frozen state has nothing to interpolate toward
+    pub fn alpha(&self) -> f32 {
+        if self.paused {
+            return 1.0;
+        }
+        self.accumulator.as_secs_f32() / self.fixed_timestep.as_secs_f32()
+    }
 }
 
 impl Default for GameLoop {
@@ -46,5 +103,61 @@ impl Default for GameLoop {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::engine::anim::{AnimClip, AnimConfig, Sex, NUM_ANIMATIONS};
+    use crate::engine::md3::FrameBlend;
+    use crate::game::input::PlayerInput;
+    use crate::game::world::World;
+
+    fn looping_config() -> AnimConfig {
+        AnimConfig {
+            sex: Sex::Male,
+            footsteps: "default".to_string(),
+            head_offset: [0.0; 3],
+            clips: vec![AnimClip { first_frame: 0, num_frames: 10, looping_frames: 10, fps: 20 }; NUM_ANIMATIONS],
+            named: HashMap::new(),
+        }
+    }
+
+    // One rendered frame the way the game drives it: fixed world steps, then animation by frame time
+    fn frame(game_loop: &mut GameLoop, world: &mut World, input: &PlayerInput, config: &AnimConfig) -> FrameBlend {
+        std::thread::sleep(Duration::from_millis(5));
+        game_loop.tick(|dt| {
+            world.step(std::slice::from_ref(input), dt);
+        });
+        let render_dt = world.scaled_dt(game_loop.sim_frame_time());
+        world.players[0].anim.advance(render_dt, config).1
+    }
+
+    #[test]
+    fn paused_loop_holds_physics_and_animation() {
+        let config = looping_config();
+        let mut game_loop = GameLoop::new(1000);
+        let mut world = World::with_seed(1);
+        let player_id = world.add_player();
+        let input = PlayerInput { player_id, move_right: true, ..Default::default() };
 
+        frame(&mut game_loop, &mut world, &input, &config);
+        game_loop.set_paused(true);
+        let torso = frame(&mut game_loop, &mut world, &input, &config);
+        let player = &world.players[0];
+        let (position, velocity, legs_time, time) = ((player.x, player.y), (player.vx, player.vy), player.anim.legs_time(), world.time);
 
+        for _ in 0..3 {
+            assert_eq!(frame(&mut game_loop, &mut world, &input, &config), torso);
+            assert!(game_loop.frame_time() > 0.0);
+        }
+        let player = &world.players[0];
+        assert_eq!(((player.x, player.y), (player.vx, player.vy)), (position, velocity));
+        assert_eq!((player.anim.legs_time(), world.time), (legs_time, time));
+        assert_eq!(game_loop.alpha(), 1.0);
+
+        game_loop.toggle_pause();
+        frame(&mut game_loop, &mut world, &input, &config);
+        assert!(world.time > time);
+        assert!(world.players[0].anim.legs_time() > legs_time);
+    }
+}
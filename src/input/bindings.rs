@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+use winit::keyboard::KeyCode;
+
+pub const BINDINGS_PATH: &str = "bindings.cfg";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Crouch,
+    Fire,
+    Gesture,
+    SwitchModel,
+    WeaponSlot(u8),
+}
+
+impl Action {
+    pub const ALL: [Action; 16] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::Crouch,
+        Action::Fire,
+        Action::Gesture,
+        Action::SwitchModel,
+        Action::WeaponSlot(1),
+        Action::WeaponSlot(2),
+        Action::WeaponSlot(3),
+        Action::WeaponSlot(4),
+        Action::WeaponSlot(5),
+        Action::WeaponSlot(6),
+        Action::WeaponSlot(7),
+        Action::WeaponSlot(8),
+        Action::WeaponSlot(9),
+    ];
+
+    // Console command the action is bound to, Q3 style
+    pub fn name(&self) -> String {
+        match self {
+            Action::MoveLeft => "+moveleft".to_string(),
+            Action::MoveRight => "+moveright".to_string(),
+            Action::Jump => "+moveup".to_string(),
+            Action::Crouch => "+movedown".to_string(),
+            Action::Fire => "+attack".to_string(),
+            Action::Gesture => "+gesture".to_string(),
+            Action::SwitchModel => "switchmodel".to_string(),
+            Action::WeaponSlot(slot) => format!("weapon {}", slot),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+        Self::ALL.iter().copied().find(|action| action.name() == name)
+    }
+}
+
+// Key names as they appear in bindings.cfg
+const KEY_NAMES: &[(KeyCode, &str)] = &[
+    (KeyCode::KeyA, "a"), (KeyCode::KeyB, "b"), (KeyCode::KeyC, "c"), (KeyCode::KeyD, "d"),
+    (KeyCode::KeyE, "e"), (KeyCode::KeyF, "f"), (KeyCode::KeyG, "g"), (KeyCode::KeyH, "h"),
+    (KeyCode::KeyI, "i"), (KeyCode::KeyJ, "j"), (KeyCode::KeyK, "k"), (KeyCode::KeyL, "l"),
+    (KeyCode::KeyM, "m"), (KeyCode::KeyN, "n"), (KeyCode::KeyO, "o"), (KeyCode::KeyP, "p"),
+    (KeyCode::KeyQ, "q"), (KeyCode::KeyR, "r"), (KeyCode::KeyS, "s"), (KeyCode::KeyT, "t"),
+    (KeyCode::KeyU, "u"), (KeyCode::KeyV, "v"), (KeyCode::KeyW, "w"), (KeyCode::KeyX, "x"),
+    (KeyCode::KeyY, "y"), (KeyCode::KeyZ, "z"),
+    (KeyCode::Digit0, "0"), (KeyCode::Digit1, "1"), (KeyCode::Digit2, "2"), (KeyCode::Digit3, "3"),
+    (KeyCode::Digit4, "4"), (KeyCode::Digit5, "5"), (KeyCode::Digit6, "6"), (KeyCode::Digit7, "7"),
+    (KeyCode::Digit8, "8"), (KeyCode::Digit9, "9"),
+    (KeyCode::ArrowUp, "uparrow"), (KeyCode::ArrowDown, "downarrow"),
+    (KeyCode::ArrowLeft, "leftarrow"), (KeyCode::ArrowRight, "rightarrow"),
+    (KeyCode::Space, "space"), (KeyCode::Enter, "enter"), (KeyCode::Tab, "tab"),
+    (KeyCode::Backspace, "backspace"),
+    (KeyCode::ShiftLeft, "shift"), (KeyCode::ShiftRight, "rshift"),
+    (KeyCode::ControlLeft, "ctrl"), (KeyCode::ControlRight, "rctrl"),
+    (KeyCode::AltLeft, "alt"), (KeyCode::AltRight, "ralt"),
+    (KeyCode::Numpad0, "kp_0"), (KeyCode::Numpad1, "kp_1"), (KeyCode::Numpad2, "kp_2"),
+    (KeyCode::Numpad3, "kp_3"), (KeyCode::Numpad4, "kp_4"), (KeyCode::Numpad5, "kp_5"),
+    (KeyCode::Numpad6, "kp_6"), (KeyCode::Numpad7, "kp_7"), (KeyCode::Numpad8, "kp_8"),
+    (KeyCode::Numpad9, "kp_9"),
+];
+
+pub fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES.iter().find(|(k, _)| *k == key).map(|(_, name)| *name)
+}
+
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    let name = name.to_lowercase();
+    KEY_NAMES.iter().find(|(_, n)| *n == name).map(|(key, _)| *key)
+}
+
+#[derive(Clone, Debug)]
+pub struct KeyBindings {
+    keys: HashMap<KeyCode, Action>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let mut keys = HashMap::new();
+        keys.insert(KeyCode::KeyA, Action::MoveLeft);
+        keys.insert(KeyCode::KeyD, Action::MoveRight);
+        keys.insert(KeyCode::KeyW, Action::Jump);
+        keys.insert(KeyCode::KeyS, Action::Crouch);
+        keys.insert(KeyCode::Space, Action::Fire);
+        keys.insert(KeyCode::KeyG, Action::Gesture);
+        keys.insert(KeyCode::KeyM, Action::SwitchModel);
+        let digits = [
+            KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4, KeyCode::Digit5,
+            KeyCode::Digit6, KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+        ];
+        for (i, key) in digits.into_iter().enumerate() {
+            keys.insert(key, Action::WeaponSlot(i as u8 + 1));
+        }
+        Self { keys }
+    }
+}
+
+impl KeyBindings {
+    pub fn action(&self, key: KeyCode) -> Option<Action> {
+        self.keys.get(&key).copied()
+    }
+
+    pub fn keys_for(&self, action: Action) -> Vec<KeyCode> {
+        let mut keys: Vec<KeyCode> = self.keys.iter().filter(|(_, a)| **a == action).map(|(k, _)| *k).collect();
+        keys.sort_by_key(|key| key_name(*key));
+        keys
+    }
+
+    pub fn bind(&mut self, key: KeyCode, action: Action) {
+        self.keys.insert(key, action);
+    }
+
+    pub fn unbind(&mut self, key: KeyCode) {
+        self.keys.remove(&key);
+    }
+
+    pub fn unbind_all(&mut self) {
+        self.keys.clear();
+    }
+
+    // Replaces whatever the action was bound to; the key loses its previous action
+    pub fn rebind(&mut self, action: Action, key: KeyCode) {
+        self.keys.retain(|_, a| *a != action);
+        self.keys.insert(key, action);
+    }
+
+    // Handles `bind <key> <command>`, `unbind <key>` and `unbindall`
+    pub fn execute(&mut self, command: &str) -> Result<(), String> {
+        let parts: Vec<&str> = command.split_whitespace().collect();
+        let key = |name: &str| key_from_name(name).ok_or_else(|| format!("unknown key '{}'", name));
+        match parts.as_slice() {
+            ["bind", key_arg, action @ ..] if !action.is_empty() => {
+                let joined = action.join(" ").trim_matches('"').to_string();
+                let action = Action::from_name(&joined)
+                    .ok_or_else(|| format!("unknown command '{}'", joined))?;
+                self.bind(key(key_arg)?, action);
+            }
+            ["unbind", key_arg] => self.unbind(key(key_arg)?),
+            ["unbindall"] => self.unbind_all(),
+            _ => return Err(format!("expected bind, unbind or unbindall: '{}'", command)),
+        }
+        Ok(())
+    }
+
+    pub fn is_bind_command(command: &str) -> bool {
+        matches!(command.split_whitespace().next(), Some("bind" | "unbind" | "unbindall"))
+    }
+
+    // Console commands applied over the defaults; `//` starts a comment
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut bindings = Self::default();
+        for (line_no, line) in content.lines().enumerate() {
+            let line = line.split("//").next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            bindings.execute(line).map_err(|e| format!("line {}: {}", line_no + 1, e))?;
+        }
+        Ok(bindings)
+    }
+
+    // Starts with unbindall so loading the output reproduces these bindings exactly
+    pub fn to_config(&self) -> String {
+        let mut lines: Vec<String> = self
+            .keys
+            .iter()
+            .filter_map(|(key, action)| {
+                let name = key_name(*key)?;
+                Some(format!("bind {} \"{}\"", name, action.name()))
+            })
+            .collect();
+        lines.sort();
+        lines.insert(0, "unbindall".to_string());
+        lines.join("\n") + "\n"
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read key bindings {}: {}", path, e))?;
+        Self::parse(&content).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    // Missing file means defaults; a broken one is reported and also falls back
+    pub fn load_or_default(path: &str) -> Self {
+        if !std::path::Path::new(path).exists() {
+            return Self::default();
+        }
+        Self::load(path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        std::fs::write(path, self.to_config())
+            .map_err(|e| format!("Failed to write key bindings {}: {}", path, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_file_round_trips() {
+        let mut bindings = KeyBindings::default();
+        bindings.rebind(Action::MoveLeft, KeyCode::ArrowLeft);
+        bindings.rebind(Action::MoveRight, KeyCode::ArrowRight);
+        bindings.unbind(KeyCode::KeyG);
+
+        let path = std::env::temp_dir().join(format!("sas2_bindings_{}.cfg", std::process::id()));
+        let path = path.to_str().unwrap();
+        bindings.save(path).unwrap();
+        let loaded = KeyBindings::load(path);
+        let _ = std::fs::remove_file(path);
+        let loaded = loaded.unwrap();
+
+        assert_eq!(loaded.action(KeyCode::ArrowLeft), Some(Action::MoveLeft));
+        assert_eq!(loaded.action(KeyCode::KeyA), None);
+        assert_eq!(loaded.action(KeyCode::KeyG), None);
+        assert_eq!(loaded.to_config(), bindings.to_config());
+    }
+
+    #[test]
+    fn parse_applies_binds_over_defaults() {
+        let bindings = KeyBindings::parse("// comment\nbind uparrow \"+moveup\"\nbind 0 \"weapon 9\"\n").unwrap();
+        assert_eq!(bindings.action(KeyCode::ArrowUp), Some(Action::Jump));
+        assert_eq!(bindings.action(KeyCode::KeyW), Some(Action::Jump));
+        assert_eq!(bindings.action(KeyCode::Digit0), Some(Action::WeaponSlot(9)));
+        assert!(KeyBindings::parse("bind nosuchkey +attack").is_err());
+    }
+}
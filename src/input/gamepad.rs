@@ -0,0 +1,98 @@
+use glam::Vec2;
+use gilrs::{Axis, Button, Event, EventType, GamepadId, Gilrs};
+
+// Mouse counts per second at full right stick deflection, scaled by mouse_sensitivity
+const GAMEPAD_LOOK_SPEED: f32 = 1500.0;
+
+pub struct Gamepad {
+    gilrs: Gilrs,
+    active: Option<GamepadId>,
+    left_stick: Vec2,
+    right_stick: Vec2,
+    look_scale: f32,
+    button_changes: Vec<(Button, bool)>,
+    disconnected: bool,
+}
+
+impl Gamepad {
+    pub fn new() -> Result<Self, String> {
+        let gilrs = Gilrs::new().map_err(|e| format!("Failed to initialize gamepad input: {}", e))?;
+        let active = gilrs.gamepads().next().map(|(id, _)| id);
+        Ok(Self {
+            gilrs,
+            active,
+            left_stick: Vec2::ZERO,
+            right_stick: Vec2::ZERO,
+            look_scale: 0.0,
+            button_changes: Vec::new(),
+            disconnected: false,
+        })
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.active.is_some()
+    }
+
+    // Raw stick values, x right and y up, before any deadzone
+    pub fn left_stick(&self) -> Vec2 {
+        self.left_stick
+    }
+
+    pub fn right_stick(&self) -> Vec2 {
+        self.right_stick
+    }
+
+    // Right stick motion this frame in mouse counts, as fed to InputState::handle_mouse_motion
+    pub fn look_delta(&self) -> Vec2 {
+        self.right_stick * self.look_scale
+    }
+
+    // Buttons pressed (true) or released (false) on the active pad since the last poll
+    pub fn button_changes(&self) -> &[(Button, bool)] {
+        &self.button_changes
+    }
+
+    pub fn disconnected(&self) -> bool {
+        self.disconnected
+    }
+
+    // Call once per frame before InputState::apply_gamepad; the most recently used pad is active
+    pub fn poll(&mut self, dt: f32) {
+        self.button_changes.clear();
+        self.disconnected = false;
+
+        while let Some(Event { id, event, .. }) = self.gilrs.next_event() {
+            match event {
+                EventType::Disconnected => {
+                    if self.active == Some(id) {
+                        self.active = None;
+                        self.disconnected = true;
+                    }
+                    continue;
+                }
+                EventType::Connected => {
+                    self.active.get_or_insert(id);
+                }
+                _ => self.active = Some(id),
+            }
+            if self.active != Some(id) {
+                continue;
+            }
+            match event {
+                EventType::ButtonPressed(button, _) => self.button_changes.push((button, true)),
+                EventType::ButtonReleased(button, _) => self.button_changes.push((button, false)),
+                _ => {}
+            }
+        }
+
+        let Some(id) = self.active else {
+            self.left_stick = Vec2::ZERO;
+            self.right_stick = Vec2::ZERO;
+            return;
+        };
+        let pad = self.gilrs.gamepad(id);
+        self.left_stick = Vec2::new(pad.value(Axis::LeftStickX), pad.value(Axis::LeftStickY));
+        self.right_stick = Vec2::new(pad.value(Axis::RightStickX), pad.value(Axis::RightStickY));
+        self.look_scale = GAMEPAD_LOOK_SPEED * dt;
+    }
+}
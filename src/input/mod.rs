@@ -1,6 +1,15 @@
+use glam::Vec2;
 use winit::keyboard::KeyCode;
 use crate::game::weapon::Weapon;
 
+pub mod bindings;
+#[cfg(feature = "gamepad")]
+pub mod gamepad;
+
+pub use bindings::{Action, KeyBindings};
+#[cfg(feature = "gamepad")]
+pub use gamepad::Gamepad;
+
 #[derive(Default)]
 pub struct InputState {
     pub move_left: bool,
@@ -15,46 +24,129 @@ pub struct InputState {
     pub mouse_x: f32,
     pub mouse_y: f32,
     pub weapon_switch: Option<Weapon>,
+    // Pending next (+) / previous (-) weapon steps
+    pub weapon_cycle: i32,
+    scroll_accum: f32,
+    pub move_axis: Vec2,
+    pub bindings: KeyBindings,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub mouse_sensitivity: f32,
+    // Stick deflection below this is treated as centered
+    pub gamepad_deadzone: f32,
+    mouse_delta: Vec2,
+    gamepad_axis: Vec2,
 }
 
+pub const DEFAULT_GAMEPAD_DEADZONE: f32 = 0.2;
+// Radians per mouse count, roughly Q3's sensitivity 5 with m_yaw 0.022
+pub const DEFAULT_MOUSE_SENSITIVITY: f32 = 0.0019;
+const MAX_PITCH: f32 = 89.0 * std::f32::consts::PI / 180.0;
+
 impl InputState {
     pub fn new() -> Self {
-        Self::default()
+        Self {
+            mouse_sensitivity: DEFAULT_MOUSE_SENSITIVITY,
+            gamepad_deadzone: DEFAULT_GAMEPAD_DEADZONE,
+            ..Self::default()
+        }
     }
 
     pub fn handle_key_press(&mut self, keycode: KeyCode) {
-        match keycode {
-            KeyCode::KeyA => self.move_left = true,
-            KeyCode::KeyD => self.move_right = true,
-            KeyCode::KeyW => self.jump = true,
-            KeyCode::KeyS => self.crouch = true,
-            KeyCode::Space => self.fire = true,
-            KeyCode::KeyG => self.gesture = true,
-            KeyCode::KeyM => self.switch_model = true,
-            KeyCode::Digit1 => self.weapon_switch = Some(Weapon::Gauntlet),
-            KeyCode::Digit2 => self.weapon_switch = Some(Weapon::MachineGun),
-            KeyCode::Digit3 => self.weapon_switch = Some(Weapon::Shotgun),
-            KeyCode::Digit4 => self.weapon_switch = Some(Weapon::GrenadeLauncher),
-            KeyCode::Digit5 => self.weapon_switch = Some(Weapon::RocketLauncher),
-            KeyCode::Digit6 => self.weapon_switch = Some(Weapon::Lightning),
-            KeyCode::Digit7 => self.weapon_switch = Some(Weapon::Railgun),
-            KeyCode::Digit8 => self.weapon_switch = Some(Weapon::Plasmagun),
-            KeyCode::Digit9 => self.weapon_switch = Some(Weapon::BFG),
-            _ => {}
+        match self.bindings.action(keycode) {
+            Some(Action::WeaponSlot(slot)) => {
+                if let Some(weapon) = (slot as usize).checked_sub(1).and_then(Weapon::from_index) {
+                    self.weapon_switch = Some(weapon);
+                }
+            }
+            Some(action) => self.set_action(action, true),
+            None => {}
         }
+        self.update_move_axis();
     }
 
     pub fn handle_key_release(&mut self, keycode: KeyCode) {
-        match keycode {
-            KeyCode::KeyA => self.move_left = false,
-            KeyCode::KeyD => self.move_right = false,
-            KeyCode::KeyW => self.jump = false,
-            KeyCode::KeyS => self.crouch = false,
-            KeyCode::Space => self.fire = false,
-            KeyCode::KeyG => self.gesture = false,
-            KeyCode::KeyM => self.switch_model = false,
-            _ => {}
+        if let Some(action) = self.bindings.action(keycode) {
+            self.set_action(action, false);
+        }
+        self.update_move_axis();
+    }
+
+    fn set_action(&mut self, action: Action, pressed: bool) {
+        match action {
+            Action::MoveLeft => self.move_left = pressed,
+            Action::MoveRight => self.move_right = pressed,
+            Action::Jump => {
+                self.jump = pressed;
+                self.move_up = pressed;
+            }
+            Action::Crouch => {
+                self.crouch = pressed;
+                self.move_down = pressed;
+            }
+            Action::Fire => self.fire = pressed,
+            Action::Gesture => self.gesture = pressed,
+            Action::SwitchModel => self.switch_model = pressed,
+            Action::WeaponSlot(_) => {}
+        }
+    }
+
+    // x is right, y is up; magnitude above 1 is clamped
+    pub fn set_gamepad_axis(&mut self, x: f32, y: f32) {
+        self.gamepad_axis = Vec2::new(x, y).clamp_length_max(1.0);
+        self.update_move_axis();
+    }
+
+    fn keyboard_axis(&self) -> Vec2 {
+        let x = self.move_right as i32 - self.move_left as i32;
+        let y = self.move_up as i32 - self.move_down as i32;
+        // Normalized so diagonals aren't faster than a single direction
+        Vec2::new(x as f32, y as f32).normalize_or_zero()
+    }
+
+    // Merges a polled pad: sticks drive movement and look, buttons only act on press/release
+    #[cfg(feature = "gamepad")]
+    pub fn apply_gamepad(&mut self, gamepad: &Gamepad) {
+        use gilrs::Button;
+
+        if gamepad.disconnected() {
+            self.fire = false;
+            self.set_action(Action::Jump, false);
+            self.set_action(Action::Crouch, false);
+            self.gesture = false;
+        }
+        let stick = gamepad.left_stick();
+        self.set_gamepad_axis(stick.x, stick.y);
+        if gamepad.right_stick().length() > self.gamepad_deadzone {
+            // Stick up is positive, mouse motion down is positive
+            let look = gamepad.look_delta();
+            self.handle_mouse_motion(look.x as f64, -look.y as f64);
+        }
+
+        for &(button, pressed) in gamepad.button_changes() {
+            match button {
+                Button::RightTrigger2 => self.fire = pressed,
+                Button::South => self.set_action(Action::Jump, pressed),
+                Button::East => self.set_action(Action::Crouch, pressed),
+                Button::West => self.gesture = pressed,
+                Button::RightTrigger if pressed => self.weapon_cycle += 1,
+                Button::LeftTrigger if pressed => self.weapon_cycle -= 1,
+                _ => {}
+            }
         }
+        self.update_move_axis();
+    }
+
+    pub fn gamepad_active(&self) -> bool {
+        self.gamepad_axis.length() > self.gamepad_deadzone
+    }
+
+    fn update_move_axis(&mut self) {
+        self.move_axis = if self.gamepad_active() {
+            self.gamepad_axis
+        } else {
+            self.keyboard_axis()
+        };
     }
 
     pub fn handle_mouse_button_press(&mut self) {
@@ -65,6 +157,19 @@ impl InputState {
         self.fire = false;
     }
 
+    // Relative motion from DeviceEvent::MouseMotion; moving the mouse up looks up
+    pub fn handle_mouse_motion(&mut self, dx: f64, dy: f64) {
+        let delta = Vec2::new(dx as f32, -dy as f32) * self.mouse_sensitivity;
+        self.mouse_delta += delta;
+        self.yaw = (self.yaw + delta.x).rem_euclid(std::f32::consts::TAU);
+        self.pitch = (self.pitch + delta.y).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    // Scaled motion since the last call, x right and y up; call once per frame
+    pub fn take_mouse_delta(&mut self) -> Vec2 {
+        std::mem::take(&mut self.mouse_delta)
+    }
+
     pub fn update_mouse_position(&mut self, x: f32, y: f32) {
         self.mouse_x = x;
         self.mouse_y = y;
@@ -73,9 +178,88 @@ impl InputState {
     pub fn reset_one_shot_inputs(&mut self) {
         self.switch_model = false;
         self.weapon_switch = None;
+        self.weapon_cycle = 0;
+    }
+
+    // Wheel lines; up selects the previous weapon as in Q3. Fractional deltas accumulate
+    pub fn handle_scroll(&mut self, delta: f32) {
+        self.scroll_accum += delta;
+        let steps = self.scroll_accum.trunc();
+        self.scroll_accum -= steps;
+        self.weapon_cycle -= steps as i32;
+    }
+
+    pub fn next_weapon(&mut self, current: Weapon, owned: &[Weapon]) {
+        self.weapon_switch = cycle_weapon(current, owned, 1);
+    }
+
+    pub fn prev_weapon(&mut self, current: Weapon, owned: &[Weapon]) {
+        self.weapon_switch = cycle_weapon(current, owned, -1);
+    }
+
+    // Resolves pending wheel/gamepad steps into a weapon_switch
+    pub fn apply_weapon_cycle(&mut self, current: Weapon, owned: &[Weapon]) {
+        let mut weapon = current;
+        for _ in 0..self.weapon_cycle.unsigned_abs() {
+            match cycle_weapon(weapon, owned, self.weapon_cycle.signum()) {
+                Some(next) => weapon = next,
+                None => break,
+            }
+        }
+        self.weapon_cycle = 0;
+        if weapon != current {
+            self.weapon_switch = Some(weapon);
+        }
     }
 
     pub fn take_weapon_switch(&mut self) -> Option<Weapon> {
         self.weapon_switch.take()
     }
 }
+
+const WEAPON_COUNT: i32 = 9;
+
+// Next owned weapon in slot order, wrapping around; None if nothing else is owned
+fn cycle_weapon(current: Weapon, owned: &[Weapon], step: i32) -> Option<Weapon> {
+    let start = current as i32;
+    (1..WEAPON_COUNT)
+        .filter_map(|i| Weapon::from_index((start + step * i).rem_euclid(WEAPON_COUNT) as usize))
+        .find(|weapon| owned.contains(weapon))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diagonal_keys_give_a_normalized_axis() {
+        let mut input = InputState::new();
+        input.handle_key_press(KeyCode::KeyW);
+        input.handle_key_press(KeyCode::KeyD);
+        assert!((input.move_axis - Vec2::splat(std::f32::consts::FRAC_1_SQRT_2)).length() < 1e-6);
+        assert!(input.move_up && input.move_right);
+
+        // A deflected stick overrides the keys until it is centered again
+        input.set_gamepad_axis(-0.5, 0.0);
+        assert_eq!(input.move_axis, Vec2::new(-0.5, 0.0));
+        input.set_gamepad_axis(0.05, 0.0);
+        assert!((input.move_axis.length() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn weapon_cycling_wraps_and_skips_unowned_weapons() {
+        let all: Vec<Weapon> = (0..WEAPON_COUNT as usize).filter_map(Weapon::from_index).collect();
+        let mut input = InputState::new();
+        input.prev_weapon(Weapon::Gauntlet, &all);
+        assert_eq!(input.take_weapon_switch(), Some(Weapon::BFG));
+        input.next_weapon(Weapon::BFG, &all);
+        assert_eq!(input.take_weapon_switch(), Some(Weapon::Gauntlet));
+
+        // Two wheel notches down skip the shotgun, which isn't owned
+        let owned = [Weapon::Gauntlet, Weapon::MachineGun, Weapon::RocketLauncher];
+        input.handle_scroll(-0.5);
+        input.handle_scroll(-1.5);
+        input.apply_weapon_cycle(Weapon::Gauntlet, &owned);
+        assert_eq!(input.take_weapon_switch(), Some(Weapon::RocketLauncher));
+    }
+}
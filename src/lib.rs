@@ -3,8 +3,10 @@ pub mod audio;
 pub mod input;
 pub mod render;
 pub mod game;
+pub mod net;
 
 pub mod app;
 pub mod game_loop;
 pub mod console;
 pub mod resource_path;
+pub mod skin;
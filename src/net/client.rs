@@ -0,0 +1,113 @@
+use std::io::ErrorKind;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use crate::game::snapshot::WorldSnapshot;
+use super::protocol::{self, ClientMessage, PlayerInput, ServerMessage, MAX_PACKET_SIZE};
+
+pub struct Client {
+    socket: UdpSocket,
+    server_addr: SocketAddr,
+    pub player_id: Option<u32>,
+    sequence: u32,
+    last_snapshot_tick: Option<u32>,
+}
+
+impl Client {
+    pub fn connect<A: ToSocketAddrs>(server_addr: A) -> Result<Self, String> {
+        let server_addr = server_addr
+            .to_socket_addrs()
+            .map_err(|e| format!("Invalid server address: {}", e))?
+            .next()
+            .ok_or_else(|| "Invalid server address".to_string())?;
+
+        let bind_addr = if server_addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+        let socket = UdpSocket::bind(bind_addr).map_err(|e| format!("Failed to bind client socket: {}", e))?;
+        socket.set_nonblocking(true).map_err(|e| format!("Failed to set non-blocking: {}", e))?;
+
+        let client = Self {
+            socket,
+            server_addr,
+            player_id: None,
+            sequence: 0,
+            last_snapshot_tick: None,
+        };
+        client.send(&ClientMessage::Connect)?;
+        Ok(client)
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.player_id.is_some()
+    }
+
+    pub fn send_input(&mut self, input: &PlayerInput) -> Result<(), String> {
+        if !self.is_connected() {
+            return self.send(&ClientMessage::Connect);
+        }
+        self.sequence += 1;
+        self.send(&ClientMessage::Input {
+            sequence: self.sequence,
+            input: input.clone(),
+        })
+    }
+
+    pub fn disconnect(&mut self) -> Result<(), String> {
+        self.player_id = None;
+        self.send(&ClientMessage::Disconnect)
+    }
+
+    pub fn poll(&mut self) -> Result<Option<WorldSnapshot>, String> {
+        let mut buf = [0u8; MAX_PACKET_SIZE];
+        let mut latest = None;
+
+        loop {
+            let (len, addr) = match self.socket.recv_from(&mut buf) {
+                Ok(r) => r,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::ConnectionReset => continue,
+                Err(e) => return Err(format!("Client receive failed: {}", e)),
+            };
+
+            if addr != self.server_addr {
+                continue;
+            }
+
+            match protocol::decode(&buf[..len]) {
+                Ok(ServerMessage::Welcome { player_id }) => {
+                    self.player_id = Some(player_id);
+                }
+                Ok(ServerMessage::Snapshot(data)) => {
+                    let snapshot = match WorldSnapshot::from_bytes(&data) {
+                        Ok(snapshot) => snapshot,
+                        Err(e) => {
+                            eprintln!("Dropping snapshot from server: {}", e);
+                            continue;
+                        }
+                    };
+                    let is_newer = self.last_snapshot_tick.is_none_or(|t| snapshot.tick > t);
+                    if is_newer {
+                        self.last_snapshot_tick = Some(snapshot.tick);
+                        latest = Some(snapshot);
+                    }
+                }
+                Err(e) => eprintln!("Dropping packet from server: {}", e),
+            }
+        }
+
+        Ok(latest)
+    }
+
+    fn send(&self, message: &ClientMessage) -> Result<(), String> {
+        let data = protocol::encode(message)?;
+        self.socket
+            .send_to(&data, self.server_addr)
+            .map_err(|e| format!("Failed to send to server: {}", e))?;
+        Ok(())
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        if self.is_connected() {
+            let _ = self.disconnect();
+        }
+    }
+}
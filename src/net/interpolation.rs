@@ -0,0 +1,244 @@
+use std::collections::VecDeque;
+use crate::game::snapshot::{PlayerSnapshot, WorldSnapshot};
+
+pub const DEFAULT_INTERPOLATION_DELAY: f32 = 0.1;
+pub const DEFAULT_MAX_EXTRAPOLATION: f32 = 0.25;
+const MAX_BUFFERED_SNAPSHOTS: usize = 32;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RemotePlayerState {
+    pub id: u32,
+    pub x: f32,
+    pub y: f32,
+    pub vx: f32,
+    pub vy: f32,
+    pub aim_angle: f32,
+    pub extrapolated: bool,
+}
+
+pub struct SnapshotBuffer {
+    snapshots: VecDeque<WorldSnapshot>,
+    server_time_offset: Option<f32>,
+    pub interpolation_delay: f32,
+    pub max_extrapolation: f32,
+}
+
+impl SnapshotBuffer {
+    pub fn new() -> Self {
+        Self {
+            snapshots: VecDeque::new(),
+            server_time_offset: None,
+            interpolation_delay: DEFAULT_INTERPOLATION_DELAY,
+            max_extrapolation: DEFAULT_MAX_EXTRAPOLATION,
+        }
+    }
+
+    pub fn with_delay(interpolation_delay: f32) -> Self {
+        Self {
+            interpolation_delay,
+            ..Self::new()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.server_time_offset = None;
+    }
+
+    pub fn push(&mut self, snapshot: WorldSnapshot, local_time: f32) {
+        if let Some(last) = self.snapshots.back() {
+            if snapshot.time <= last.time {
+                return;
+            }
+        }
+
+        let offset = snapshot.time - local_time;
+        self.server_time_offset = Some(match self.server_time_offset {
+            Some(prev) if offset < prev => prev + (offset - prev) * 0.1,
+            _ => offset,
+        });
+
+        self.snapshots.push_back(snapshot);
+        while self.snapshots.len() > MAX_BUFFERED_SNAPSHOTS {
+            self.snapshots.pop_front();
+        }
+    }
+
+    pub fn render_time(&self, local_time: f32) -> Option<f32> {
+        self.server_time_offset
+            .map(|offset| local_time + offset - self.interpolation_delay)
+    }
+
+    pub fn sample(&self, local_time: f32) -> Vec<RemotePlayerState> {
+        match self.render_time(local_time) {
+            Some(render_time) => self.sample_at(render_time),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn sample_at(&self, render_time: f32) -> Vec<RemotePlayerState> {
+        let (from, to) = match self.bracket(render_time) {
+            Some(pair) => pair,
+            None => return Vec::new(),
+        };
+
+        let span = to.time - from.time;
+        let t = if span > 0.0 { (render_time - from.time) / span } else { 1.0 };
+
+        if t <= 1.0 {
+            return to.players.iter()
+                .map(|b| match from.players.iter().find(|a| a.id == b.id) {
+                    Some(a) => lerp_player(a, b, t.max(0.0)),
+                    None => exact_player(b),
+                })
+                .collect();
+        }
+
+        let ahead = (render_time - to.time).min(self.max_extrapolation);
+        to.players.iter()
+            .map(|p| RemotePlayerState {
+                id: p.id,
+                x: p.x + p.vx * ahead,
+                y: p.y + p.vy * ahead,
+                vx: p.vx,
+                vy: p.vy,
+                aim_angle: p.aim_angle,
+                extrapolated: true,
+            })
+            .collect()
+    }
+
+    fn bracket(&self, render_time: f32) -> Option<(&WorldSnapshot, &WorldSnapshot)> {
+        let last = self.snapshots.back()?;
+        if self.snapshots.len() == 1 {
+            return Some((last, last));
+        }
+
+        for i in 0..self.snapshots.len() - 1 {
+            let b = &self.snapshots[i + 1];
+            if render_time <= b.time {
+                return Some((&self.snapshots[i], b));
+            }
+        }
+
+        let prev = &self.snapshots[self.snapshots.len() - 2];
+        Some((prev, last))
+    }
+}
+
+impl Default for SnapshotBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn exact_player(p: &PlayerSnapshot) -> RemotePlayerState {
+    RemotePlayerState {
+        id: p.id,
+        x: p.x,
+        y: p.y,
+        vx: p.vx,
+        vy: p.vy,
+        aim_angle: p.aim_angle,
+        extrapolated: false,
+    }
+}
+
+fn lerp_player(a: &PlayerSnapshot, b: &PlayerSnapshot, t: f32) -> RemotePlayerState {
+    use std::f32::consts::{PI, TAU};
+    // Shortest way round; a non-finite angle from a bad snapshot just stays non-finite
+    let angle_diff = (b.aim_angle - a.aim_angle + PI).rem_euclid(TAU) - PI;
+
+    RemotePlayerState {
+        id: b.id,
+        x: a.x + (b.x - a.x) * t,
+        y: a.y + (b.y - a.y) * t,
+        vx: a.vx + (b.vx - a.vx) * t,
+        vy: a.vy + (b.vy - a.vy) * t,
+        aim_angle: a.aim_angle + angle_diff * t,
+        extrapolated: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::player::Player;
+
+    fn snapshot_with_aim(aim_angle: f32) -> PlayerSnapshot {
+        let mut player = Player::new(1);
+        player.aim_angle = aim_angle;
+        PlayerSnapshot::from_player(&player)
+    }
+
+    fn world_at(time: f32, x: f32, vx: f32) -> WorldSnapshot {
+        let mut player = Player::new(1);
+        player.x = x;
+        player.y = 2.0;
+        player.vx = vx;
+        player.vy = 0.0;
+        WorldSnapshot {
+            version: crate::game::snapshot::SNAPSHOT_VERSION,
+            tick: (time * 60.0) as u32,
+            time,
+            players: vec![PlayerSnapshot::from_player(&player)],
+            items: Vec::new(),
+        }
+    }
+
+    fn buffer_with(snapshots: &[WorldSnapshot]) -> SnapshotBuffer {
+        let mut buffer = SnapshotBuffer::new();
+        for snapshot in snapshots {
+            buffer.push(snapshot.clone(), snapshot.time);
+        }
+        buffer
+    }
+
+    #[test]
+    fn midpoint_sample_averages_the_two_snapshots() {
+        let buffer = buffer_with(&[world_at(1.0, 0.0, 4.0), world_at(1.5, 2.0, 8.0)]);
+        let mid = &buffer.sample_at(1.25)[0];
+        assert_eq!((mid.x, mid.y, mid.vx), (1.0, 2.0, 6.0));
+        assert!(!mid.extrapolated);
+    }
+
+    #[test]
+    fn sampling_past_the_last_snapshot_extrapolates_up_to_the_cap() {
+        let buffer = buffer_with(&[world_at(1.0, 0.0, 4.0), world_at(1.5, 2.0, 4.0)]);
+
+        let ahead = &buffer.sample_at(1.625)[0];
+        assert_eq!(ahead.x, 2.5);
+        assert!(ahead.extrapolated);
+
+        // Far past the last snapshot the guess stops at max_extrapolation seconds of velocity
+        let capped = &buffer.sample_at(10.0)[0];
+        assert_eq!(capped.x, 2.0 + 4.0 * DEFAULT_MAX_EXTRAPOLATION);
+        assert!(capped.extrapolated);
+    }
+
+    #[test]
+    fn aim_interpolates_the_short_way_round() {
+        let a = snapshot_with_aim(3.0);
+        let b = snapshot_with_aim(-3.0);
+        let mid = lerp_player(&a, &b, 0.5);
+        // 3.0 and -3.0 are about 0.28 apart across PI, not 6.0 apart through zero
+        assert!((mid.aim_angle - (3.0 + (std::f32::consts::TAU - 6.0) * 0.5)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn non_finite_aim_does_not_hang() {
+        let a = snapshot_with_aim(0.0);
+        for bad in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            let b = snapshot_with_aim(bad);
+            assert!(!lerp_player(&a, &b, 0.5).aim_angle.is_finite());
+        }
+    }
+}
@@ -0,0 +1,9 @@
+pub mod protocol;
+pub mod server;
+pub mod client;
+pub mod interpolation;
+
+pub use protocol::{ClientMessage, ServerMessage, PlayerInput};
+pub use server::Server;
+pub use client::Client;
+pub use interpolation::{SnapshotBuffer, RemotePlayerState};
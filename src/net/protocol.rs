@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+use crate::game::snapshot::WorldSnapshot;
+pub use crate::game::input::PlayerInput;
+
+pub const DEFAULT_PORT: u16 = 27960;
+pub const MAX_PACKET_SIZE: usize = 65507;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ClientMessage {
+    Connect,
+    Input { sequence: u32, input: PlayerInput },
+    Disconnect,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ServerMessage {
+    Welcome { player_id: u32 },
+    // WorldSnapshot::to_bytes output, so from_bytes checks its version on arrival
+    Snapshot(Vec<u8>),
+}
+
+impl ServerMessage {
+    pub fn snapshot(snapshot: &WorldSnapshot) -> Result<Self, String> {
+        snapshot.to_bytes().map(Self::Snapshot)
+    }
+}
+
+pub fn encode<T: Serialize>(message: &T) -> Result<Vec<u8>, String> {
+    let data = bincode::serialize(message).map_err(|e| format!("Failed to encode message: {}", e))?;
+    if data.len() > MAX_PACKET_SIZE {
+        return Err(format!("Message too large: {} bytes", data.len()));
+    }
+    Ok(data)
+}
+
+pub fn decode<'a, T: Deserialize<'a>>(data: &'a [u8]) -> Result<T, String> {
+    bincode::deserialize(data).map_err(|e| format!("Failed to decode message: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::world::World;
+
+    #[test]
+    fn snapshot_from_another_version_fails_with_a_version_error() {
+        let mut snapshot = WorldSnapshot::capture(&World::with_seed(1), 3);
+        snapshot.version += 1;
+        let data = encode(&ServerMessage::snapshot(&snapshot).unwrap()).unwrap();
+
+        let ServerMessage::Snapshot(payload) = decode(&data).unwrap() else {
+            panic!("expected a snapshot message");
+        };
+        assert!(WorldSnapshot::from_bytes(&payload).unwrap_err().contains("version"));
+    }
+}
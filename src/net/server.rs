@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+use crate::game::snapshot::WorldSnapshot;
+use crate::game::world::World;
+use super::protocol::{self, ClientMessage, PlayerInput, ServerMessage, MAX_PACKET_SIZE};
+
+// Clients that send nothing for this long are dropped along with their player
+pub const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+struct ClientSlot {
+    player_id: u32,
+    input: PlayerInput,
+    last_sequence: u32,
+    last_heard: Instant,
+}
+
+pub struct Server {
+    socket: UdpSocket,
+    pub world: World,
+    clients: HashMap<SocketAddr, ClientSlot>,
+    tick: u32,
+    client_timeout: Duration,
+}
+
+impl Server {
+    pub fn bind<A: ToSocketAddrs>(addr: A, world: World) -> Result<Self, String> {
+        let socket = UdpSocket::bind(addr).map_err(|e| format!("Failed to bind server socket: {}", e))?;
+        socket.set_nonblocking(true).map_err(|e| format!("Failed to set non-blocking: {}", e))?;
+
+        Ok(Self {
+            socket,
+            world,
+            clients: HashMap::new(),
+            tick: 0,
+            client_timeout: CLIENT_TIMEOUT,
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, String> {
+        self.socket.local_addr().map_err(|e| format!("Failed to get local address: {}", e))
+    }
+
+    pub fn num_clients(&self) -> usize {
+        self.clients.len()
+    }
+
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    pub fn set_client_timeout(&mut self, timeout: Duration) {
+        self.client_timeout = timeout;
+    }
+
+    pub fn poll(&mut self) -> Result<(), String> {
+        let mut buf = [0u8; MAX_PACKET_SIZE];
+
+        loop {
+            let (len, addr) = match self.socket.recv_from(&mut buf) {
+                Ok(r) => r,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                    self.drop_timed_out_clients();
+                    return Ok(());
+                }
+                Err(e) if e.kind() == ErrorKind::ConnectionReset => continue,
+                Err(e) => return Err(format!("Server receive failed: {}", e)),
+            };
+
+            let message: ClientMessage = match protocol::decode(&buf[..len]) {
+                Ok(m) => m,
+                Err(e) => {
+                    eprintln!("Dropping packet from {}: {}", addr, e);
+                    continue;
+                }
+            };
+
+            if let Some(slot) = self.clients.get_mut(&addr) {
+                slot.last_heard = Instant::now();
+            }
+            self.handle_message(addr, message)?;
+        }
+    }
+
+    pub fn drop_timed_out_clients(&mut self) {
+        let timeout = self.client_timeout;
+        let timed_out: Vec<SocketAddr> = self.clients
+            .iter()
+            .filter(|(_, slot)| slot.last_heard.elapsed() > timeout)
+            .map(|(addr, _)| *addr)
+            .collect();
+        for addr in timed_out {
+            if let Some(slot) = self.clients.remove(&addr) {
+                println!("Client {} (player {}) timed out", addr, slot.player_id);
+                self.world.remove_player(slot.player_id);
+            }
+        }
+    }
+
+    fn handle_message(&mut self, addr: SocketAddr, message: ClientMessage) -> Result<(), String> {
+        match message {
+            ClientMessage::Connect => {
+                let player_id = match self.clients.get(&addr) {
+                    Some(slot) => slot.player_id,
+                    None => {
+                        let player_id = self.world.add_player();
+                        println!("Client {} connected as player {}", addr, player_id);
+                        self.clients.insert(addr, ClientSlot {
+                            player_id,
+                            input: PlayerInput::default(),
+                            last_sequence: 0,
+                            last_heard: Instant::now(),
+                        });
+                        player_id
+                    }
+                };
+                self.send(addr, &ServerMessage::Welcome { player_id })?;
+            }
+            ClientMessage::Input { sequence, input } => {
+                if let Some(slot) = self.clients.get_mut(&addr) {
+                    if sequence > slot.last_sequence {
+                        slot.last_sequence = sequence;
+                        slot.input = input;
+                    }
+                }
+            }
+            ClientMessage::Disconnect => {
+                if let Some(slot) = self.clients.remove(&addr) {
+                    println!("Client {} (player {}) disconnected", addr, slot.player_id);
+                    self.world.remove_player(slot.player_id);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn step(&mut self, dt: f32) {
+        let mut inputs: Vec<PlayerInput> = self.clients.values_mut()
+            .map(|slot| {
+                let mut input = slot.input.clone();
+                input.player_id = slot.player_id;
+                slot.input.weapon_switch = None;
+                input
+            })
+            .collect();
+        inputs.sort_by_key(|input| input.player_id);
+
+        self.world.step(&inputs, dt);
+        self.tick += 1;
+    }
+
+    // A client that can't be reached is logged and skipped; the rest still get the snapshot
+    pub fn broadcast_snapshot(&self) -> Result<(), String> {
+        let snapshot = ServerMessage::snapshot(&WorldSnapshot::capture(&self.world, self.tick))?;
+        let data = protocol::encode(&snapshot)?;
+        for addr in self.clients.keys() {
+            if let Err(e) = self.socket.send_to(&data, addr) {
+                eprintln!("Failed to send snapshot to {}: {}", addr, e);
+            }
+        }
+        Ok(())
+    }
+
+    fn send(&self, addr: SocketAddr, message: &ServerMessage) -> Result<(), String> {
+        let data = protocol::encode(message)?;
+        self.socket.send_to(&data, addr).map_err(|e| format!("Failed to send to {}: {}", addr, e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::Client;
+
+    fn pump(server: &mut Server, client: &mut Client) -> Option<WorldSnapshot> {
+        let mut latest = None;
+        for _ in 0..200 {
+            server.poll().unwrap();
+            if let Some(snapshot) = client.poll().unwrap() {
+                latest = Some(snapshot);
+            }
+            if client.is_connected() && latest.is_some() {
+                break;
+            }
+            if client.is_connected() {
+                server.broadcast_snapshot().unwrap();
+            }
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        latest
+    }
+
+    #[test]
+    fn loopback_input_moves_player() {
+        let mut server = Server::bind("127.0.0.1:0", World::with_seed(1)).unwrap();
+        let mut client = Client::connect(server.local_addr().unwrap()).unwrap();
+        pump(&mut server, &mut client);
+        let player_id = client.player_id.expect("client never got a welcome");
+        let start_x = server.world.players.iter().find(|p| p.id == player_id).unwrap().x;
+
+        let input = PlayerInput { player_id, move_right: true, ..PlayerInput::default() };
+        client.send_input(&input).unwrap();
+        for _ in 0..100 {
+            server.poll().unwrap();
+            if server.clients.values().any(|slot| slot.input.move_right) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        for _ in 0..30 {
+            server.step(1.0 / 60.0);
+        }
+        server.broadcast_snapshot().unwrap();
+
+        let snapshot = pump(&mut server, &mut client).expect("no snapshot received");
+        let player = snapshot.players.iter().find(|p| p.id == player_id).unwrap();
+        assert!(player.x > start_x, "player didn't move: {} -> {}", start_x, player.x);
+    }
+
+    #[test]
+    fn player_ids_are_not_reused_after_disconnect() {
+        let mut world = World::with_seed(1);
+        let first = world.add_player();
+        let second = world.add_player();
+        world.remove_player(first);
+        let third = world.add_player();
+        assert_ne!(third, second);
+        assert_ne!(third, first);
+    }
+
+    #[test]
+    fn late_joiner_finds_its_player_by_id_after_another_client_left() {
+        let mut server = Server::bind("127.0.0.1:0", World::with_seed(1)).unwrap();
+        let addr = server.local_addr().unwrap();
+        let mut first = Client::connect(addr).unwrap();
+        pump(&mut server, &mut first);
+        first.disconnect().unwrap();
+        for _ in 0..100 {
+            server.poll().unwrap();
+            if server.num_clients() == 0 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        assert_eq!(server.num_clients(), 0);
+
+        let mut second = Client::connect(addr).unwrap();
+        let snapshot = pump(&mut server, &mut second).expect("no snapshot received");
+        let player_id = second.player_id.expect("client never got a welcome");
+
+        let mut world = World::with_seed(1);
+        snapshot.apply(&mut world);
+        assert_eq!(world.players.len(), 1);
+        assert_ne!(player_id, 0, "the departed client's id was handed out again");
+        assert_eq!(world.player(player_id).map(|p| p.id), Some(player_id));
+    }
+
+    #[test]
+    fn silent_clients_time_out() {
+        let mut server = Server::bind("127.0.0.1:0", World::with_seed(1)).unwrap();
+        let mut client = Client::connect(server.local_addr().unwrap()).unwrap();
+        pump(&mut server, &mut client);
+        assert_eq!(server.num_clients(), 1);
+
+        server.set_client_timeout(Duration::ZERO);
+        std::thread::sleep(Duration::from_millis(5));
+        server.drop_timed_out_clients();
+        assert_eq!(server.num_clients(), 0);
+        assert!(server.world.players.is_empty());
+    }
+}
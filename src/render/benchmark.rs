@@ -0,0 +1,553 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use glam::{Mat3, Mat4, Vec3};
+use wgpu::*;
+use crate::engine::md3::{decode_normal, MD3Model};
+use super::buffers::decode_frame_vertices;
+use super::md3_renderer::MD3Renderer;
+use super::types::{RenderTarget, WgpuTexture, DEPTH_FORMAT};
+use super::wgpu_renderer::{request_adapter_with_fallback, required_limits};
+
+const BENCHMARK_TEXTURE_KEY: &str = "benchmark/white";
+const COLOR_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BenchmarkShadows {
+    None,
+    Planar,
+    Volumes,
+}
+
+impl BenchmarkShadows {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(BenchmarkShadows::None),
+            "planar" => Some(BenchmarkShadows::Planar),
+            "volumes" => Some(BenchmarkShadows::Volumes),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BenchmarkConfig {
+    pub model_path: PathBuf,
+    pub frames: usize,
+    pub warmup_frames: usize,
+    pub width: u32,
+    pub height: u32,
+    pub lights: usize,
+    pub shadows: BenchmarkShadows,
+}
+
+impl BenchmarkConfig {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let value = |flag: &str| args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1));
+
+        let frames = match value("--benchmark") {
+            Some(v) => v.parse().map_err(|_| format!("Invalid frame count: {}", v))?,
+            None => 100,
+        };
+        let model_path = value("--model")
+            .map(PathBuf::from)
+            .ok_or_else(|| "--benchmark requires --model <path.md3>".to_string())?;
+        let lights = match value("--lights") {
+            Some(v) => v.parse().map_err(|_| format!("Invalid light count: {}", v))?,
+            None => 1,
+        };
+        let shadows = match value("--shadows") {
+            Some(v) => BenchmarkShadows::from_name(v).ok_or_else(|| format!("Unknown shadow mode: {}", v))?,
+            None => BenchmarkShadows::None,
+        };
+        let (width, height) = match value("--size") {
+            Some(v) => {
+                let (w, h) = v.split_once('x').ok_or_else(|| format!("Invalid size: {}", v))?;
+                (
+                    w.parse().map_err(|_| format!("Invalid width: {}", w))?,
+                    h.parse().map_err(|_| format!("Invalid height: {}", h))?,
+                )
+            }
+            None => (1280, 720),
+        };
+
+        Ok(Self {
+            model_path,
+            frames,
+            warmup_frames: 5,
+            width,
+            height,
+            lights,
+            shadows,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimingStats {
+    pub avg: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl TimingStats {
+    pub fn from_samples(samples: &[f64]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let percentile = |p: f64| {
+            let idx = ((sorted.len() - 1) as f64 * p).round() as usize;
+            sorted[idx]
+        };
+        Self {
+            avg: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            p50: percentile(0.50),
+            p95: percentile(0.95),
+            p99: percentile(0.99),
+            max: sorted[sorted.len() - 1],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BenchmarkReport {
+    pub adapter: String,
+    pub frames: usize,
+    pub cpu_frame: TimingStats,
+    pub cpu_model_pass: TimingStats,
+    pub cpu_shadow_pass: TimingStats,
+    pub gpu_model_pass: Option<TimingStats>,
+    pub gpu_shadow_pass: Option<TimingStats>,
+}
+
+impl BenchmarkReport {
+    pub fn print(&self) {
+        let row = |name: &str, stats: &TimingStats| {
+            println!(
+                "  {:<18} avg {:>8.3} | p50 {:>8.3} | p95 {:>8.3} | p99 {:>8.3} | max {:>8.3}",
+                name, stats.avg, stats.p50, stats.p95, stats.p99, stats.max
+            );
+        };
+        println!("Benchmark: {} frames on {} (ms)", self.frames, self.adapter);
+        row("cpu frame", &self.cpu_frame);
+        row("cpu model pass", &self.cpu_model_pass);
+        row("cpu shadow pass", &self.cpu_shadow_pass);
+        match (&self.gpu_model_pass, &self.gpu_shadow_pass) {
+            (Some(model), Some(shadow)) => {
+                row("gpu model pass", model);
+                row("gpu shadow pass", shadow);
+            }
+            _ => println!("  GPU timestamps not supported by this adapter"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BufferBuildReport {
+    pub iterations: usize,
+    pub vertices: usize,
+    pub cached_normals: TimingStats,
+    pub decoded_normals: TimingStats,
+}
+
+impl BufferBuildReport {
+    pub fn print(&self) {
+        let row = |name: &str, stats: &TimingStats| {
+            println!(
+                "  {:<18} avg {:>8.3} | p50 {:>8.3} | p95 {:>8.3} | max {:>8.3}",
+                name, stats.avg, stats.p50, stats.p95, stats.max
+            );
+        };
+        println!(
+            "Buffer build: {} rebuilds of {} vertices (ms)",
+            self.iterations, self.vertices
+        );
+        row("cached normals", &self.cached_normals);
+        row("decoded normals", &self.decoded_normals);
+    }
+}
+
+// Rebuilds the vertex data of every frame, once copying the normals decoded at load and once
+// also decoding each packed normal, which is what every buffer build used to pay for
+pub fn run_buffer_build_benchmark(model_path: &Path, iterations: usize) -> Result<BufferBuildReport, String> {
+    let model = MD3Model::load(model_path)
+        .map_err(|e| format!("Failed to load {}: {}", model_path.display(), e))?;
+
+    let build_all = |decode: bool| {
+        let mut vertices = 0;
+        for (mesh_idx, mesh) in model.meshes.iter().enumerate() {
+            for frame_idx in 0..mesh.vertices.len() {
+                let Some(mut frame) = decode_frame_vertices(&model, 0, mesh_idx, frame_idx) else {
+                    continue;
+                };
+                if decode {
+                    for (vertex, packed) in frame.iter_mut().zip(&mesh.vertices[frame_idx]) {
+                        vertex.normal = decode_normal(packed.normal);
+                    }
+                }
+                vertices += std::hint::black_box(frame).len();
+            }
+        }
+        vertices
+    };
+
+    let time = |decode: bool| {
+        let mut samples = Vec::with_capacity(iterations);
+        let mut vertices = 0;
+        for _ in 0..iterations {
+            let start = Instant::now();
+            vertices = build_all(decode);
+            samples.push(start.elapsed().as_secs_f64() * 1000.0);
+        }
+        (TimingStats::from_samples(&samples), vertices)
+    };
+
+    let (cached_normals, vertices) = time(false);
+    let (decoded_normals, _) = time(true);
+    Ok(BufferBuildReport {
+        iterations,
+        vertices,
+        cached_normals,
+        decoded_normals,
+    })
+}
+
+struct GpuTimer {
+    query_set: QuerySet,
+    resolve_buffer: Buffer,
+    readback_buffer: Buffer,
+    period: f32,
+}
+
+const TIMESTAMP_COUNT: u32 = 3;
+
+impl GpuTimer {
+    fn new(device: &Device, queue: &Queue) -> Self {
+        let size = TIMESTAMP_COUNT as u64 * std::mem::size_of::<u64>() as u64;
+        Self {
+            query_set: device.create_query_set(&QuerySetDescriptor {
+                label: Some("Benchmark Timestamps"),
+                ty: QueryType::Timestamp,
+                count: TIMESTAMP_COUNT,
+            }),
+            resolve_buffer: device.create_buffer(&BufferDescriptor {
+                label: Some("Benchmark Timestamp Resolve Buffer"),
+                size,
+                usage: BufferUsages::QUERY_RESOLVE | BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            }),
+            readback_buffer: device.create_buffer(&BufferDescriptor {
+                label: Some("Benchmark Timestamp Readback Buffer"),
+                size,
+                usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }),
+            period: queue.get_timestamp_period(),
+        }
+    }
+
+    fn resolve(&self, encoder: &mut CommandEncoder) {
+        encoder.resolve_query_set(&self.query_set, 0..TIMESTAMP_COUNT, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &self.readback_buffer, 0, self.resolve_buffer.size());
+    }
+
+    fn read(&self, device: &Device) -> Option<(f64, f64)> {
+        let slice = self.readback_buffer.slice(..);
+        slice.map_async(MapMode::Read, |_| {});
+        device.poll(Maintain::Wait);
+        let timestamps: Vec<u64> = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+        self.readback_buffer.unmap();
+
+        let to_ms = |start: u64, end: u64| end.saturating_sub(start) as f64 * self.period as f64 / 1_000_000.0;
+        match timestamps.as_slice() {
+            [start, model, shadow] => Some((to_ms(*start, *model), to_ms(*model, *shadow))),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn create_white_texture(device: &Device, queue: &Queue) -> WgpuTexture {
+    let size = Extent3d {
+        width: 1,
+        height: 1,
+        depth_or_array_layers: 1,
+    };
+    let texture = device.create_texture(&TextureDescriptor {
+        label: Some("Benchmark White Texture"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: TextureFormat::Rgba8UnormSrgb,
+        usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    queue.write_texture(
+        ImageCopyTexture {
+            texture: &texture,
+            mip_level: 0,
+            origin: Origin3d::ZERO,
+            aspect: TextureAspect::All,
+        },
+        &[255, 255, 255, 255],
+        ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(4),
+            rows_per_image: Some(1),
+        },
+        size,
+    );
+    let view = texture.create_view(&TextureViewDescriptor::default());
+    let sampler = device.create_sampler(&SamplerDescriptor::default());
+    WgpuTexture {
+        texture,
+        view,
+        sampler,
+    }
+}
+
+pub(crate) fn create_headless_device() -> Result<(Arc<Device>, Arc<Queue>, AdapterInfo, bool), String> {
+    let instance = Instance::new(InstanceDescriptor {
+        backends: Backends::all(),
+        ..Default::default()
+    });
+
+    let adapter = pollster::block_on(request_adapter_with_fallback(&instance, None))?;
+
+    let timestamp_features = Features::TIMESTAMP_QUERY | Features::TIMESTAMP_QUERY_INSIDE_ENCODERS;
+    let timestamps = adapter.features().contains(timestamp_features);
+
+    let (device, queue) = pollster::block_on(adapter.request_device(
+        &DeviceDescriptor {
+            required_features: if timestamps { timestamp_features } else { Features::empty() },
+            required_limits: required_limits(&adapter),
+            label: Some("Benchmark Device"),
+        },
+        None,
+    ))
+    .map_err(|e| format!("Failed to create device: {:?}", e))?;
+
+    Ok((Arc::new(device), Arc::new(queue), adapter.get_info(), timestamps))
+}
+
+fn benchmark_lights(count: usize, radius: f32) -> Vec<(Vec3, Vec3, f32)> {
+    (0..count)
+        .map(|i| {
+            let angle = i as f32 / count.max(1) as f32 * std::f32::consts::TAU;
+            (
+                Vec3::new(angle.cos() * radius, radius, angle.sin() * radius),
+                Vec3::ONE,
+                radius * 4.0,
+            )
+        })
+        .collect()
+}
+
+pub fn run_benchmark(config: &BenchmarkConfig) -> Result<BenchmarkReport, String> {
+    let model = MD3Model::load(&config.model_path)
+        .map_err(|e| format!("Failed to load {}: {}", config.model_path.display(), e))?;
+    let (device, queue, adapter_info, timestamps) = create_headless_device()?;
+
+    let mut md3_renderer = MD3Renderer::new(device.clone(), queue.clone());
+    md3_renderer.create_pipeline(COLOR_FORMAT);
+    md3_renderer
+        .model_textures
+        .insert(BENCHMARK_TEXTURE_KEY.to_string(), create_white_texture(&device, &queue));
+    let texture_paths = vec![Some(BENCHMARK_TEXTURE_KEY.to_string()); model.meshes.len()];
+
+    let size = Extent3d {
+        width: config.width.max(1),
+        height: config.height.max(1),
+        depth_or_array_layers: 1,
+    };
+    let color_texture = device.create_texture(&TextureDescriptor {
+        label: Some("Benchmark Color Target"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: COLOR_FORMAT,
+        usage: TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let depth_texture = device.create_texture(&TextureDescriptor {
+        label: Some("Benchmark Depth Target"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: DEPTH_FORMAT,
+        usage: TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let color_view = color_texture.create_view(&TextureViewDescriptor::default());
+    let depth_view = depth_texture.create_view(&TextureViewDescriptor::default());
+
+    let height = model.get_height(0).max(1.0);
+    let model_matrix = Mat4::from_mat3(Mat3::from_rotation_x(-std::f32::consts::FRAC_PI_2));
+    let lights = benchmark_lights(config.lights, height * 2.0);
+    let camera_pos = Vec3::new(0.0, height * 0.75, height * 2.5);
+    let view = Mat4::look_at_rh(camera_pos, Vec3::new(0.0, height * 0.5, 0.0), Vec3::Y);
+    let proj = Mat4::perspective_rh(std::f32::consts::FRAC_PI_4, size.width as f32 / size.height as f32, 0.1, 1000.0);
+    let view_proj = proj * view;
+    let num_frames = model.header.num_bone_frames.max(1) as usize;
+
+    let gpu_timer = if timestamps { Some(GpuTimer::new(&device, &queue)) } else { None };
+
+    let mut cpu_frame = Vec::with_capacity(config.frames);
+    let mut cpu_model_pass = Vec::with_capacity(config.frames);
+    let mut cpu_shadow_pass = Vec::with_capacity(config.frames);
+    let mut gpu_model_pass = Vec::with_capacity(config.frames);
+    let mut gpu_shadow_pass = Vec::with_capacity(config.frames);
+
+    for frame in 0..config.warmup_frames + config.frames {
+        let frame_idx = frame % num_frames;
+        let frame_start = Instant::now();
+
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("Benchmark Encoder"),
+        });
+        {
+            let _pass = encoder.begin_render_pass(&RenderPassDescriptor {
+                label: Some("Benchmark Clear Pass"),
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: &color_view,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Clear(Color::BLACK),
+                        store: StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                    view: &depth_view,
+                    depth_ops: Some(Operations {
+                        load: LoadOp::Clear(1.0),
+                        store: StoreOp::Store,
+                    }),
+                    stencil_ops: Some(Operations {
+                        load: LoadOp::Clear(0),
+                        store: StoreOp::Store,
+                    }),
+                }),
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+        }
+
+        if let Some(ref timer) = gpu_timer {
+            encoder.write_timestamp(&timer.query_set, 0);
+        }
+
+        let model_start = Instant::now();
+        md3_renderer.render_model(
+            &mut encoder,
+            &color_view,
+            &depth_view,
+            COLOR_FORMAT,
+            &model,
+            frame_idx as f32,
+            &texture_paths,
+            model_matrix,
+            view_proj,
+            camera_pos,
+            &lights,
+            0.3,
+            false,
+        );
+        let model_time = model_start.elapsed().as_secs_f64() * 1000.0;
+
+        if let Some(ref timer) = gpu_timer {
+            encoder.write_timestamp(&timer.query_set, 1);
+        }
+
+        let shadow_start = Instant::now();
+        let shadow_models = [(&model, frame_idx, model_matrix)];
+        match config.shadows {
+            BenchmarkShadows::None => {}
+            BenchmarkShadows::Planar => {
+                let target = RenderTarget { color: &color_view, depth: &depth_view, format: COLOR_FORMAT };
+                md3_renderer.render_planar_shadows(&mut encoder, target, view_proj, &shadow_models, &lights);
+            }
+            BenchmarkShadows::Volumes => {
+                md3_renderer.render_shadow_volumes(&mut encoder, &color_view, &depth_view, view_proj, &shadow_models, &lights);
+            }
+        }
+        let shadow_time = shadow_start.elapsed().as_secs_f64() * 1000.0;
+
+        if let Some(ref timer) = gpu_timer {
+            encoder.write_timestamp(&timer.query_set, 2);
+            timer.resolve(&mut encoder);
+        }
+
+        queue.submit(Some(encoder.finish()));
+        device.poll(Maintain::Wait);
+        let gpu_times = gpu_timer.as_ref().and_then(|timer| timer.read(&device));
+        let frame_time = frame_start.elapsed().as_secs_f64() * 1000.0;
+
+        if frame < config.warmup_frames {
+            continue;
+        }
+
+        cpu_frame.push(frame_time);
+        cpu_model_pass.push(model_time);
+        cpu_shadow_pass.push(shadow_time);
+        if let Some((model_ms, shadow_ms)) = gpu_times {
+            gpu_model_pass.push(model_ms);
+            gpu_shadow_pass.push(shadow_ms);
+        }
+    }
+
+    let gpu_stats = |samples: &[f64]| {
+        if samples.is_empty() {
+            None
+        } else {
+            Some(TimingStats::from_samples(samples))
+        }
+    };
+
+    Ok(BenchmarkReport {
+        adapter: format!("{} ({:?})", adapter_info.name, adapter_info.backend),
+        frames: config.frames,
+        cpu_frame: TimingStats::from_samples(&cpu_frame),
+        cpu_model_pass: TimingStats::from_samples(&cpu_model_pass),
+        cpu_shadow_pass: TimingStats::from_samples(&cpu_shadow_pass),
+        gpu_model_pass: gpu_stats(&gpu_model_pass),
+        gpu_shadow_pass: gpu_stats(&gpu_shadow_pass),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::md3::tests::md3_bytes;
+
+    #[test]
+    fn benchmark_renders_a_few_frames_headless() {
+        if create_headless_device().is_err() {
+            eprintln!("No adapter available, skipping");
+            return;
+        }
+        let model_path = std::env::temp_dir().join(format!("sas2_benchmark_{}.md3", std::process::id()));
+        std::fs::write(&model_path, md3_bytes(3, &[[0, 1, 2]])).unwrap();
+        let config = BenchmarkConfig {
+            model_path: model_path.clone(),
+            frames: 3,
+            warmup_frames: 1,
+            width: 64,
+            height: 64,
+            lights: 1,
+            shadows: BenchmarkShadows::None,
+        };
+        let report = run_benchmark(&config);
+        let _ = std::fs::remove_file(&model_path);
+
+        let report = report.unwrap();
+        assert_eq!(report.frames, 3);
+        assert!(!report.adapter.is_empty());
+        assert!(report.cpu_frame.max >= report.cpu_frame.p50);
+    }
+}
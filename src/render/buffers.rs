@@ -1,14 +1,18 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
 use std::sync::Arc;
 use wgpu::*;
 use wgpu::util::DeviceExt;
 use glam::{Mat4, Vec3};
-use crate::engine::md3::MD3Model;
+use crate::engine::math::{transform_aabb, Frustum};
+use crate::engine::md3::{FrameBlend, MD3Model, ModelId, PoseBlend};
 use crate::render::types::*;
+use crate::resource_path::{extension_candidates, strip_resources_prefix, IMAGE_EXTENSIONS};
 
 #[derive(Hash, PartialEq, Eq, Clone)]
 pub struct BufferCacheKey {
-    pub model_id: usize,
+    pub model_id: ModelId,
+    pub lod: usize,
     pub mesh_idx: usize,
     pub frame_idx: usize,
 }
@@ -19,16 +23,198 @@ pub struct CachedBuffers {
     pub num_indices: u32,
 }
 
+pub const DEFAULT_BUFFER_CACHE_CAPACITY: usize = 512;
+
+pub type BufferCache = LruCache<BufferCacheKey, CachedBuffers>;
+pub type FrameCache = LruCache<BufferCacheKey, Arc<Vec<VertexData>>>;
+
+// Map with a size cap; inserting past it evicts the least recently used entry
+pub struct LruCache<K, V> {
+    entries: HashMap<K, (V, u64)>,
+    capacity: usize,
+    tick: u64,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            capacity: capacity.max(1),
+            tick: 0,
+        }
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.entries.get_mut(key).map(|(value, last_used)| {
+            *last_used = tick;
+            &*value
+        })
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        if !self.entries.contains_key(&key) {
+            self.evict_to(self.capacity - 1);
+        }
+        self.tick += 1;
+        self.entries.insert(key, (value, self.tick));
+    }
+
+    pub fn retain(&mut self, mut keep: impl FnMut(&K) -> bool) {
+        self.entries.retain(|key, _| keep(key));
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        self.evict_to(self.capacity);
+    }
+
+    // Linear scan for the oldest entry; only runs on a miss, and the cap keeps the map small
+    fn evict_to(&mut self, len: usize) {
+        while self.entries.len() > len {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(key) => self.entries.remove(&key),
+                None => break,
+            };
+        }
+    }
+}
+
+// Vertex buffers for blended poses, kept across frames and refilled with queue.write_buffer.
+// Each draw in a frame takes its own slot, so two instances of one mesh don't overwrite each
+// other's vertices before the frame is submitted
+pub struct BlendBufferPool {
+    buffers: HashMap<(ModelId, usize, usize), Vec<Arc<Buffer>>>,
+    used: HashMap<(ModelId, usize, usize), usize>,
+    created: usize,
+}
+
+impl BlendBufferPool {
+    pub fn new() -> Self {
+        Self {
+            buffers: HashMap::new(),
+            used: HashMap::new(),
+            created: 0,
+        }
+    }
+
+    // Call once per frame; makes every slot available again
+    pub fn begin_frame(&mut self) {
+        self.used.clear();
+    }
+
+    pub fn clear(&mut self) {
+        self.buffers.clear();
+        self.used.clear();
+    }
+
+    pub fn clear_model(&mut self, model_id: ModelId) {
+        self.buffers.retain(|(id, _, _), _| *id != model_id);
+        self.used.retain(|(id, _, _), _| *id != model_id);
+    }
+
+    pub fn buffers_created(&self) -> usize {
+        self.created
+    }
+
+    pub fn write(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        key: (ModelId, usize, usize),
+        vertices: &[VertexData],
+    ) -> Arc<Buffer> {
+        let contents: &[u8] = bytemuck::cast_slice(vertices);
+        let slot = self.used.entry(key).or_insert(0);
+        let buffers = self.buffers.entry(key).or_default();
+        if *slot == buffers.len() {
+            buffers.push(Arc::new(device.create_buffer(&BufferDescriptor {
+                label: Some("MD3 Blended Vertex Buffer"),
+                size: contents.len() as u64,
+                usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })));
+            self.created += 1;
+        }
+        let buffer = buffers[*slot].clone();
+        *slot += 1;
+        queue.write_buffer(&buffer, 0, contents);
+        buffer
+    }
+}
+
+impl Default for BlendBufferPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Everything prepare_mesh_data keeps between draws
+pub struct MeshCaches {
+    pub buffers: BufferCache,
+    pub frames: FrameCache,
+    pub blends: BlendBufferPool,
+    // Texture paths already reported missing, so each is only logged once
+    pub missing_textures: HashSet<String>,
+}
+
+impl MeshCaches {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            buffers: BufferCache::new(capacity),
+            frames: FrameCache::new(capacity),
+            blends: BlendBufferPool::new(),
+            missing_textures: HashSet::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.buffers.clear();
+        self.frames.clear();
+        self.blends.clear();
+        self.missing_textures.clear();
+    }
+
+    pub fn clear_model(&mut self, model_id: ModelId) {
+        self.buffers.retain(|key| key.model_id != model_id);
+        self.frames.retain(|key| key.model_id != model_id);
+        self.blends.clear_model(model_id);
+    }
+}
+
 pub fn get_or_create_buffers(
-    buffer_cache: &mut HashMap<BufferCacheKey, CachedBuffers>,
+    buffer_cache: &mut BufferCache,
     device: &Device,
     model: &MD3Model,
+    lod: usize,
     mesh_idx: usize,
     frame_idx: usize,
 ) -> Option<(Arc<Buffer>, Arc<Buffer>, u32)> {
-    let model_id = std::ptr::addr_of!(*model) as usize;
     let key = BufferCacheKey {
-        model_id,
+        model_id: model.id,
+        lod,
         mesh_idx,
         frame_idx,
     };
@@ -37,7 +223,7 @@ pub fn get_or_create_buffers(
         return Some((cached.vertex_buffer.clone(), cached.index_buffer.clone(), cached.num_indices));
     }
     
-    let (vertex_buffer, index_buffer, num_indices) = create_buffers_internal(device, model, mesh_idx, frame_idx)?;
+    let (vertex_buffer, index_buffer, num_indices) = create_buffers_internal(device, model, lod, mesh_idx, frame_idx)?;
     let cached = CachedBuffers {
         vertex_buffer: Arc::new(vertex_buffer),
         index_buffer: Arc::new(index_buffer),
@@ -48,24 +234,15 @@ pub fn get_or_create_buffers(
     Some(result)
 }
 
-pub fn create_buffers_internal(
-    device: &Device,
+pub fn decode_frame_vertices(
     model: &MD3Model,
+    lod: usize,
     mesh_idx: usize,
     frame_idx: usize,
-) -> Option<(Buffer, Buffer, u32)> {
-    if mesh_idx >= model.meshes.len() {
-        return None;
-    }
-    
-    let mesh = &model.meshes[mesh_idx];
-    if frame_idx >= mesh.vertices.len() {
-        return None;
-    }
-    
-    let frame_vertices = &mesh.vertices[frame_idx];
-    let mut vertices = Vec::new();
-    let mut indices = Vec::new();
+) -> Option<Vec<VertexData>> {
+    let mesh = model.lod_meshes(lod).get(mesh_idx)?;
+    let frame_vertices = mesh.vertices.get(frame_idx)?;
+    let mut vertices = Vec::with_capacity(frame_vertices.len());
 
     for (i, vertex) in frame_vertices.iter().enumerate() {
         let vertex_data = vertex.vertex;
@@ -73,13 +250,6 @@ pub fn create_buffers_internal(
         let y = vertex_data[1] as f32 * (1.0 / 64.0);
         let z = vertex_data[2] as f32 * (1.0 / 64.0);
 
-        let normal_encoded = vertex.normal;
-        let lat = ((normal_encoded >> 8) & 0xFF) as f32 * 2.0 * std::f32::consts::PI / 255.0;
-        let lng = (normal_encoded & 0xFF) as f32 * 2.0 * std::f32::consts::PI / 255.0;
-        let nx = lat.cos() * lng.sin();
-        let ny = lat.sin() * lng.sin();
-        let nz = lng.cos();
-
         let tex_coord = if i < mesh.tex_coords.len() {
             mesh.tex_coords[i].coord
         } else {
@@ -90,30 +260,225 @@ pub fn create_buffers_internal(
             position: [x, y, z],
             uv: [tex_coord[0], tex_coord[1]],
             color: [1.0, 1.0, 1.0, 1.0],
-            normal: [nx, ny, nz],
+            normal: vertex.normal_f32,
         });
     }
 
+    Some(vertices)
+}
+
+pub fn mesh_edge_indices(model: &MD3Model, lod: usize, mesh_idx: usize) -> Option<Vec<u16>> {
+    let mesh = model.lod_meshes(lod).get(mesh_idx)?;
+    let mut seen = HashSet::with_capacity(mesh.triangles.len() * 3);
+    let mut indices = Vec::with_capacity(mesh.triangles.len() * 6);
+    for triangle in &mesh.triangles {
+        for edge in 0..3 {
+            let a = triangle.vertex[edge] as u16;
+            let b = triangle.vertex[(edge + 1) % 3] as u16;
+            if seen.insert((a.min(b), a.max(b))) {
+                indices.push(a);
+                indices.push(b);
+            }
+        }
+    }
+    Some(indices)
+}
+
+pub fn get_or_create_edge_buffer(
+    edge_cache: &mut HashMap<(ModelId, usize, usize), (Arc<Buffer>, u32)>,
+    device: &Device,
+    model: &MD3Model,
+    lod: usize,
+    mesh_idx: usize,
+) -> Option<(Arc<Buffer>, u32)> {
+    let key = (model.id, lod, mesh_idx);
+    if let Some((buffer, num_indices)) = edge_cache.get(&key) {
+        return Some((buffer.clone(), *num_indices));
+    }
+
+    let indices = mesh_edge_indices(model, lod, mesh_idx)?;
+    if indices.is_empty() {
+        return None;
+    }
+    let buffer = Arc::new(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("MD3 Edge Index Buffer"),
+        contents: bytemuck::cast_slice(&indices),
+        usage: BufferUsages::INDEX,
+    }));
+    let num_indices = indices.len() as u32;
+    edge_cache.insert(key, (buffer.clone(), num_indices));
+    Some((buffer, num_indices))
+}
+
+pub fn mesh_indices(model: &MD3Model, lod: usize, mesh_idx: usize) -> Option<Vec<u16>> {
+    let mesh = model.lod_meshes(lod).get(mesh_idx)?;
+    let mut indices = Vec::with_capacity(mesh.triangles.len() * 3);
     for triangle in &mesh.triangles {
         indices.push(triangle.vertex[0] as u16);
         indices.push(triangle.vertex[1] as u16);
         indices.push(triangle.vertex[2] as u16);
     }
-    
+    Some(indices)
+}
+
+fn slerp_normal(a: Vec3, b: Vec3, t: f32) -> Vec3 {
+    let dot = a.dot(b).clamp(-1.0, 1.0);
+    let theta = dot.acos();
+    if theta.abs() < 1e-4 || (std::f32::consts::PI - theta).abs() < 1e-4 {
+        return a.lerp(b, t).normalize_or_zero();
+    }
+    let sin_theta = theta.sin();
+    let wa = ((1.0 - t) * theta).sin() / sin_theta;
+    let wb = (t * theta).sin() / sin_theta;
+    a * wa + b * wb
+}
+
+pub fn interpolate_vertices(a: &[VertexData], b: &[VertexData], t: f32) -> Vec<VertexData> {
+    a.iter()
+        .zip(b.iter())
+        .map(|(va, vb)| {
+            let position = Vec3::from(va.position).lerp(Vec3::from(vb.position), t);
+            let normal = slerp_normal(Vec3::from(va.normal), Vec3::from(vb.normal), t);
+            VertexData {
+                position: position.to_array(),
+                uv: va.uv,
+                color: va.color,
+                normal: normal.to_array(),
+            }
+        })
+        .collect()
+}
+
+pub fn create_buffers_internal(
+    device: &Device,
+    model: &MD3Model,
+    lod: usize,
+    mesh_idx: usize,
+    frame_idx: usize,
+) -> Option<(Buffer, Buffer, u32)> {
+    let vertices = decode_frame_vertices(model, lod, mesh_idx, frame_idx)?;
+    let indices = mesh_indices(model, lod, mesh_idx)?;
+    let (vertex_buffer, index_buffer) = upload_mesh(device, &vertices, &indices);
+    Some((vertex_buffer, index_buffer, indices.len() as u32))
+}
+
+pub fn create_buffers_interpolated(
+    device: &Device,
+    model: &MD3Model,
+    lod: usize,
+    mesh_idx: usize,
+    frame_a: usize,
+    frame_b: usize,
+    t: f32,
+) -> Option<(Buffer, Buffer, u32)> {
+    let a = decode_frame_vertices(model, lod, mesh_idx, frame_a)?;
+    let b = decode_frame_vertices(model, lod, mesh_idx, frame_b)?;
+    let vertices = interpolate_vertices(&a, &b, t);
+    let indices = mesh_indices(model, lod, mesh_idx)?;
+    let (vertex_buffer, index_buffer) = upload_mesh(device, &vertices, &indices);
+    Some((vertex_buffer, index_buffer, indices.len() as u32))
+}
+
+fn upload_mesh(device: &Device, vertices: &[VertexData], indices: &[u16]) -> (Buffer, Buffer) {
     let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
         label: Some("MD3 Vertex Buffer"),
-        contents: bytemuck::cast_slice(&vertices),
+        contents: bytemuck::cast_slice(vertices),
         usage: BufferUsages::VERTEX,
     });
-    
+
     let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
         label: Some("MD3 Index Buffer"),
-        contents: bytemuck::cast_slice(&indices),
+        contents: bytemuck::cast_slice(indices),
         usage: BufferUsages::INDEX,
     });
-    
-    let num_indices = indices.len() as u32;
-    
+
+    (vertex_buffer, index_buffer)
+}
+
+fn get_or_decode_frame(
+    frame_cache: &mut FrameCache,
+    model: &MD3Model,
+    lod: usize,
+    mesh_idx: usize,
+    frame_idx: usize,
+) -> Option<Arc<Vec<VertexData>>> {
+    let key = BufferCacheKey {
+        model_id: model.id,
+        lod,
+        mesh_idx,
+        frame_idx,
+    };
+    if let Some(cached) = frame_cache.get(&key) {
+        return Some(cached.clone());
+    }
+    let vertices = Arc::new(decode_frame_vertices(model, lod, mesh_idx, frame_idx)?);
+    frame_cache.insert(key, vertices.clone());
+    Some(vertices)
+}
+
+pub fn get_or_create_interpolated_buffers(
+    caches: &mut MeshCaches,
+    device: &Device,
+    queue: &Queue,
+    model: &MD3Model,
+    lod: usize,
+    mesh_idx: usize,
+    blend: FrameBlend,
+) -> Option<(Arc<Buffer>, Arc<Buffer>, u32)> {
+    let num_frames = model.lod_meshes(lod).get(mesh_idx)?.vertices.len();
+    let FrameBlend { from, to, t } = blend.clamped(num_frames);
+    let (vertex_buffer, index_buffer, num_indices) =
+        get_or_create_buffers(&mut caches.buffers, device, model, lod, mesh_idx, from)?;
+    if from == to || t <= f32::EPSILON {
+        return Some((vertex_buffer, index_buffer, num_indices));
+    }
+
+    let a = get_or_decode_frame(&mut caches.frames, model, lod, mesh_idx, from)?;
+    let b = get_or_decode_frame(&mut caches.frames, model, lod, mesh_idx, to)?;
+    let vertices = interpolate_vertices(&a, &b, t);
+    let vertex_buffer = caches.blends.write(device, queue, (model.id, lod, mesh_idx), &vertices);
+    Some((vertex_buffer, index_buffer, num_indices))
+}
+
+fn interpolated_frame_vertices(
+    frame_cache: &mut FrameCache,
+    model: &MD3Model,
+    lod: usize,
+    mesh_idx: usize,
+    blend: FrameBlend,
+) -> Option<Vec<VertexData>> {
+    let a = get_or_decode_frame(frame_cache, model, lod, mesh_idx, blend.from)?;
+    if blend.from == blend.to || blend.t <= f32::EPSILON {
+        return Some(a.as_ref().clone());
+    }
+    let b = get_or_decode_frame(frame_cache, model, lod, mesh_idx, blend.to)?;
+    Some(interpolate_vertices(&a, &b, blend.t))
+}
+
+pub fn get_or_create_pose_buffers(
+    caches: &mut MeshCaches,
+    device: &Device,
+    queue: &Queue,
+    model: &MD3Model,
+    lod: usize,
+    mesh_idx: usize,
+    pose: PoseBlend,
+) -> Option<(Arc<Buffer>, Arc<Buffer>, u32)> {
+    if pose.a == pose.b || pose.weight <= f32::EPSILON {
+        return get_or_create_interpolated_buffers(caches, device, queue, model, lod, mesh_idx, pose.a);
+    }
+    if pose.weight >= 1.0 - f32::EPSILON {
+        return get_or_create_interpolated_buffers(caches, device, queue, model, lod, mesh_idx, pose.b);
+    }
+
+    let num_frames = model.lod_meshes(lod).get(mesh_idx)?.vertices.len();
+    let pose = pose.clamped(num_frames);
+    let (_, index_buffer, num_indices) =
+        get_or_create_buffers(&mut caches.buffers, device, model, lod, mesh_idx, pose.a.from)?;
+    let a = interpolated_frame_vertices(&mut caches.frames, model, lod, mesh_idx, pose.a)?;
+    let b = interpolated_frame_vertices(&mut caches.frames, model, lod, mesh_idx, pose.b)?;
+    let vertices = interpolate_vertices(&a, &b, pose.weight);
+    let vertex_buffer = caches.blends.write(device, queue, (model.id, lod, mesh_idx), &vertices);
     Some((vertex_buffer, index_buffer, num_indices))
 }
 
@@ -147,7 +512,8 @@ pub fn create_uniforms(
         lights: light_data,
         num_lights: lights.len().min(MAX_LIGHTS) as i32,
         ambient_light,
-        _padding: [0.0; 2],
+        render_mode: 0,
+        reflectivity: 0.0,
     }
 }
 
@@ -155,49 +521,103 @@ pub fn update_uniform_buffer(queue: &Queue, uniforms: &MD3Uniforms, buffer: &Buf
     queue.write_buffer(buffer, 0, bytemuck::cast_slice(&[*uniforms]));
 }
 
+// Keys are stored without the resources prefix, and skins often name a .tga that was
+// shipped as .png or .jpg, so every image extension is tried
 pub fn find_texture<'a>(
     model_textures: &'a HashMap<String, WgpuTexture>,
     path: &str,
 ) -> Option<&'a WgpuTexture> {
-    let mut alt_paths = vec![
-        path.to_string(),
-        format!("../{}", path),
-        path.replace("../", ""),
-    ];
-    
-    if path.ends_with(".TGA") {
-        let png_path = path.replace(".TGA", ".png");
-        alt_paths.push(png_path.clone());
-        alt_paths.push(format!("../{}", png_path));
-        alt_paths.push(path.replace(".TGA", ".jpg"));
-        alt_paths.push(format!("../{}", path.replace(".TGA", ".jpg")));
-    } else if path.ends_with(".tga") {
-        alt_paths.push(path.replace(".tga", ".png"));
-        alt_paths.push(path.replace(".tga", ".jpg"));
-        alt_paths.push(format!("../{}", path.replace(".tga", ".png")));
-        alt_paths.push(format!("../{}", path.replace(".tga", ".jpg")));
-    } else if path.ends_with(".png") {
-        alt_paths.push(path.replace(".png", ".tga"));
-        alt_paths.push(path.replace(".png", ".jpg"));
-        alt_paths.push(format!("../{}", path.replace(".png", ".tga")));
-        alt_paths.push(format!("../{}", path.replace(".png", ".jpg")));
-    } else if path.ends_with(".jpg") {
-        alt_paths.push(path.replace(".jpg", ".tga"));
-        alt_paths.push(path.replace(".jpg", ".png"));
-        alt_paths.push(format!("../{}", path.replace(".jpg", ".tga")));
-        alt_paths.push(format!("../{}", path.replace(".jpg", ".png")));
+    let relative = strip_resources_prefix(path);
+    // Keys without an extension, such as generated textures, only match exactly
+    if let Some(texture) = model_textures.get(&relative) {
+        return Some(texture);
     }
-    
-    for alt_path in &alt_paths {
-        if let Some(tex) = model_textures.get(alt_path) {
-            return Some(tex);
+    extension_candidates(&relative, IMAGE_EXTENSIONS)
+        .iter()
+        .find_map(|candidate| model_textures.get(candidate.to_str()?))
+}
+
+// One MD3Uniforms-sized window at the start of `buffer`, moved by the draw's dynamic offset
+pub fn uniform_slot(buffer: &Buffer) -> BindingResource<'_> {
+    BindingResource::Buffer(BufferBinding {
+        buffer,
+        offset: 0,
+        size: std::num::NonZeroU64::new(std::mem::size_of::<MD3Uniforms>() as u64),
+    })
+}
+
+// Stride between packed MD3Uniforms, rounded up to the device's dynamic offset alignment
+pub fn uniform_stride(device: &Device) -> u64 {
+    let size = std::mem::size_of::<MD3Uniforms>() as u64;
+    let align = device.limits().min_uniform_buffer_offset_alignment.max(1) as u64;
+    size.div_ceil(align) * align
+}
+
+const UNIFORM_ARENA_CHUNK_SIZE: u64 = 64 * 1024;
+
+// Per-frame linear allocator over reused uniform buffers. Regions handed out since the last
+// reset never overlap, so queue writes can't clobber data an earlier draw this frame still needs
+pub struct UniformArena {
+    chunks: Vec<Arc<Buffer>>,
+    chunk: usize,
+    cursor: u64,
+    buffers_created: usize,
+}
+
+impl UniformArena {
+    pub fn new() -> Self {
+        Self {
+            chunks: Vec::new(),
+            chunk: 0,
+            cursor: 0,
+            buffers_created: 0,
         }
     }
-    
-    println!("Warning: texture not found in HashMap for path: {:?}", path);
-    println!("Tried paths: {:?}", alt_paths);
-    println!("Available texture keys: {:?}", model_textures.keys().collect::<Vec<_>>());
-    None
+
+    pub fn reset(&mut self) {
+        self.chunk = 0;
+        self.cursor = 0;
+    }
+
+    // Returns the buffer and the offset the contents were written at, aligned to `align`
+    pub fn push(&mut self, device: &Device, queue: &Queue, contents: &[u8], align: u64) -> (Arc<Buffer>, u64) {
+        let size = (contents.len() as u64).div_ceil(COPY_BUFFER_ALIGNMENT) * COPY_BUFFER_ALIGNMENT;
+        loop {
+            let offset = self.cursor.div_ceil(align) * align;
+            match self.chunks.get(self.chunk) {
+                Some(buffer) if offset + size <= buffer.size() => {
+                    let buffer = buffer.clone();
+                    queue.write_buffer(&buffer, offset, contents);
+                    self.cursor = offset + size;
+                    return (buffer, offset);
+                }
+                Some(_) => {
+                    self.chunk += 1;
+                    self.cursor = 0;
+                }
+                None => {
+                    self.chunks.push(Arc::new(device.create_buffer(&BufferDescriptor {
+                        label: Some("Uniform Arena Chunk"),
+                        size: size.max(UNIFORM_ARENA_CHUNK_SIZE),
+                        usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                        mapped_at_creation: false,
+                    })));
+                    self.buffers_created += 1;
+                }
+            }
+        }
+    }
+
+    // Only grows while the per-frame working set is still increasing
+    pub fn buffers_created(&self) -> usize {
+        self.buffers_created
+    }
+}
+
+impl Default for UniformArena {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 pub fn create_mesh_bind_groups(
@@ -214,7 +634,7 @@ pub fn create_mesh_bind_groups(
         entries: &[
             BindGroupEntry {
                 binding: 0,
-                resource: uniform_buffer.as_entire_binding(),
+                resource: uniform_slot(uniform_buffer),
             },
             BindGroupEntry {
                 binding: 1,
@@ -234,7 +654,7 @@ pub fn create_mesh_bind_groups(
             entries: &[
                 BindGroupEntry {
                     binding: 0,
-                    resource: shadow_uniform_buffer.unwrap().as_entire_binding(),
+                    resource: uniform_slot(shadow_uniform_buffer.unwrap()),
                 },
                 BindGroupEntry {
                     binding: 1,
@@ -253,27 +673,75 @@ pub fn create_mesh_bind_groups(
     (bind_group, shadow_bind_group)
 }
 
+// Whole-model test against the frame boxes of every frame the pose touches, ahead of per-mesh culling
+pub fn model_visible(model: &MD3Model, pose: PoseBlend, frustum: &Frustum, model_matrix: Mat4) -> bool {
+    let pose = pose.clamped(model.frames.len().max(1));
+    let (min, max) = [pose.a.from, pose.a.to, pose.b.from, pose.b.to]
+        .iter()
+        .map(|&frame| model.frame_bounds(frame))
+        .reduce(|(min_a, max_a), (min_b, max_b)| (min_a.min(min_b), max_a.max(max_b)))
+        .unwrap_or((Vec3::ZERO, Vec3::ZERO));
+    let (min, max) = transform_aabb(model_matrix, min, max);
+    frustum.intersects_aabb(min, max)
+}
+
+// A blended pose can reach any of its source frames, so the mesh stays if any of their spheres is visible
+pub fn mesh_visible(model: &MD3Model, lod: usize, mesh_idx: usize, pose: PoseBlend, frustum: &Frustum, model_matrix: Mat4) -> bool {
+    let mesh = match model.lod_meshes(lod).get(mesh_idx) {
+        Some(mesh) => mesh,
+        None => return false,
+    };
+    let pose = pose.clamped(mesh.vertices.len());
+    let scale = model_matrix.x_axis.truncate().length()
+        .max(model_matrix.y_axis.truncate().length())
+        .max(model_matrix.z_axis.truncate().length());
+    [pose.a.from, pose.a.to, pose.b.from, pose.b.to].iter().any(|&frame| {
+        match mesh.bounding_sphere(frame) {
+            Some((center, radius)) => frustum.contains_sphere(model_matrix.transform_point3(center), radius * scale),
+            None => true,
+        }
+    })
+}
+
+// Device state and material tables prepare_mesh_data reads but never changes
+#[derive(Clone, Copy)]
+pub struct MeshResources<'a> {
+    pub device: &'a Device,
+    pub queue: &'a Queue,
+    pub bind_group_layout: &'a BindGroupLayout,
+    pub model_textures: &'a HashMap<String, WgpuTexture>,
+    pub cutout_materials: &'a HashMap<String, CutoutMode>,
+}
+
+// A shadow slot also builds each mesh's shadow bind group
 pub fn prepare_mesh_data(
-    buffer_cache: &mut HashMap<BufferCacheKey, CachedBuffers>,
-    device: &Device,
-    bind_group_layout: &BindGroupLayout,
-    model_textures: &HashMap<String, WgpuTexture>,
-    model: &MD3Model,
-    frame_idx: usize,
-    texture_paths: &[Option<String>],
-    uniform_buffer: Arc<Buffer>,
-    shadow_uniform_buffer: Option<Arc<Buffer>>,
-    render_shadow: bool,
+    caches: &mut MeshCaches,
+    resources: MeshResources,
+    draw: &ModelDrawCall,
+    lod: usize,
+    uniforms: UniformSlot,
+    shadow: Option<UniformSlot>,
+    cull: Option<&Frustum>,
 ) -> Vec<MeshRenderData> {
+    let MeshResources { device, queue, bind_group_layout, model_textures, cutout_materials } = resources;
+    let &ModelDrawCall { model, pose, textures: texture_paths, matrix: model_matrix } = draw;
     let mut buffers_vec = Vec::new();
     
-    for (mesh_idx, _mesh) in model.meshes.iter().enumerate() {
-        let (vertex_buffer, index_buffer, num_indices) = match get_or_create_buffers(
-            buffer_cache,
+    for mesh_idx in 0..model.lod_meshes(lod).len() {
+        if let Some(frustum) = cull {
+            if !mesh_visible(model, lod, mesh_idx, pose, frustum, model_matrix) {
+                continue;
+            }
+        }
+
+        let (vertex_buffer, index_buffer, num_indices) = match get_or_create_pose_buffers(
+            caches,
             device,
+            queue,
             model,
+            lod,
             mesh_idx,
-            frame_idx,
+            pose,
         ) {
             Some(buffers) => buffers,
             None => continue,
@@ -282,26 +750,35 @@ pub fn prepare_mesh_data(
         let texture_path = texture_paths.get(mesh_idx).and_then(|p| p.as_ref().map(|s| s.clone()));
 
         if texture_path.is_some() {
-            buffers_vec.push((vertex_buffer, index_buffer, num_indices, texture_path));
+            buffers_vec.push((mesh_idx, vertex_buffer, index_buffer, num_indices, texture_path));
         }
     }
     
     let mut mesh_data = Vec::new();
-    for (vertex_buffer, index_buffer, num_indices, texture_path) in buffers_vec {
+    for (mesh_idx, vertex_buffer, index_buffer, num_indices, texture_path) in buffers_vec {
         let texture = texture_path.as_ref().and_then(|path| find_texture(model_textures, path));
+        if texture.is_none() {
+            if let Some(path) = &texture_path {
+                if caches.missing_textures.insert(path.clone()) {
+                    println!("Warning: no texture loaded for {:?}", path);
+                }
+            }
+        }
         if let Some(texture) = texture {
             let (bind_group, shadow_bind_group) = create_mesh_bind_groups(
                 device,
                 bind_group_layout,
                 texture,
-                &uniform_buffer,
-                shadow_uniform_buffer.as_ref().map(|b| b.as_ref()),
-                render_shadow,
+                &uniforms.buffer,
+                shadow.as_ref().map(|slot| slot.buffer.as_ref()),
+                shadow.is_some(),
             );
 
             let is_additive = texture_path.as_ref()
                 .map(|path| path.ends_with(".TGA"))
                 .unwrap_or(false);
+            let cutout = texture_path.as_ref()
+                .and_then(|path| cutout_materials.get(path).copied());
 
             mesh_data.push(MeshRenderData {
                 vertex_buffer,
@@ -309,9 +786,13 @@ pub fn prepare_mesh_data(
                 num_indices,
                 bind_group,
                 shadow_bind_group,
-                uniform_buffer: uniform_buffer.clone(),
-                shadow_uniform_buffer: shadow_uniform_buffer.clone(),
+                uniform_buffer: uniforms.buffer.clone(),
+                shadow_uniform_buffer: shadow.as_ref().map(|slot| slot.buffer.clone()),
                 is_additive,
+                cutout,
+                mesh_idx,
+                uniform_offset: uniforms.offset,
+                shadow_uniform_offset: shadow.as_ref().map_or(0, |slot| slot.offset),
             });
         }
     }
@@ -319,3 +800,41 @@ pub fn prepare_mesh_data(
     mesh_data
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vertex(position: [f32; 3]) -> VertexData {
+        VertexData {
+            position,
+            uv: [0.0, 0.0],
+            color: [1.0; 4],
+            normal: [0.0, 0.0, 1.0],
+        }
+    }
+
+    #[test]
+    fn face_winding_mode_sets_the_flag_the_shader_checks() {
+        use crate::engine::shaders::MD3_SHADER;
+
+        let uniforms = create_uniforms(Mat4::IDENTITY, Mat4::IDENTITY, Vec3::ZERO, &[], 0.5);
+        assert_eq!(uniforms.render_mode, RenderMode::Lit.shader_flag());
+
+        let flag = RenderMode::FaceWinding.shader_flag();
+        assert_ne!(flag, RenderMode::Lit.shader_flag());
+        assert!(MD3_SHADER.contains(&format!("uniforms.render_mode == {}u", flag)));
+    }
+
+    #[test]
+    fn blend_weight_mixes_vertex_positions() {
+        let a = [vertex([0.0, 0.0, 0.0]), vertex([2.0, 2.0, 2.0])];
+        let b = [vertex([2.0, 4.0, 6.0]), vertex([2.0, 2.0, 2.0])];
+
+        assert_eq!(interpolate_vertices(&a, &b, 0.0)[0].position, a[0].position);
+        assert_eq!(interpolate_vertices(&a, &b, 1.0)[0].position, b[0].position);
+        let half = interpolate_vertices(&a, &b, 0.5);
+        assert_eq!(half[0].position, [1.0, 2.0, 3.0]);
+        assert_eq!(half[1].position, [2.0, 2.0, 2.0]);
+    }
+}
@@ -12,8 +12,26 @@ struct Vertex {
 struct Uniforms {
     resolution: [f32; 2],
     position: [f32; 2],
+    color: [f32; 4],
+    scale: f32,
+    textured: u32,
+    _padding: [f32; 2],
 }
 
+// Q3's crosshair set; style 0 is the built-in outlined cross, styles 1.. are whichever of these load
+const CROSSHAIR_TEXTURES: [&str; 10] = [
+    "gfx/2d/crosshaira.tga",
+    "gfx/2d/crosshairb.tga",
+    "gfx/2d/crosshairc.tga",
+    "gfx/2d/crosshaird.tga",
+    "gfx/2d/crosshaire.tga",
+    "gfx/2d/crosshairf.tga",
+    "gfx/2d/crosshairg.tga",
+    "gfx/2d/crosshairh.tga",
+    "gfx/2d/crosshairi.tga",
+    "gfx/2d/crosshairj.tga",
+];
+
 const VERTICES: &[Vertex] = &[
     Vertex { position: [-1.0, -1.0] },
     Vertex { position: [1.0, -1.0] },
@@ -37,16 +55,64 @@ impl Vertex {
     }
 }
 
+// Style 0 is the built-in cross; color tints it and scale 1 draws 32px across
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CrosshairStyle {
+    pub index: usize,
+    pub color: [f32; 4],
+    pub scale: f32,
+}
+
 pub struct Crosshair {
     pipeline: RenderPipeline,
     vertex_buffer: Buffer,
     index_buffer: Buffer,
     uniform_buffer: Buffer,
-    bind_group: BindGroup,
+    bind_groups: Vec<BindGroup>,
+    format: TextureFormat,
+}
+
+fn load_crosshair_image(path: &str) -> Option<image::RgbaImage> {
+    let full_path = crate::resource_path::find_q3_resource(path)?;
+    super::texture_loader::load_rgba(full_path).ok()
+}
+
+fn create_crosshair_texture(device: &Device, queue: &Queue, width: u32, height: u32, pixels: &[u8]) -> TextureView {
+    let size = Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+    };
+    let texture = device.create_texture(&TextureDescriptor {
+        label: Some("Crosshair Texture"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: TextureFormat::Rgba8UnormSrgb,
+        usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    queue.write_texture(
+        ImageCopyTexture {
+            texture: &texture,
+            mip_level: 0,
+            origin: Origin3d::ZERO,
+            aspect: TextureAspect::All,
+        },
+        pixels,
+        ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(4 * width),
+            rows_per_image: Some(height),
+        },
+        size,
+    );
+    texture.create_view(&TextureViewDescriptor::default())
 }
 
 impl Crosshair {
-    pub fn new(device: &Device, format: TextureFormat) -> Self {
+    pub fn new(device: &Device, queue: &Queue, format: TextureFormat) -> Self {
         let shader = device.create_shader_module(ShaderModuleDescriptor {
             label: Some("Crosshair Shader"),
             source: ShaderSource::Wgsl(include_str!("../shaders/crosshair.wgsl").into()),
@@ -55,6 +121,10 @@ impl Crosshair {
         let uniforms = Uniforms {
             resolution: [1280.0, 720.0],
             position: [640.0, 360.0],
+            color: [1.0, 1.0, 1.0, 1.0],
+            scale: 1.0,
+            textured: 0,
+            _padding: [0.0; 2],
         };
 
         let uniform_buffer = device.create_buffer_init(&util::BufferInitDescriptor {
@@ -65,27 +135,76 @@ impl Crosshair {
 
         let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
             label: Some("Crosshair Bind Group Layout"),
-            entries: &[BindGroupLayoutEntry {
-                binding: 0,
-                visibility: ShaderStages::FRAGMENT,
-                ty: BindingType::Buffer {
-                    ty: BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
                 },
-                count: None,
-            }],
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
         });
 
-        let bind_group = device.create_bind_group(&BindGroupDescriptor {
-            label: Some("Crosshair Bind Group"),
-            layout: &bind_group_layout,
-            entries: &[BindGroupEntry {
-                binding: 0,
-                resource: uniform_buffer.as_entire_binding(),
-            }],
+        let sampler = device.create_sampler(&SamplerDescriptor {
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..Default::default()
         });
 
+        // The built-in style still needs something bound at the texture slot
+        let mut views = vec![create_crosshair_texture(device, queue, 1, 1, &[255, 255, 255, 255])];
+        for path in CROSSHAIR_TEXTURES {
+            if let Some(img) = load_crosshair_image(path) {
+                views.push(create_crosshair_texture(device, queue, img.width(), img.height(), &img));
+            }
+        }
+
+        let bind_groups = views
+            .iter()
+            .map(|view| {
+                device.create_bind_group(&BindGroupDescriptor {
+                    label: Some("Crosshair Bind Group"),
+                    layout: &bind_group_layout,
+                    entries: &[
+                        BindGroupEntry {
+                            binding: 0,
+                            resource: uniform_buffer.as_entire_binding(),
+                        },
+                        BindGroupEntry {
+                            binding: 1,
+                            resource: BindingResource::TextureView(view),
+                        },
+                        BindGroupEntry {
+                            binding: 2,
+                            resource: BindingResource::Sampler(&sampler),
+                        },
+                    ],
+                })
+            })
+            .collect();
+
         let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
             label: Some("Crosshair Pipeline Layout"),
             bind_group_layouts: &[&bind_group_layout],
@@ -142,23 +261,36 @@ impl Crosshair {
             vertex_buffer,
             index_buffer,
             uniform_buffer,
-            bind_group,
+            bind_groups,
+            format,
         }
     }
 
+    pub fn style_count(&self) -> usize {
+        self.bind_groups.len()
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
     pub fn render(
         &self,
         encoder: &mut CommandEncoder,
         view: &TextureView,
         queue: &Queue,
-        screen_x: f32,
-        screen_y: f32,
-        width: u32,
-        height: u32,
+        position: [f32; 2],
+        viewport: (u32, u32),
+        style: CrosshairStyle,
     ) {
+        let index = style.index % self.bind_groups.len();
         let uniforms = Uniforms {
-            resolution: [width as f32, height as f32],
-            position: [screen_x, screen_y],
+            resolution: [viewport.0 as f32, viewport.1 as f32],
+            position,
+            color: style.color,
+            scale: style.scale.max(0.1),
+            textured: (index > 0) as u32,
+            _padding: [0.0; 2],
         };
 
         queue.write_buffer(
@@ -183,7 +315,7 @@ impl Crosshair {
         });
 
         render_pass.set_pipeline(&self.pipeline);
-        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_bind_group(0, &self.bind_groups[index], &[]);
         render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
         render_pass.set_index_buffer(self.index_buffer.slice(..), IndexFormat::Uint16);
         render_pass.draw_indexed(0..INDICES.len() as u32, 0, 0..1);
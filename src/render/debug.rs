@@ -3,10 +3,79 @@ use wgpu::*;
 use wgpu::util::DeviceExt;
 use glam::{Mat4, Vec3};
 use bytemuck::{Pod, Zeroable};
-use crate::render::types::VertexData;
+use crate::render::types::{RenderTarget, VertexData, DEPTH_FORMAT};
 use crate::engine::shaders::{DEBUG_LIGHT_SPHERE_SHADER, DEBUG_LIGHT_RAY_SHADER};
 use super::pipelines::*;
 
+pub const DEFAULT_DEBUG_LINE_WIDTH: f32 = 2.0;
+pub const VERTICES_PER_LINE_SEGMENT: usize = 6;
+
+#[derive(Clone, Copy, Debug)]
+pub struct LineSegment {
+    pub start: Vec3,
+    pub end: Vec3,
+    pub start_color: [f32; 4],
+    pub end_color: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct LineQuadVertex {
+    pub start: [f32; 3],
+    pub end: [f32; 3],
+    pub color: [f32; 4],
+    pub t_side: [f32; 2],
+}
+
+impl LineQuadVertex {
+    pub fn desc() -> VertexBufferLayout<'static> {
+        VertexBufferLayout {
+            array_stride: std::mem::size_of::<LineQuadVertex>() as BufferAddress,
+            step_mode: VertexStepMode::Vertex,
+            attributes: &[
+                VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: VertexFormat::Float32x3,
+                },
+                VertexAttribute {
+                    offset: 12,
+                    shader_location: 1,
+                    format: VertexFormat::Float32x3,
+                },
+                VertexAttribute {
+                    offset: 24,
+                    shader_location: 2,
+                    format: VertexFormat::Float32x4,
+                },
+                VertexAttribute {
+                    offset: 40,
+                    shader_location: 3,
+                    format: VertexFormat::Float32x2,
+                },
+            ],
+        }
+    }
+}
+
+pub fn expand_line_segments(segments: &[LineSegment]) -> Vec<LineQuadVertex> {
+    let mut vertices = Vec::with_capacity(segments.len() * VERTICES_PER_LINE_SEGMENT);
+    for segment in segments {
+        let corner = |t: f32, side: f32| LineQuadVertex {
+            start: segment.start.to_array(),
+            end: segment.end.to_array(),
+            color: if t == 0.0 { segment.start_color } else { segment.end_color },
+            t_side: [t, side],
+        };
+        let a = corner(0.0, -1.0);
+        let b = corner(0.0, 1.0);
+        let c = corner(1.0, 1.0);
+        let d = corner(1.0, -1.0);
+        vertices.extend_from_slice(&[a, b, c, a, c, d]);
+    }
+    vertices
+}
+
 pub struct DebugRenderer {
     device: Arc<Device>,
     queue: Arc<Queue>,
@@ -20,6 +89,8 @@ pub struct DebugRenderer {
     debug_sphere_index_buffer: Option<Buffer>,
     debug_sphere_instance_buffer: Option<Buffer>,
     debug_ray_vertex_buffer: Option<Buffer>,
+    line_width: f32,
+    sample_count: u32,
 }
 
 impl DebugRenderer {
@@ -28,6 +99,7 @@ impl DebugRenderer {
         queue: Arc<Queue>,
         _debug_light_sphere_bind_group_layout: &BindGroupLayout,
         _debug_light_ray_bind_group_layout: &BindGroupLayout,
+        sample_count: u32,
     ) -> Self {
         Self {
             device,
@@ -42,9 +114,19 @@ impl DebugRenderer {
             debug_sphere_index_buffer: None,
             debug_sphere_instance_buffer: None,
             debug_ray_vertex_buffer: None,
+            line_width: DEFAULT_DEBUG_LINE_WIDTH,
+            sample_count,
         }
     }
 
+    pub fn line_width(&self) -> f32 {
+        self.line_width
+    }
+
+    pub fn set_line_width(&mut self, width: f32) {
+        self.line_width = width.max(1.0);
+    }
+
     fn init_debug_light_sphere(&mut self, surface_format: TextureFormat, debug_light_sphere_bind_group_layout: &BindGroupLayout) {
         if self.debug_sphere_vertex_buffer.is_some() {
             return;
@@ -175,18 +257,18 @@ impl DebugRenderer {
             fragment: Some(FragmentState {
                 module: &shader,
                 entry_point: "fs_main",
-                targets: &[Some(create_color_target_state(surface_format))],
+                targets: &[Some(create_color_target_state(surface_format, true))],
                 compilation_options: PipelineCompilationOptions::default(),
             }),
             primitive: create_primitive_state(Some(Face::Back)),
             depth_stencil: Some(DepthStencilState {
-                format: TextureFormat::Depth24PlusStencil8,
+                format: DEPTH_FORMAT,
                 depth_write_enabled: true,
                 depth_compare: CompareFunction::LessEqual,
                 stencil: StencilState::default(),
                 bias: DepthBiasState::default(),
             }),
-            multisample: create_multisample_state(),
+            multisample: create_multisample_state_with(self.sample_count, false),
             multiview: None,
         });
 
@@ -194,13 +276,13 @@ impl DebugRenderer {
     }
 
     fn init_debug_light_ray(&mut self, surface_format: TextureFormat, debug_light_ray_bind_group_layout: &BindGroupLayout) {
-        if self.debug_ray_vertex_buffer.is_some() {
+        if self.debug_light_ray_pipeline.is_some() {
             return;
         }
 
         let uniform_buffer = self.device.create_buffer(&BufferDescriptor {
             label: Some("Debug Light Ray Uniform Buffer"),
-            size: std::mem::size_of::<[[f32; 4]; 4]>() as u64,
+            size: std::mem::size_of::<[[f32; 4]; 5]>() as u64,
             usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
@@ -234,47 +316,24 @@ impl DebugRenderer {
             vertex: VertexState {
                 module: &shader,
                 entry_point: "vs_main",
-                buffers: &[VertexBufferLayout {
-                    array_stride: std::mem::size_of::<[f32; 7]>() as BufferAddress,
-                    step_mode: VertexStepMode::Vertex,
-                    attributes: &[
-                        VertexAttribute {
-                            offset: 0,
-                            shader_location: 0,
-                            format: VertexFormat::Float32x3,
-                        },
-                        VertexAttribute {
-                            offset: std::mem::size_of::<[f32; 3]>() as BufferAddress,
-                            shader_location: 1,
-                            format: VertexFormat::Float32x4,
-                        },
-                    ],
-                }],
+                buffers: &[LineQuadVertex::desc()],
                 compilation_options: PipelineCompilationOptions::default(),
             },
             fragment: Some(FragmentState {
                 module: &shader,
                 entry_point: "fs_main",
-                targets: &[Some(create_color_target_state(surface_format))],
+                targets: &[Some(create_color_target_state(surface_format, true))],
                 compilation_options: PipelineCompilationOptions::default(),
             }),
-            primitive: PrimitiveState {
-                topology: PrimitiveTopology::LineList,
-                strip_index_format: None,
-                front_face: FrontFace::Ccw,
-                cull_mode: None,
-                polygon_mode: PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
+            primitive: create_primitive_state(None),
             depth_stencil: Some(DepthStencilState {
-                format: TextureFormat::Depth24PlusStencil8,
-                depth_write_enabled: true,
+                format: DEPTH_FORMAT,
+                depth_write_enabled: false,
                 depth_compare: CompareFunction::LessEqual,
                 stencil: StencilState::default(),
                 bias: DepthBiasState::default(),
             }),
-            multisample: create_multisample_state(),
+            multisample: create_multisample_state_with(self.sample_count, false),
             multiview: None,
         });
 
@@ -373,41 +432,35 @@ impl DebugRenderer {
     pub fn render_debug_light_rays(
         &mut self,
         encoder: &mut CommandEncoder,
-        output_view: &TextureView,
-        depth_view: &TextureView,
+        target: RenderTarget,
         view_proj: Mat4,
+        viewport: (u32, u32),
         lights: &[(Vec3, Vec3, f32)],
-        surface_format: TextureFormat,
         debug_light_ray_bind_group_layout: &BindGroupLayout,
     ) {
         if lights.is_empty() {
             return;
         }
 
-        self.init_debug_light_ray(surface_format, debug_light_ray_bind_group_layout);
+        self.init_debug_light_ray(target.format, debug_light_ray_bind_group_layout);
 
         #[repr(C)]
         #[derive(Copy, Clone, Pod, Zeroable)]
         struct DebugLightRayUniforms {
             view_proj: [[f32; 4]; 4],
+            viewport_line_width: [f32; 4],
         }
 
         let uniforms = DebugLightRayUniforms {
             view_proj: view_proj.to_cols_array_2d(),
+            viewport_line_width: [viewport.0.max(1) as f32, viewport.1.max(1) as f32, self.line_width, 0.0],
         };
 
         if let Some(ref uniform_buffer) = self.debug_light_ray_uniform_buffer {
             self.queue.write_buffer(uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
         }
 
-        #[repr(C)]
-        #[derive(Copy, Clone, Pod, Zeroable)]
-        struct RayVertex {
-            position: [f32; 3],
-            color: [f32; 4],
-        }
-
-        let mut vertices = Vec::new();
+        let mut segments = Vec::new();
         
         for (light_pos, light_color, radius) in lights {
             let ray_color = [light_color.x * 0.5, light_color.y * 0.5, light_color.z * 0.5, 0.6];
@@ -424,17 +477,17 @@ impl DebugRenderer {
                     light_pos.z + dir_z * radius * 0.5,
                 );
                 
-                vertices.push(RayVertex {
-                    position: [light_pos.x, light_pos.y, light_pos.z],
-                    color: ray_color,
-                });
-                vertices.push(RayVertex {
-                    position: [end_pos.x, end_pos.y, end_pos.z],
-                    color: [ray_color[0], ray_color[1], ray_color[2], 0.0],
+                segments.push(LineSegment {
+                    start: *light_pos,
+                    end: end_pos,
+                    start_color: ray_color,
+                    end_color: [ray_color[0], ray_color[1], ray_color[2], 0.0],
                 });
             }
         }
 
+        let vertices = expand_line_segments(&segments);
+
         if vertices.is_empty() {
             return;
         }
@@ -451,7 +504,7 @@ impl DebugRenderer {
         let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
             label: Some("Debug Light Ray Render Pass"),
             color_attachments: &[Some(RenderPassColorAttachment {
-                view: output_view,
+                view: target.color,
                 resolve_target: None,
                 ops: Operations {
                     load: LoadOp::Load,
@@ -459,7 +512,7 @@ impl DebugRenderer {
                 },
             })],
             depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
-                view: depth_view,
+                view: target.depth,
                 depth_ops: Some(Operations {
                     load: LoadOp::Load,
                     store: StoreOp::Store,
@@ -477,3 +530,33 @@ impl DebugRenderer {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start: Vec3, end: Vec3) -> LineSegment {
+        LineSegment {
+            start,
+            end,
+            start_color: [1.0, 0.0, 0.0, 1.0],
+            end_color: [0.0, 0.0, 1.0, 1.0],
+        }
+    }
+
+    #[test]
+    fn each_segment_expands_to_two_triangles() {
+        let segments = [segment(Vec3::ZERO, Vec3::X), segment(Vec3::Y, Vec3::Z), segment(Vec3::ONE, Vec3::NEG_ONE)];
+        let vertices = expand_line_segments(&segments);
+        assert_eq!(vertices.len(), segments.len() * VERTICES_PER_LINE_SEGMENT);
+        assert!(expand_line_segments(&[]).is_empty());
+
+        // Both sides of both ends appear, with each end's own color
+        let quad = &vertices[..VERTICES_PER_LINE_SEGMENT];
+        for t_side in [[0.0, -1.0], [0.0, 1.0], [1.0, 1.0], [1.0, -1.0]] {
+            let corner = quad.iter().find(|v| v.t_side == t_side).unwrap();
+            let color = if t_side[0] == 0.0 { segments[0].start_color } else { segments[0].end_color };
+            assert_eq!(corner.color, color);
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use crate::render::types::VertexData;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FloorGrid {
+    pub spacing: f32,
+    pub extent: f32,
+    pub color: [f32; 4],
+    pub x_axis_color: [f32; 4],
+    pub z_axis_color: [f32; 4],
+}
+
+impl Default for FloorGrid {
+    fn default() -> Self {
+        Self {
+            spacing: 8.0,
+            extent: 64.0,
+            color: [0.4, 0.4, 0.4, 1.0],
+            x_axis_color: [1.0, 0.2, 0.2, 1.0],
+            z_axis_color: [0.2, 0.4, 1.0, 1.0],
+        }
+    }
+}
+
+pub struct FloorGridMesh {
+    pub vertices: Vec<VertexData>,
+    // u32 so fine spacing over a large extent can't wrap past 65535 vertices
+    pub indices: Vec<u32>,
+}
+
+impl FloorGridMesh {
+    pub fn generate(grid: &FloorGrid) -> Self {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+
+        if grid.spacing <= 0.0 || grid.extent <= 0.0 {
+            return Self { vertices, indices };
+        }
+
+        let extent = grid.extent;
+        let half_lines = (extent / grid.spacing).floor() as i32;
+
+        for i in -half_lines..=half_lines {
+            let offset = i as f32 * grid.spacing;
+
+            let color = if i == 0 { grid.z_axis_color } else { grid.color };
+            add_line(&mut vertices, &mut indices, [offset, 0.0, -extent], [offset, 0.0, extent], color);
+
+            let color = if i == 0 { grid.x_axis_color } else { grid.color };
+            add_line(&mut vertices, &mut indices, [-extent, 0.0, offset], [extent, 0.0, offset], color);
+        }
+
+        Self { vertices, indices }
+    }
+
+    pub fn num_segments(&self) -> usize {
+        self.indices.len() / 2
+    }
+}
+
+fn add_line(
+    vertices: &mut Vec<VertexData>,
+    indices: &mut Vec<u32>,
+    start: [f32; 3],
+    end: [f32; 3],
+    color: [f32; 4],
+) {
+    let base = vertices.len() as u32;
+
+    vertices.push(VertexData {
+        position: start,
+        uv: [0.0, 0.0],
+        color,
+        normal: [0.0, 1.0, 0.0],
+    });
+    vertices.push(VertexData {
+        position: end,
+        uv: [1.0, 0.0],
+        color,
+        normal: [0.0, 1.0, 0.0],
+    });
+
+    indices.extend_from_slice(&[base, base + 1]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_has_two_lines_per_step_through_the_origin() {
+        let mesh = FloorGridMesh::generate(&FloorGrid::default());
+        // 64 / 8 = 8 lines each side of the axis plus the axis itself, in both directions
+        assert_eq!(mesh.num_segments(), 2 * 17);
+        assert_eq!(mesh.vertices.len(), mesh.indices.len());
+    }
+
+    #[test]
+    fn dense_grid_indices_do_not_wrap() {
+        let grid = FloorGrid {
+            spacing: 1.0,
+            extent: 20_000.0,
+            ..FloorGrid::default()
+        };
+        let mesh = FloorGridMesh::generate(&grid);
+        assert!(mesh.vertices.len() > u16::MAX as usize);
+        assert_eq!(*mesh.indices.last().unwrap() as usize, mesh.vertices.len() - 1);
+    }
+
+    #[test]
+    fn degenerate_grid_is_empty() {
+        let grid = FloorGrid { spacing: 0.0, ..FloorGrid::default() };
+        assert_eq!(FloorGridMesh::generate(&grid).num_segments(), 0);
+    }
+}
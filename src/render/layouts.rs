@@ -1,6 +1,7 @@
 use wgpu::*;
-use crate::render::types::MD3Uniforms;
+use crate::render::types::{MD3Uniforms, SkyboxUniforms, WireOverlayUniforms};
 
+// Uniforms use a dynamic offset so batched models can share one packed buffer
 pub fn create_md3_bind_group_layout(device: &Device) -> BindGroupLayout {
     device.create_bind_group_layout(&BindGroupLayoutDescriptor {
         label: Some("MD3 Bind Group Layout"),
@@ -10,7 +11,7 @@ pub fn create_md3_bind_group_layout(device: &Device) -> BindGroupLayout {
                 visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
                 ty: BindingType::Buffer {
                     ty: BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
+                    has_dynamic_offset: true,
                     min_binding_size: std::num::NonZeroU64::new(std::mem::size_of::<MD3Uniforms>() as u64),
                 },
                 count: None,
@@ -65,6 +66,22 @@ pub fn create_ground_bind_group_layout(device: &Device) -> BindGroupLayout {
                 ty: BindingType::Sampler(SamplerBindingType::Filtering),
                 count: None,
             },
+            BindGroupLayoutEntry {
+                binding: 3,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Texture {
+                    sample_type: TextureSampleType::Float { filterable: true },
+                    view_dimension: TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            BindGroupLayoutEntry {
+                binding: 4,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                count: None,
+            },
         ],
     })
 }
@@ -197,6 +214,7 @@ pub fn create_debug_light_ray_bind_group_layout(device: &Device) -> BindGroupLay
     #[repr(C)]
     struct DebugLightRayUniforms {
         view_proj: [[f32; 4]; 4],
+        viewport_line_width: [f32; 4],
     }
     device.create_bind_group_layout(&BindGroupLayoutDescriptor {
         label: Some("Debug Light Ray Bind Group Layout"),
@@ -216,6 +234,15 @@ pub fn create_debug_light_ray_bind_group_layout(device: &Device) -> BindGroupLay
 }
 
 pub fn create_shadow_volume_bind_group_layout(device: &Device) -> BindGroupLayout {
+    shadow_uniform_layout(device, "Shadow Volume Bind Group Layout", false)
+}
+
+// Planar shadows read each light's uniforms from the frame's uniform arena at a dynamic offset
+pub fn create_planar_shadow_bind_group_layout(device: &Device) -> BindGroupLayout {
+    shadow_uniform_layout(device, "Planar Shadow Bind Group Layout", true)
+}
+
+fn shadow_uniform_layout(device: &Device, label: &str, has_dynamic_offset: bool) -> BindGroupLayout {
     #[repr(C)]
     struct ShadowVolumeUniforms {
         view_proj: [[f32; 4]; 4],
@@ -224,14 +251,14 @@ pub fn create_shadow_volume_bind_group_layout(device: &Device) -> BindGroupLayou
         _padding: [f32; 3],
     }
     device.create_bind_group_layout(&BindGroupLayoutDescriptor {
-        label: Some("Shadow Volume Bind Group Layout"),
+        label: Some(label),
         entries: &[
             BindGroupLayoutEntry {
                 binding: 0,
                 visibility: ShaderStages::VERTEX,
                 ty: BindingType::Buffer {
                     ty: BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
+                    has_dynamic_offset,
                     min_binding_size: std::num::NonZeroU64::new(std::mem::size_of::<ShadowVolumeUniforms>() as u64),
                 },
                 count: None,
@@ -240,6 +267,7 @@ pub fn create_shadow_volume_bind_group_layout(device: &Device) -> BindGroupLayou
     })
 }
 
+// Tile uniforms also come from the uniform arena, at a dynamic offset
 pub fn create_tile_bind_group_layout(device: &Device) -> BindGroupLayout {
     device.create_bind_group_layout(&BindGroupLayoutDescriptor {
         label: Some("Tile Bind Group Layout"),
@@ -249,7 +277,7 @@ pub fn create_tile_bind_group_layout(device: &Device) -> BindGroupLayout {
                 visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
                 ty: BindingType::Buffer {
                     ty: BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
+                    has_dynamic_offset: true,
                     min_binding_size: std::num::NonZeroU64::new(std::mem::size_of::<MD3Uniforms>() as u64),
                 },
                 count: None,
@@ -272,4 +300,56 @@ pub fn create_tile_bind_group_layout(device: &Device) -> BindGroupLayout {
             },
         ],
     })
-}
\ No newline at end of file
+}
+// One slot per overlaid model, packed into the frame's uniform arena
+pub fn create_wire_overlay_bind_group_layout(device: &Device) -> BindGroupLayout {
+    device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+        label: Some("Wire Overlay Bind Group Layout"),
+        entries: &[
+            BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: true,
+                    min_binding_size: std::num::NonZeroU64::new(std::mem::size_of::<WireOverlayUniforms>() as u64),
+                },
+                count: None,
+            },
+        ],
+    })
+}
+
+pub fn create_skybox_bind_group_layout(device: &Device) -> BindGroupLayout {
+    device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+        label: Some("Skybox Bind Group Layout"),
+        entries: &[
+            BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: std::num::NonZeroU64::new(std::mem::size_of::<SkyboxUniforms>() as u64),
+                },
+                count: None,
+            },
+            BindGroupLayoutEntry {
+                binding: 1,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Texture {
+                    sample_type: TextureSampleType::Float { filterable: true },
+                    view_dimension: TextureViewDimension::Cube,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            BindGroupLayoutEntry {
+                binding: 2,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                count: None,
+            },
+        ],
+    })
+}
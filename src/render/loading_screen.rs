@@ -0,0 +1,236 @@
+use wgpu::*;
+use wgpu::util::DeviceExt;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: [f32; 2],
+    color: [f32; 4],
+}
+
+impl Vertex {
+    fn desc() -> VertexBufferLayout<'static> {
+        VertexBufferLayout {
+            array_stride: std::mem::size_of::<Vertex>() as BufferAddress,
+            step_mode: VertexStepMode::Vertex,
+            attributes: &[
+                VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: VertexFormat::Float32x2,
+                },
+                VertexAttribute {
+                    offset: 8,
+                    shader_location: 1,
+                    format: VertexFormat::Float32x4,
+                },
+            ],
+        }
+    }
+}
+
+const MAX_QUADS: usize = 3;
+const BAR_WIDTH: f32 = 0.6;
+const BAR_HEIGHT: f32 = 0.04;
+const BAR_BORDER: f32 = 0.005;
+
+const BACKGROUND_COLOR: Color = Color { r: 0.02, g: 0.02, b: 0.03, a: 1.0 };
+const FRAME_COLOR: [f32; 4] = [0.35, 0.35, 0.4, 1.0];
+const TRACK_COLOR: [f32; 4] = [0.08, 0.08, 0.1, 1.0];
+const FILL_COLOR: [f32; 4] = [0.9, 0.55, 0.1, 1.0];
+
+pub fn progress_bar_width(progress: f32, max_width: f32) -> f32 {
+    max_width * progress.clamp(0.0, 1.0)
+}
+
+// Counts finished load steps so the bar tracks real work rather than fixed milestones
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoadProgress {
+    done: usize,
+    total: usize,
+}
+
+impl LoadProgress {
+    pub fn new(total: usize) -> Self {
+        Self { done: 0, total }
+    }
+
+    pub fn add_total(&mut self, count: usize) {
+        self.total += count;
+    }
+
+    pub fn advance(&mut self, count: usize) {
+        self.done = (self.done + count).min(self.total);
+    }
+
+    pub fn done(&self) -> usize {
+        self.done
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    // Nothing to load counts as finished
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.done as f32 / self.total as f32
+        }
+    }
+}
+
+fn push_quad(vertices: &mut Vec<Vertex>, min: [f32; 2], max: [f32; 2], color: [f32; 4]) {
+    let corners = [
+        [min[0], min[1]],
+        [max[0], min[1]],
+        [max[0], max[1]],
+        [min[0], min[1]],
+        [max[0], max[1]],
+        [min[0], max[1]],
+    ];
+    for position in corners {
+        vertices.push(Vertex { position, color });
+    }
+}
+
+pub struct LoadingScreen {
+    pipeline: RenderPipeline,
+    vertex_buffer: Buffer,
+}
+
+impl LoadingScreen {
+    pub fn new(device: &Device, format: TextureFormat) -> Self {
+        let shader = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("Loading Screen Shader"),
+            source: ShaderSource::Wgsl(include_str!("../shaders/loading.wgsl").into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("Loading Screen Pipeline Layout"),
+            bind_group_layouts: &[],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("Loading Screen Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(ColorTargetState {
+                    format,
+                    blend: Some(BlendState::ALPHA_BLENDING),
+                    write_mask: ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            multiview: None,
+        });
+
+        let vertex_buffer = device.create_buffer_init(&util::BufferInitDescriptor {
+            label: Some("Loading Screen Vertex Buffer"),
+            contents: bytemuck::cast_slice(&[Vertex { position: [0.0; 2], color: [0.0; 4] }; MAX_QUADS * 6]),
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+        });
+
+        Self {
+            pipeline,
+            vertex_buffer,
+        }
+    }
+
+    pub fn render(
+        &self,
+        encoder: &mut CommandEncoder,
+        view: &TextureView,
+        queue: &Queue,
+        progress: f32,
+        width: u32,
+        height: u32,
+    ) {
+        let aspect = width.max(1) as f32 / height.max(1) as f32;
+        let half_width = BAR_WIDTH * 0.5;
+        let bar_height = BAR_HEIGHT * aspect.min(2.0);
+        let border_y = BAR_BORDER * aspect.min(2.0);
+
+        let mut vertices = Vec::with_capacity(MAX_QUADS * 6);
+        push_quad(
+            &mut vertices,
+            [-half_width - BAR_BORDER, -bar_height - border_y],
+            [half_width + BAR_BORDER, border_y],
+            FRAME_COLOR,
+        );
+        push_quad(&mut vertices, [-half_width, -bar_height], [half_width, 0.0], TRACK_COLOR);
+        let fill = progress_bar_width(progress, BAR_WIDTH);
+        push_quad(&mut vertices, [-half_width, -bar_height], [-half_width + fill, 0.0], FILL_COLOR);
+
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+
+        let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Loading Screen Render Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(BACKGROUND_COLOR),
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.draw(0..vertices.len() as u32, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bar_width_is_linear_in_progress() {
+        assert_eq!(progress_bar_width(0.0, 400.0), 0.0);
+        assert_eq!(progress_bar_width(0.25, 400.0), 100.0);
+        assert_eq!(progress_bar_width(0.5, 400.0), 200.0);
+        assert_eq!(progress_bar_width(1.0, 400.0), 400.0);
+        assert_eq!(progress_bar_width(1.5, 400.0), 400.0);
+        assert_eq!(progress_bar_width(-0.5, 400.0), 0.0);
+    }
+
+    #[test]
+    fn load_progress_tracks_completed_steps() {
+        let mut progress = LoadProgress::new(4);
+        assert_eq!(progress.fraction(), 0.0);
+        progress.advance(1);
+        assert_eq!(progress.fraction(), 0.25);
+        progress.add_total(4);
+        progress.advance(3);
+        assert_eq!(progress.fraction(), 0.5);
+        progress.advance(10);
+        assert_eq!(progress.done(), progress.total());
+        assert_eq!(LoadProgress::default().fraction(), 1.0);
+    }
+}
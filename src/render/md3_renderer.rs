@@ -3,22 +3,35 @@ use std::sync::Arc;
 use wgpu::*;
 use wgpu::util::DeviceExt;
 use glam::{Mat4, Vec3};
-use crate::engine::md3::MD3Model;
+use crate::engine::math::Frustum;
+use crate::engine::md3::{FrameBlend, MD3Model, ModelId};
 use crate::render::types::*;
-use crate::engine::shaders::{MD3_SHADER, MD3_ADDITIVE_SHADER, GROUND_SHADER, SHADOW_SHADER, WALL_SHADOW_SHADER, WALL_SHADER, SHADOW_VOLUME_SHADER, SHADOW_APPLY_SHADER, SHADOW_PLANAR_SHADER, COORDINATE_GRID_SHADER, TILE_SHADER};
+use crate::engine::shaders::{MD3_SHADER, MD3_ADDITIVE_SHADER, GROUND_SHADER, SHADOW_SHADER, WALL_SHADOW_SHADER, WALL_SHADER, SHADOW_VOLUME_SHADER, SHADOW_APPLY_SHADER, SHADOW_PLANAR_SHADER, COORDINATE_GRID_SHADER, TILE_SHADER, WIRE_OVERLAY_SHADER, SKYBOX_SHADER};
 
-use super::buffers::{BufferCacheKey, CachedBuffers};
+use super::buffers::{MeshCaches, DEFAULT_BUFFER_CACHE_CAPACITY};
 use super::layouts::*;
 use super::pipelines::*;
 use super::textures;
 use super::shadows::ShadowRenderer;
-use super::particles::ParticleRenderer;
+use super::particles::{ParticleConfig, ParticleRenderer, DEFAULT_MAX_PARTICLES};
 use super::debug::DebugRenderer;
+use super::crosshair::{Crosshair, CrosshairStyle};
+use super::floor_grid::{FloorGrid, FloorGridMesh};
+use super::reflection::ReflectionTarget;
+use crate::resource_path::strip_resources_prefix;
+
+pub const MUZZLE_FLASH_DURATION: f32 = 0.1;
+pub const MUZZLE_FLASH_SIZE: f32 = 16.0;
 
 pub struct MD3Renderer {
     pub device: Arc<Device>,
     pub queue: Arc<Queue>,
     pub pipeline: Option<RenderPipeline>,
+    double_sided_pipeline: Option<RenderPipeline>,
+    cutout_pipelines: HashMap<CutoutMode, RenderPipeline>,
+    cutout_materials: HashMap<String, CutoutMode>,
+    sample_count: u32,
+    anisotropy: u16,
     pub additive_pipeline: Option<RenderPipeline>,
     pub ground_pipeline: Option<RenderPipeline>,
     pub wall_pipeline: Option<RenderPipeline>,
@@ -33,6 +46,7 @@ pub struct MD3Renderer {
     pub model_textures: HashMap<String, WgpuTexture>,
     pub ground_vertex_buffer: Option<Buffer>,
     pub ground_index_buffer: Option<Buffer>,
+    pub ground_num_indices: u32,
     pub ground_texture: Option<WgpuTexture>,
     pub wall_vertex_buffer: Option<Buffer>,
     pub wall_index_buffer: Option<Buffer>,
@@ -42,31 +56,67 @@ pub struct MD3Renderer {
     pub tile_index_buffer: Option<Buffer>,
     pub tile_num_indices: u32,
     pub tile_texture: Option<WgpuTexture>,
-    tile_uniform_buffer: Option<Buffer>,
+    // Arena chunk the cached tile bind group points into
+    tile_uniform_buffer: Option<Arc<Buffer>>,
     tile_bind_group: Option<BindGroup>,
     pub tile_pipeline: Option<RenderPipeline>,
-    buffer_cache: HashMap<BufferCacheKey, CachedBuffers>,
+    mesh_caches: MeshCaches,
     ground_uniform_buffer: Option<Buffer>,
     wall_uniform_buffer: Option<Buffer>,
     ground_bind_group: Option<BindGroup>,
     wall_bind_group: Option<BindGroup>,
     smoke_texture: Option<WgpuTexture>,
+    particle_alpha_mode: ParticleAlphaMode,
+    max_particles: usize,
+    particle_surface_format: Option<TextureFormat>,
+    scene_stats: SceneStats,
+    uniform_arena: super::buffers::UniformArena,
+    render_mode: RenderMode,
+    shadow_support: ShadowSupport,
+    shadow_ground_plane: (Vec3, f32),
+    floor_reflectivity: f32,
+    reflection_target: Option<ReflectionTarget>,
+    reflection_pass: bool,
     flame_texture: Option<WgpuTexture>,
     debug_light_sphere_bind_group_layout: BindGroupLayout,
     debug_light_ray_bind_group_layout: BindGroupLayout,
     shadow_renderer: Option<ShadowRenderer>,
     particle_renderer: Option<ParticleRenderer>,
     debug_renderer: Option<DebugRenderer>,
+    crosshair: Option<Crosshair>,
     coordinate_grid_pipeline: Option<RenderPipeline>,
     coordinate_grid_vertex_buffer: Option<Buffer>,
     coordinate_grid_index_buffer: Option<Buffer>,
     coordinate_grid_uniform_buffer: Option<Buffer>,
     coordinate_grid_bind_group: Option<BindGroup>,
     coordinate_grid_bind_group_layout: BindGroupLayout,
+    skybox_pipeline: Option<RenderPipeline>,
+    skybox_texture: Option<WgpuTexture>,
+    skybox_uniform_buffer: Option<Buffer>,
+    skybox_bind_group: Option<BindGroup>,
+    skybox_bind_group_layout: BindGroupLayout,
+    floor_grid: Option<FloorGrid>,
+    floor_grid_vertex_buffer: Option<Buffer>,
+    floor_grid_index_buffer: Option<Buffer>,
+    floor_grid_num_indices: u32,
+    floor_grid_uniform_buffer: Option<Buffer>,
+    floor_grid_bind_group: Option<BindGroup>,
+    wire_overlay: Option<[f32; 4]>,
+    wire_overlay_pipeline: Option<RenderPipeline>,
+    wire_overlay_bind_group_layout: BindGroupLayout,
+    // Arena chunk the cached wire overlay bind group points into
+    wire_overlay_uniform_buffer: Option<Arc<Buffer>>,
+    wire_overlay_bind_group: Option<BindGroup>,
+    edge_cache: HashMap<(ModelId, usize, usize), (Arc<Buffer>, u32)>,
+    lod_bias: f32,
 }
 
 impl MD3Renderer {
     pub fn new(device: Arc<Device>, queue: Arc<Queue>) -> Self {
+        Self::with_max_particles(device, queue, DEFAULT_MAX_PARTICLES)
+    }
+
+    pub fn with_max_particles(device: Arc<Device>, queue: Arc<Queue>, max_particles: usize) -> Self {
         let bind_group_layout = create_md3_bind_group_layout(&device);
         let ground_bind_group_layout = create_ground_bind_group_layout(&device);
         let wall_bind_group_layout = create_wall_bind_group_layout(&device);
@@ -74,6 +124,8 @@ impl MD3Renderer {
         let particle_bind_group_layout = create_particle_bind_group_layout(&device);
         let debug_light_sphere_bind_group_layout = create_debug_light_sphere_bind_group_layout(&device);
         let debug_light_ray_bind_group_layout = create_debug_light_ray_bind_group_layout(&device);
+        let skybox_bind_group_layout = create_skybox_bind_group_layout(&device);
+        let wire_overlay_bind_group_layout = create_wire_overlay_bind_group_layout(&device);
 
         let coordinate_grid_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("Coordinate Grid Bind Group Layout"),
@@ -96,12 +148,18 @@ impl MD3Renderer {
             queue.clone(),
             &debug_light_sphere_bind_group_layout,
             &debug_light_ray_bind_group_layout,
+            1,
         ));
 
         Self {
             device,
             queue,
             pipeline: None,
+            double_sided_pipeline: None,
+            cutout_pipelines: HashMap::new(),
+            cutout_materials: HashMap::new(),
+            sample_count: 1,
+            anisotropy: 1,
             additive_pipeline: None,
             ground_pipeline: None,
             wall_pipeline: None,
@@ -116,6 +174,7 @@ impl MD3Renderer {
             model_textures: HashMap::new(),
             ground_vertex_buffer: None,
             ground_index_buffer: None,
+            ground_num_indices: 0,
             ground_texture: None,
             wall_vertex_buffer: None,
             wall_index_buffer: None,
@@ -128,32 +187,86 @@ impl MD3Renderer {
             tile_uniform_buffer: None,
             tile_bind_group: None,
             tile_pipeline: None,
-            buffer_cache: HashMap::new(),
+            mesh_caches: MeshCaches::new(DEFAULT_BUFFER_CACHE_CAPACITY),
             ground_uniform_buffer: None,
             wall_uniform_buffer: None,
             ground_bind_group: None,
             wall_bind_group: None,
             smoke_texture: None,
+            particle_alpha_mode: ParticleAlphaMode::default(),
+            max_particles,
+            particle_surface_format: None,
+            scene_stats: SceneStats::default(),
+            uniform_arena: super::buffers::UniformArena::new(),
+            render_mode: RenderMode::default(),
+            shadow_support: ShadowSupport::default(),
+            shadow_ground_plane: (Vec3::Y, 0.0),
+            floor_reflectivity: 0.0,
+            reflection_target: None,
+            reflection_pass: false,
             flame_texture: None,
             debug_light_sphere_bind_group_layout,
             debug_light_ray_bind_group_layout,
             shadow_renderer: None,
             particle_renderer: None,
             debug_renderer,
+            crosshair: None,
             coordinate_grid_pipeline: None,
             coordinate_grid_vertex_buffer: None,
             coordinate_grid_index_buffer: None,
             coordinate_grid_uniform_buffer: None,
             coordinate_grid_bind_group: None,
             coordinate_grid_bind_group_layout,
+            skybox_pipeline: None,
+            skybox_texture: None,
+            skybox_uniform_buffer: None,
+            skybox_bind_group: None,
+            skybox_bind_group_layout,
+            floor_grid: None,
+            floor_grid_vertex_buffer: None,
+            floor_grid_index_buffer: None,
+            floor_grid_num_indices: 0,
+            floor_grid_uniform_buffer: None,
+            floor_grid_bind_group: None,
+            wire_overlay: None,
+            wire_overlay_pipeline: None,
+            wire_overlay_bind_group_layout,
+            wire_overlay_uniform_buffer: None,
+            wire_overlay_bind_group: None,
+            edge_cache: HashMap::new(),
+            lod_bias: 0.0,
         }
     }
 
-    pub fn clear_model_cache(&mut self) {
-        self.buffer_cache.clear();
+    pub fn clear_all_model_caches(&mut self) {
+        self.mesh_caches.clear();
+        self.edge_cache.clear();
         if let Some(ref mut shadow_renderer) = self.shadow_renderer {
             shadow_renderer.clear_cache();
+        }
+    }
+
+    // Drops everything cached for one model; call when the model itself is dropped
+    pub fn clear_model_cache(&mut self, model_id: ModelId) {
+        self.mesh_caches.clear_model(model_id);
+        self.edge_cache.retain(|(id, _, _), _| *id != model_id);
+        if let Some(ref mut shadow_renderer) = self.shadow_renderer {
+            shadow_renderer.clear_model_cache(model_id);
+        }
     }
+
+    // Per-frame vertex buffers kept across frames; the least recently used are evicted past this
+    pub fn set_buffer_cache_capacity(&mut self, capacity: usize) {
+        self.mesh_caches.buffers.set_capacity(capacity);
+        self.mesh_caches.frames.set_capacity(capacity);
+    }
+
+    pub fn buffer_cache_capacity(&self) -> usize {
+        self.mesh_caches.buffers.capacity()
+    }
+
+    pub fn buffer_cache_len(&self) -> usize {
+        self.mesh_caches.buffers.len()
     }
 
     fn create_uniforms(
@@ -164,7 +277,9 @@ impl MD3Renderer {
         lights: &[(Vec3, Vec3, f32)],
         ambient_light: f32,
     ) -> MD3Uniforms {
-        super::buffers::create_uniforms(view_proj, model, camera_pos, lights, ambient_light)
+        let mut uniforms = super::buffers::create_uniforms(view_proj, model, camera_pos, lights, ambient_light);
+        uniforms.render_mode = self.render_mode.shader_flag();
+        uniforms
     }
 
     fn update_uniform_buffer(&self, uniforms: &MD3Uniforms, buffer: &Buffer) {
@@ -173,43 +288,311 @@ impl MD3Renderer {
 
     fn prepare_mesh_data(
         &mut self,
-        model: &MD3Model,
-        frame_idx: usize,
-        texture_paths: &[Option<String>],
-        uniform_buffer: Arc<Buffer>,
-        shadow_uniform_buffer: Option<Arc<Buffer>>,
-        render_shadow: bool,
+        draw: &ModelDrawCall,
+        lod: usize,
+        uniforms: UniformSlot,
+        shadow: Option<UniformSlot>,
+        cull: Option<&Frustum>,
     ) -> Vec<MeshRenderData> {
-        super::buffers::prepare_mesh_data(
-            &mut self.buffer_cache,
-            &self.device,
-            &self.bind_group_layout,
-            &self.model_textures,
-            model,
-            frame_idx,
-            texture_paths,
-            uniform_buffer,
-            shadow_uniform_buffer,
-            render_shadow,
-        )
+        let resources = super::buffers::MeshResources {
+            device: &self.device,
+            queue: &self.queue,
+            bind_group_layout: &self.bind_group_layout,
+            model_textures: &self.model_textures,
+            cutout_materials: &self.cutout_materials,
+        };
+        super::buffers::prepare_mesh_data(&mut self.mesh_caches, resources, draw, lod, uniforms, shadow, cull)
+    }
+
+    // Packs one MD3Uniforms per entry at the dynamic offset stride into this frame's arena;
+    // returns the buffer, the offset of the first entry and the stride
+    fn push_packed_uniforms(&mut self, uniforms: &[MD3Uniforms]) -> (Arc<Buffer>, u64, u64) {
+        let stride = super::buffers::uniform_stride(&self.device);
+        let mut contents = vec![0u8; stride as usize * uniforms.len().max(1)];
+        for (i, u) in uniforms.iter().enumerate() {
+            let start = i * stride as usize;
+            contents[start..start + std::mem::size_of::<MD3Uniforms>()].copy_from_slice(bytemuck::bytes_of(u));
+        }
+        let (buffer, base) = self.uniform_arena.push(&self.device, &self.queue, &contents, stride);
+        (buffer, base, stride)
+    }
+
+    // Call once per frame before drawing; recycles the uniform arena and blend buffers
+    pub fn begin_frame(&mut self) {
+        self.uniform_arena.reset();
+        self.mesh_caches.blends.begin_frame();
+        self.reset_scene_stats();
+    }
+
+    pub fn reset_scene_stats(&mut self) {
+        self.scene_stats = SceneStats::default();
+    }
+
+    pub fn scene_stats(&self) -> SceneStats {
+        SceneStats {
+            uniform_buffers: self.uniform_arena.buffers_created() as u32,
+            ..self.scene_stats
+        }
+    }
+
+    pub fn shadow_support(&self) -> ShadowSupport {
+        self.shadow_support
+    }
+
+    pub fn set_shadow_support(&mut self, shadow_support: ShadowSupport) {
+        self.shadow_support = shadow_support;
+    }
+
+    pub fn render_mode(&self) -> RenderMode {
+        self.render_mode
+    }
+
+    pub fn set_render_mode(&mut self, render_mode: RenderMode) {
+        self.render_mode = render_mode;
+    }
+
+    pub fn set_ground_mesh(&mut self, vertices: &[VertexData], indices: &[u16]) {
+        let ground_vertex_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Ground Vertex Buffer"),
+            contents: bytemuck::cast_slice(vertices),
+            usage: BufferUsages::VERTEX,
+        });
+
+        let ground_index_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Ground Index Buffer"),
+            contents: bytemuck::cast_slice(indices),
+            usage: BufferUsages::INDEX,
+        });
+
+        self.ground_vertex_buffer = Some(ground_vertex_buffer);
+        self.ground_index_buffer = Some(ground_index_buffer);
+        self.ground_num_indices = indices.len() as u32;
+    }
+
+    pub fn shadow_ground_plane(&self) -> (Vec3, f32) {
+        self.shadow_ground_plane
+    }
+
+    pub fn set_shadow_ground_plane(&mut self, normal: Vec3, d: f32) {
+        self.shadow_ground_plane = (normal.normalize_or_zero(), d);
+        if let Some(ref mut shadow_renderer) = self.shadow_renderer {
+            shadow_renderer.set_ground_plane(self.shadow_ground_plane.0, d);
+        }
+    }
+
+    pub fn floor_reflectivity(&self) -> f32 {
+        self.floor_reflectivity
+    }
+
+    pub fn set_floor_reflectivity(&mut self, reflectivity: f32) {
+        self.floor_reflectivity = reflectivity.clamp(0.0, 1.0);
+        if self.floor_reflectivity == 0.0 && self.reflection_target.is_some() {
+            self.reflection_target = None;
+            self.ground_bind_group = None;
+        }
+    }
+
+    pub fn begin_floor_reflection(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        surface_format: TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> Option<(TextureView, TextureView)> {
+        if self.floor_reflectivity == 0.0 {
+            return None;
+        }
+
+        let recreate = match self.reflection_target {
+            Some(ref target) => !target.matches(surface_format, width, height, self.sample_count),
+            None => true,
+        };
+        if recreate {
+            self.reflection_target = Some(ReflectionTarget::new(
+                &self.device,
+                surface_format,
+                width,
+                height,
+                self.sample_count,
+            ));
+            self.ground_bind_group = None;
+        }
+
+        let target = self.reflection_target.as_ref().unwrap();
+        target.clear(encoder);
+        self.reflection_pass = true;
+        Some((target.color_view(), target.depth_view()))
+    }
+
+    pub fn end_floor_reflection(&mut self, encoder: &mut CommandEncoder) {
+        if let Some(ref target) = self.reflection_target {
+            target.resolve(encoder);
+        }
+        self.reflection_pass = false;
+    }
+
+    pub fn wire_overlay(&self) -> Option<[f32; 4]> {
+        self.wire_overlay
+    }
+
+    pub fn set_wire_overlay(&mut self, color: Option<[f32; 4]>) {
+        self.wire_overlay = color;
+    }
+
+    pub fn lod_bias(&self) -> f32 {
+        self.lod_bias
+    }
+
+    // Positive values switch to cheaper LODs closer to the camera, negative values keep detail longer
+    pub fn set_lod_bias(&mut self, lod_bias: f32) {
+        self.lod_bias = lod_bias;
+    }
+
+    fn select_lod(&self, model: &MD3Model, model_matrix: Mat4, camera_pos: Vec3) -> usize {
+        let distance = camera_pos.distance(model_matrix.w_axis.truncate());
+        model.select_lod(distance * 2f32.powf(self.lod_bias))
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    pub fn anisotropy(&self) -> u16 {
+        self.anisotropy
+    }
+
+    // Samplers can't be modified, so they're rebuilt and the cached bind groups using them dropped
+    pub fn set_anisotropy(&mut self, anisotropy: u16) {
+        let anisotropy = anisotropy.clamp(1, 16);
+        if self.anisotropy == anisotropy {
+            return;
+        }
+        self.anisotropy = anisotropy;
+        let textures = self.model_textures.values_mut()
+            .chain(self.ground_texture.as_mut())
+            .chain(self.wall_texture.as_mut())
+            .chain(self.wall_curb_texture.as_mut())
+            .chain(self.tile_texture.as_mut());
+        for texture in textures {
+            texture.sampler = textures::create_model_sampler(&self.device, anisotropy);
+        }
+        self.ground_bind_group = None;
+        self.wall_bind_group = None;
+        self.tile_bind_group = None;
+    }
+
+    // Pipelines are built for a fixed sample count, so changing it rebuilds everything already created
+    pub fn set_sample_count(&mut self, sample_count: u32) {
+        let sample_count = sample_count.max(1);
+        if self.sample_count == sample_count {
+            return;
+        }
+        self.sample_count = sample_count;
+
+        self.pipeline = None;
+        self.double_sided_pipeline = None;
+        self.cutout_pipelines.clear();
+        self.additive_pipeline = None;
+        self.ground_pipeline = None;
+        self.wall_pipeline = None;
+        self.shadow_pipeline = None;
+        self.wall_shadow_pipeline = None;
+        self.tile_pipeline = None;
+        self.coordinate_grid_pipeline = None;
+        self.skybox_pipeline = None;
+        self.wire_overlay_pipeline = None;
+        self.shadow_renderer = None;
+        self.particle_renderer = None;
+        self.reflection_target = None;
+        self.ground_bind_group = None;
+        let line_width = self.debug_renderer.as_ref().map(|debug_renderer| debug_renderer.line_width());
+        let mut debug_renderer = DebugRenderer::new(
+            self.device.clone(),
+            self.queue.clone(),
+            &self.debug_light_sphere_bind_group_layout,
+            &self.debug_light_ray_bind_group_layout,
+            sample_count,
+        );
+        if let Some(line_width) = line_width {
+            debug_renderer.set_line_width(line_width);
+        }
+        self.debug_renderer = Some(debug_renderer);
+
+        if let Some(surface_format) = self.particle_surface_format {
+            self.create_pipeline(surface_format);
+        }
+    }
+
+    pub fn material_cutout(&self, texture_path: &str) -> Option<CutoutMode> {
+        self.cutout_materials.get(texture_path).copied()
+    }
+
+    pub fn set_material_cutout(&mut self, texture_path: &str, mode: Option<CutoutMode>) {
+        match mode {
+            Some(mode) => self.cutout_materials.insert(texture_path.to_string(), mode),
+            None => self.cutout_materials.remove(texture_path),
+        };
+    }
+
+    pub fn cutout_multisample_state(&self, mode: CutoutMode) -> MultisampleState {
+        let mode = mode.resolve(self.sample_count);
+        create_multisample_state_with(self.sample_count, mode == CutoutMode::AlphaToCoverage)
     }
 
+    pub fn max_particles(&self) -> usize {
+        self.max_particles
+    }
+
+    pub fn particle_alpha_mode(&self) -> ParticleAlphaMode {
+        self.particle_alpha_mode
+    }
+
+    pub fn set_particle_alpha_mode(&mut self, alpha_mode: ParticleAlphaMode) {
+        if self.particle_alpha_mode == alpha_mode {
+            return;
+        }
+        self.particle_alpha_mode = alpha_mode;
+        self.smoke_texture = None;
+        self.particle_renderer = None;
+        if let Some(surface_format) = self.particle_surface_format {
+            self.init_particle_renderer(surface_format);
+        }
+    }
+
+    // Keys are stored the way find_texture looks them up, without the resources prefix
     pub fn load_texture(&mut self, path: &str, texture: WgpuTexture) {
-        self.model_textures.insert(path.to_string(), texture);
+        self.model_textures.insert(strip_resources_prefix(path), texture);
+    }
+
+    pub fn texture_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.model_textures.keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    pub fn texture(&self, key: &str) -> Option<&WgpuTexture> {
+        self.model_textures.get(&strip_resources_prefix(key))
+    }
+
+    pub fn texture_size(&self, key: &str) -> Option<(u32, u32)> {
+        self.texture(key).map(|t| {
+            let size = t.texture.size();
+            (size.width, size.height)
+        })
     }
 
     fn create_ground_texture(&mut self) {
-        self.ground_texture = Some(textures::create_ground_texture(&self.device, &self.queue));
+        self.ground_texture = Some(textures::create_ground_texture(&self.device, &self.queue, self.anisotropy));
     }
 
     fn create_wall_texture(&mut self) {
-        let (wall_texture, curb_texture) = textures::create_wall_texture(&self.device, &self.queue);
+        let (wall_texture, curb_texture) = textures::create_wall_texture(&self.device, &self.queue, self.anisotropy);
         self.wall_texture = Some(wall_texture);
         self.wall_curb_texture = Some(curb_texture);
     }
 
     fn create_smoke_texture(&mut self) {
-        self.smoke_texture = Some(textures::create_smoke_texture(&self.device, &self.queue));
+        self.smoke_texture = Some(textures::create_smoke_texture(&self.device, &self.queue, self.particle_alpha_mode));
     }
 
     fn create_flame_texture(&mut self) {
@@ -240,17 +623,72 @@ impl MD3Renderer {
             fragment: Some(FragmentState {
                 module: &shader,
                 entry_point: "fs_main",
-                targets: &[Some(create_color_target_state(surface_format))],
+                targets: &[Some(create_color_target_state(surface_format, false))],
                 compilation_options: PipelineCompilationOptions::default(),
             }),
             primitive: create_primitive_state(Some(Face::Back)),
             depth_stencil: Some(create_depth_stencil_state(true)),
-            multisample: create_multisample_state(),
+            multisample: create_multisample_state_with(self.sample_count, false),
             multiview: None,
         });
 
         self.pipeline = Some(pipeline);
 
+        let double_sided_pipeline = self.device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("MD3 Double Sided Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[VertexData::desc()],
+                compilation_options: PipelineCompilationOptions::default(),
+            },
+            fragment: Some(FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(create_color_target_state(surface_format, false))],
+                compilation_options: PipelineCompilationOptions::default(),
+            }),
+            primitive: create_primitive_state(None),
+            depth_stencil: Some(create_depth_stencil_state(true)),
+            multisample: create_multisample_state_with(self.sample_count, false),
+            multiview: None,
+        });
+
+        self.double_sided_pipeline = Some(double_sided_pipeline);
+
+        self.cutout_pipelines.clear();
+        for mode in [CutoutMode::AlphaTest, CutoutMode::AlphaToCoverage] {
+            if mode.resolve(self.sample_count) != mode {
+                continue;
+            }
+            let cutout_pipeline = self.device.create_render_pipeline(&RenderPipelineDescriptor {
+                label: Some("MD3 Cutout Pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[VertexData::desc()],
+                    compilation_options: PipelineCompilationOptions::default(),
+                },
+                fragment: Some(FragmentState {
+                    module: &shader,
+                    entry_point: mode.fragment_entry_point(),
+                    targets: &[Some(ColorTargetState {
+                        format: surface_format,
+                        blend: None,
+                        write_mask: ColorWrites::ALL,
+                    })],
+                    compilation_options: PipelineCompilationOptions::default(),
+                }),
+                primitive: create_primitive_state(None),
+                depth_stencil: Some(create_depth_stencil_state(true)),
+                multisample: self.cutout_multisample_state(mode),
+                multiview: None,
+            });
+            self.cutout_pipelines.insert(mode, cutout_pipeline);
+        }
+
         let additive_color_target = ColorTargetState {
             format: surface_format,
             blend: Some(BlendState {
@@ -290,13 +728,13 @@ impl MD3Renderer {
             }),
             primitive: create_primitive_state(None),
             depth_stencil: Some(DepthStencilState {
-                format: TextureFormat::Depth24PlusStencil8,
+                format: DEPTH_FORMAT,
                 depth_write_enabled: false,
                 depth_compare: CompareFunction::LessEqual,
                 stencil: StencilState::default(),
                 bias: DepthBiasState::default(),
             }),
-            multisample: create_multisample_state(),
+            multisample: create_multisample_state_with(self.sample_count, false),
             multiview: None,
         });
 
@@ -325,12 +763,12 @@ impl MD3Renderer {
             fragment: Some(FragmentState {
                 module: &ground_shader,
                 entry_point: "fs_main",
-                targets: &[Some(create_color_target_state(surface_format))],
+                targets: &[Some(create_color_target_state(surface_format, false))],
                 compilation_options: PipelineCompilationOptions::default(),
             }),
             primitive: create_primitive_state(None),
             depth_stencil: Some(create_depth_stencil_state(true)),
-            multisample: create_multisample_state(),
+            multisample: create_multisample_state_with(self.sample_count, false),
             multiview: None,
         });
 
@@ -359,12 +797,12 @@ impl MD3Renderer {
             fragment: Some(FragmentState {
                 module: &wall_shader,
                 entry_point: "fs_main",
-                targets: &[Some(create_color_target_state(surface_format))],
+                targets: &[Some(create_color_target_state(surface_format, false))],
                 compilation_options: PipelineCompilationOptions::default(),
             }),
             primitive: create_primitive_state(None),
             depth_stencil: Some(create_depth_stencil_state(true)),
-            multisample: create_multisample_state(),
+            multisample: create_multisample_state_with(self.sample_count, false),
             multiview: None,
         });
 
@@ -401,7 +839,7 @@ impl MD3Renderer {
         };
 
         let shadow_depth_stencil = DepthStencilState {
-            format: TextureFormat::Depth24PlusStencil8,
+            format: DEPTH_FORMAT,
             depth_write_enabled: false,
             depth_compare: CompareFunction::Less,
             stencil: StencilState {
@@ -440,7 +878,7 @@ impl MD3Renderer {
             }),
             primitive: create_primitive_state(None),
             depth_stencil: Some(shadow_depth_stencil),
-            multisample: create_multisample_state(),
+            multisample: create_multisample_state_with(self.sample_count, false),
             multiview: None,
         });
 
@@ -458,7 +896,7 @@ impl MD3Renderer {
         });
 
         let wall_shadow_depth_stencil = DepthStencilState {
-            format: TextureFormat::Depth24PlusStencil8,
+            format: DEPTH_FORMAT,
             depth_write_enabled: false,
             depth_compare: CompareFunction::Less,
             stencil: StencilState {
@@ -497,7 +935,7 @@ impl MD3Renderer {
             }),
             primitive: create_primitive_state(None),
             depth_stencil: Some(wall_shadow_depth_stencil),
-            multisample: create_multisample_state(),
+            multisample: create_multisample_state_with(self.sample_count, false),
             multiview: None,
         });
 
@@ -526,7 +964,7 @@ impl MD3Renderer {
             fragment: Some(FragmentState {
                 module: &tile_shader,
                 entry_point: "fs_main",
-                targets: &[Some(create_color_target_state(surface_format))],
+                targets: &[Some(create_color_target_state(surface_format, false))],
                 compilation_options: PipelineCompilationOptions::default(),
             }),
             primitive: PrimitiveState {
@@ -539,56 +977,17 @@ impl MD3Renderer {
                 conservative: false,
             },
             depth_stencil: Some(create_depth_stencil_state(true)),
-            multisample: create_multisample_state(),
+            multisample: create_multisample_state_with(self.sample_count, false),
             multiview: None,
         });
 
         self.tile_pipeline = Some(tile_pipeline);
 
-        let ground_size = 500.0;
-        let ground_y = 0.0;
-        let ground_vertices = vec![
-            VertexData {
-                position: [-ground_size, ground_y, -ground_size],
-                uv: [0.0, 0.0],
-                color: [1.0, 1.0, 1.0, 1.0],
-                normal: [0.0, 1.0, 0.0],
-            },
-            VertexData {
-                position: [ground_size, ground_y, -ground_size],
-                uv: [1.0, 0.0],
-                color: [1.0, 1.0, 1.0, 1.0],
-                normal: [0.0, 1.0, 0.0],
-            },
-            VertexData {
-                position: [ground_size, ground_y, ground_size],
-                uv: [1.0, 1.0],
-                color: [1.0, 1.0, 1.0, 1.0],
-                normal: [0.0, 1.0, 0.0],
-            },
-            VertexData {
-                position: [-ground_size, ground_y, ground_size],
-                uv: [0.0, 1.0],
-                color: [1.0, 1.0, 1.0, 1.0],
-                normal: [0.0, 1.0, 0.0],
-            },
-        ];
-        let ground_indices: Vec<u16> = vec![0, 1, 2, 0, 2, 3];
-
-        let ground_vertex_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Ground Vertex Buffer"),
-            contents: bytemuck::cast_slice(&ground_vertices),
-            usage: BufferUsages::VERTEX,
-        });
-
-        let ground_index_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Ground Index Buffer"),
-            contents: bytemuck::cast_slice(&ground_indices),
-            usage: BufferUsages::INDEX,
-        });
-
-        self.ground_vertex_buffer = Some(ground_vertex_buffer);
-        self.ground_index_buffer = Some(ground_index_buffer);
+        // Built once; pipeline rebuilds must not replace a ground mesh set by the caller
+        if self.ground_vertex_buffer.is_none() {
+            let (ground_vertices, ground_indices) = default_ground_mesh();
+            self.set_ground_mesh(&ground_vertices, &ground_indices);
+        }
         
         self.create_ground_texture();
 
@@ -639,6 +1038,49 @@ impl MD3Renderer {
         self.wall_vertex_buffer = Some(wall_vertex_buffer);
         self.wall_index_buffer = Some(wall_index_buffer);
 
+        self.init_particle_renderer(surface_format);
+        self.init_shadow_pipelines(surface_format);
+
+        // Drawn straight onto the resolved frame, so only a format change needs a new one
+        if self.crosshair.as_ref().map(Crosshair::format) != Some(surface_format) {
+            self.crosshair = Some(Crosshair::new(&self.device, &self.queue, surface_format));
+        }
+    }
+
+    pub fn crosshair(&self) -> Option<&Crosshair> {
+        self.crosshair.as_ref()
+    }
+
+    pub fn crosshair_style_count(&self) -> usize {
+        self.crosshair.as_ref().map_or(0, Crosshair::style_count)
+    }
+
+    // Blends a crosshair over the finished frame at the center of the viewport. `output_view` must
+    // be the single-sampled target, after any MSAA resolve
+    pub fn render_crosshair(
+        &self,
+        encoder: &mut CommandEncoder,
+        output_view: &TextureView,
+        viewport: (u32, u32),
+        style: usize,
+        color: [f32; 4],
+        scale: f32,
+    ) {
+        let Some(crosshair) = self.crosshair.as_ref() else {
+            return;
+        };
+        let center = [viewport.0 as f32 * 0.5, viewport.1 as f32 * 0.5];
+        crosshair.render(
+            encoder,
+            output_view,
+            &self.queue,
+            center,
+            viewport,
+            CrosshairStyle { index: style, color, scale },
+        );
+    }
+
+    fn init_particle_renderer(&mut self, surface_format: TextureFormat) {
         if self.smoke_texture.is_none() {
             self.create_smoke_texture();
         }
@@ -656,9 +1098,13 @@ impl MD3Renderer {
             smoke_tex,
             flame_tex,
             surface_format,
+            ParticleConfig {
+                alpha_mode: self.particle_alpha_mode,
+                max_particles: self.max_particles,
+                sample_count: self.sample_count,
+            },
         ));
-
-        self.init_shadow_pipelines(surface_format);
+        self.particle_surface_format = Some(surface_format);
     }
 
     fn init_shadow_pipelines(&mut self, surface_format: TextureFormat) {
@@ -678,7 +1124,7 @@ impl MD3Renderer {
         });
 
         let shadow_volume_depth_stencil_front = DepthStencilState {
-            format: TextureFormat::Depth24PlusStencil8,
+            format: DEPTH_FORMAT,
             depth_write_enabled: false,
             depth_compare: CompareFunction::LessEqual,
             stencil: StencilState {
@@ -731,7 +1177,7 @@ impl MD3Renderer {
                 conservative: false,
             },
             depth_stencil: Some(shadow_volume_depth_stencil_front.clone()),
-            multisample: create_multisample_state(),
+            multisample: create_multisample_state_with(self.sample_count, false),
             multiview: None,
         });
 
@@ -766,7 +1212,7 @@ impl MD3Renderer {
                 conservative: false,
             },
             depth_stencil: Some(shadow_volume_depth_stencil_back),
-            multisample: create_multisample_state(),
+            multisample: create_multisample_state_with(self.sample_count, false),
             multiview: None,
         });
 
@@ -801,7 +1247,7 @@ impl MD3Renderer {
         };
 
         let shadow_apply_depth_stencil = DepthStencilState {
-            format: TextureFormat::Depth24PlusStencil8,
+            format: DEPTH_FORMAT,
             depth_write_enabled: false,
             depth_compare: CompareFunction::LessEqual,
             stencil: StencilState {
@@ -856,7 +1302,7 @@ impl MD3Renderer {
                 conservative: false,
             },
             depth_stencil: Some(shadow_apply_depth_stencil),
-            multisample: create_multisample_state(),
+            multisample: create_multisample_state_with(self.sample_count, false),
             multiview: None,
         });
 
@@ -880,9 +1326,10 @@ impl MD3Renderer {
             source: ShaderSource::Wgsl(SHADOW_PLANAR_SHADER.into()),
         });
 
+        let planar_shadow_bind_group_layout = create_planar_shadow_bind_group_layout(&self.device);
         let shadow_planar_pipeline_layout = self.device.create_pipeline_layout(&PipelineLayoutDescriptor {
             label: Some("Shadow Planar Pipeline Layout"),
-            bind_group_layouts: &[&shadow_volume_bind_group_layout],
+            bind_group_layouts: &[&planar_shadow_bind_group_layout],
             push_constant_ranges: &[],
         });
 
@@ -906,7 +1353,7 @@ impl MD3Renderer {
         };
 
         let shadow_planar_depth_stencil = DepthStencilState {
-            format: TextureFormat::Depth24PlusStencil8,
+            format: DEPTH_FORMAT,
             depth_write_enabled: false,
             depth_compare: CompareFunction::LessEqual,
             stencil: StencilState::default(),
@@ -946,17 +1393,19 @@ impl MD3Renderer {
                 conservative: false,
             },
             depth_stencil: Some(shadow_planar_depth_stencil),
-            multisample: create_multisample_state(),
+            multisample: create_multisample_state_with(self.sample_count, false),
             multiview: None,
         });
 
         let mut shadow_renderer = ShadowRenderer::new(
             self.device.clone(),
+            self.queue.clone(),
             shadow_volume_bind_group_layout,
         );
         shadow_renderer.set_volume_pipelines(shadow_volume_front_pipeline, shadow_volume_back_pipeline);
         shadow_renderer.set_apply_pipeline(shadow_apply_pipeline, shadow_apply_vertex_buffer);
-        shadow_renderer.set_planar_pipeline(shadow_planar_pipeline);
+        shadow_renderer.set_planar_pipeline(shadow_planar_pipeline, planar_shadow_bind_group_layout);
+        shadow_renderer.set_ground_plane(self.shadow_ground_plane.0, self.shadow_ground_plane.1);
         self.shadow_renderer = Some(shadow_renderer);
     }
 
@@ -986,6 +1435,14 @@ impl MD3Renderer {
         if self.ground_bind_group.is_none() {
             let ground_tex = self.ground_texture.as_ref().unwrap();
             let ground_uniform_buffer = self.ground_uniform_buffer.as_ref().unwrap();
+            let reflection_view = match self.reflection_target {
+                Some(ref target) => target.sampled_view(),
+                None => ground_tex.texture.create_view(&TextureViewDescriptor::default()),
+            };
+            let reflection_sampler = match self.reflection_target {
+                Some(ref target) => &target.sampler,
+                None => &ground_tex.sampler,
+            };
             self.ground_bind_group = Some(self.device.create_bind_group(&BindGroupDescriptor {
                 label: Some("Ground Bind Group"),
                 layout: &self.ground_bind_group_layout,
@@ -1002,17 +1459,28 @@ impl MD3Renderer {
                         binding: 2,
                         resource: BindingResource::Sampler(&ground_tex.sampler),
                     },
+                    BindGroupEntry {
+                        binding: 3,
+                        resource: BindingResource::TextureView(&reflection_view),
+                    },
+                    BindGroupEntry {
+                        binding: 4,
+                        resource: BindingResource::Sampler(reflection_sampler),
+                    },
                 ],
             }));
         }
 
-        let uniforms = self.create_uniforms(
+        let mut uniforms = self.create_uniforms(
             view_proj,
             Mat4::IDENTITY,
             camera_pos,
             lights,
             ambient_light,
         );
+        if self.reflection_target.is_some() {
+            uniforms.reflectivity = self.floor_reflectivity;
+        }
 
         let ground_uniform_buffer = self.ground_uniform_buffer.as_ref().unwrap();
         self.update_uniform_buffer(&uniforms, ground_uniform_buffer);
@@ -1044,7 +1512,7 @@ impl MD3Renderer {
         render_pass.set_bind_group(0, self.ground_bind_group.as_ref().unwrap(), &[]);
         render_pass.set_vertex_buffer(0, self.ground_vertex_buffer.as_ref().unwrap().slice(..));
         render_pass.set_index_buffer(self.ground_index_buffer.as_ref().unwrap().slice(..), IndexFormat::Uint16);
-        render_pass.draw_indexed(0..6, 0, 0..1);
+        render_pass.draw_indexed(0..self.ground_num_indices, 0, 0..1);
     }
 
     pub fn render_wall(
@@ -1156,7 +1624,7 @@ impl MD3Renderer {
         depth_view: &TextureView,
         surface_format: TextureFormat,
         model: &MD3Model,
-        frame_idx: usize,
+        frame: f32,
         texture_paths: &[Option<String>],
         model_matrix: Mat4,
         view_proj: Mat4,
@@ -1165,44 +1633,64 @@ impl MD3Renderer {
         ambient_light: f32,
         render_shadow: bool,
     ) {
+        self.render_models(
+            encoder,
+            RenderTarget { color: output_view, depth: depth_view, format: surface_format },
+            &[ModelDrawCall::at_frame(model, frame, texture_paths, model_matrix)],
+            SceneView { view_proj, camera_pos, lights, ambient_light },
+            render_shadow,
+        );
+    }
+
+    // Draws every model in one pass, with their uniforms packed into a single dynamically offset buffer
+    pub fn render_models(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        target: RenderTarget,
+        models: &[ModelDrawCall],
+        scene: SceneView,
+        render_shadow: bool,
+    ) {
+        let RenderTarget { color: output_view, depth: depth_view, format: surface_format } = target;
+        let SceneView { view_proj, camera_pos, lights, ambient_light } = scene;
+        if models.is_empty() {
+            return;
+        }
         if self.pipeline.is_none() {
             self.create_pipeline(surface_format);
         }
 
-        let uniforms = self.create_uniforms(
-            view_proj,
-            model_matrix,
-            camera_pos,
-            lights,
-            ambient_light,
-        );
-
-        let uniform_buffer = Arc::new(self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Model Uniform Buffer"),
-            contents: bytemuck::cast_slice(&[uniforms]),
-            usage: BufferUsages::UNIFORM,
-        }));
+        let uniforms: Vec<MD3Uniforms> = models
+            .iter()
+            .map(|draw| self.create_uniforms(view_proj, draw.matrix, camera_pos, lights, ambient_light))
+            .collect();
+        let (uniform_buffer, base, stride) = self.push_packed_uniforms(&uniforms);
+
+        // Shadow passes below still use every mesh, since off-screen parts can cast into view
+        let frustum = Frustum::from_view_proj(view_proj);
+        let mut lods = Vec::with_capacity(models.len());
+        let mut model_mesh_data = Vec::with_capacity(models.len());
+        for (i, draw) in models.iter().enumerate() {
+            let &ModelDrawCall { model, pose, matrix: model_matrix, .. } = draw;
+            let lod = self.select_lod(model, model_matrix, camera_pos);
+            if !super::buffers::model_visible(model, pose, &frustum, model_matrix) {
+                self.scene_stats.culled_models += 1;
+                lods.push(lod);
+                model_mesh_data.push(Vec::new());
+                continue;
+            }
+            let slot = UniformSlot { buffer: uniform_buffer.clone(), offset: (base + i as u64 * stride) as u32 };
+            let mesh_data = self.prepare_mesh_data(draw, lod, slot, None, Some(&frustum));
+            lods.push(lod);
+            model_mesh_data.push(mesh_data);
+        }
+        let wire_edges = self.prepare_wire_overlay(surface_format, models, &lods, &model_mesh_data, view_proj);
 
-        let shadow_uniform_buffer = if render_shadow {
-            Some(Arc::new(self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Model Shadow Uniform Buffer"),
-                contents: bytemuck::cast_slice(&[uniforms]),
-                usage: BufferUsages::UNIFORM,
-            })))
+        let pipeline = if self.reflection_pass || self.render_mode == RenderMode::FaceWinding {
+            self.double_sided_pipeline.as_ref().unwrap()
         } else {
-            None
+            self.pipeline.as_ref().unwrap()
         };
-        
-        let mesh_data = self.prepare_mesh_data(
-            model,
-            frame_idx,
-            texture_paths,
-            uniform_buffer.clone(),
-            shadow_uniform_buffer,
-            render_shadow,
-        );
-
-        let pipeline = self.pipeline.as_ref().unwrap();
         let additive_pipeline = self.additive_pipeline.as_ref().unwrap();
         let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
             label: Some("MD3 Render Pass"),
@@ -1225,17 +1713,36 @@ impl MD3Renderer {
             occlusion_query_set: None,
             timestamp_writes: None,
         });
-        
-        for mesh in &mesh_data {
+
+        for mesh in model_mesh_data.iter().flatten() {
+            let cutout_pipeline = mesh.cutout
+                .and_then(|mode| self.cutout_pipelines.get(&mode.resolve(self.sample_count)));
             if mesh.is_additive {
                 render_pass.set_pipeline(additive_pipeline);
+            } else if let Some(cutout_pipeline) = cutout_pipeline {
+                render_pass.set_pipeline(cutout_pipeline);
             } else {
                 render_pass.set_pipeline(pipeline);
             }
-            render_pass.set_bind_group(0, &mesh.bind_group, &[]);
+            render_pass.set_bind_group(0, &mesh.bind_group, &[mesh.uniform_offset]);
             render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
             render_pass.set_index_buffer(mesh.index_buffer.slice(..), IndexFormat::Uint16);
             render_pass.draw_indexed(0..mesh.num_indices, 0, 0..1);
+            self.scene_stats.visible_triangles += mesh.num_indices / 3;
+            self.scene_stats.meshes += 1;
+        }
+
+        if let (Some(wire_pipeline), Some(wire_bind_group)) = (&self.wire_overlay_pipeline, &self.wire_overlay_bind_group) {
+            if !wire_edges.is_empty() {
+                render_pass.set_pipeline(wire_pipeline);
+            }
+            for (offset, vertex_buffer, edge_buffer, num_edge_indices) in &wire_edges {
+                render_pass.set_bind_group(0, wire_bind_group, &[*offset]);
+                render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                render_pass.set_index_buffer(edge_buffer.slice(..), IndexFormat::Uint16);
+                render_pass.draw_indexed(0..*num_edge_indices, 0, 0..1);
+                self.scene_stats.wire_overlay_draws += 1;
+            }
         }
 
         drop(render_pass);
@@ -1243,29 +1750,19 @@ impl MD3Renderer {
         if render_shadow && !lights.is_empty() {
             for light_idx in 0..lights.len() {
                 let single_light = &[lights[light_idx]];
-                let shadow_uniforms = self.create_uniforms(
-                    view_proj,
-                    model_matrix,
-                    camera_pos,
-                    single_light,
-                    ambient_light,
-                );
-                
-                let shadow_buffer = Arc::new(self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Model Shadow Uniform Buffer"),
-                    contents: bytemuck::cast_slice(&[shadow_uniforms]),
-                    usage: BufferUsages::UNIFORM,
-                }));
-                
-                let shadow_mesh_data = self.prepare_mesh_data(
-                    model,
-                    frame_idx,
-                    texture_paths,
-                    uniform_buffer.clone(),
-                    Some(shadow_buffer),
-                    true,
-                );
-                
+                let shadow_uniforms: Vec<MD3Uniforms> = models
+                    .iter()
+                    .map(|draw| self.create_uniforms(view_proj, draw.matrix, camera_pos, single_light, ambient_light))
+                    .collect();
+                let (shadow_buffer, shadow_base, _) = self.push_packed_uniforms(&shadow_uniforms);
+
+                let mut shadow_mesh_data = Vec::new();
+                for (i, draw) in models.iter().enumerate() {
+                    let slot = UniformSlot { buffer: uniform_buffer.clone(), offset: (base + i as u64 * stride) as u32 };
+                    let shadow_slot = UniformSlot { buffer: shadow_buffer.clone(), offset: (shadow_base + i as u64 * stride) as u32 };
+                    shadow_mesh_data.extend(self.prepare_mesh_data(draw, lods[i], slot, Some(shadow_slot), None));
+                }
+
                 let shadow_pipeline = self.shadow_pipeline.as_ref().unwrap();
                 let mut shadow_pass = encoder.begin_render_pass(&RenderPassDescriptor {
                     label: Some("Shadow Render Pass"),
@@ -1297,7 +1794,7 @@ impl MD3Renderer {
 
                 for mesh in &shadow_mesh_data {
                     if let Some(ref shadow_bind_group) = mesh.shadow_bind_group {
-                        shadow_pass.set_bind_group(0, shadow_bind_group, &[]);
+                        shadow_pass.set_bind_group(0, shadow_bind_group, &[mesh.shadow_uniform_offset]);
                         shadow_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
                         shadow_pass.set_index_buffer(mesh.index_buffer.slice(..), IndexFormat::Uint16);
                         shadow_pass.draw_indexed(0..mesh.num_indices, 0, 0..1);
@@ -1307,8 +1804,128 @@ impl MD3Renderer {
         }
     }
 
-    pub fn render_wall_shadows_batch(
-        &mut self,
+    fn init_wire_overlay(&mut self, surface_format: TextureFormat) {
+        if self.wire_overlay_pipeline.is_some() {
+            return;
+        }
+
+        let shader = self.device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("Wire Overlay Shader"),
+            source: ShaderSource::Wgsl(WIRE_OVERLAY_SHADER.into()),
+        });
+
+        let pipeline_layout = self.device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("Wire Overlay Pipeline Layout"),
+            bind_group_layouts: &[&self.wire_overlay_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = self.device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("Wire Overlay Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[VertexData::desc()],
+                compilation_options: PipelineCompilationOptions::default(),
+            },
+            fragment: Some(FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(create_color_target_state(surface_format, true))],
+                compilation_options: PipelineCompilationOptions::default(),
+            }),
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::LineList,
+                strip_index_format: None,
+                front_face: FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: CompareFunction::LessEqual,
+                stencil: StencilState::default(),
+                bias: DepthBiasState::default(),
+            }),
+            multisample: create_multisample_state_with(self.sample_count, false),
+            multiview: None,
+        });
+
+        self.wire_overlay_pipeline = Some(pipeline);
+    }
+
+    // Packs one overlay slot per model into the uniform arena and collects the edge lists
+    // that render_models draws at the end of its pass
+    fn prepare_wire_overlay(
+        &mut self,
+        surface_format: TextureFormat,
+        models: &[ModelDrawCall],
+        lods: &[usize],
+        model_mesh_data: &[Vec<MeshRenderData>],
+        view_proj: Mat4,
+    ) -> Vec<(u32, Arc<Buffer>, Arc<Buffer>, u32)> {
+        let Some(color) = self.wire_overlay else { return Vec::new() };
+        self.init_wire_overlay(surface_format);
+
+        let size = std::mem::size_of::<WireOverlayUniforms>();
+        let align = self.device.limits().min_uniform_buffer_offset_alignment.max(1) as u64;
+        let stride = (size as u64).div_ceil(align) * align;
+        let mut contents = vec![0u8; stride as usize * models.len()];
+        for (i, draw) in models.iter().enumerate() {
+            let uniforms = WireOverlayUniforms {
+                view_proj: view_proj.to_cols_array_2d(),
+                model: draw.matrix.to_cols_array_2d(),
+                color,
+            };
+            let start = i * stride as usize;
+            contents[start..start + size].copy_from_slice(bytemuck::bytes_of(&uniforms));
+        }
+        let (uniform_buffer, base) = self.uniform_arena.push(&self.device, &self.queue, &contents, stride);
+
+        let same_chunk = self.wire_overlay_uniform_buffer.as_ref().is_some_and(|buffer| Arc::ptr_eq(buffer, &uniform_buffer));
+        if !same_chunk || self.wire_overlay_bind_group.is_none() {
+            self.wire_overlay_bind_group = Some(self.device.create_bind_group(&BindGroupDescriptor {
+                label: Some("Wire Overlay Bind Group"),
+                layout: &self.wire_overlay_bind_group_layout,
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::Buffer(BufferBinding {
+                            buffer: &uniform_buffer,
+                            offset: 0,
+                            size: std::num::NonZeroU64::new(size as u64),
+                        }),
+                    },
+                ],
+            }));
+            self.wire_overlay_uniform_buffer = Some(uniform_buffer);
+        }
+
+        let mut edges = Vec::new();
+        for (i, draw) in models.iter().enumerate() {
+            let offset = (base + i as u64 * stride) as u32;
+            for mesh in &model_mesh_data[i] {
+                let edge = super::buffers::get_or_create_edge_buffer(
+                    &mut self.edge_cache,
+                    &self.device,
+                    draw.model,
+                    lods[i],
+                    mesh.mesh_idx,
+                );
+                if let Some((edge_buffer, num_edge_indices)) = edge {
+                    edges.push((offset, mesh.vertex_buffer.clone(), edge_buffer, num_edge_indices));
+                }
+            }
+        }
+        edges
+    }
+
+    pub fn render_wall_shadows_batch(
+        &mut self,
         encoder: &mut CommandEncoder,
         output_view: &TextureView,
         depth_view: &TextureView,
@@ -1340,20 +1957,16 @@ impl MD3Renderer {
                     ambient_light,
                 );
 
-                let uniform_buffer = Arc::new(self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Wall Shadow Model Uniform Buffer"),
-                    contents: bytemuck::cast_slice(&[uniforms]),
-                    usage: BufferUsages::UNIFORM,
-                }));
+                let (uniform_buffer, base, _) = self.push_packed_uniforms(&[uniforms]);
 
-                let mesh_data = self.prepare_mesh_data(
+                let draw = ModelDrawCall {
                     model,
-                    *frame_idx,
-                    texture_paths,
-                    uniform_buffer,
-                    None,
-                    false,
-                );
+                    pose: FrameBlend::fixed(*frame_idx).into(),
+                    textures: texture_paths,
+                    matrix: *model_matrix,
+                };
+                let slot = UniformSlot { buffer: uniform_buffer, offset: base as u32 };
+                let mesh_data = self.prepare_mesh_data(&draw, 0, slot, None, None);
 
                 all_mesh_data.extend(mesh_data);
             }
@@ -1388,7 +2001,7 @@ impl MD3Renderer {
             shadow_pass.set_stencil_reference(0);
 
             for mesh in &all_mesh_data {
-                shadow_pass.set_bind_group(0, &mesh.bind_group, &[]);
+                shadow_pass.set_bind_group(0, &mesh.bind_group, &[mesh.uniform_offset]);
                 shadow_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
                 shadow_pass.set_index_buffer(mesh.index_buffer.slice(..), IndexFormat::Uint16);
                 shadow_pass.draw_indexed(0..mesh.num_indices, 0, 0..1);
@@ -1407,6 +2020,7 @@ impl MD3Renderer {
     ) {
         if let Some(ref mut particle_renderer) = self.particle_renderer {
             particle_renderer.render_particles(encoder, output_view, depth_view, view_proj, camera_pos, particles);
+            self.scene_stats.particles += particles.len() as u32;
         }
     }
 
@@ -1421,6 +2035,31 @@ impl MD3Renderer {
     ) {
         if let Some(ref mut particle_renderer) = self.particle_renderer {
             particle_renderer.render_flames(encoder, output_view, depth_view, view_proj, camera_pos, flames);
+            self.scene_stats.particles += flames.len() as u32;
+        }
+    }
+
+    // Additive flash at the world matrix of a weapon's tag_flash, fading out over
+    // MUZZLE_FLASH_DURATION seconds of `age`
+    pub fn render_muzzle_flash(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        target: RenderTarget,
+        view_proj: Mat4,
+        camera_pos: Vec3,
+        flash_matrix: Mat4,
+        age: f32,
+    ) {
+        if !(0.0..MUZZLE_FLASH_DURATION).contains(&age) {
+            return;
+        }
+        if let Some(ref mut particle_renderer) = self.particle_renderer {
+            let fade = 1.0 - age / MUZZLE_FLASH_DURATION;
+            let position = flash_matrix.transform_point3(Vec3::ZERO);
+            let scale = flash_matrix.x_axis.truncate().length();
+            let size = MUZZLE_FLASH_SIZE * scale * (0.5 + 0.5 * fade);
+            particle_renderer.render_flash(encoder, target.color, target.depth, view_proj, camera_pos, (position, size, fade));
+            self.scene_stats.particles += 1;
         }
     }
 
@@ -1448,23 +2087,27 @@ impl MD3Renderer {
         }
     }
 
+    pub fn set_debug_line_width(&mut self, width: f32) {
+        if let Some(ref mut debug_renderer) = self.debug_renderer {
+            debug_renderer.set_line_width(width);
+        }
+    }
+
     pub fn render_debug_light_rays(
         &mut self,
         encoder: &mut CommandEncoder,
-        output_view: &TextureView,
-        depth_view: &TextureView,
+        target: RenderTarget,
         view_proj: Mat4,
+        viewport: (u32, u32),
         lights: &[(Vec3, Vec3, f32)],
-        surface_format: TextureFormat,
     ) {
         if let Some(ref mut debug_renderer) = self.debug_renderer {
             debug_renderer.render_debug_light_rays(
                 encoder,
-                output_view,
-                depth_view,
+                target,
                 view_proj,
+                viewport,
                 lights,
-                surface_format,
                 &self.debug_light_ray_bind_group_layout,
             );
         }
@@ -1473,8 +2116,7 @@ impl MD3Renderer {
     pub fn render_planar_shadows(
         &mut self,
         encoder: &mut CommandEncoder,
-        output_view: &TextureView,
-        depth_view: &TextureView,
+        target: RenderTarget,
         view_proj: Mat4,
         models: &[(
             &MD3Model,
@@ -1483,8 +2125,11 @@ impl MD3Renderer {
         )],
         lights: &[(Vec3, Vec3, f32)],
     ) {
+        if !self.shadow_support.planar {
+            return;
+        }
         if let Some(ref mut shadow_renderer) = self.shadow_renderer {
-            shadow_renderer.render_planar_shadows(encoder, output_view, depth_view, view_proj, models, lights);
+            shadow_renderer.render_planar_shadows(encoder, target, &mut self.uniform_arena, view_proj, models, lights);
         }
     }
 
@@ -1501,8 +2146,12 @@ impl MD3Renderer {
         )],
         lights: &[(Vec3, Vec3, f32)],
     ) {
+        if !self.shadow_support.stencil_volumes {
+            return;
+        }
         if let Some(ref mut shadow_renderer) = self.shadow_renderer {
-            shadow_renderer.render_shadow_volumes(encoder, output_view, depth_view, view_proj, models, lights);
+            self.scene_stats.shadow_volume_triangles +=
+                shadow_renderer.render_shadow_volumes(encoder, output_view, depth_view, view_proj, models, lights);
         }
     }
 
@@ -1636,7 +2285,7 @@ impl MD3Renderer {
             fragment: Some(FragmentState {
                 module: &shader,
                 entry_point: "fs_main",
-                targets: &[Some(create_color_target_state(surface_format))],
+                targets: &[Some(create_color_target_state(surface_format, true))],
                 compilation_options: PipelineCompilationOptions::default(),
             }),
             primitive: PrimitiveState {
@@ -1649,13 +2298,13 @@ impl MD3Renderer {
                 conservative: false,
             },
             depth_stencil: Some(DepthStencilState {
-                format: TextureFormat::Depth24PlusStencil8,
+                format: DEPTH_FORMAT,
                 depth_write_enabled: false,
                 depth_compare: CompareFunction::LessEqual,
                 stencil: StencilState::default(),
                 bias: DepthBiasState::default(),
             }),
-            multisample: create_multisample_state(),
+            multisample: create_multisample_state_with(self.sample_count, false),
             multiview: None,
         });
 
@@ -1722,6 +2371,246 @@ impl MD3Renderer {
         }
     }
 
+    // Faces are q3-resources relative paths in +X, -X, +Y, -Y, +Z, -Z order; missing ones become a gradient
+    pub fn set_skybox(&mut self, face_paths: [&str; 6]) {
+        self.skybox_texture = Some(textures::create_skybox_texture(&self.device, &self.queue, face_paths));
+        self.skybox_bind_group = None;
+    }
+
+    fn init_skybox(&mut self, surface_format: TextureFormat) {
+        if self.skybox_texture.is_none() {
+            self.skybox_texture = Some(textures::create_skybox_texture(&self.device, &self.queue, [""; 6]));
+        }
+
+        if self.skybox_uniform_buffer.is_none() {
+            self.skybox_uniform_buffer = Some(self.device.create_buffer(&BufferDescriptor {
+                label: Some("Skybox Uniform Buffer"),
+                size: std::mem::size_of::<SkyboxUniforms>() as u64,
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }));
+        }
+
+        if self.skybox_bind_group.is_none() {
+            let texture = self.skybox_texture.as_ref().unwrap();
+            self.skybox_bind_group = Some(self.device.create_bind_group(&BindGroupDescriptor {
+                label: Some("Skybox Bind Group"),
+                layout: &self.skybox_bind_group_layout,
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: self.skybox_uniform_buffer.as_ref().unwrap().as_entire_binding(),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::TextureView(&texture.view),
+                    },
+                    BindGroupEntry {
+                        binding: 2,
+                        resource: BindingResource::Sampler(&texture.sampler),
+                    },
+                ],
+            }));
+        }
+
+        if self.skybox_pipeline.is_some() {
+            return;
+        }
+
+        let shader = self.device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("Skybox Shader"),
+            source: ShaderSource::Wgsl(SKYBOX_SHADER.into()),
+        });
+
+        let pipeline_layout = self.device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("Skybox Pipeline Layout"),
+            bind_group_layouts: &[&self.skybox_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = self.device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("Skybox Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: PipelineCompilationOptions::default(),
+            },
+            fragment: Some(FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(ColorTargetState {
+                    format: surface_format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+                compilation_options: PipelineCompilationOptions::default(),
+            }),
+            primitive: create_primitive_state(None),
+            // Drawn at the far plane so everything else still passes the depth test
+            depth_stencil: Some(DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: CompareFunction::LessEqual,
+                stencil: StencilState::default(),
+                bias: DepthBiasState::default(),
+            }),
+            multisample: create_multisample_state_with(self.sample_count, false),
+            multiview: None,
+        });
+
+        self.skybox_pipeline = Some(pipeline);
+    }
+
+    pub fn render_skybox(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        output_view: &TextureView,
+        depth_view: &TextureView,
+        view_proj: Mat4,
+        camera_pos: Vec3,
+        surface_format: TextureFormat,
+    ) {
+        self.init_skybox(surface_format);
+
+        let uniforms = SkyboxUniforms {
+            inv_view_proj: view_proj.inverse().to_cols_array_2d(),
+            camera_pos: [camera_pos.x, camera_pos.y, camera_pos.z, 1.0],
+        };
+        if let Some(ref uniform_buffer) = self.skybox_uniform_buffer {
+            self.queue.write_buffer(uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+        }
+
+        let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Skybox Render Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: output_view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                view: depth_view,
+                depth_ops: Some(Operations {
+                    load: LoadOp::Load,
+                    store: StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+
+        render_pass.set_pipeline(self.skybox_pipeline.as_ref().unwrap());
+        render_pass.set_bind_group(0, self.skybox_bind_group.as_ref().unwrap(), &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    fn update_floor_grid(&mut self, grid: &FloorGrid) {
+        if self.floor_grid.as_ref() == Some(grid) {
+            return;
+        }
+
+        let mesh = FloorGridMesh::generate(grid);
+        self.floor_grid = Some(*grid);
+        self.floor_grid_num_indices = mesh.indices.len() as u32;
+
+        if mesh.indices.is_empty() {
+            self.floor_grid_vertex_buffer = None;
+            self.floor_grid_index_buffer = None;
+            return;
+        }
+
+        self.floor_grid_vertex_buffer = Some(self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Floor Grid Vertex Buffer"),
+            contents: bytemuck::cast_slice(&mesh.vertices),
+            usage: BufferUsages::VERTEX,
+        }));
+
+        self.floor_grid_index_buffer = Some(self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Floor Grid Index Buffer"),
+            contents: bytemuck::cast_slice(&mesh.indices),
+            usage: BufferUsages::INDEX,
+        }));
+
+        if self.floor_grid_uniform_buffer.is_none() {
+            let uniform_buffer = self.device.create_buffer(&BufferDescriptor {
+                label: Some("Floor Grid Uniform Buffer"),
+                size: (std::mem::size_of::<[[f32; 4]; 4]>() * 2) as u64,
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+
+            let bind_group = self.device.create_bind_group(&BindGroupDescriptor {
+                label: Some("Floor Grid Bind Group"),
+                layout: &self.coordinate_grid_bind_group_layout,
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: uniform_buffer.as_entire_binding(),
+                    },
+                ],
+            });
+
+            self.floor_grid_uniform_buffer = Some(uniform_buffer);
+            self.floor_grid_bind_group = Some(bind_group);
+        }
+    }
+
+    pub fn render_floor_grid(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        target: RenderTarget,
+        grid: &FloorGrid,
+        model_matrix: Mat4,
+        view_proj: Mat4,
+    ) {
+        self.init_coordinate_grid(target.format);
+        self.update_floor_grid(grid);
+
+        let (vertex_buffer, index_buffer) = match (&self.floor_grid_vertex_buffer, &self.floor_grid_index_buffer) {
+            (Some(v), Some(i)) => (v, i),
+            _ => return,
+        };
+
+        let uniforms = [view_proj.to_cols_array_2d(), model_matrix.to_cols_array_2d()];
+        if let Some(ref uniform_buffer) = self.floor_grid_uniform_buffer {
+            self.queue.write_buffer(uniform_buffer, 0, bytemuck::cast_slice(&uniforms));
+        }
+
+        let pipeline = self.coordinate_grid_pipeline.as_ref().unwrap();
+        let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Floor Grid Render Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: target.color,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                view: target.depth,
+                depth_ops: Some(Operations {
+                    load: LoadOp::Load,
+                    store: StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, self.floor_grid_bind_group.as_ref().unwrap(), &[]);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), IndexFormat::Uint32);
+        render_pass.draw_indexed(0..self.floor_grid_num_indices, 0, 0..1);
+    }
+
     pub fn load_map_tiles(&mut self, map: &crate::game::map::Map) {
         use crate::render::map_meshes::TileMeshes;
         use crate::render::textures_tile::create_tile_texture;
@@ -1749,7 +2638,7 @@ impl MD3Renderer {
         self.tile_num_indices = tile_meshes.indices.len() as u32;
 
         if self.tile_texture.is_none() {
-            self.tile_texture = Some(create_tile_texture(&self.device, &self.queue));
+            self.tile_texture = Some(create_tile_texture(&self.device, &self.queue, self.anisotropy));
         }
 
         println!("Loaded map tiles: {} vertices, {} indices", tile_meshes.vertices.len(), tile_meshes.indices.len());
@@ -1782,31 +2671,31 @@ impl MD3Renderer {
             ambient_light,
         );
 
-        let uniform_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Tile Uniform Buffer"),
-            contents: bytemuck::cast_slice(&[uniforms]),
-            usage: BufferUsages::UNIFORM,
-        });
-
-        let tile_texture = self.tile_texture.as_ref().unwrap();
-        let bind_group = self.device.create_bind_group(&BindGroupDescriptor {
-            label: Some("Tile Bind Group"),
-            layout: &self.tile_bind_group_layout,
-            entries: &[
-                BindGroupEntry {
-                    binding: 0,
-                    resource: uniform_buffer.as_entire_binding(),
-                },
-                BindGroupEntry {
-                    binding: 1,
-                    resource: BindingResource::TextureView(&tile_texture.view),
-                },
-                BindGroupEntry {
-                    binding: 2,
-                    resource: BindingResource::Sampler(&tile_texture.sampler),
-                },
-            ],
-        });
+        let (uniform_buffer, offset, _) = self.push_packed_uniforms(&[uniforms]);
+        // Arena chunks are reused across frames, so the bind group only changes with the chunk
+        let same_chunk = self.tile_uniform_buffer.as_ref().is_some_and(|buffer| Arc::ptr_eq(buffer, &uniform_buffer));
+        if !same_chunk || self.tile_bind_group.is_none() {
+            let tile_texture = self.tile_texture.as_ref().unwrap();
+            self.tile_bind_group = Some(self.device.create_bind_group(&BindGroupDescriptor {
+                label: Some("Tile Bind Group"),
+                layout: &self.tile_bind_group_layout,
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: super::buffers::uniform_slot(&uniform_buffer),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::TextureView(&tile_texture.view),
+                    },
+                    BindGroupEntry {
+                        binding: 2,
+                        resource: BindingResource::Sampler(&tile_texture.sampler),
+                    },
+                ],
+            }));
+            self.tile_uniform_buffer = Some(uniform_buffer);
+        }
 
         let pipeline = self.tile_pipeline.as_ref().unwrap();
         let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
@@ -1832,10 +2721,275 @@ impl MD3Renderer {
         });
 
         render_pass.set_pipeline(pipeline);
-        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.set_bind_group(0, self.tile_bind_group.as_ref().unwrap(), &[offset as u32]);
         render_pass.set_vertex_buffer(0, self.tile_vertex_buffer.as_ref().unwrap().slice(..));
         render_pass.set_index_buffer(self.tile_index_buffer.as_ref().unwrap().slice(..), IndexFormat::Uint16);
         render_pass.draw_indexed(0..self.tile_num_indices, 0, 0..1);
     }
 }
 
+fn default_ground_mesh() -> (Vec<VertexData>, Vec<u16>) {
+    let ground_size = 500.0;
+    let ground_y = 0.0;
+    let vertices = vec![
+        VertexData {
+            position: [-ground_size, ground_y, -ground_size],
+            uv: [0.0, 0.0],
+            color: [1.0, 1.0, 1.0, 1.0],
+            normal: [0.0, 1.0, 0.0],
+        },
+        VertexData {
+            position: [ground_size, ground_y, -ground_size],
+            uv: [1.0, 0.0],
+            color: [1.0, 1.0, 1.0, 1.0],
+            normal: [0.0, 1.0, 0.0],
+        },
+        VertexData {
+            position: [ground_size, ground_y, ground_size],
+            uv: [1.0, 1.0],
+            color: [1.0, 1.0, 1.0, 1.0],
+            normal: [0.0, 1.0, 0.0],
+        },
+        VertexData {
+            position: [-ground_size, ground_y, ground_size],
+            uv: [0.0, 1.0],
+            color: [1.0, 1.0, 1.0, 1.0],
+            normal: [0.0, 1.0, 0.0],
+        },
+    ];
+    (vertices, vec![0, 1, 2, 0, 2, 3])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::md3::tests::md3_bytes;
+    use crate::engine::md3::LoadOptions;
+    use crate::render::benchmark::create_headless_device;
+
+    fn vertex(x: f32, z: f32) -> VertexData {
+        VertexData {
+            position: [x, 0.0, z],
+            uv: [0.0, 0.0],
+            color: [1.0; 4],
+            normal: [0.0, 1.0, 0.0],
+        }
+    }
+
+    fn target_view(device: &Device, format: TextureFormat) -> TextureView {
+        device
+            .create_texture(&TextureDescriptor {
+                label: None,
+                size: Extent3d { width: 16, height: 16, depth_or_array_layers: 1 },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format,
+                usage: TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            })
+            .create_view(&TextureViewDescriptor::default())
+    }
+
+    #[test]
+    fn custom_ground_mesh_sets_the_draw_index_count() {
+        let Ok((device, queue, _, _)) = create_headless_device() else {
+            eprintln!("No adapter available, skipping");
+            return;
+        };
+        let mut renderer = MD3Renderer::new(device, queue);
+        let plane = renderer.shadow_ground_plane();
+
+        let vertices = [vertex(-1.0, -1.0), vertex(1.0, -1.0), vertex(1.0, 1.0), vertex(-1.0, 1.0)];
+        renderer.set_ground_mesh(&vertices, &[0, 1, 2]);
+        assert_eq!(renderer.ground_num_indices, 3);
+        renderer.set_ground_mesh(&vertices, &[0, 1, 2, 0, 2, 3]);
+        assert_eq!(renderer.ground_num_indices, 6);
+        assert!(renderer.ground_index_buffer.is_some());
+        // The shadow plane is configured on its own
+        assert_eq!(renderer.shadow_ground_plane(), plane);
+    }
+
+    #[test]
+    fn cutout_multisample_state_follows_the_msaa_setting() {
+        let Ok((device, queue, _, _)) = create_headless_device() else {
+            eprintln!("No adapter available, skipping");
+            return;
+        };
+        let mut renderer = MD3Renderer::new(device, queue);
+
+        renderer.set_sample_count(4);
+        let coverage = renderer.cutout_multisample_state(CutoutMode::AlphaToCoverage);
+        assert_eq!(coverage.count, 4);
+        assert!(coverage.alpha_to_coverage_enabled);
+        assert!(!renderer.cutout_multisample_state(CutoutMode::AlphaTest).alpha_to_coverage_enabled);
+
+        // Without MSAA the cutout falls back to the plain alpha test
+        renderer.set_sample_count(1);
+        let fallback = renderer.cutout_multisample_state(CutoutMode::AlphaToCoverage);
+        assert_eq!(fallback.count, 1);
+        assert!(!fallback.alpha_to_coverage_enabled);
+    }
+
+    #[test]
+    fn scene_stats_count_drawn_meshes_and_triangles_but_not_culled_models() {
+        let Ok((device, queue, _, _)) = create_headless_device() else {
+            eprintln!("No adapter available, skipping");
+            return;
+        };
+        const FORMAT: TextureFormat = TextureFormat::Rgba8Unorm;
+        let mut renderer = MD3Renderer::new(device.clone(), queue.clone());
+        renderer.load_texture("white", crate::render::benchmark::create_white_texture(&device, &queue));
+        let model = MD3Model::from_bytes(&md3_bytes(4, &[[0, 1, 2], [0, 2, 3]]), LoadOptions::default()).unwrap();
+        let textures = vec![Some("white".to_string()); model.meshes.len()];
+
+        let (color_view, depth_view) = (target_view(&device, FORMAT), target_view(&device, DEPTH_FORMAT));
+        let camera_pos = Vec3::new(0.0, 0.0, 5.0);
+        let view_proj = Mat4::perspective_rh(1.0, 1.0, 0.1, 100.0)
+            * Mat4::look_at_rh(camera_pos, Vec3::ZERO, Vec3::Y);
+        let draws = [
+            ModelDrawCall::at_frame(&model, 0.0, &textures, Mat4::IDENTITY),
+            ModelDrawCall::at_frame(&model, 0.0, &textures, Mat4::from_translation(Vec3::new(1.0, 0.0, 0.0))),
+            // Behind the camera
+            ModelDrawCall::at_frame(&model, 0.0, &textures, Mat4::from_translation(Vec3::new(0.0, 0.0, 50.0))),
+        ];
+
+        renderer.begin_frame();
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        renderer.render_models(
+            &mut encoder,
+            RenderTarget { color: &color_view, depth: &depth_view, format: FORMAT },
+            &draws,
+            SceneView { view_proj, camera_pos, lights: &[], ambient_light: 1.0 },
+            false,
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let stats = renderer.scene_stats();
+        assert_eq!(stats.meshes, 2);
+        assert_eq!(stats.visible_triangles, 4);
+        assert_eq!(stats.culled_models, 1);
+    }
+
+    #[test]
+    fn textures_are_found_under_either_prefix_and_any_image_extension() {
+        let Ok((device, queue, _, _)) = create_headless_device() else {
+            eprintln!("No adapter available, skipping");
+            return;
+        };
+        let mut renderer = MD3Renderer::new(device.clone(), queue.clone());
+        let white = || crate::render::benchmark::create_white_texture(&device, &queue);
+        renderer.load_texture("../q3-resources/models/players/sarge/band.png", white());
+        renderer.load_texture("q3-resources/models/players/sarge/skin.jpg", white());
+        renderer.load_texture("white", white());
+        assert_eq!(renderer.texture_keys(), ["models/players/sarge/band.png", "models/players/sarge/skin.jpg", "white"]);
+
+        let find = |path| crate::render::buffers::find_texture(&renderer.model_textures, path).is_some();
+        assert!(find("models/players/sarge/band.tga"));
+        assert!(find("q3-resources/models/players/sarge/band.TGA"));
+        assert!(find("../q3-resources/models/players/sarge/skin.tga"));
+        assert!(find("models/players/sarge/skin"));
+        assert!(find("white"));
+        assert!(!find("models/players/sarge/head.tga"));
+        assert!(renderer.texture("q3-resources/models/players/sarge/band.png").is_some());
+    }
+
+    #[test]
+    fn missing_textures_are_reported_once_per_path() {
+        let Ok((device, queue, _, _)) = create_headless_device() else {
+            eprintln!("No adapter available, skipping");
+            return;
+        };
+        const FORMAT: TextureFormat = TextureFormat::Rgba8Unorm;
+        let mut renderer = MD3Renderer::new(device.clone(), queue.clone());
+        let model = MD3Model::from_bytes(&md3_bytes(3, &[[0, 1, 2]]), LoadOptions::default()).unwrap();
+        let textures = vec![Some("models/missing.tga".to_string()); model.meshes.len()];
+        let (color_view, depth_view) = (target_view(&device, FORMAT), target_view(&device, DEPTH_FORMAT));
+        let camera_pos = Vec3::new(0.0, 0.0, 5.0);
+        let view_proj = Mat4::perspective_rh(1.0, 1.0, 0.1, 100.0)
+            * Mat4::look_at_rh(camera_pos, Vec3::ZERO, Vec3::Y);
+        let draw = ModelDrawCall::at_frame(&model, 0.0, &textures, Mat4::IDENTITY);
+
+        for _ in 0..3 {
+            renderer.begin_frame();
+            let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+            renderer.render_models(
+                &mut encoder,
+                RenderTarget { color: &color_view, depth: &depth_view, format: FORMAT },
+                &[draw, draw],
+                SceneView { view_proj, camera_pos, lights: &[], ambient_light: 1.0 },
+                false,
+            );
+            queue.submit(Some(encoder.finish()));
+            assert_eq!(renderer.scene_stats().meshes, 0);
+        }
+        assert_eq!(renderer.mesh_caches.missing_textures.len(), 1);
+    }
+
+    #[test]
+    fn wire_overlay_draws_lines_over_every_filled_mesh() {
+        let Ok((device, queue, _, _)) = create_headless_device() else {
+            eprintln!("No adapter available, skipping");
+            return;
+        };
+        const FORMAT: TextureFormat = TextureFormat::Rgba8Unorm;
+        let mut renderer = MD3Renderer::new(device.clone(), queue.clone());
+        renderer.load_texture("white", crate::render::benchmark::create_white_texture(&device, &queue));
+        let model = MD3Model::from_bytes(&md3_bytes(3, &[[0, 1, 2]]), LoadOptions::default()).unwrap();
+        let textures = vec![Some("white".to_string()); model.meshes.len()];
+
+        let (color_view, depth_view) = (target_view(&device, FORMAT), target_view(&device, DEPTH_FORMAT));
+        let camera_pos = Vec3::new(0.0, 0.0, 5.0);
+        let view_proj = Mat4::perspective_rh(1.0, 1.0, 0.1, 100.0)
+            * Mat4::look_at_rh(camera_pos, Vec3::ZERO, Vec3::Y);
+        let draw = ModelDrawCall::at_frame(&model, 0.0, &textures, Mat4::IDENTITY);
+        let target = RenderTarget { color: &color_view, depth: &depth_view, format: FORMAT };
+        let scene = SceneView { view_proj, camera_pos, lights: &[], ambient_light: 1.0 };
+
+        let render = |renderer: &mut MD3Renderer| {
+            renderer.begin_frame();
+            let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+            renderer.render_models(&mut encoder, target, &[draw], scene, false);
+            queue.submit(Some(encoder.finish()));
+            renderer.scene_stats()
+        };
+
+        let filled = render(&mut renderer);
+        assert_eq!(filled.meshes, 1);
+        assert_eq!(filled.wire_overlay_draws, 0);
+
+        renderer.set_wire_overlay(Some([0.0, 1.0, 0.0, 1.0]));
+        render(&mut renderer);
+        let overlaid = render(&mut renderer);
+        assert_eq!(overlaid.meshes, 1);
+        assert_eq!(overlaid.wire_overlay_draws, 1);
+        // Overlay uniforms share the frame's arena chunk with the model uniforms
+        assert_eq!(overlaid.uniform_buffers, 1);
+    }
+
+    #[test]
+    fn planar_shadows_reuse_arena_and_vertex_buffers_across_frames() {
+        let Ok((device, queue, _, _)) = create_headless_device() else {
+            eprintln!("No adapter available, skipping");
+            return;
+        };
+        const FORMAT: TextureFormat = TextureFormat::Rgba8Unorm;
+        let mut renderer = MD3Renderer::new(device.clone(), queue.clone());
+        renderer.create_pipeline(FORMAT);
+        let model = MD3Model::from_bytes(&md3_bytes(3, &[[0, 1, 2]]), LoadOptions::default()).unwrap();
+        let models = [(&model, 0, Mat4::from_translation(Vec3::new(0.0, 10.0, 0.0)))];
+        let lights = [(Vec3::new(0.0, 100.0, 0.0), Vec3::ONE, 300.0), (Vec3::new(50.0, 80.0, 0.0), Vec3::ONE, 300.0)];
+
+        let (color_view, depth_view) = (target_view(&device, FORMAT), target_view(&device, DEPTH_FORMAT));
+        let target = RenderTarget { color: &color_view, depth: &depth_view, format: FORMAT };
+
+        for _ in 0..3 {
+            renderer.begin_frame();
+            let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+            renderer.render_planar_shadows(&mut encoder, target, Mat4::IDENTITY, &models, &lights);
+            queue.submit(Some(encoder.finish()));
+        }
+        assert_eq!(renderer.scene_stats().uniform_buffers, 1);
+        assert_eq!(renderer.shadow_renderer.as_ref().unwrap().buffer_allocations(), 1);
+    }
+}
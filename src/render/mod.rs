@@ -8,16 +8,25 @@ pub mod shadows;
 pub mod pipelines;
 pub mod textures;
 pub mod textures_tile;
+pub mod texture_loader;
 pub mod buffers;
 pub mod layouts;
 pub mod debug;
 pub mod particles;
 pub mod map_meshes;
+pub mod floor_grid;
+pub mod loading_screen;
+pub mod texture_viewer;
+pub mod reflection;
+pub mod benchmark;
 
 pub use wgpu_renderer::WgpuRenderer;
-pub use md3_renderer::MD3Renderer;
+pub use md3_renderer::{MD3Renderer, MUZZLE_FLASH_DURATION};
 pub use menu_renderer::MenuRenderer;
 pub use text_renderer::TextRenderer;
-pub use crosshair::Crosshair;
+pub use crosshair::{Crosshair, CrosshairStyle};
 pub use types::*;
 pub use shadows::ShadowRenderer;
+pub use floor_grid::FloorGrid;
+pub use loading_screen::{LoadProgress, LoadingScreen};
+pub use texture_viewer::TextureViewer;
@@ -3,18 +3,77 @@ use wgpu::*;
 use wgpu::util::DeviceExt;
 use glam::{Mat4, Vec3};
 use bytemuck::{Pod, Zeroable};
-use crate::render::types::{VertexData, WgpuTexture};
+use crate::render::types::{ParticleAlphaMode, VertexData, WgpuTexture, DEPTH_FORMAT};
 use crate::engine::shaders::{PARTICLE_SHADER, FLAME_SHADER};
 use super::pipelines::*;
 
+pub const DEFAULT_MAX_PARTICLES: usize = 1000;
+
+// Settings baked into the pipelines and buffers; changing any of them means a new renderer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParticleConfig {
+    pub alpha_mode: ParticleAlphaMode,
+    pub max_particles: usize,
+    pub sample_count: u32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+struct FlameInstance {
+    position_size: [f32; 4],
+    alpha: f32,
+    _padding: [f32; 3],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+struct FlameUniforms {
+    view_proj: [[f32; 4]; 4],
+    camera_pos: [f32; 4],
+}
+
+// Which instance buffer draw_flames reads; the flash is a single instance of its own
+#[derive(Clone, Copy)]
+enum FlameBatch {
+    Flames(u32),
+    Flash,
+}
+
+pub fn clamp_instances<'a, T>(items: &'a [T], capacity: usize, warned: &mut bool, label: &str) -> &'a [T] {
+    if items.len() <= capacity {
+        return items;
+    }
+    if !*warned {
+        println!(
+            "Warning: {} {} instances exceed buffer capacity {}, extra instances are dropped",
+            items.len(),
+            label,
+            capacity
+        );
+        *warned = true;
+    }
+    &items[..capacity]
+}
+
+pub fn back_to_front_order(positions: &[Vec3], camera_pos: Vec3) -> Vec<usize> {
+    let distances: Vec<f32> = positions.iter().map(|p| p.distance_squared(camera_pos)).collect();
+    let mut order: Vec<usize> = (0..positions.len()).collect();
+    order.sort_unstable_by(|&a, &b| distances[b].total_cmp(&distances[a]));
+    order
+}
+
 pub struct ParticleRenderer {
     queue: Arc<Queue>,
+    max_particles: usize,
+    particle_overflow_warned: bool,
+    flame_overflow_warned: bool,
     particle_pipeline: Option<RenderPipeline>,
     flame_pipeline: Option<RenderPipeline>,
     particle_quad_vertex_buffer: Option<Buffer>,
     particle_quad_index_buffer: Option<Buffer>,
     particle_instance_buffer: Option<Buffer>,
     flame_instance_buffer: Option<Buffer>,
+    flash_instance_buffer: Option<Buffer>,
     particle_uniform_buffer: Option<Buffer>,
     flame_uniform_buffer: Option<Buffer>,
     particle_bind_group: Option<BindGroup>,
@@ -22,6 +81,10 @@ pub struct ParticleRenderer {
 }
 
 impl ParticleRenderer {
+    pub fn max_particles(&self) -> usize {
+        self.max_particles
+    }
+
     pub fn new(
         device: Arc<Device>,
         queue: Arc<Queue>,
@@ -29,7 +92,9 @@ impl ParticleRenderer {
         smoke_texture: &WgpuTexture,
         flame_texture: &WgpuTexture,
         surface_format: TextureFormat,
+        config: ParticleConfig,
     ) -> Self {
+        let ParticleConfig { alpha_mode, max_particles, sample_count } = config;
         let particle_shader = device.create_shader_module(ShaderModuleDescriptor {
             label: Some("Particle Shader"),
             source: ShaderSource::Wgsl(PARTICLE_SHADER.into()),
@@ -41,18 +106,7 @@ impl ParticleRenderer {
             push_constant_ranges: &[],
         });
 
-        let particle_blend_state = BlendState {
-            color: BlendComponent {
-                src_factor: BlendFactor::SrcAlpha,
-                dst_factor: BlendFactor::OneMinusSrcAlpha,
-                operation: BlendOperation::Add,
-            },
-            alpha: BlendComponent {
-                src_factor: BlendFactor::One,
-                dst_factor: BlendFactor::OneMinusSrcAlpha,
-                operation: BlendOperation::Add,
-            },
-        };
+        let particle_blend_state = alpha_mode.blend_state();
 
         let instance_buffer_layout = VertexBufferLayout {
             array_stride: std::mem::size_of::<[f32; 4]>() as BufferAddress * 2,
@@ -82,7 +136,7 @@ impl ParticleRenderer {
             },
             fragment: Some(FragmentState {
                 module: &particle_shader,
-                entry_point: "fs_main",
+                entry_point: alpha_mode.fragment_entry_point(),
                 targets: &[Some(ColorTargetState {
                     format: surface_format,
                     blend: Some(particle_blend_state),
@@ -92,13 +146,13 @@ impl ParticleRenderer {
             }),
             primitive: create_primitive_state(None),
             depth_stencil: Some(DepthStencilState {
-                format: TextureFormat::Depth24PlusStencil8,
+                format: DEPTH_FORMAT,
                 depth_write_enabled: false,
                 depth_compare: CompareFunction::Less,
                 stencil: StencilState::default(),
                 bias: DepthBiasState::default(),
             }),
-            multisample: create_multisample_state(),
+            multisample: create_multisample_state_with(sample_count, false),
             multiview: None,
         });
 
@@ -114,7 +168,7 @@ impl ParticleRenderer {
         });
 
         let flame_instance_buffer_layout = VertexBufferLayout {
-            array_stride: std::mem::size_of::<[f32; 4]>() as BufferAddress,
+            array_stride: std::mem::size_of::<FlameInstance>() as BufferAddress,
             step_mode: VertexStepMode::Instance,
             attributes: &[
                 VertexAttribute {
@@ -122,6 +176,11 @@ impl ParticleRenderer {
                     shader_location: 4,
                     format: VertexFormat::Float32x4,
                 },
+                VertexAttribute {
+                    offset: 16,
+                    shader_location: 5,
+                    format: VertexFormat::Float32,
+                },
             ],
         };
 
@@ -157,13 +216,13 @@ impl ParticleRenderer {
             }),
             primitive: create_primitive_state(None),
             depth_stencil: Some(DepthStencilState {
-                format: TextureFormat::Depth24PlusStencil8,
+                format: DEPTH_FORMAT,
                 depth_write_enabled: false,
                 depth_compare: CompareFunction::Less,
                 stencil: StencilState::default(),
                 bias: DepthBiasState::default(),
             }),
-            multisample: create_multisample_state(),
+            multisample: create_multisample_state_with(sample_count, false),
             multiview: None,
         });
 
@@ -207,7 +266,7 @@ impl ParticleRenderer {
             usage: BufferUsages::INDEX,
         });
 
-        let max_particles = 1000;
+        let max_particles = max_particles.max(1);
         #[repr(C)]
         #[derive(Copy, Clone, Pod, Zeroable)]
         struct ParticleInstance {
@@ -223,15 +282,17 @@ impl ParticleRenderer {
             mapped_at_creation: false,
         });
 
-        #[repr(C)]
-        #[derive(Copy, Clone, Pod, Zeroable)]
-        struct FlameInstanceData {
-            position_size: [f32; 4],
-        }
-
         let flame_instance_buffer = device.create_buffer(&BufferDescriptor {
             label: Some("Flame Instance Buffer"),
-            size: (std::mem::size_of::<FlameInstanceData>() * max_particles) as u64,
+            size: (std::mem::size_of::<FlameInstance>() * max_particles) as u64,
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // Separate from the flame instances so a flash drawn in the same frame doesn't overwrite them
+        let flash_instance_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("Flash Instance Buffer"),
+            size: std::mem::size_of::<FlameInstance>() as u64,
             usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
@@ -309,12 +370,16 @@ impl ParticleRenderer {
 
         Self {
             queue,
+            max_particles,
+            particle_overflow_warned: false,
+            flame_overflow_warned: false,
             particle_pipeline: Some(particle_pipeline),
             flame_pipeline: Some(flame_pipeline),
             particle_quad_vertex_buffer: Some(particle_quad_vertex_buffer),
             particle_quad_index_buffer: Some(particle_quad_index_buffer),
             particle_instance_buffer: Some(particle_instance_buffer),
             flame_instance_buffer: Some(flame_instance_buffer),
+            flash_instance_buffer: Some(flash_instance_buffer),
             particle_uniform_buffer: Some(particle_uniform_buffer),
             flame_uniform_buffer: Some(flame_uniform_buffer),
             particle_bind_group: Some(particle_bind_group),
@@ -340,6 +405,8 @@ impl ParticleRenderer {
             return;
         }
 
+        let particles = clamp_instances(particles, self.max_particles, &mut self.particle_overflow_warned, "particle");
+
         #[repr(C)]
         #[derive(Copy, Clone, Pod, Zeroable)]
         struct ParticleUniforms {
@@ -364,8 +431,12 @@ impl ParticleRenderer {
             _padding: [f32; 3],
         }
 
+        let positions: Vec<Vec3> = particles.iter().map(|(position, _, _)| *position).collect();
+        let order = back_to_front_order(&positions, camera_pos);
+
         let mut instance_data: Vec<ParticleInstance> = Vec::with_capacity(particles.len());
-        for (position, size, alpha) in particles {
+        for &idx in &order {
+            let (position, size, alpha) = &particles[idx];
             instance_data.push(ParticleInstance {
                 position_size: [position.x, position.y, position.z, *size],
                 alpha: *alpha,
@@ -421,56 +492,87 @@ impl ParticleRenderer {
         camera_pos: Vec3,
         flames: &[(Vec3, f32, u32)],
     ) {
-        if self.flame_pipeline.is_none()
-            || self.particle_quad_vertex_buffer.is_none()
-            || self.particle_quad_index_buffer.is_none()
-            || self.flame_instance_buffer.is_none()
-            || self.flame_uniform_buffer.is_none()
-            || self.flame_bind_group.is_none()
-            || flames.is_empty() {
+        if self.flame_instance_buffer.is_none() || flames.is_empty() {
             return;
         }
 
-        #[repr(C)]
-        #[derive(Copy, Clone, Pod, Zeroable)]
-        struct FlameUniforms {
-            view_proj: [[f32; 4]; 4],
-            camera_pos: [f32; 4],
-        }
+        let flames = clamp_instances(flames, self.max_particles, &mut self.flame_overflow_warned, "flame");
+        let instance_data: Vec<FlameInstance> = flames
+            .iter()
+            .map(|(position, size, _texture_index)| FlameInstance {
+                position_size: [position.x, position.y, position.z, *size],
+                alpha: 1.0,
+                _padding: [0.0; 3],
+            })
+            .collect();
+        self.queue.write_buffer(
+            self.flame_instance_buffer.as_ref().unwrap(),
+            0,
+            bytemuck::cast_slice(&instance_data),
+        );
+
+        self.draw_flames(encoder, output_view, depth_view, view_proj, camera_pos, FlameBatch::Flames(instance_data.len() as u32));
+    }
 
-        #[repr(C)]
-        #[derive(Copy, Clone, Pod, Zeroable)]
-        struct FlameInstance {
-            position_size: [f32; 4],
+    pub fn render_flash(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        output_view: &TextureView,
+        depth_view: &TextureView,
+        view_proj: Mat4,
+        camera_pos: Vec3,
+        (position, size, alpha): (Vec3, f32, f32),
+    ) {
+        if self.flash_instance_buffer.is_none() || alpha <= 0.0 {
+            return;
         }
 
-        let uniforms = FlameUniforms {
-            view_proj: view_proj.to_cols_array_2d(),
-            camera_pos: [camera_pos.x, camera_pos.y, camera_pos.z, 0.0],
+        let instance = FlameInstance {
+            position_size: [position.x, position.y, position.z, size],
+            alpha,
+            _padding: [0.0; 3],
         };
+        self.queue.write_buffer(
+            self.flash_instance_buffer.as_ref().unwrap(),
+            0,
+            bytemuck::cast_slice(&[instance]),
+        );
 
-        if let Some(ref uniform_buffer) = self.flame_uniform_buffer {
-            self.queue.write_buffer(uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
-        }
+        self.draw_flames(encoder, output_view, depth_view, view_proj, camera_pos, FlameBatch::Flash);
+    }
 
-        let mut instance_data: Vec<FlameInstance> = Vec::with_capacity(flames.len());
-        for (position, size, _texture_index) in flames {
-            instance_data.push(FlameInstance {
-                position_size: [position.x, position.y, position.z, *size],
-            });
-        }
+    fn draw_flames(
+        &self,
+        encoder: &mut CommandEncoder,
+        output_view: &TextureView,
+        depth_view: &TextureView,
+        view_proj: Mat4,
+        camera_pos: Vec3,
+        batch: FlameBatch,
+    ) {
+        let (instance_buffer, count, label) = match batch {
+            FlameBatch::Flames(count) => (&self.flame_instance_buffer, count, "Flame Render Pass"),
+            FlameBatch::Flash => (&self.flash_instance_buffer, 1, "Flash Render Pass"),
+        };
+        let (Some(pipeline), Some(vertex_buffer), Some(index_buffer), Some(instance_buffer), Some(uniform_buffer), Some(bind_group)) = (
+            self.flame_pipeline.as_ref(),
+            self.particle_quad_vertex_buffer.as_ref(),
+            self.particle_quad_index_buffer.as_ref(),
+            instance_buffer.as_ref(),
+            self.flame_uniform_buffer.as_ref(),
+            self.flame_bind_group.as_ref(),
+        ) else {
+            return;
+        };
 
-        if !instance_data.is_empty() {
-            self.queue.write_buffer(
-                self.flame_instance_buffer.as_ref().unwrap(),
-                0,
-                bytemuck::cast_slice(&instance_data),
-            );
-        }
+        let uniforms = FlameUniforms {
+            view_proj: view_proj.to_cols_array_2d(),
+            camera_pos: [camera_pos.x, camera_pos.y, camera_pos.z, 0.0],
+        };
+        self.queue.write_buffer(uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
 
-        let pipeline = self.flame_pipeline.as_ref().unwrap();
         let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
-            label: Some("Flame Render Pass"),
+            label: Some(label),
             color_attachments: &[Some(RenderPassColorAttachment {
                 view: output_view,
                 resolve_target: None,
@@ -492,11 +594,40 @@ impl ParticleRenderer {
         });
 
         render_pass.set_pipeline(pipeline);
-        render_pass.set_bind_group(0, self.flame_bind_group.as_ref().unwrap(), &[]);
-        render_pass.set_vertex_buffer(0, self.particle_quad_vertex_buffer.as_ref().unwrap().slice(..));
-        render_pass.set_vertex_buffer(1, self.flame_instance_buffer.as_ref().unwrap().slice(..));
-        render_pass.set_index_buffer(self.particle_quad_index_buffer.as_ref().unwrap().slice(..), IndexFormat::Uint16);
-        render_pass.draw_indexed(0..6, 0, 0..flames.len() as u32);
+        render_pass.set_bind_group(0, bind_group, &[]);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), IndexFormat::Uint16);
+        render_pass.draw_indexed(0..6, 0, 0..count);
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oversized_draws_are_clamped_with_one_warning() {
+        let particles = [0u32; 5];
+        let mut warned = false;
+        assert_eq!(clamp_instances(&particles, 3, &mut warned, "particle").len(), 3);
+        assert!(warned);
+        // The flag stays set, so later frames draw the capacity without logging again
+        assert_eq!(clamp_instances(&particles, 3, &mut warned, "particle").len(), 3);
+        assert!(warned);
+
+        let mut quiet = false;
+        assert_eq!(clamp_instances(&particles[..3], 3, &mut quiet, "particle").len(), 3);
+        assert!(!quiet);
+    }
+
+    #[test]
+    fn instances_sort_far_to_near() {
+        let camera = Vec3::new(0.0, 0.0, 10.0);
+        let positions = [Vec3::ZERO, Vec3::new(0.0, 0.0, -20.0), Vec3::new(0.0, 0.0, 9.0), Vec3::new(5.0, 0.0, 0.0)];
+        let order = back_to_front_order(&positions, camera);
+        assert_eq!(order, vec![1, 3, 0, 2]);
+        let distances: Vec<f32> = order.iter().map(|&i| positions[i].distance(camera)).collect();
+        assert!(distances.windows(2).all(|pair| pair[0] >= pair[1]));
+    }
+}
@@ -1,8 +1,9 @@
 use wgpu::*;
+use super::types::DEPTH_FORMAT;
 
 pub fn create_depth_stencil_state(depth_write_enabled: bool) -> DepthStencilState {
     DepthStencilState {
-        format: TextureFormat::Depth24PlusStencil8,
+        format: DEPTH_FORMAT,
         depth_write_enabled,
         depth_compare: CompareFunction::Less,
         stencil: StencilState::default(),
@@ -23,17 +24,22 @@ pub fn create_primitive_state(cull_mode: Option<Face>) -> PrimitiveState {
 }
 
 pub fn create_multisample_state() -> MultisampleState {
+    create_multisample_state_with(1, false)
+}
+
+pub fn create_multisample_state_with(sample_count: u32, alpha_to_coverage: bool) -> MultisampleState {
     MultisampleState {
-        count: 1,
+        count: sample_count.max(1),
         mask: !0,
-        alpha_to_coverage_enabled: false,
+        alpha_to_coverage_enabled: alpha_to_coverage && sample_count > 1,
     }
 }
 
-pub fn create_color_target_state(surface_format: TextureFormat) -> ColorTargetState {
+// Opaque geometry overwrites what's behind it; only translucent passes alpha blend
+pub fn create_color_target_state(surface_format: TextureFormat, translucent: bool) -> ColorTargetState {
     ColorTargetState {
         format: surface_format,
-        blend: Some(BlendState::ALPHA_BLENDING),
+        blend: Some(if translucent { BlendState::ALPHA_BLENDING } else { BlendState::REPLACE }),
         write_mask: ColorWrites::ALL,
     }
 }
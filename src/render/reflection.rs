@@ -0,0 +1,159 @@
+use wgpu::*;
+use super::types::DEPTH_FORMAT;
+
+pub struct ReflectionTarget {
+    pub color: Texture,
+    // Single-sampled copy of `color` that the floor samples when rendering with MSAA
+    pub resolve: Option<Texture>,
+    pub depth: Texture,
+    pub sampler: Sampler,
+    pub format: TextureFormat,
+    pub width: u32,
+    pub height: u32,
+    pub sample_count: u32,
+}
+
+impl ReflectionTarget {
+    pub fn new(device: &Device, format: TextureFormat, width: u32, height: u32, sample_count: u32) -> Self {
+        let width = width.max(1);
+        let height = height.max(1);
+        let size = Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+
+        let sample_count = sample_count.max(1);
+        let color_usage = if sample_count > 1 {
+            TextureUsages::RENDER_ATTACHMENT
+        } else {
+            TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING
+        };
+        let color = device.create_texture(&TextureDescriptor {
+            label: Some("Reflection Color Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count,
+            dimension: TextureDimension::D2,
+            format,
+            usage: color_usage,
+            view_formats: &[],
+        });
+
+        let resolve = (sample_count > 1).then(|| {
+            device.create_texture(&TextureDescriptor {
+                label: Some("Reflection Resolve Texture"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format,
+                usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+        });
+
+        let depth = device.create_texture(&TextureDescriptor {
+            label: Some("Reflection Depth Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count,
+            dimension: TextureDimension::D2,
+            format: DEPTH_FORMAT,
+            usage: TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+
+        let sampler = device.create_sampler(&SamplerDescriptor {
+            label: Some("Reflection Sampler"),
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            address_mode_w: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            mipmap_filter: FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        Self {
+            color,
+            resolve,
+            depth,
+            sampler,
+            format,
+            width,
+            height,
+            sample_count,
+        }
+    }
+
+    pub fn matches(&self, format: TextureFormat, width: u32, height: u32, sample_count: u32) -> bool {
+        self.format == format
+            && self.width == width.max(1)
+            && self.height == height.max(1)
+            && self.sample_count == sample_count.max(1)
+    }
+
+    pub fn sampled_view(&self) -> TextureView {
+        self.resolve
+            .as_ref()
+            .unwrap_or(&self.color)
+            .create_view(&TextureViewDescriptor::default())
+    }
+
+    pub fn resolve(&self, encoder: &mut CommandEncoder) {
+        let resolve_view = match self.resolve {
+            Some(ref resolve) => resolve.create_view(&TextureViewDescriptor::default()),
+            None => return,
+        };
+        let color_view = self.color_view();
+        let _pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Reflection Resolve Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: &color_view,
+                resolve_target: Some(&resolve_view),
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+    }
+
+    pub fn color_view(&self) -> TextureView {
+        self.color.create_view(&TextureViewDescriptor::default())
+    }
+
+    pub fn depth_view(&self) -> TextureView {
+        self.depth.create_view(&TextureViewDescriptor::default())
+    }
+
+    pub fn clear(&self, encoder: &mut CommandEncoder) {
+        let color_view = self.color_view();
+        let depth_view = self.depth_view();
+        let _pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Reflection Clear Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: &color_view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Color::TRANSPARENT),
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                view: &depth_view,
+                depth_ops: Some(Operations {
+                    load: LoadOp::Clear(1.0),
+                    store: StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+    }
+}
@@ -1,10 +1,11 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 use wgpu::*;
-use wgpu::util::DeviceExt;
 use glam::{Mat4, Vec3};
 use bytemuck::{Pod, Zeroable};
-use crate::engine::md3::MD3Model;
+use crate::engine::md3::{MD3Model, ModelId};
+use super::buffers::UniformArena;
+use super::types::RenderTarget;
 
 #[derive(Clone, Copy, Debug)]
 struct Edge {
@@ -52,28 +53,68 @@ impl ShadowVolumeVertex {
     }
 }
 
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+struct ShadowVolumeUniforms {
+    view_proj: [[f32; 4]; 4],
+    light_pos: [f32; 4],
+    extrude_distance: f32,
+    _padding: [f32; 3],
+}
+
+struct ShadowVolumeResources {
+    vertex_buffer: Buffer,
+    vertex_capacity: u64,
+    index_buffer: Buffer,
+    index_capacity: u64,
+    uniform_buffer: Buffer,
+    bind_group: BindGroup,
+}
+
+fn grow_capacity(required: u64) -> u64 {
+    required.next_power_of_two().max(1024)
+}
+
 pub struct ShadowRenderer {
     device: Arc<Device>,
+    queue: Arc<Queue>,
     shadow_volume_front_pipeline: Option<RenderPipeline>,
     shadow_volume_back_pipeline: Option<RenderPipeline>,
     shadow_volume_bind_group_layout: BindGroupLayout,
     shadow_apply_pipeline: Option<RenderPipeline>,
     shadow_apply_vertex_buffer: Option<Buffer>,
     shadow_planar_pipeline: Option<RenderPipeline>,
-    silhouette_cache: HashMap<(usize, usize), ModelSilhouetteCache>,
+    planar_bind_group_layout: Option<BindGroupLayout>,
+    // One bind group per uniform arena chunk; lights pick their slot with a dynamic offset
+    planar_bind_groups: Vec<(Arc<Buffer>, BindGroup)>,
+    planar_vertex_buffer: Option<(Buffer, u64)>,
+    silhouette_cache: HashMap<(ModelId, usize), ModelSilhouetteCache>,
+    volume_resources: HashMap<usize, ShadowVolumeResources>,
+    // Volume and planar geometry buffers created so far; flat once sizes settle
+    buffer_allocations: u32,
+    debug_log: bool,
+    ground_plane: (Vec3, f32),
 }
 
 impl ShadowRenderer {
-    pub fn new(device: Arc<Device>, shadow_volume_bind_group_layout: BindGroupLayout) -> Self {
+    pub fn new(device: Arc<Device>, queue: Arc<Queue>, shadow_volume_bind_group_layout: BindGroupLayout) -> Self {
         Self {
             device,
+            queue,
             shadow_volume_front_pipeline: None,
             shadow_volume_back_pipeline: None,
             shadow_volume_bind_group_layout,
             shadow_apply_pipeline: None,
             shadow_apply_vertex_buffer: None,
             shadow_planar_pipeline: None,
+            planar_bind_group_layout: None,
+            planar_bind_groups: Vec::new(),
+            planar_vertex_buffer: None,
             silhouette_cache: HashMap::new(),
+            volume_resources: HashMap::new(),
+            buffer_allocations: 0,
+            debug_log: false,
+            ground_plane: (Vec3::Y, 0.0),
         }
     }
 
@@ -81,6 +122,97 @@ impl ShadowRenderer {
         self.silhouette_cache.clear();
     }
 
+    pub fn clear_model_cache(&mut self, model_id: ModelId) {
+        self.silhouette_cache.retain(|(id, _), _| *id != model_id);
+    }
+
+    pub fn set_ground_plane(&mut self, normal: Vec3, d: f32) {
+        self.ground_plane = (normal, d);
+    }
+
+    pub fn set_debug_log(&mut self, enabled: bool) {
+        self.debug_log = enabled;
+    }
+
+    pub fn buffer_allocations(&self) -> u32 {
+        self.buffer_allocations
+    }
+
+    fn prepare_volume_resources(
+        &mut self,
+        light_idx: usize,
+        vertices: &[ShadowVolumeVertex],
+        indices: &[u16],
+        uniforms: &ShadowVolumeUniforms,
+    ) {
+        let vertex_bytes: &[u8] = bytemuck::cast_slice(vertices);
+        let mut index_bytes: Vec<u8> = bytemuck::cast_slice(indices).to_vec();
+        index_bytes.resize(index_bytes.len().next_multiple_of(COPY_BUFFER_ALIGNMENT as usize), 0);
+
+        let fits = match self.volume_resources.get(&light_idx) {
+            Some(resources) => {
+                resources.vertex_capacity >= vertex_bytes.len() as u64
+                    && resources.index_capacity >= index_bytes.len() as u64
+            }
+            None => false,
+        };
+
+        if !fits {
+            let vertex_capacity = grow_capacity(vertex_bytes.len() as u64);
+            let index_capacity = grow_capacity(index_bytes.len() as u64);
+
+            let vertex_buffer = self.device.create_buffer(&BufferDescriptor {
+                label: Some("Shadow Volume Vertex Buffer"),
+                size: vertex_capacity,
+                usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+
+            let index_buffer = self.device.create_buffer(&BufferDescriptor {
+                label: Some("Shadow Volume Index Buffer"),
+                size: index_capacity,
+                usage: BufferUsages::INDEX | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+
+            let uniform_buffer = match self.volume_resources.remove(&light_idx) {
+                Some(resources) => resources.uniform_buffer,
+                None => self.device.create_buffer(&BufferDescriptor {
+                    label: Some("Shadow Volume Uniform Buffer"),
+                    size: std::mem::size_of::<ShadowVolumeUniforms>() as u64,
+                    usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                }),
+            };
+
+            let bind_group = self.device.create_bind_group(&BindGroupDescriptor {
+                label: Some("Shadow Volume Bind Group"),
+                layout: &self.shadow_volume_bind_group_layout,
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: uniform_buffer.as_entire_binding(),
+                    },
+                ],
+            });
+
+            self.volume_resources.insert(light_idx, ShadowVolumeResources {
+                vertex_buffer,
+                vertex_capacity,
+                index_buffer,
+                index_capacity,
+                uniform_buffer,
+                bind_group,
+            });
+            self.buffer_allocations += 1;
+        }
+
+        let resources = &self.volume_resources[&light_idx];
+        self.queue.write_buffer(&resources.vertex_buffer, 0, vertex_bytes);
+        self.queue.write_buffer(&resources.index_buffer, 0, &index_bytes);
+        self.queue.write_buffer(&resources.uniform_buffer, 0, bytemuck::cast_slice(&[*uniforms]));
+    }
+
     pub fn set_volume_pipelines(&mut self, front: RenderPipeline, back: RenderPipeline) {
         self.shadow_volume_front_pipeline = Some(front);
         self.shadow_volume_back_pipeline = Some(back);
@@ -91,8 +223,51 @@ impl ShadowRenderer {
         self.shadow_apply_vertex_buffer = Some(vertex_buffer);
     }
 
-    pub fn set_planar_pipeline(&mut self, pipeline: RenderPipeline) {
+    pub fn set_planar_pipeline(&mut self, pipeline: RenderPipeline, bind_group_layout: BindGroupLayout) {
         self.shadow_planar_pipeline = Some(pipeline);
+        self.planar_bind_group_layout = Some(bind_group_layout);
+        self.planar_bind_groups.clear();
+    }
+
+    fn planar_bind_group_index(&mut self, buffer: &Arc<Buffer>) -> usize {
+        if let Some(index) = self.planar_bind_groups.iter().position(|(b, _)| Arc::ptr_eq(b, buffer)) {
+            return index;
+        }
+        let bind_group = self.device.create_bind_group(&BindGroupDescriptor {
+            label: Some("Planar Shadow Bind Group"),
+            layout: self.planar_bind_group_layout.as_ref().unwrap(),
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Buffer(BufferBinding {
+                        buffer,
+                        offset: 0,
+                        size: std::num::NonZeroU64::new(std::mem::size_of::<ShadowVolumeUniforms>() as u64),
+                    }),
+                },
+            ],
+        });
+        self.planar_bind_groups.push((buffer.clone(), bind_group));
+        self.planar_bind_groups.len() - 1
+    }
+
+    // Every light's vertices share one buffer that only grows, so it's written once per call
+    fn write_planar_vertices(&mut self, vertices: &[[f32; 3]]) {
+        let bytes: &[u8] = bytemuck::cast_slice(vertices);
+        let fits = self.planar_vertex_buffer.as_ref().is_some_and(|(_, capacity)| *capacity >= bytes.len() as u64);
+        if !fits {
+            let capacity = grow_capacity(bytes.len() as u64);
+            let buffer = self.device.create_buffer(&BufferDescriptor {
+                label: Some("Planar Shadow Vertex Buffer"),
+                size: capacity,
+                usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            self.planar_vertex_buffer = Some((buffer, capacity));
+            self.buffer_allocations += 1;
+        }
+        let (buffer, _) = self.planar_vertex_buffer.as_ref().unwrap();
+        self.queue.write_buffer(buffer, 0, bytes);
     }
 
     fn build_silhouette_cache(&mut self, model: &MD3Model, mesh_idx: usize) -> Option<()> {
@@ -100,8 +275,7 @@ impl ShadowRenderer {
             return None;
         }
 
-        let model_id = std::ptr::addr_of!(*model) as usize;
-        let cache_key = (model_id, mesh_idx);
+        let cache_key = (model.id, mesh_idx);
 
         if self.silhouette_cache.contains_key(&cache_key) {
             return Some(());
@@ -173,8 +347,7 @@ impl ShadowRenderer {
 
         self.build_silhouette_cache(model, mesh_idx);
 
-        let model_id = std::ptr::addr_of!(*model) as usize;
-        let cache_key = (model_id, mesh_idx);
+        let cache_key = (model.id, mesh_idx);
         
         let cache = match self.silhouette_cache.get(&cache_key) {
             Some(c) => c,
@@ -345,11 +518,12 @@ impl ShadowRenderer {
         out
     }
 
+    // Uniforms go through the frame's arena; call once per frame, since the vertex buffer is reused
     pub fn render_planar_shadows(
         &mut self,
         encoder: &mut CommandEncoder,
-        output_view: &TextureView,
-        depth_view: &TextureView,
+        target: RenderTarget,
+        arena: &mut UniformArena,
         view_proj: Mat4,
         models: &[(
             &MD3Model,
@@ -362,7 +536,9 @@ impl ShadowRenderer {
             return;
         }
 
-        let pipeline = self.shadow_planar_pipeline.as_ref().unwrap();
+        let align = self.device.limits().min_uniform_buffer_offset_alignment.max(1) as u64;
+        let mut vertices: Vec<[f32; 3]> = Vec::new();
+        let mut draws = Vec::with_capacity(lights.len());
 
         for (light_pos, _light_color, _radius) in lights {
             let mut triangles = Vec::new();
@@ -393,82 +569,59 @@ impl ShadowRenderer {
                 continue;
             }
 
-            let ground_proj = Self::project_triangles_to_plane(&triangles, *light_pos, Vec3::new(0.0, 1.0, 0.0), 0.0, 0.002);
+            let ground_proj = Self::project_triangles_to_plane(&triangles, *light_pos, self.ground_plane.0, self.ground_plane.1, 0.002);
             let wall_proj = Self::project_triangles_to_plane(&triangles, *light_pos, Vec3::new(0.0, 0.0, 1.0), 3.0, 0.01);
-
-            let mut all_proj = Vec::new();
-            all_proj.extend(ground_proj);
-            all_proj.extend(wall_proj);
-
-            if all_proj.is_empty() {
+            if ground_proj.is_empty() && wall_proj.is_empty() {
                 continue;
             }
 
-            let vertex_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Planar Shadow Vertex Buffer"),
-                contents: bytemuck::cast_slice(&all_proj),
-                usage: BufferUsages::VERTEX,
-            });
-
-            #[repr(C)]
-            #[derive(Copy, Clone, Pod, Zeroable)]
-            struct ShadowPlanarUniforms {
-                view_proj: [[f32; 4]; 4],
-                light_pos: [f32; 4],
-                extrude_distance: f32,
-                _pad: [f32; 3],
-            }
-
-            let uniforms = ShadowPlanarUniforms {
+            let uniforms = ShadowVolumeUniforms {
                 view_proj: view_proj.to_cols_array_2d(),
                 light_pos: [light_pos.x, light_pos.y, light_pos.z, 1.0],
                 extrude_distance: 0.0,
-                _pad: [0.0; 3],
+                _padding: [0.0; 3],
             };
+            let (uniform_buffer, offset) = arena.push(&self.device, &self.queue, bytemuck::bytes_of(&uniforms), align);
+            let bind_group = self.planar_bind_group_index(&uniform_buffer);
 
-            let uniform_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Planar Shadow Uniform Buffer"),
-                contents: bytemuck::cast_slice(&[uniforms]),
-                usage: BufferUsages::UNIFORM,
-            });
+            let first = vertices.len() as u32;
+            vertices.extend(ground_proj);
+            vertices.extend(wall_proj);
+            draws.push((first..vertices.len() as u32, bind_group, offset as u32));
+        }
 
-            let bind_group = self.device.create_bind_group(&BindGroupDescriptor {
-                label: Some("Planar Shadow Bind Group"),
-                layout: &self.shadow_volume_bind_group_layout,
-                entries: &[
-                    BindGroupEntry {
-                        binding: 0,
-                        resource: uniform_buffer.as_entire_binding(),
-                    },
-                ],
-            });
+        if draws.is_empty() {
+            return;
+        }
+        self.write_planar_vertices(&vertices);
 
-            let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
-                label: Some("Planar Shadow Pass"),
-                color_attachments: &[Some(RenderPassColorAttachment {
-                    view: output_view,
-                    resolve_target: None,
-                    ops: Operations {
-                        load: LoadOp::Load,
-                        store: StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
-                    view: depth_view,
-                    depth_ops: Some(Operations {
-                        load: LoadOp::Load,
-                        store: StoreOp::Store,
-                    }),
-                    stencil_ops: None,
+        let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Planar Shadow Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: target.color,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                view: target.depth,
+                depth_ops: Some(Operations {
+                    load: LoadOp::Load,
+                    store: StoreOp::Store,
                 }),
-                occlusion_query_set: None,
-                timestamp_writes: None,
-            });
+                stencil_ops: None,
+            }),
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
 
-            pass.set_pipeline(pipeline);
-            pass.set_bind_group(0, &bind_group, &[]);
-            pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-            pass.draw(0..(all_proj.len() as u32), 0..1);
+        pass.set_pipeline(self.shadow_planar_pipeline.as_ref().unwrap());
+        pass.set_vertex_buffer(0, self.planar_vertex_buffer.as_ref().unwrap().0.slice(..));
+        for (range, bind_group, offset) in draws {
+            pass.set_bind_group(0, &self.planar_bind_groups[bind_group].1, &[offset]);
+            pass.draw(range, 0..1);
         }
     }
 
@@ -484,28 +637,40 @@ impl ShadowRenderer {
             Mat4,
         )],
         lights: &[(Vec3, Vec3, f32)],
-    ) {
+    ) -> u32 {
         if self.shadow_volume_front_pipeline.is_none() || self.shadow_volume_back_pipeline.is_none() {
-            println!("Shadow volume pipeline is None!");
-            return;
+            if self.debug_log {
+                println!("Shadow volume pipeline is None!");
+            }
+            return 0;
         }
         if models.is_empty() {
-            println!("No models for shadows!");
-            return;
+            if self.debug_log {
+                println!("No models for shadows!");
+            }
+            return 0;
         }
         if lights.is_empty() {
-            println!("No lights for shadows!");
-            return;
+            if self.debug_log {
+                println!("No lights for shadows!");
+            }
+            return 0;
+        }
+
+        if self.debug_log {
+            println!("render_shadow_volumes: {} models, {} lights", models.len(), lights.len());
         }
 
-        println!("render_shadow_volumes: {} models, {} lights", models.len(), lights.len());
+        let mut volume_triangles = 0u32;
 
         for (light_idx, (light_pos, _light_color, light_radius)) in lights.iter().enumerate() {
             let mut all_silhouette_edges = Vec::new();
             let mut cap_triangles = Vec::new();
 
             for (model_idx, (model, frame_idx, model_matrix)) in models.iter().enumerate() {
-                println!("  Light {}, Model {}: {} meshes, frame={}", light_idx, model_idx, model.meshes.len(), frame_idx);
+                if self.debug_log {
+                    println!("  Light {}, Model {}: {} meshes, frame={}", light_idx, model_idx, model.meshes.len(), frame_idx);
+                }
                 
                 for mesh_idx in 0..model.meshes.len() {
                     let edges = self.extract_silhouette_edges(
@@ -515,7 +680,9 @@ impl ShadowRenderer {
                         *model_matrix,
                         *light_pos,
                     );
-                    println!("    Mesh {}: {} silhouette edges", mesh_idx, edges.len());
+                    if self.debug_log {
+                        println!("    Mesh {}: {} silhouette edges", mesh_idx, edges.len());
+                    }
                     all_silhouette_edges.extend(edges);
 
                     let mesh = &model.meshes[mesh_idx];
@@ -539,43 +706,32 @@ impl ShadowRenderer {
                 }
             }
 
-            println!("  Total silhouette edges: {}", all_silhouette_edges.len());
+            if self.debug_log {
+                println!("  Total silhouette edges: {}", all_silhouette_edges.len());
+            }
 
             if all_silhouette_edges.is_empty() {
-                println!("  Skipping light {} - no silhouette edges", light_idx);
+                if self.debug_log {
+                    println!("  Skipping light {} - no silhouette edges", light_idx);
+                }
                 continue;
             }
 
             let extrude_dist = light_radius.max(20.0) * 4.0;
             let (vertices, indices) = self.build_shadow_volume(&all_silhouette_edges, &cap_triangles, *light_pos, extrude_dist);
 
-            println!("  Shadow volume: {} vertices, {} indices", vertices.len(), indices.len());
+            if self.debug_log {
+                println!("  Shadow volume: {} vertices, {} indices", vertices.len(), indices.len());
+            }
 
             if vertices.is_empty() || indices.is_empty() {
-                println!("  Skipping light {} - empty geometry", light_idx);
+                if self.debug_log {
+                    println!("  Skipping light {} - empty geometry", light_idx);
+                }
                 continue;
             }
 
-            let vertex_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Shadow Volume Vertex Buffer"),
-                contents: bytemuck::cast_slice(&vertices),
-                usage: BufferUsages::VERTEX,
-            });
-
-            let index_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Shadow Volume Index Buffer"),
-                contents: bytemuck::cast_slice(&indices),
-                usage: BufferUsages::INDEX,
-            });
-
-            #[repr(C)]
-            #[derive(Copy, Clone, Pod, Zeroable)]
-            struct ShadowVolumeUniforms {
-                view_proj: [[f32; 4]; 4],
-                light_pos: [f32; 4],
-                extrude_distance: f32,
-                _padding: [f32; 3],
-            }
+            volume_triangles += indices.len() as u32 / 3;
 
             let uniforms = ShadowVolumeUniforms {
                 view_proj: view_proj.to_cols_array_2d(),
@@ -584,22 +740,9 @@ impl ShadowRenderer {
                 _padding: [0.0; 3],
             };
 
-            let uniform_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Shadow Volume Uniform Buffer"),
-                contents: bytemuck::cast_slice(&[uniforms]),
-                usage: BufferUsages::UNIFORM,
-            });
-
-            let bind_group = self.device.create_bind_group(&BindGroupDescriptor {
-                label: Some("Shadow Volume Bind Group"),
-                layout: &self.shadow_volume_bind_group_layout,
-                entries: &[
-                    BindGroupEntry {
-                        binding: 0,
-                        resource: uniform_buffer.as_entire_binding(),
-                    },
-                ],
-            });
+            self.prepare_volume_resources(light_idx, &vertices, &indices, &uniforms);
+            let resources = &self.volume_resources[&light_idx];
+            let index_count = indices.len() as u32;
 
             let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
                 label: Some("Shadow Volume Render Pass"),
@@ -628,21 +771,21 @@ impl ShadowRenderer {
 
             let pipeline_front = self.shadow_volume_front_pipeline.as_ref().unwrap();
             render_pass.set_pipeline(pipeline_front);
-            render_pass.set_bind_group(0, &bind_group, &[]);
-            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-            render_pass.set_index_buffer(index_buffer.slice(..), IndexFormat::Uint16);
-            render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+            render_pass.set_bind_group(0, &resources.bind_group, &[]);
+            render_pass.set_vertex_buffer(0, resources.vertex_buffer.slice(..));
+            render_pass.set_index_buffer(resources.index_buffer.slice(..), IndexFormat::Uint16);
+            render_pass.draw_indexed(0..index_count, 0, 0..1);
 
             let pipeline_back = self.shadow_volume_back_pipeline.as_ref().unwrap();
             render_pass.set_pipeline(pipeline_back);
-            render_pass.set_bind_group(0, &bind_group, &[]);
-            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-            render_pass.set_index_buffer(index_buffer.slice(..), IndexFormat::Uint16);
-            render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+            render_pass.set_bind_group(0, &resources.bind_group, &[]);
+            render_pass.set_vertex_buffer(0, resources.vertex_buffer.slice(..));
+            render_pass.set_index_buffer(resources.index_buffer.slice(..), IndexFormat::Uint16);
+            render_pass.draw_indexed(0..index_count, 0, 0..1);
         }
 
         if self.shadow_apply_pipeline.is_none() || self.shadow_apply_vertex_buffer.is_none() {
-            return;
+            return volume_triangles;
         }
 
         let mut shadow_apply_pass = encoder.begin_render_pass(&RenderPassDescriptor {
@@ -675,6 +818,43 @@ impl ShadowRenderer {
         shadow_apply_pass.set_stencil_reference(0);
         shadow_apply_pass.set_vertex_buffer(0, self.shadow_apply_vertex_buffer.as_ref().unwrap().slice(..));
         shadow_apply_pass.draw(0..6, 0..1);
+
+        volume_triangles
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::benchmark::create_headless_device;
+    use crate::render::layouts::create_shadow_volume_bind_group_layout;
+
+    #[test]
+    fn static_pose_reuses_shadow_volume_buffers() {
+        let Ok((device, queue, _, _)) = create_headless_device() else {
+            eprintln!("No adapter available, skipping");
+            return;
+        };
+        let layout = create_shadow_volume_bind_group_layout(&device);
+        let mut shadows = ShadowRenderer::new(device, queue, layout);
+
+        let vertices = [ShadowVolumeVertex { position: [0.0; 3], extrude: 0.0 }; 3];
+        let indices = [0u16, 1, 2];
+        let uniforms = ShadowVolumeUniforms {
+            view_proj: Mat4::IDENTITY.to_cols_array_2d(),
+            light_pos: [0.0, 100.0, 0.0, 1.0],
+            extrude_distance: 100.0,
+            _padding: [0.0; 3],
+        };
+        for _ in 0..5 {
+            shadows.prepare_volume_resources(0, &vertices, &indices, &uniforms);
+        }
+        assert_eq!(shadows.buffer_allocations(), 1);
+
+        // A second light gets its own buffers, which are then reused as well
+        for _ in 0..5 {
+            shadows.prepare_volume_resources(1, &vertices, &indices, &uniforms);
+        }
+        assert_eq!(shadows.buffer_allocations(), 2);
+    }
+}
@@ -0,0 +1,238 @@
+use std::path::Path;
+use image::RgbaImage;
+
+// Image type codes from the TGA header
+const TGA_COLOR_MAPPED: u8 = 1;
+const TGA_TRUE_COLOR: u8 = 2;
+const TGA_GRAYSCALE: u8 = 3;
+const TGA_RLE_COLOR_MAPPED: u8 = 9;
+const TGA_RLE_TRUE_COLOR: u8 = 10;
+const TGA_RLE_GRAYSCALE: u8 = 11;
+
+const TGA_HEADER_LEN: usize = 18;
+// Descriptor bits: low nibble is alpha depth, then right-to-left and top-to-bottom origin
+const TGA_ALPHA_BITS_MASK: u8 = 0x0f;
+const TGA_RIGHT_ORIGIN: u8 = 0x10;
+const TGA_TOP_ORIGIN: u8 = 0x20;
+
+// Reads an image as straight-alpha RGBA with its first row at the top
+pub fn load_rgba(path: impl AsRef<Path>) -> Result<RgbaImage, String> {
+    let path = path.as_ref();
+    let data = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    decode_rgba(&data, is_tga_path(path)).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// TGA has no magic number, so the caller says whether `data` is one
+pub fn decode_rgba(data: &[u8], is_tga: bool) -> Result<RgbaImage, String> {
+    if is_tga {
+        if let Some(img) = decode_tga(data)? {
+            return Ok(img);
+        }
+    }
+    image::load_from_memory(data)
+        .map(|img| img.to_rgba8())
+        .map_err(|e| format!("Failed to decode image: {}", e))
+}
+
+pub fn is_tga_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("tga"))
+}
+
+// None for color-mapped files, which are left to `image`
+pub fn decode_tga(data: &[u8]) -> Result<Option<RgbaImage>, String> {
+    if data.len() < TGA_HEADER_LEN {
+        return Err("TGA header is truncated".to_string());
+    }
+    let id_len = data[0] as usize;
+    let color_map_type = data[1];
+    let image_type = data[2];
+    let color_map_len = u16::from_le_bytes([data[5], data[6]]) as usize;
+    let color_map_entry_bits = data[7] as usize;
+    let width = u16::from_le_bytes([data[12], data[13]]) as u32;
+    let height = u16::from_le_bytes([data[14], data[15]]) as u32;
+    let bits = data[16];
+    let descriptor = data[17];
+
+    let (grayscale, rle) = match image_type {
+        TGA_TRUE_COLOR => (false, false),
+        TGA_GRAYSCALE => (true, false),
+        TGA_RLE_TRUE_COLOR => (false, true),
+        TGA_RLE_GRAYSCALE => (true, true),
+        TGA_COLOR_MAPPED | TGA_RLE_COLOR_MAPPED => return Ok(None),
+        other => return Err(format!("unsupported TGA image type {}", other)),
+    };
+    let bytes_per_pixel = match (grayscale, bits) {
+        (true, 8) => 1,
+        (true, 16) => 2,
+        (false, 16) => 2,
+        (false, 24) => 3,
+        (false, 32) => 4,
+        _ => return Err(format!("unsupported TGA pixel depth {}", bits)),
+    };
+    if width == 0 || height == 0 {
+        return Err("TGA has zero size".to_string());
+    }
+
+    let mut offset = TGA_HEADER_LEN + id_len;
+    if color_map_type != 0 {
+        offset += color_map_len * color_map_entry_bits.div_ceil(8);
+    }
+    let pixel_count = (width * height) as usize;
+    let raw = if rle {
+        read_tga_rle(data.get(offset..).unwrap_or(&[]), pixel_count, bytes_per_pixel)?
+    } else {
+        let len = pixel_count * bytes_per_pixel;
+        data.get(offset..offset + len)
+            .ok_or_else(|| "TGA pixel data is truncated".to_string())?
+            .to_vec()
+    };
+
+    let mut pixels = Vec::with_capacity(pixel_count * 4);
+    for px in raw.chunks_exact(bytes_per_pixel) {
+        let rgba = match (grayscale, bytes_per_pixel) {
+            (true, 1) => [px[0], px[0], px[0], 255],
+            (true, _) => [px[0], px[0], px[0], px[1]],
+            (false, 2) => {
+                // A1R5G5B5, little endian
+                let v = u16::from_le_bytes([px[0], px[1]]);
+                let channel = |shift: u16| (((v >> shift) & 0x1f) as u32 * 255 / 31) as u8;
+                let alpha = if v & 0x8000 != 0 { 255 } else { 0 };
+                [channel(10), channel(5), channel(0), alpha]
+            }
+            (false, 3) => [px[2], px[1], px[0], 255],
+            _ => [px[2], px[1], px[0], px[3]],
+        };
+        pixels.extend_from_slice(&rgba);
+    }
+
+    // Writers that don't declare alpha bits often leave the channel zeroed; treat that as opaque
+    // rather than fully transparent. Alpha is otherwise kept straight, never premultiplied
+    let has_alpha_channel = bytes_per_pixel == 4 || bytes_per_pixel == 2;
+    if has_alpha_channel
+        && descriptor & TGA_ALPHA_BITS_MASK == 0
+        && pixels.chunks_exact(4).all(|px| px[3] == 0)
+    {
+        pixels.chunks_exact_mut(4).for_each(|px| px[3] = 255);
+    }
+
+    let row_len = width as usize * 4;
+    if descriptor & TGA_RIGHT_ORIGIN != 0 {
+        for row in pixels.chunks_exact_mut(row_len) {
+            let mirrored: Vec<u8> = row.chunks_exact(4).rev().flatten().copied().collect();
+            row.copy_from_slice(&mirrored);
+        }
+    }
+    // Bottom-left is the TGA default, so rows are flipped unless the top origin bit is set
+    if descriptor & TGA_TOP_ORIGIN == 0 {
+        let rows: Vec<&[u8]> = pixels.chunks_exact(row_len).rev().collect();
+        pixels = rows.concat();
+    }
+
+    RgbaImage::from_raw(width, height, pixels)
+        .map(Some)
+        .ok_or_else(|| "TGA pixel buffer has the wrong size".to_string())
+}
+
+fn read_tga_rle(data: &[u8], pixel_count: usize, bytes_per_pixel: usize) -> Result<Vec<u8>, String> {
+    let truncated = || "TGA RLE data is truncated".to_string();
+    // The header's size is untrusted, so only reserve what the input could plausibly fill
+    let mut out = Vec::with_capacity((pixel_count * bytes_per_pixel).min(data.len()));
+    let mut pos = 0;
+    while out.len() < pixel_count * bytes_per_pixel {
+        let header = *data.get(pos).ok_or_else(truncated)?;
+        pos += 1;
+        let count = (header & 0x7f) as usize + 1;
+        if header & 0x80 != 0 {
+            let px = data.get(pos..pos + bytes_per_pixel).ok_or_else(truncated)?;
+            pos += bytes_per_pixel;
+            for _ in 0..count {
+                out.extend_from_slice(px);
+            }
+        } else {
+            let len = count * bytes_per_pixel;
+            out.extend_from_slice(data.get(pos..pos + len).ok_or_else(truncated)?);
+            pos += len;
+        }
+    }
+    // A packet may run past the last pixel
+    out.truncate(pixel_count * bytes_per_pixel);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tga_header(image_type: u8, width: u16, height: u16, bits: u8, descriptor: u8) -> Vec<u8> {
+        let mut header = vec![0u8; TGA_HEADER_LEN];
+        header[2] = image_type;
+        header[12..14].copy_from_slice(&width.to_le_bytes());
+        header[14..16].copy_from_slice(&height.to_le_bytes());
+        header[16] = bits;
+        header[17] = descriptor;
+        header
+    }
+
+    // Stored as BGR
+    const RED: [u8; 3] = [0, 0, 255];
+    const GREEN: [u8; 3] = [0, 255, 0];
+    const BLUE: [u8; 3] = [255, 0, 0];
+    const WHITE: [u8; 3] = [255, 255, 255];
+
+    fn assert_pixel_order(img: &RgbaImage) {
+        assert_eq!(img.dimensions(), (2, 2));
+        assert_eq!(img.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(img.get_pixel(1, 0).0, [0, 255, 0, 255]);
+        assert_eq!(img.get_pixel(0, 1).0, [0, 0, 255, 255]);
+        assert_eq!(img.get_pixel(1, 1).0, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn bottom_left_origin_is_flipped_to_top_row_first() {
+        let mut data = tga_header(TGA_TRUE_COLOR, 2, 2, 24, 0);
+        for px in [BLUE, WHITE, RED, GREEN] {
+            data.extend_from_slice(&px);
+        }
+        assert_pixel_order(&decode_tga(&data).unwrap().unwrap());
+    }
+
+    #[test]
+    fn top_left_origin_keeps_row_order() {
+        let mut data = tga_header(TGA_TRUE_COLOR, 2, 2, 24, TGA_TOP_ORIGIN);
+        for px in [RED, GREEN, BLUE, WHITE] {
+            data.extend_from_slice(&px);
+        }
+        assert_pixel_order(&decode_tga(&data).unwrap().unwrap());
+    }
+
+    #[test]
+    fn rle_packets_decode_in_pixel_order() {
+        let mut data = tga_header(TGA_RLE_TRUE_COLOR, 2, 2, 24, TGA_TOP_ORIGIN);
+        // A raw packet of two pixels, then a run packet repeating one
+        data.push(0x01);
+        data.extend_from_slice(&RED);
+        data.extend_from_slice(&GREEN);
+        data.push(0x80);
+        data.extend_from_slice(&BLUE);
+        data.push(0x80);
+        data.extend_from_slice(&WHITE);
+        assert_pixel_order(&decode_tga(&data).unwrap().unwrap());
+    }
+
+    #[test]
+    fn zero_alpha_without_alpha_bits_is_opaque() {
+        let mut data = tga_header(TGA_TRUE_COLOR, 1, 1, 32, 0);
+        data.extend_from_slice(&[10, 20, 30, 0]);
+        let img = decode_tga(&data).unwrap().unwrap();
+        assert_eq!(img.get_pixel(0, 0).0, [30, 20, 10, 255]);
+    }
+
+    #[test]
+    fn huge_rle_header_with_little_data_fails_cleanly() {
+        let mut data = tga_header(TGA_RLE_TRUE_COLOR, u16::MAX, u16::MAX, 32, 0);
+        data.extend_from_slice(&[0xff, 1, 2, 3, 4]);
+        assert!(decode_tga(&data).is_err());
+    }
+}
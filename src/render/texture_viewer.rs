@@ -0,0 +1,256 @@
+use wgpu::*;
+use wgpu::util::DeviceExt;
+use super::types::WgpuTexture;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: [f32; 2],
+    uv: [f32; 2],
+}
+
+impl Vertex {
+    fn desc() -> VertexBufferLayout<'static> {
+        VertexBufferLayout {
+            array_stride: std::mem::size_of::<Vertex>() as BufferAddress,
+            step_mode: VertexStepMode::Vertex,
+            attributes: &[
+                VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: VertexFormat::Float32x2,
+                },
+                VertexAttribute {
+                    offset: 8,
+                    shader_location: 1,
+                    format: VertexFormat::Float32x2,
+                },
+            ],
+        }
+    }
+}
+
+pub fn fit_quad(texture_width: u32, texture_height: u32, width: u32, height: u32, margin: f32) -> [f32; 2] {
+    let screen_aspect = width.max(1) as f32 / height.max(1) as f32;
+    let texture_aspect = texture_width.max(1) as f32 / texture_height.max(1) as f32;
+    let extent = 1.0 - margin;
+    if texture_aspect > screen_aspect {
+        [extent, extent * screen_aspect / texture_aspect]
+    } else {
+        [extent * texture_aspect / screen_aspect, extent]
+    }
+}
+
+pub struct TextureViewer {
+    pipeline: RenderPipeline,
+    bind_group_layout: BindGroupLayout,
+    vertex_buffer: Buffer,
+}
+
+impl TextureViewer {
+    pub fn new(device: &Device, format: TextureFormat) -> Self {
+        let shader = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("Texture Viewer Shader"),
+            source: ShaderSource::Wgsl(include_str!("../shaders/texture_view.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Texture Viewer Bind Group Layout"),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: TextureViewDimension::D2,
+                        sample_type: TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("Texture Viewer Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("Texture Viewer Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(ColorTargetState {
+                    format,
+                    blend: Some(BlendState::ALPHA_BLENDING),
+                    write_mask: ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            multiview: None,
+        });
+
+        let vertex_buffer = device.create_buffer_init(&util::BufferInitDescriptor {
+            label: Some("Texture Viewer Vertex Buffer"),
+            contents: bytemuck::cast_slice(&[Vertex { position: [0.0; 2], uv: [0.0; 2] }; 6]),
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+        });
+
+        Self {
+            pipeline,
+            bind_group_layout,
+            vertex_buffer,
+        }
+    }
+
+    pub fn render(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        view: &TextureView,
+        queue: &Queue,
+        texture: &WgpuTexture,
+        viewport: (u32, u32),
+    ) {
+        let (width, height) = viewport;
+        let size = texture.texture.size();
+        let [half_w, half_h] = fit_quad(size.width, size.height, width, height, 0.1);
+        let vertices = [
+            Vertex { position: [-half_w, -half_h], uv: [0.0, 1.0] },
+            Vertex { position: [half_w, -half_h], uv: [1.0, 1.0] },
+            Vertex { position: [half_w, half_h], uv: [1.0, 0.0] },
+            Vertex { position: [-half_w, -half_h], uv: [0.0, 1.0] },
+            Vertex { position: [half_w, half_h], uv: [1.0, 0.0] },
+            Vertex { position: [-half_w, half_h], uv: [0.0, 0.0] },
+        ];
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("Texture Viewer Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(&texture.view),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Sampler(&texture.sampler),
+                },
+            ],
+        });
+
+        let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Texture Viewer Render Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Color { r: 0.15, g: 0.15, b: 0.15, a: 1.0 }),
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.draw(0..6, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::benchmark::{create_headless_device, create_white_texture};
+    use crate::render::MD3Renderer;
+
+    const SIZE: u32 = 64;
+    const FORMAT: TextureFormat = TextureFormat::Rgba8Unorm;
+
+    #[test]
+    fn draws_a_loaded_texture_by_key() {
+        let Ok((device, queue, _, _)) = create_headless_device() else {
+            eprintln!("No adapter available, skipping");
+            return;
+        };
+        let mut md3_renderer = MD3Renderer::new(device.clone(), queue.clone());
+        md3_renderer.load_texture("models/test/white.tga", create_white_texture(&device, &queue));
+        let viewer = TextureViewer::new(&device, FORMAT);
+
+        let target = device.create_texture(&TextureDescriptor {
+            label: None,
+            size: Extent3d { width: SIZE, height: SIZE, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: FORMAT,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let readback = device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: (SIZE * SIZE * 4) as u64,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let key = &md3_renderer.texture_keys()[0];
+        let texture = md3_renderer.texture(key).unwrap();
+        let view = target.create_view(&TextureViewDescriptor::default());
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        viewer.render(&device, &mut encoder, &view, &queue, texture, (SIZE, SIZE));
+        encoder.copy_texture_to_buffer(
+            target.as_image_copy(),
+            ImageCopyBuffer {
+                buffer: &readback,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(SIZE * 4),
+                    rows_per_image: Some(SIZE),
+                },
+            },
+            target.size(),
+        );
+        queue.submit(Some(encoder.finish()));
+
+        readback.slice(..).map_async(MapMode::Read, |_| ());
+        device.poll(Maintain::Wait);
+        let pixels = readback.slice(..).get_mapped_range();
+        let pixel = |x: u32, y: u32| {
+            let i = ((y * SIZE + x) * 4) as usize;
+            [pixels[i], pixels[i + 1], pixels[i + 2]]
+        };
+        // The texture fills the middle; the margin keeps the cleared background
+        assert_eq!(pixel(SIZE / 2, SIZE / 2), [255, 255, 255]);
+        assert!(pixel(0, 0)[0] < 64);
+    }
+}
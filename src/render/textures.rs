@@ -1,5 +1,7 @@
 use wgpu::*;
-use crate::render::types::WgpuTexture;
+use crate::render::types::{ParticleAlphaMode, WgpuTexture};
+use crate::resource_path::resolve;
+use crate::render::texture_loader::load_rgba;
 
 fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
     let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
@@ -79,76 +81,73 @@ fn add_coordinate_grid_to_texture(
     pixels
 }
 
-pub fn create_ground_texture(device: &Device, queue: &Queue) -> WgpuTexture {
-    let texture_paths = vec![
-        "../q3-resources/textures/base_floor/clang_floor3b.png",
-        "../q3-resources/textures/base_floor/clang_floor3.png",
-        "../q3-resources/textures/base_floor/clang_floor2.png",
-        "../q3-resources/textures/base_floor/clang_floor1.png",
-        "../q3-resources/textures/base_floor/floor1.png",
-        "q3-resources/textures/base_floor/clang_floor3b.png",
-        "q3-resources/textures/base_floor/clang_floor3.png",
-        "q3-resources/textures/base_floor/clang_floor2.png",
-        "q3-resources/textures/base_floor/clang_floor1.png",
-        "q3-resources/textures/base_floor/floor1.png",
+// Matches the samplers of MD3 skins and the ground, wall and tile textures, so they can all be
+// rebuilt when anisotropy changes
+pub fn create_model_sampler(device: &Device, anisotropy: u16) -> Sampler {
+    device.create_sampler(&SamplerDescriptor {
+        address_mode_u: AddressMode::Repeat,
+        address_mode_v: AddressMode::Repeat,
+        address_mode_w: AddressMode::Repeat,
+        mag_filter: FilterMode::Linear,
+        min_filter: FilterMode::Linear,
+        mipmap_filter: FilterMode::Linear,
+        anisotropy_clamp: anisotropy,
+        ..Default::default()
+    })
+}
+
+pub fn create_ground_texture(device: &Device, queue: &Queue, anisotropy: u16) -> WgpuTexture {
+    let texture_paths = [
+        "textures/base_floor/clang_floor3b.png",
+        "textures/base_floor/clang_floor3.png",
+        "textures/base_floor/clang_floor2.png",
+        "textures/base_floor/clang_floor1.png",
+        "textures/base_floor/floor1.png",
     ];
 
-    for texture_path in texture_paths {
-        if std::path::Path::new(&texture_path).exists() {
-            if let Ok(data) = std::fs::read(&texture_path) {
-                if let Ok(img) = image::load_from_memory(&data) {
-                    let img = img.to_rgba8();
-                    let size = Extent3d {
-                        width: img.width(),
-                        height: img.height(),
-                        depth_or_array_layers: 1,
-                    };
-                    let texture = device.create_texture(&TextureDescriptor {
-                        label: Some("Ground Texture"),
-                        size,
-                        mip_level_count: 1,
-                        sample_count: 1,
-                        dimension: TextureDimension::D2,
-                        format: TextureFormat::Rgba8UnormSrgb,
-                        usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
-                        view_formats: &[],
-                    });
-
-                    queue.write_texture(
-                        ImageCopyTexture {
-                            texture: &texture,
-                            mip_level: 0,
-                            origin: Origin3d::ZERO,
-                            aspect: TextureAspect::All,
-                        },
-                        &img,
-                        ImageDataLayout {
-                            offset: 0,
-                            bytes_per_row: Some(4 * img.width()),
-                            rows_per_image: Some(img.height()),
-                        },
-                        size,
-                    );
-
-                    let view = texture.create_view(&TextureViewDescriptor::default());
-                    let sampler = device.create_sampler(&SamplerDescriptor {
-                        address_mode_u: AddressMode::Repeat,
-                        address_mode_v: AddressMode::Repeat,
-                        address_mode_w: AddressMode::Repeat,
-                        mag_filter: FilterMode::Linear,
-                        min_filter: FilterMode::Linear,
-                        mipmap_filter: FilterMode::Linear,
-                        ..Default::default()
-                    });
-
-                    println!("Loaded ground texture from: {}", texture_path);
-                    return WgpuTexture {
-                        texture,
-                        view,
-                        sampler,
-                    };
-                }
-            }
+    for texture_path in texture_paths.iter().filter_map(|path| resolve(path, &[])) {
+        if let Ok(img) = load_rgba(&texture_path) {
+            let size = Extent3d {
+                width: img.width(),
+                height: img.height(),
+                depth_or_array_layers: 1,
+            };
+            let texture = device.create_texture(&TextureDescriptor {
+                label: Some("Ground Texture"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::Rgba8UnormSrgb,
+                usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+                view_formats: &[],
+            });
+
+            queue.write_texture(
+                ImageCopyTexture {
+                    texture: &texture,
+                    mip_level: 0,
+                    origin: Origin3d::ZERO,
+                    aspect: TextureAspect::All,
+                },
+                &img,
+                ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * img.width()),
+                    rows_per_image: Some(img.height()),
+                },
+                size,
+            );
+
+            let view = texture.create_view(&TextureViewDescriptor::default());
+            let sampler = create_model_sampler(device, anisotropy);
+
+            println!("Loaded ground texture from: {}", texture_path.display());
+            return WgpuTexture {
+                texture,
+                view,
+                sampler,
+            };
         }
     }
 
@@ -210,15 +209,7 @@ pub fn create_ground_texture(device: &Device, queue: &Queue) -> WgpuTexture {
     );
 
     let view = texture.create_view(&TextureViewDescriptor::default());
-    let sampler = device.create_sampler(&SamplerDescriptor {
-        address_mode_u: AddressMode::Repeat,
-        address_mode_v: AddressMode::Repeat,
-        address_mode_w: AddressMode::Repeat,
-        mag_filter: FilterMode::Linear,
-        min_filter: FilterMode::Linear,
-        mipmap_filter: FilterMode::Linear,
-        ..Default::default()
-    });
+    let sampler = create_model_sampler(device, anisotropy);
 
     WgpuTexture {
         texture,
@@ -227,160 +218,122 @@ pub fn create_ground_texture(device: &Device, queue: &Queue) -> WgpuTexture {
     }
 }
 
-pub fn create_wall_texture(device: &Device, queue: &Queue) -> (WgpuTexture, WgpuTexture) {
-    let texture_paths = vec![
-        "../q3-resources/textures/base_wall/atech2_c.png",
-        "../q3-resources/textures/base_wall/atech3_a.png",
-        "../q3-resources/textures/base_wall/basewall04.png",
-        "../q3-resources/textures/base_wall/concrete.png",
-        "../q3-resources/textures/base_wall/atech1_a.png",
-        "q3-resources/textures/base_wall/atech2_c.png",
-        "q3-resources/textures/base_wall/atech3_a.png",
-        "q3-resources/textures/base_wall/basewall04.png",
-        "q3-resources/textures/base_wall/concrete.png",
-        "q3-resources/textures/base_wall/atech1_a.png",
+pub fn create_wall_texture(device: &Device, queue: &Queue, anisotropy: u16) -> (WgpuTexture, WgpuTexture) {
+    let texture_paths = [
+        "textures/base_wall/atech2_c.png",
+        "textures/base_wall/atech3_a.png",
+        "textures/base_wall/basewall04.png",
+        "textures/base_wall/concrete.png",
+        "textures/base_wall/atech1_a.png",
     ];
 
     let mut wall_texture = None;
-    for texture_path in texture_paths {
-        if std::path::Path::new(&texture_path).exists() {
-            if let Ok(data) = std::fs::read(&texture_path) {
-                if let Ok(img) = image::load_from_memory(&data) {
-                    let img = img.to_rgba8();
-                    let size = Extent3d {
-                        width: img.width(),
-                        height: img.height(),
-                        depth_or_array_layers: 1,
-                    };
-                    
-                    println!("Loaded wall texture from: {}", texture_path);
-                    
-                    let img_data = img.as_raw();
-                    
-                    let texture = device.create_texture(&TextureDescriptor {
-                        label: Some("Wall Texture"),
-                        size,
-                        mip_level_count: 1,
-                        sample_count: 1,
-                        dimension: TextureDimension::D2,
-                        format: TextureFormat::Rgba8UnormSrgb,
-                        usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
-                        view_formats: &[],
-                    });
-                    
-                    queue.write_texture(
-                        ImageCopyTexture {
-                            texture: &texture,
-                            mip_level: 0,
-                            origin: Origin3d::ZERO,
-                            aspect: TextureAspect::All,
-                        },
-                        img_data,
-                        ImageDataLayout {
-                            offset: 0,
-                            bytes_per_row: Some(4 * img.width()),
-                            rows_per_image: Some(img.height()),
-                        },
-                        size,
-                    );
-                    
-                    let view = texture.create_view(&TextureViewDescriptor::default());
-                    let sampler = device.create_sampler(&SamplerDescriptor {
-                        address_mode_u: AddressMode::Repeat,
-                        address_mode_v: AddressMode::Repeat,
-                        address_mode_w: AddressMode::Repeat,
-                        mag_filter: FilterMode::Linear,
-                        min_filter: FilterMode::Linear,
-                        mipmap_filter: FilterMode::Linear,
-                        ..Default::default()
-                    });
-                    
-                    wall_texture = Some(WgpuTexture {
-                        texture,
-                        view,
-                        sampler,
-                    });
-                    break;
-                }
-            }
+    for texture_path in texture_paths.iter().filter_map(|path| resolve(path, &[])) {
+        if let Ok(img) = load_rgba(&texture_path) {
+            let size = Extent3d {
+                width: img.width(),
+                height: img.height(),
+                depth_or_array_layers: 1,
+            };
+            
+            println!("Loaded wall texture from: {}", texture_path.display());
+            
+            let img_data = img.as_raw();
+            
+            let texture = device.create_texture(&TextureDescriptor {
+                label: Some("Wall Texture"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::Rgba8UnormSrgb,
+                usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+                view_formats: &[],
+            });
+            
+            queue.write_texture(
+                ImageCopyTexture {
+                    texture: &texture,
+                    mip_level: 0,
+                    origin: Origin3d::ZERO,
+                    aspect: TextureAspect::All,
+                },
+                img_data,
+                ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * img.width()),
+                    rows_per_image: Some(img.height()),
+                },
+                size,
+            );
+            
+            let view = texture.create_view(&TextureViewDescriptor::default());
+            let sampler = create_model_sampler(device, anisotropy);
+            
+            wall_texture = Some(WgpuTexture {
+                texture,
+                view,
+                sampler,
+            });
+            break;
         }
     }
 
-    let curb_texture_paths = vec![
-        "../q3-resources/textures/base_trim/border11.png",
-        "../q3-resources/textures/base_trim/spiderbit4.png",
-        "../q3-resources/textures/base_trim/dirty_pewter_big.png",
-        "../q3-resources/textures/base_trim/rusty_pewter_big.png",
-        "../q3-resources/textures/base_trim/metal2_2.png",
-        "../q3-resources/textures/base_trim/pewter.png",
-        "../q3-resources/textures/base_trim/tin.png",
-        "q3-resources/textures/base_trim/border11.png",
-        "q3-resources/textures/base_trim/spiderbit4.png",
-        "q3-resources/textures/base_trim/dirty_pewter_big.png",
-        "q3-resources/textures/base_trim/rusty_pewter_big.png",
-        "q3-resources/textures/base_trim/metal2_2.png",
-        "q3-resources/textures/base_trim/pewter.png",
-        "q3-resources/textures/base_trim/tin.png",
+    let curb_texture_paths = [
+        "textures/base_trim/border11.png",
+        "textures/base_trim/spiderbit4.png",
+        "textures/base_trim/dirty_pewter_big.png",
+        "textures/base_trim/rusty_pewter_big.png",
+        "textures/base_trim/metal2_2.png",
+        "textures/base_trim/pewter.png",
+        "textures/base_trim/tin.png",
     ];
 
     let mut curb_texture = None;
-    for texture_path in curb_texture_paths {
-        if std::path::Path::new(&texture_path).exists() {
-            if let Ok(data) = std::fs::read(&texture_path) {
-                if let Ok(img) = image::load_from_memory(&data) {
-                    let img = img.to_rgba8();
-                    let size = Extent3d {
-                        width: img.width(),
-                        height: img.height(),
-                        depth_or_array_layers: 1,
-                    };
-                    let texture = device.create_texture(&TextureDescriptor {
-                        label: Some("Wall Curb Texture"),
-                        size,
-                        mip_level_count: 1,
-                        sample_count: 1,
-                        dimension: TextureDimension::D2,
-                        format: TextureFormat::Rgba8UnormSrgb,
-                        usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
-                        view_formats: &[],
-                    });
-
-                    queue.write_texture(
-                        ImageCopyTexture {
-                            texture: &texture,
-                            mip_level: 0,
-                            origin: Origin3d::ZERO,
-                            aspect: TextureAspect::All,
-                        },
-                        &img,
-                        ImageDataLayout {
-                            offset: 0,
-                            bytes_per_row: Some(4 * img.width()),
-                            rows_per_image: Some(img.height()),
-                        },
-                        size,
-                    );
-
-                    let view = texture.create_view(&TextureViewDescriptor::default());
-                    let sampler = device.create_sampler(&SamplerDescriptor {
-                        address_mode_u: AddressMode::Repeat,
-                        address_mode_v: AddressMode::Repeat,
-                        address_mode_w: AddressMode::Repeat,
-                        mag_filter: FilterMode::Linear,
-                        min_filter: FilterMode::Linear,
-                        mipmap_filter: FilterMode::Linear,
-                        ..Default::default()
-                    });
-
-                    println!("Loaded wall curb texture from: {}", texture_path);
-                    curb_texture = Some(WgpuTexture {
-                        texture,
-                        view,
-                        sampler,
-                    });
-                    break;
-                }
-            }
+    for texture_path in curb_texture_paths.iter().filter_map(|path| resolve(path, &[])) {
+        if let Ok(img) = load_rgba(&texture_path) {
+            let size = Extent3d {
+                width: img.width(),
+                height: img.height(),
+                depth_or_array_layers: 1,
+            };
+            let texture = device.create_texture(&TextureDescriptor {
+                label: Some("Wall Curb Texture"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::Rgba8UnormSrgb,
+                usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+                view_formats: &[],
+            });
+
+            queue.write_texture(
+                ImageCopyTexture {
+                    texture: &texture,
+                    mip_level: 0,
+                    origin: Origin3d::ZERO,
+                    aspect: TextureAspect::All,
+                },
+                &img,
+                ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * img.width()),
+                    rows_per_image: Some(img.height()),
+                },
+                size,
+            );
+
+            let view = texture.create_view(&TextureViewDescriptor::default());
+            let sampler = create_model_sampler(device, anisotropy);
+
+            println!("Loaded wall curb texture from: {}", texture_path.display());
+            curb_texture = Some(WgpuTexture {
+                texture,
+                view,
+                sampler,
+            });
+            break;
         }
     }
 
@@ -463,15 +416,7 @@ pub fn create_wall_texture(device: &Device, queue: &Queue) -> (WgpuTexture, Wgpu
         );
 
         let view = texture.create_view(&TextureViewDescriptor::default());
-        let sampler = device.create_sampler(&SamplerDescriptor {
-            address_mode_u: AddressMode::Repeat,
-            address_mode_v: AddressMode::Repeat,
-            address_mode_w: AddressMode::Repeat,
-            mag_filter: FilterMode::Linear,
-            min_filter: FilterMode::Linear,
-            mipmap_filter: FilterMode::Linear,
-            ..Default::default()
-        });
+        let sampler = create_model_sampler(device, anisotropy);
 
         WgpuTexture {
             texture,
@@ -543,15 +488,7 @@ pub fn create_wall_texture(device: &Device, queue: &Queue) -> (WgpuTexture, Wgpu
         );
 
         let view = texture.create_view(&TextureViewDescriptor::default());
-        let sampler = device.create_sampler(&SamplerDescriptor {
-            address_mode_u: AddressMode::Repeat,
-            address_mode_v: AddressMode::Repeat,
-            address_mode_w: AddressMode::Repeat,
-            mag_filter: FilterMode::Linear,
-            min_filter: FilterMode::Linear,
-            mipmap_filter: FilterMode::Linear,
-            ..Default::default()
-        });
+        let sampler = create_model_sampler(device, anisotropy);
 
         WgpuTexture {
             texture,
@@ -563,81 +500,49 @@ pub fn create_wall_texture(device: &Device, queue: &Queue) -> (WgpuTexture, Wgpu
     (wall_texture, curb_texture)
 }
 
-pub fn create_smoke_texture(device: &Device, queue: &Queue) -> WgpuTexture {
-    let candidates = vec![
-        "q3-resources/gfx/misc/smokepuff2b.png",
-        "q3-resources/gfx/misc/smokepuff3.png",
-        "../q3-resources/gfx/misc/smokepuff2b.png",
-        "../q3-resources/gfx/misc/smokepuff3.png",
-    ];
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
 
-    for path in candidates {
-        if std::path::Path::new(path).exists() {
-            if let Ok(data) = std::fs::read(path) {
-                if let Ok(img) = image::load_from_memory(&data) {
-                    let img = img.to_rgba8();
-                    let size = Extent3d {
-                        width: img.width(),
-                        height: img.height(),
-                        depth_or_array_layers: 1,
-                    };
-                    let texture = device.create_texture(&TextureDescriptor {
-                        label: Some("Smoke Texture"),
-                        size,
-                        mip_level_count: 1,
-                        sample_count: 1,
-                        dimension: TextureDimension::D2,
-                        format: TextureFormat::Rgba8Unorm,
-                        usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
-                        view_formats: &[],
-                    });
-
-                    queue.write_texture(
-                        ImageCopyTexture {
-                            texture: &texture,
-                            mip_level: 0,
-                            origin: Origin3d::ZERO,
-                            aspect: TextureAspect::All,
-                        },
-                        &img,
-                        ImageDataLayout {
-                            offset: 0,
-                            bytes_per_row: Some(4 * img.width()),
-                            rows_per_image: Some(img.height()),
-                        },
-                        size,
-                    );
-
-                    let view = texture.create_view(&TextureViewDescriptor::default());
-                    let sampler = device.create_sampler(&SamplerDescriptor {
-                        address_mode_u: AddressMode::ClampToEdge,
-                        address_mode_v: AddressMode::ClampToEdge,
-                        address_mode_w: AddressMode::ClampToEdge,
-                        mag_filter: FilterMode::Linear,
-                        min_filter: FilterMode::Linear,
-                        mipmap_filter: FilterMode::Linear,
-                        ..Default::default()
-                    });
-
-                    return WgpuTexture {
-                        texture,
-                        view,
-                        sampler,
-                    };
-                }
-            }
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+pub fn premultiply_srgba8(pixels: &mut [u8]) {
+    for px in pixels.chunks_exact_mut(4) {
+        let a = px[3] as f32 / 255.0;
+        for c in &mut px[..3] {
+            let linear = srgb_to_linear(*c as f32 / 255.0) * a;
+            *c = (linear_to_srgb(linear) * 255.0).round() as u8;
         }
     }
+}
 
-    let size = 64u32;
+fn alpha_from_luminance_if_opaque(pixels: &mut [u8]) {
+    if pixels.chunks_exact(4).any(|px| px[3] != 255) {
+        return;
+    }
+    for px in pixels.chunks_exact_mut(4) {
+        let luminance = 0.299 * px[0] as f32 + 0.587 * px[1] as f32 + 0.114 * px[2] as f32;
+        px[3] = luminance.round().min(255.0) as u8;
+    }
+}
+
+pub fn smoke_puff_pixels(size: u32, alpha_mode: ParticleAlphaMode) -> Vec<u8> {
     let mut pixels = Vec::with_capacity((size * size * 4) as usize);
     let center = size as f32 / 2.0;
     for y in 0..size {
         for x in 0..size {
-            let fx = x as f32;
-            let fy = y as f32;
-            let dx = fx - center;
-            let dy = fy - center;
+            let dx = x as f32 + 0.5 - center;
+            let dy = y as f32 + 0.5 - center;
             let dist = (dx * dx + dy * dy).sqrt();
             let max_dist = center * 0.9;
             let normalized_dist = (dist / max_dist).min(1.0);
@@ -649,17 +554,25 @@ pub fn create_smoke_texture(device: &Device, queue: &Queue) -> WgpuTexture {
             pixels.push((alpha.min(1.0) * 255.0) as u8);
         }
     }
+    if alpha_mode == ParticleAlphaMode::Premultiplied {
+        premultiply_srgba8(&mut pixels);
+    }
+    pixels
+}
+
+fn upload_smoke_texture(device: &Device, queue: &Queue, label: &str, pixels: &[u8], width: u32, height: u32) -> WgpuTexture {
+    let size = Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+    };
     let texture = device.create_texture(&TextureDescriptor {
-        label: Some("Smoke Texture Fallback"),
-        size: Extent3d {
-            width: size,
-            height: size,
-            depth_or_array_layers: 1,
-        },
+        label: Some(label),
+        size,
         mip_level_count: 1,
         sample_count: 1,
         dimension: TextureDimension::D2,
-        format: TextureFormat::Rgba8Unorm,
+        format: TextureFormat::Rgba8UnormSrgb,
         usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
         view_formats: &[],
     });
@@ -670,17 +583,13 @@ pub fn create_smoke_texture(device: &Device, queue: &Queue) -> WgpuTexture {
             origin: Origin3d::ZERO,
             aspect: TextureAspect::All,
         },
-        &pixels,
+        pixels,
         ImageDataLayout {
             offset: 0,
-            bytes_per_row: Some(4 * size),
-            rows_per_image: Some(size),
-        },
-        Extent3d {
-            width: size,
-            height: size,
-            depth_or_array_layers: 1,
+            bytes_per_row: Some(4 * width),
+            rows_per_image: Some(height),
         },
+        size,
     );
     let view = texture.create_view(&TextureViewDescriptor::default());
     let sampler = device.create_sampler(&SamplerDescriptor {
@@ -699,71 +608,86 @@ pub fn create_smoke_texture(device: &Device, queue: &Queue) -> WgpuTexture {
     }
 }
 
-pub fn create_flame_texture(device: &Device, queue: &Queue) -> WgpuTexture {
-    let candidates = vec![
-        "q3-resources/models/ammo/rocket/rockflar.png",
-        "q3-resources/models/ammo/rocket/rockfls1.png",
-        "q3-resources/models/ammo/rocket/rockfls2.png",
-        "../q3-resources/models/ammo/rocket/rockflar.png",
-        "../q3-resources/models/ammo/rocket/rockfls1.png",
-        "../q3-resources/models/ammo/rocket/rockfls2.png",
+pub fn create_smoke_texture(device: &Device, queue: &Queue, alpha_mode: ParticleAlphaMode) -> WgpuTexture {
+    let candidates = [
+        "gfx/misc/smokepuff2b.png",
+        "gfx/misc/smokepuff3.png",
     ];
 
-    for path in candidates {
-        if std::path::Path::new(path).exists() {
-            if let Ok(data) = std::fs::read(path) {
-                if let Ok(img) = image::load_from_memory(&data) {
-                    let img = img.to_rgba8();
-                    let size = Extent3d {
-                        width: img.width(),
-                        height: img.height(),
-                        depth_or_array_layers: 1,
-                    };
-                    let texture = device.create_texture(&TextureDescriptor {
-                        label: Some("Flame Texture"),
-                        size,
-                        mip_level_count: 1,
-                        sample_count: 1,
-                        dimension: TextureDimension::D2,
-                        format: TextureFormat::Rgba8UnormSrgb,
-                        usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
-                        view_formats: &[],
-                    });
-
-                    queue.write_texture(
-                        ImageCopyTexture {
-                            texture: &texture,
-                            mip_level: 0,
-                            origin: Origin3d::ZERO,
-                            aspect: TextureAspect::All,
-                        },
-                        &img,
-                        ImageDataLayout {
-                            offset: 0,
-                            bytes_per_row: Some(4 * img.width()),
-                            rows_per_image: Some(img.height()),
-                        },
-                        size,
-                    );
-
-                    let view = texture.create_view(&TextureViewDescriptor::default());
-                    let sampler = device.create_sampler(&SamplerDescriptor {
-                        address_mode_u: AddressMode::ClampToEdge,
-                        address_mode_v: AddressMode::ClampToEdge,
-                        address_mode_w: AddressMode::ClampToEdge,
-                        mag_filter: FilterMode::Linear,
-                        min_filter: FilterMode::Linear,
-                        mipmap_filter: FilterMode::Linear,
-                        ..Default::default()
-                    });
-
-                    return WgpuTexture {
-                        texture,
-                        view,
-                        sampler,
-                    };
-                }
+    for path in candidates.iter().filter_map(|path| resolve(path, &[])) {
+        if let Ok(img) = load_rgba(&path) {
+            let (width, height) = (img.width(), img.height());
+            let mut pixels = img.into_raw();
+            alpha_from_luminance_if_opaque(&mut pixels);
+            if alpha_mode == ParticleAlphaMode::Premultiplied {
+                premultiply_srgba8(&mut pixels);
             }
+            return upload_smoke_texture(device, queue, "Smoke Texture", &pixels, width, height);
+        }
+    }
+
+    let size = 64u32;
+    let pixels = smoke_puff_pixels(size, alpha_mode);
+    upload_smoke_texture(device, queue, "Smoke Texture Fallback", &pixels, size, size)
+}
+
+pub fn create_flame_texture(device: &Device, queue: &Queue) -> WgpuTexture {
+    let candidates = [
+        "models/ammo/rocket/rockflar.png",
+        "models/ammo/rocket/rockfls1.png",
+        "models/ammo/rocket/rockfls2.png",
+    ];
+
+    for path in candidates.iter().filter_map(|path| resolve(path, &[])) {
+        if let Ok(img) = load_rgba(&path) {
+            let size = Extent3d {
+                width: img.width(),
+                height: img.height(),
+                depth_or_array_layers: 1,
+            };
+            let texture = device.create_texture(&TextureDescriptor {
+                label: Some("Flame Texture"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::Rgba8UnormSrgb,
+                usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+                view_formats: &[],
+            });
+
+            queue.write_texture(
+                ImageCopyTexture {
+                    texture: &texture,
+                    mip_level: 0,
+                    origin: Origin3d::ZERO,
+                    aspect: TextureAspect::All,
+                },
+                &img,
+                ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * img.width()),
+                    rows_per_image: Some(img.height()),
+                },
+                size,
+            );
+
+            let view = texture.create_view(&TextureViewDescriptor::default());
+            let sampler = device.create_sampler(&SamplerDescriptor {
+                address_mode_u: AddressMode::ClampToEdge,
+                address_mode_v: AddressMode::ClampToEdge,
+                address_mode_w: AddressMode::ClampToEdge,
+                mag_filter: FilterMode::Linear,
+                min_filter: FilterMode::Linear,
+                mipmap_filter: FilterMode::Linear,
+                ..Default::default()
+            });
+
+            return WgpuTexture {
+                texture,
+                view,
+                sampler,
+            };
         }
     }
 
@@ -836,3 +760,173 @@ pub fn create_flame_texture(device: &Device, queue: &Queue) -> WgpuTexture {
     }
 }
 
+
+// Q3 `env/` skies name faces by direction; this is the +X, -X, +Y, -Y, +Z, -Z cube layer order
+pub const SKYBOX_FACE_SUFFIXES: [&str; 6] = ["rt", "lf", "up", "dn", "bk", "ft"];
+
+pub fn skybox_face_paths(name: &str) -> [String; 6] {
+    SKYBOX_FACE_SUFFIXES.map(|suffix| format!("env/{}_{}.tga", name, suffix))
+}
+
+const SKY_ZENITH: [f32; 3] = [12.0, 14.0, 30.0];
+const SKY_HORIZON: [f32; 3] = [70.0, 62.0, 80.0];
+const SKY_GROUND: [f32; 3] = [14.0, 12.0, 16.0];
+
+// Vertical gradient for one cube face, used when its image is missing
+fn skybox_gradient_face(face: usize, size: u32) -> Vec<u8> {
+    let mut pixels = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let s = 2.0 * (x as f32 + 0.5) / size as f32 - 1.0;
+            let t = 2.0 * (y as f32 + 0.5) / size as f32 - 1.0;
+            let len = (1.0 + s * s + t * t).sqrt();
+            let elevation = match face {
+                2 => 1.0 / len,
+                3 => -1.0 / len,
+                _ => -t / len,
+            };
+            let (from, to, k) = if elevation >= 0.0 {
+                (SKY_HORIZON, SKY_ZENITH, elevation.sqrt())
+            } else {
+                (SKY_HORIZON, SKY_GROUND, (-elevation).sqrt())
+            };
+            for c in 0..3 {
+                pixels.push((from[c] + (to[c] - from[c]) * k).round() as u8);
+            }
+            pixels.push(255);
+        }
+    }
+    pixels
+}
+
+pub fn create_skybox_texture(device: &Device, queue: &Queue, face_paths: [&str; 6]) -> WgpuTexture {
+    let images: Vec<Option<image::RgbaImage>> = face_paths
+        .iter()
+        .map(|path| {
+            if path.is_empty() {
+                return None;
+            }
+            let full_path = crate::resource_path::find_q3_resource(path)?;
+            load_rgba(full_path).ok()
+        })
+        .collect();
+
+    let face_size = images.iter().flatten().map(|img| img.width().max(img.height())).max().unwrap_or(64);
+    let missing = images.iter().filter(|img| img.is_none()).count();
+    if missing > 0 {
+        println!("Skybox: {} of 6 faces missing, using gradient", missing);
+    }
+
+    let size = Extent3d {
+        width: face_size,
+        height: face_size,
+        depth_or_array_layers: 6,
+    };
+    let texture = device.create_texture(&TextureDescriptor {
+        label: Some("Skybox Texture"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: TextureFormat::Rgba8UnormSrgb,
+        usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+
+    for (face, img) in images.into_iter().enumerate() {
+        let pixels = match img {
+            Some(img) if img.width() == face_size && img.height() == face_size => img.into_raw(),
+            Some(img) => {
+                image::imageops::resize(&img, face_size, face_size, image::imageops::FilterType::Triangle).into_raw()
+            }
+            None => skybox_gradient_face(face, face_size),
+        };
+        queue.write_texture(
+            ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: Origin3d {
+                    x: 0,
+                    y: 0,
+                    z: face as u32,
+                },
+                aspect: TextureAspect::All,
+            },
+            &pixels,
+            ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * face_size),
+                rows_per_image: Some(face_size),
+            },
+            Extent3d {
+                width: face_size,
+                height: face_size,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    let view = texture.create_view(&TextureViewDescriptor {
+        label: Some("Skybox View"),
+        dimension: Some(TextureViewDimension::Cube),
+        ..Default::default()
+    });
+    let sampler = device.create_sampler(&SamplerDescriptor {
+        address_mode_u: AddressMode::ClampToEdge,
+        address_mode_v: AddressMode::ClampToEdge,
+        address_mode_w: AddressMode::ClampToEdge,
+        mag_filter: FilterMode::Linear,
+        min_filter: FilterMode::Linear,
+        mipmap_filter: FilterMode::Linear,
+        ..Default::default()
+    });
+
+    WgpuTexture {
+        texture,
+        view,
+        sampler,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUFF_SIZE: u32 = 64;
+
+    fn alpha_at(pixels: &[u8], x: u32, y: u32) -> u8 {
+        pixels[((y * PUFF_SIZE + x) * 4 + 3) as usize]
+    }
+
+    #[test]
+    fn smoke_puff_alpha_falls_off_from_the_center() {
+        let pixels = smoke_puff_pixels(PUFF_SIZE, ParticleAlphaMode::Straight);
+        let center = PUFF_SIZE / 2;
+        assert_eq!(alpha_at(&pixels, center, center), 255);
+        assert_eq!(alpha_at(&pixels, 0, 0), 0);
+        assert_eq!(alpha_at(&pixels, 0, center), 0);
+
+        let row: Vec<u8> = (center..PUFF_SIZE).map(|x| alpha_at(&pixels, x, center)).collect();
+        assert!(row.windows(2).all(|pair| pair[0] >= pair[1]));
+    }
+
+    #[test]
+    fn premultiplied_puff_composites_like_straight() {
+        let straight = smoke_puff_pixels(PUFF_SIZE, ParticleAlphaMode::Straight);
+        let premultiplied = smoke_puff_pixels(PUFF_SIZE, ParticleAlphaMode::Premultiplied);
+        let background = 0.2;
+        for (s, p) in straight.chunks_exact(4).zip(premultiplied.chunks_exact(4)) {
+            assert_eq!(s[3], p[3]);
+            let alpha = s[3] as f32 / 255.0;
+            // src * a + dst * (1 - a) against src' + dst * (1 - a), in linear space
+            let over_straight = srgb_to_linear(s[0] as f32 / 255.0) * alpha + background * (1.0 - alpha);
+            let over_premultiplied = srgb_to_linear(p[0] as f32 / 255.0) + background * (1.0 - alpha);
+            assert!((over_straight - over_premultiplied).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn straight_alpha_is_the_default() {
+        assert_eq!(ParticleAlphaMode::default(), ParticleAlphaMode::Straight);
+    }
+}
@@ -1,7 +1,8 @@
 use wgpu::*;
 use crate::render::types::WgpuTexture;
+use crate::render::textures::create_model_sampler;
 
-pub fn create_tile_texture(device: &Device, queue: &Queue) -> WgpuTexture {
+pub fn create_tile_texture(device: &Device, queue: &Queue, anisotropy: u16) -> WgpuTexture {
     let width = 64;
     let height = 64;
     let mut pixels = vec![0u8; (width * height * 4) as usize];
@@ -61,15 +62,7 @@ pub fn create_tile_texture(device: &Device, queue: &Queue) -> WgpuTexture {
     );
     
     let view = texture.create_view(&TextureViewDescriptor::default());
-    let sampler = device.create_sampler(&SamplerDescriptor {
-        address_mode_u: AddressMode::Repeat,
-        address_mode_v: AddressMode::Repeat,
-        address_mode_w: AddressMode::Repeat,
-        mag_filter: FilterMode::Linear,
-        min_filter: FilterMode::Linear,
-        mipmap_filter: FilterMode::Linear,
-        ..Default::default()
-    });
+    let sampler = create_model_sampler(device, anisotropy);
     
     WgpuTexture {
         texture,
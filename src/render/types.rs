@@ -1,6 +1,8 @@
 use std::sync::Arc;
 use wgpu::*;
 use bytemuck::{Pod, Zeroable};
+use glam::{Mat4, Vec3};
+use crate::engine::md3::{FrameBlend, MD3Model, PoseBlend};
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
@@ -62,7 +64,210 @@ pub struct MD3Uniforms {
     pub lights: [LightData; MAX_LIGHTS],
     pub num_lights: i32,
     pub ambient_light: f32,
-    pub _padding: [f32; 2],
+    pub render_mode: u32,
+    pub reflectivity: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct WireOverlayUniforms {
+    pub view_proj: [[f32; 4]; 4],
+    pub model: [[f32; 4]; 4],
+    pub color: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct SkyboxUniforms {
+    pub inv_view_proj: [[f32; 4]; 4],
+    pub camera_pos: [f32; 4],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderMode {
+    #[default]
+    Lit,
+    FaceWinding,
+    // Texture times vertex color with no lighting, to tell lighting problems from texture ones
+    Fullbright,
+}
+
+impl RenderMode {
+    pub fn shader_flag(self) -> u32 {
+        match self {
+            RenderMode::Lit => 0,
+            RenderMode::FaceWinding => 1,
+            RenderMode::Fullbright => 2,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            RenderMode::Lit => RenderMode::FaceWinding,
+            RenderMode::FaceWinding => RenderMode::Fullbright,
+            RenderMode::Fullbright => RenderMode::Lit,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParticleAlphaMode {
+    // Matches how smoke has always been blended; premultiplied is opt-in
+    #[default]
+    Straight,
+    Premultiplied,
+}
+
+impl ParticleAlphaMode {
+    pub fn blend_state(self) -> BlendState {
+        let src_factor = match self {
+            ParticleAlphaMode::Straight => BlendFactor::SrcAlpha,
+            ParticleAlphaMode::Premultiplied => BlendFactor::One,
+        };
+        BlendState {
+            color: BlendComponent {
+                src_factor,
+                dst_factor: BlendFactor::OneMinusSrcAlpha,
+                operation: BlendOperation::Add,
+            },
+            alpha: BlendComponent {
+                src_factor: BlendFactor::One,
+                dst_factor: BlendFactor::OneMinusSrcAlpha,
+                operation: BlendOperation::Add,
+            },
+        }
+    }
+
+    pub fn fragment_entry_point(self) -> &'static str {
+        match self {
+            ParticleAlphaMode::Straight => "fs_main",
+            ParticleAlphaMode::Premultiplied => "fs_main_premultiplied",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CutoutMode {
+    #[default]
+    AlphaTest,
+    AlphaToCoverage,
+}
+
+impl CutoutMode {
+    pub fn resolve(self, sample_count: u32) -> Self {
+        if sample_count > 1 {
+            self
+        } else {
+            CutoutMode::AlphaTest
+        }
+    }
+
+    pub fn fragment_entry_point(self) -> &'static str {
+        match self {
+            CutoutMode::AlphaTest => "fs_cutout",
+            CutoutMode::AlphaToCoverage => "fs_coverage",
+        }
+    }
+}
+
+pub const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth24PlusStencil8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShadowTechnique {
+    StencilVolumes,
+    Planar,
+    Blob,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShadowSupport {
+    pub depth_format: TextureFormat,
+    pub depth_attachment: bool,
+    pub stencil_volumes: bool,
+    pub planar: bool,
+}
+
+impl ShadowSupport {
+    pub fn from_format_features(depth_format: TextureFormat, features: &TextureFormatFeatures) -> Self {
+        let depth_attachment = features.allowed_usages.contains(TextureUsages::RENDER_ATTACHMENT);
+        Self {
+            depth_format,
+            depth_attachment,
+            stencil_volumes: depth_attachment && depth_format.has_stencil_aspect(),
+            planar: depth_attachment && depth_format.has_depth_aspect(),
+        }
+    }
+
+    pub fn best_technique(&self) -> ShadowTechnique {
+        if self.stencil_volumes {
+            ShadowTechnique::StencilVolumes
+        } else if self.planar {
+            ShadowTechnique::Planar
+        } else {
+            ShadowTechnique::Blob
+        }
+    }
+}
+
+impl Default for ShadowSupport {
+    fn default() -> Self {
+        Self {
+            depth_format: DEPTH_FORMAT,
+            depth_attachment: true,
+            stencil_volumes: true,
+            planar: true,
+        }
+    }
+}
+
+// Color and depth views a pass draws into, and the color format its pipelines are built for
+#[derive(Clone, Copy)]
+pub struct RenderTarget<'a> {
+    pub color: &'a TextureView,
+    pub depth: &'a TextureView,
+    pub format: TextureFormat,
+}
+
+// Camera and lighting shared by every model drawn in a pass
+#[derive(Clone, Copy)]
+pub struct SceneView<'a> {
+    pub view_proj: Mat4,
+    pub camera_pos: Vec3,
+    pub lights: &'a [(Vec3, Vec3, f32)],
+    pub ambient_light: f32,
+}
+
+// One model instance for MD3Renderer::render_models
+#[derive(Clone, Copy)]
+pub struct ModelDrawCall<'a> {
+    pub model: &'a MD3Model,
+    pub pose: PoseBlend,
+    pub textures: &'a [Option<String>],
+    pub matrix: Mat4,
+}
+
+impl<'a> ModelDrawCall<'a> {
+    // Fractional `frame` blends toward the next one, as in render_model
+    pub fn at_frame(model: &'a MD3Model, frame: f32, textures: &'a [Option<String>], matrix: Mat4) -> Self {
+        Self {
+            model,
+            pose: FrameBlend::at(frame, model.num_frames()).into(),
+            textures,
+            matrix,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SceneStats {
+    pub visible_triangles: u32,
+    pub meshes: u32,
+    pub culled_models: u32,
+    pub shadow_volume_triangles: u32,
+    pub particles: u32,
+    pub wire_overlay_draws: u32,
+    // Uniform buffers the arena has allocated so far; flat once the per-frame working set is reached
+    pub uniform_buffers: u32,
 }
 
 pub struct WgpuTexture {
@@ -71,6 +276,13 @@ pub struct WgpuTexture {
     pub sampler: Sampler,
 }
 
+// A dynamic-offset slot in a packed uniform buffer
+#[derive(Clone)]
+pub struct UniformSlot {
+    pub buffer: Arc<Buffer>,
+    pub offset: u32,
+}
+
 pub struct MeshRenderData {
     pub vertex_buffer: Arc<Buffer>,
     pub index_buffer: Arc<Buffer>,
@@ -80,7 +292,33 @@ pub struct MeshRenderData {
     pub uniform_buffer: Arc<Buffer>,
     pub shadow_uniform_buffer: Option<Arc<Buffer>>,
     pub is_additive: bool,
+    pub cutout: Option<CutoutMode>,
+    pub mesh_idx: usize,
+    pub uniform_offset: u32,
+    pub shadow_uniform_offset: u32,
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
 
+    fn features(allowed_usages: TextureUsages) -> TextureFormatFeatures {
+        TextureFormatFeatures { allowed_usages, flags: TextureFormatFeatureFlags::empty() }
+    }
 
+    #[test]
+    fn shadow_support_follows_the_depth_format() {
+        let renderable = features(TextureUsages::RENDER_ATTACHMENT);
+        let support = ShadowSupport::from_format_features(DEPTH_FORMAT, &renderable);
+        assert_eq!(support.depth_format, DEPTH_FORMAT);
+        assert_eq!(support.stencil_volumes, DEPTH_FORMAT.has_stencil_aspect());
+        assert_eq!(support.best_technique(), ShadowTechnique::StencilVolumes);
+
+        let depth_only = ShadowSupport::from_format_features(TextureFormat::Depth32Float, &renderable);
+        assert!(!depth_only.stencil_volumes);
+        assert_eq!(depth_only.best_technique(), ShadowTechnique::Planar);
+
+        let unusable = ShadowSupport::from_format_features(DEPTH_FORMAT, &features(TextureUsages::TEXTURE_BINDING));
+        assert_eq!(unusable.best_technique(), ShadowTechnique::Blob);
+    }
+}
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 use wgpu::*;
 use winit::window::Window;
+use super::types::{ShadowSupport, DEPTH_FORMAT};
 
 pub struct WgpuRenderer {
     pub device: Arc<Device>,
@@ -8,18 +9,21 @@ pub struct WgpuRenderer {
     pub surface: Surface<'static>,
     pub surface_config: SurfaceConfiguration,
     pub size: winit::dpi::PhysicalSize<u32>,
-    logical_size: winit::dpi::PhysicalSize<u32>,
-    pixel_ratio: f64,
+    viewport: ViewportSize,
+    adapter_info: AdapterInfo,
+    shadow_support: ShadowSupport,
+    sample_count: u32,
+    supported_sample_counts: Vec<u32>,
+    anisotropy: u16,
+    max_anisotropy: u16,
+    msaa_texture: Option<Texture>,
+    capture_supported: bool,
 }
 
 impl WgpuRenderer {
-    pub async fn new(window: Arc<Window>) -> Result<Self, String> {
-        let pixel_ratio = 1.0;
-        let logical_size = window.inner_size();
-        let size = winit::dpi::PhysicalSize::new(
-            (logical_size.width as f64 * pixel_ratio) as u32,
-            (logical_size.height as f64 * pixel_ratio) as u32,
-        );
+    pub async fn new(window: Arc<Window>, sample_count: u32) -> Result<Self, String> {
+        let viewport = ViewportSize::new(window.inner_size(), window.scale_factor());
+        let size = viewport.surface_size();
         
         let instance = Instance::new(InstanceDescriptor {
             backends: Backends::all(),
@@ -29,37 +33,92 @@ impl WgpuRenderer {
         let surface = instance.create_surface(window.clone())
             .map_err(|e| format!("Failed to create surface: {:?}", e))?;
 
-        let adapter = instance
-            .request_adapter(&RequestAdapterOptions {
-                power_preference: PowerPreference::HighPerformance,
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-            .expect("Failed to find an appropriate adapter");
+        let adapter = request_adapter_with_fallback(&instance, Some(&surface)).await?;
+
+        let adapter_info = adapter.get_info();
+        println!(
+            "Using adapter: {} ({:?}, {:?})",
+            adapter_info.name, adapter_info.backend, adapter_info.device_type
+        );
+
+        let shadow_support = ShadowSupport::from_format_features(
+            DEPTH_FORMAT,
+            &adapter.get_texture_format_features(DEPTH_FORMAT),
+        );
+        println!("Shadow support: {:?} -> {:?}", shadow_support, shadow_support.best_technique());
+
+        let surface_caps = surface.get_capabilities(&adapter);
+        let surface_format = surface_caps
+            .formats
+            .iter()
+            .copied()
+            .find(|f| f.is_srgb())
+            .unwrap_or(surface_caps.formats[0]);
+
+        // 1x and 4x are always available; other counts need adapter-specific format features
+        let adapter_specific = adapter.features().contains(Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES);
+        let color_flags = adapter.get_texture_format_features(surface_format).flags;
+        let depth_flags = adapter.get_texture_format_features(DEPTH_FORMAT).flags;
+        let supports = |count: u32| {
+            count == 1
+                || ((count == 4 || adapter_specific)
+                    && color_flags.sample_count_supported(count)
+                    && depth_flags.sample_count_supported(count))
+        };
+        let mut chosen_samples = sample_count.clamp(1, 4).next_power_of_two();
+        while !supports(chosen_samples) {
+            chosen_samples /= 2;
+        }
+        if chosen_samples != sample_count {
+            println!("MSAA {}x not supported, falling back to {}x", sample_count, chosen_samples);
+        }
+        // Counts that work without recreating the device with different features
+        let mut supported_sample_counts = vec![1, chosen_samples];
+        if supports(4) {
+            supported_sample_counts.push(4);
+        }
+        supported_sample_counts.sort_unstable();
+        supported_sample_counts.dedup();
+        let required_features = if chosen_samples == 1 || chosen_samples == 4 {
+            Features::empty()
+        } else {
+            Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
+        };
+
+        // Without the downlevel flag wgpu silently ignores the clamp, so report 1x instead
+        let anisotropy = if adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(DownlevelFlags::ANISOTROPIC_FILTERING)
+        {
+            16
+        } else {
+            println!("Anisotropic filtering not supported, using 1x");
+            1
+        };
 
         let (device, queue) = adapter
             .request_device(
                 &DeviceDescriptor {
-                    required_features: Features::empty(),
-                    required_limits: Limits::default(),
+                    required_features,
+                    required_limits: required_limits(&adapter),
                     label: None,
                 },
                 None,
             )
             .await
-            .expect("Failed to create device");
+            .map_err(|e| format!("Failed to create device: {:?}", e))?;
 
-        let surface_caps = surface.get_capabilities(&adapter);
-        let surface_format = surface_caps
-            .formats
-            .iter()
-            .copied()
-            .find(|f| f.is_srgb())
-            .unwrap_or(surface_caps.formats[0]);
+        // Frame capture copies out of the swapchain texture, which not every surface allows
+        let capture_supported = surface_caps.usages.contains(TextureUsages::COPY_SRC);
+        let surface_usage = if capture_supported {
+            TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC
+        } else {
+            TextureUsages::RENDER_ATTACHMENT
+        };
 
         let surface_config = SurfaceConfiguration {
-            usage: TextureUsages::RENDER_ATTACHMENT,
+            usage: surface_usage,
             format: surface_format,
             width: size.width,
             height: size.height,
@@ -70,6 +129,7 @@ impl WgpuRenderer {
         };
 
         surface.configure(&device, &surface_config);
+        let msaa_texture = create_msaa_texture(&device, &surface_config, chosen_samples);
 
         Ok(Self {
             device: Arc::new(device),
@@ -77,35 +137,255 @@ impl WgpuRenderer {
             surface,
             surface_config,
             size,
-            logical_size,
-            pixel_ratio,
+            viewport,
+            adapter_info,
+            shadow_support,
+            sample_count: chosen_samples,
+            supported_sample_counts,
+            anisotropy,
+            max_anisotropy: anisotropy,
+            msaa_texture,
+            capture_supported,
         })
     }
 
+    // `new_size` is the window's physical size, as reported by WindowEvent::Resized
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
         if new_size.width > 0 && new_size.height > 0 {
-            self.logical_size = new_size;
-            let size = winit::dpi::PhysicalSize::new(
-                (new_size.width as f64 * self.pixel_ratio) as u32,
-                (new_size.height as f64 * self.pixel_ratio) as u32,
-            );
-            self.size = size;
-            self.surface_config.width = size.width;
-            self.surface_config.height = size.height;
-            self.surface.configure(&self.device, &self.surface_config);
+            self.viewport.resize(new_size);
+            self.configure_surface();
+        }
+    }
+
+    // From WindowEvent::ScaleFactorChanged. winit only sends a Resized afterwards when the physical
+    // size changes, so the surface is reconfigured here; callers must recreate depth textures
+    pub fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.viewport.set_scale_factor(scale_factor);
+        self.configure_surface();
+    }
+
+    pub fn scale_factor(&self) -> f64 {
+        self.viewport.scale_factor
+    }
+
+    pub fn render_scale(&self) -> f64 {
+        self.viewport.render_scale
+    }
+
+    pub fn pixel_ratio(&self) -> f64 {
+        self.viewport.pixel_ratio()
+    }
+
+    // Renders at a fraction (or multiple) of the display resolution; callers must recreate depth
+    // textures to match. Returns the scale actually used
+    pub fn set_render_scale(&mut self, render_scale: f64) -> f64 {
+        let clamped = render_scale.clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE);
+        if clamped != render_scale {
+            println!("Render scale {} out of range, using {}", render_scale, clamped);
         }
+        if clamped != self.viewport.render_scale {
+            self.viewport.render_scale = clamped;
+            self.configure_surface();
+        }
+        clamped
+    }
+
+    fn configure_surface(&mut self) {
+        let size = self.viewport.surface_size();
+        self.size = size;
+        self.surface_config.width = size.width;
+        self.surface_config.height = size.height;
+        self.surface.configure(&self.device, &self.surface_config);
+        self.msaa_texture = create_msaa_texture(&self.device, &self.surface_config, self.sample_count);
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    pub fn supported_sample_counts(&self) -> &[u32] {
+        &self.supported_sample_counts
+    }
+
+    // Falls back to the highest supported count below the request; callers must recreate
+    // depth textures and pipelines to match. Returns the count actually used
+    pub fn set_sample_count(&mut self, sample_count: u32) -> u32 {
+        let chosen = self
+            .supported_sample_counts
+            .iter()
+            .copied()
+            .filter(|&count| count <= sample_count)
+            .max()
+            .unwrap_or(1);
+        if chosen != sample_count {
+            println!("MSAA {}x not supported, falling back to {}x", sample_count, chosen);
+        }
+        if chosen != self.sample_count {
+            self.sample_count = chosen;
+            self.msaa_texture = create_msaa_texture(&self.device, &self.surface_config, chosen);
+        }
+        chosen
+    }
+
+    pub fn anisotropy(&self) -> u16 {
+        self.anisotropy
+    }
+
+    pub fn max_anisotropy(&self) -> u16 {
+        self.max_anisotropy
+    }
+
+    // Applies to samplers created afterwards; returns the level actually used
+    pub fn set_anisotropy(&mut self, anisotropy: u16) -> u16 {
+        let clamped = anisotropy.clamp(1, self.max_anisotropy);
+        if clamped != anisotropy {
+            println!("Anisotropy {}x not supported, using {}x", anisotropy, clamped);
+        }
+        self.anisotropy = clamped;
+        clamped
+    }
+
+    // The view scene passes should draw into: the multisampled target, or None to draw straight to the frame
+    pub fn create_msaa_view(&self) -> Option<TextureView> {
+        self.msaa_texture
+            .as_ref()
+            .map(|texture| texture.create_view(&TextureViewDescriptor::default()))
+    }
+
+    pub fn create_depth_texture(&self) -> Texture {
+        self.device.create_texture(&TextureDescriptor {
+            label: Some("Depth Texture"),
+            size: Extent3d {
+                width: self.surface_config.width,
+                height: self.surface_config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: self.sample_count,
+            dimension: TextureDimension::D2,
+            format: DEPTH_FORMAT,
+            usage: TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        })
+    }
+
+    pub fn resolve_msaa(&self, encoder: &mut CommandEncoder, frame_view: &TextureView) {
+        let msaa_view = match self.create_msaa_view() {
+            Some(view) => view,
+            None => return,
+        };
+        let _pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("MSAA Resolve Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: &msaa_view,
+                resolve_target: Some(frame_view),
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: StoreOp::Discard,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
     }
 
-    pub fn begin_frame(&mut self) -> Option<SurfaceTexture> {
-        self.surface.get_current_texture().ok()
+    // Ok(None) means skip this frame; Err is fatal and should be reported by the caller
+    pub fn begin_frame(&mut self) -> Result<Option<SurfaceTexture>, String> {
+        acquire_frame(
+            || self.surface.get_current_texture(),
+            || self.surface.configure(&self.device, &self.surface_config),
+        )
     }
 
     pub fn end_frame(&mut self, frame: SurfaceTexture) {
         frame.present();
     }
 
+    pub fn capture_supported(&self) -> bool {
+        self.capture_supported
+    }
+
+    // Reads back `texture`, usually the frame from begin_frame once everything has been submitted
+    // and before end_frame. Blocks until the copy finishes; alpha is forced opaque
+    pub fn capture_frame(&self, texture: &Texture) -> Result<image::RgbaImage, String> {
+        if !texture.usage().contains(TextureUsages::COPY_SRC) {
+            return Err("Frame capture not supported: texture can't be copied from".to_string());
+        }
+        let bgra = match texture.format() {
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => false,
+            TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => true,
+            format => return Err(format!("Frame capture not supported for {:?}", format)),
+        };
+        let (width, height) = (texture.width(), texture.height());
+        // Buffer rows are padded to COPY_BYTES_PER_ROW_ALIGNMENT and stripped again below
+        let row_bytes = width * 4;
+        let padded_row_bytes = row_bytes.div_ceil(COPY_BYTES_PER_ROW_ALIGNMENT) * COPY_BYTES_PER_ROW_ALIGNMENT;
+
+        let buffer = self.device.create_buffer(&BufferDescriptor {
+            label: Some("Frame Capture Buffer"),
+            size: padded_row_bytes as u64 * height as u64,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self.device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("Frame Capture Encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            ImageCopyBuffer {
+                buffer: &buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_bytes),
+                    rows_per_image: Some(height),
+                },
+            },
+            texture.size(),
+        );
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(Maintain::Wait);
+        receiver
+            .recv()
+            .map_err(|e| format!("Frame capture was never mapped: {}", e))?
+            .map_err(|e| format!("Failed to map frame capture buffer: {}", e))?;
+
+        let mut pixels = Vec::with_capacity((row_bytes * height) as usize);
+        for row in slice.get_mapped_range().chunks_exact(padded_row_bytes as usize) {
+            pixels.extend_from_slice(&row[..row_bytes as usize]);
+        }
+        buffer.unmap();
+        for px in pixels.chunks_exact_mut(4) {
+            if bgra {
+                px.swap(0, 2);
+            }
+            px[3] = 255;
+        }
+
+        image::RgbaImage::from_raw(width, height, pixels)
+            .ok_or_else(|| "Frame capture has the wrong size".to_string())
+    }
+
     pub fn get_viewport_size(&self) -> (u32, u32) {
-        (self.logical_size.width, self.logical_size.height)
+        self.viewport.viewport_size()
+    }
+
+    pub fn adapter_info(&self) -> &AdapterInfo {
+        &self.adapter_info
+    }
+
+    pub fn shadow_support(&self) -> ShadowSupport {
+        self.shadow_support
+    }
+
+    pub fn adapter_summary(&self) -> String {
+        adapter_summary(&self.adapter_info)
     }
 
     pub fn get_surface_size(&self) -> (u32, u32) {
@@ -113,3 +393,201 @@ impl WgpuRenderer {
     }
 }
 
+// Name, backend and device type, for titles and bug reports
+pub fn adapter_summary(info: &AdapterInfo) -> String {
+    format!("{} ({:?}, {:?})", info.name, info.backend, info.device_type)
+}
+
+// Discrete GPU first, then an integrated one, then whatever software adapter the platform has
+pub async fn request_adapter_with_fallback(
+    instance: &Instance,
+    compatible_surface: Option<&Surface<'_>>,
+) -> Result<Adapter, String> {
+    let attempts = [
+        (PowerPreference::HighPerformance, false),
+        (PowerPreference::LowPower, false),
+        (PowerPreference::LowPower, true),
+    ];
+    for (power_preference, force_fallback_adapter) in attempts {
+        let adapter = instance
+            .request_adapter(&RequestAdapterOptions {
+                power_preference,
+                compatible_surface,
+                force_fallback_adapter,
+            })
+            .await;
+        if let Some(adapter) = adapter {
+            return Ok(adapter);
+        }
+        println!(
+            "No adapter for {:?}{}, trying fallback",
+            power_preference,
+            if force_fallback_adapter { " (software)" } else { "" }
+        );
+    }
+    Err("Failed to find an appropriate adapter".to_string())
+}
+
+// Downlevel limits so GLES and WebGL adapters can create the device, with texture sizes raised
+// to whatever the adapter supports
+pub fn required_limits(adapter: &Adapter) -> Limits {
+    let base = if adapter.get_info().backend == Backend::Gl {
+        Limits::downlevel_webgl2_defaults()
+    } else {
+        Limits::downlevel_defaults()
+    };
+    base.using_resolution(adapter.limits())
+}
+
+const MIN_RENDER_SCALE: f64 = 0.25;
+const MAX_RENDER_SCALE: f64 = 2.0;
+
+// The window's logical size and the scales that turn it into surface pixels
+struct ViewportSize {
+    logical: winit::dpi::LogicalSize<u32>,
+    scale_factor: f64,
+    render_scale: f64,
+}
+
+impl ViewportSize {
+    fn new(physical: winit::dpi::PhysicalSize<u32>, scale_factor: f64) -> Self {
+        Self {
+            logical: physical.to_logical(scale_factor),
+            scale_factor,
+            render_scale: 1.0,
+        }
+    }
+
+    fn resize(&mut self, physical: winit::dpi::PhysicalSize<u32>) {
+        self.logical = physical.to_logical(self.scale_factor);
+    }
+
+    // The window keeps its physical size until a Resized says otherwise, so the logical size moves
+    fn set_scale_factor(&mut self, scale_factor: f64) {
+        let physical: winit::dpi::PhysicalSize<u32> = self.logical.to_physical(self.scale_factor);
+        self.scale_factor = scale_factor;
+        self.resize(physical);
+    }
+
+    // Surface pixels per logical pixel: the display scale times the render scale
+    fn pixel_ratio(&self) -> f64 {
+        self.scale_factor * self.render_scale
+    }
+
+    fn viewport_size(&self) -> (u32, u32) {
+        (self.logical.width, self.logical.height)
+    }
+
+    fn surface_size(&self) -> winit::dpi::PhysicalSize<u32> {
+        let pixel_ratio = self.pixel_ratio();
+        winit::dpi::PhysicalSize::new(
+            ((self.logical.width as f64 * pixel_ratio).round() as u32).max(1),
+            ((self.logical.height as f64 * pixel_ratio).round() as u32).max(1),
+        )
+    }
+}
+
+fn create_msaa_texture(device: &Device, config: &SurfaceConfiguration, sample_count: u32) -> Option<Texture> {
+    if sample_count <= 1 {
+        return None;
+    }
+    Some(device.create_texture(&TextureDescriptor {
+        label: Some("MSAA Color Texture"),
+        size: Extent3d {
+            width: config.width.max(1),
+            height: config.height.max(1),
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count,
+        dimension: TextureDimension::D2,
+        format: config.format,
+        usage: TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    }))
+}
+
+// Lost or Outdated surfaces (after suspend/resume or a display change) are reconfigured and
+// retried once; a Timeout just skips the frame
+fn acquire_frame<T>(
+    mut acquire: impl FnMut() -> Result<T, SurfaceError>,
+    reconfigure: impl FnOnce(),
+) -> Result<Option<T>, String> {
+    match acquire() {
+        Ok(frame) => Ok(Some(frame)),
+        Err(SurfaceError::Lost) | Err(SurfaceError::Outdated) => {
+            reconfigure();
+            match acquire() {
+                Ok(frame) => Ok(Some(frame)),
+                Err(SurfaceError::OutOfMemory) => Err("Out of memory acquiring surface texture".to_string()),
+                Err(e) => {
+                    eprintln!("Surface still unavailable after reconfigure: {:?}", e);
+                    Ok(None)
+                }
+            }
+        }
+        Err(SurfaceError::Timeout) => Ok(None),
+        Err(SurfaceError::OutOfMemory) => Err("Out of memory acquiring surface texture".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winit::dpi::PhysicalSize;
+
+    #[test]
+    fn headless_adapter_reports_its_info() {
+        let instance = Instance::new(InstanceDescriptor::default());
+        // CI machines without any adapter, software included, have nothing to report
+        let Ok(adapter) = pollster::block_on(request_adapter_with_fallback(&instance, None)) else {
+            eprintln!("No adapter available, skipping");
+            return;
+        };
+        let info = adapter.get_info();
+        assert!(!info.name.is_empty());
+        assert_ne!(info.backend, Backend::Empty);
+        let summary = adapter_summary(&info);
+        assert!(summary.starts_with(&info.name));
+        assert!(summary.contains(&format!("{:?}", info.backend)));
+    }
+
+    #[test]
+    fn viewport_is_logical_while_the_surface_is_scaled() {
+        let mut viewport = ViewportSize::new(PhysicalSize::new(1600, 1200), 2.0);
+        assert_eq!(viewport.viewport_size(), (800, 600));
+        assert_eq!(viewport.surface_size(), PhysicalSize::new(1600, 1200));
+
+        viewport.render_scale = 0.5;
+        assert_eq!(viewport.viewport_size(), (800, 600));
+        assert_eq!(viewport.surface_size(), PhysicalSize::new(800, 600));
+    }
+
+    #[test]
+    fn scale_change_without_a_resize_keeps_the_physical_size() {
+        let mut viewport = ViewportSize::new(PhysicalSize::new(1600, 1200), 2.0);
+        viewport.set_scale_factor(1.0);
+        assert_eq!(viewport.viewport_size(), (1600, 1200));
+        assert_eq!(viewport.surface_size(), PhysicalSize::new(1600, 1200));
+    }
+
+    #[test]
+    fn lost_surface_is_reconfigured_and_retried_once() {
+        let mut results = vec![Ok(7), Err(SurfaceError::Lost)];
+        let mut reconfigured = 0;
+        let frame = acquire_frame(|| results.pop().unwrap(), || reconfigured += 1);
+        assert_eq!(frame, Ok(Some(7)));
+        assert_eq!(reconfigured, 1);
+
+        // A second failure skips the frame instead of looping
+        let mut results: Vec<Result<i32, _>> = vec![Err(SurfaceError::Outdated), Err(SurfaceError::Lost)];
+        let frame = acquire_frame(|| results.pop().unwrap(), || {});
+        assert_eq!(frame, Ok(None));
+        assert!(results.is_empty());
+
+        let mut reconfigured = false;
+        let frame = acquire_frame(|| Err::<i32, _>(SurfaceError::Timeout), || reconfigured = true);
+        assert_eq!(frame, Ok(None));
+        assert!(!reconfigured);
+    }
+}
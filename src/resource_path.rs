@@ -1,25 +1,107 @@
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
-pub fn find_resource(relative_path: &str) -> Option<PathBuf> {
-    let search_paths = [
-        "",
-        "../",
-        "../../",
-    ];
-
-    for base in &search_paths {
-        let full_path = Path::new(base).join(relative_path);
-        if full_path.exists() {
-            return Some(full_path);
+// Extra resources root, searched before the default locations
+pub const RESOURCES_ENV_VAR: &str = "Q3_RESOURCES";
+pub const IMAGE_EXTENSIONS: &[&str] = &["tga", "TGA", "png", "jpg"];
+const RESOURCES_DIR: &str = "q3-resources";
+
+static RESOURCE_PATHS: OnceLock<ResourcePaths> = OnceLock::new();
+
+// Ordered roots that q3-resources relative paths are looked up under
+#[derive(Clone, Debug)]
+pub struct ResourcePaths {
+    roots: Vec<PathBuf>,
+}
+
+impl Default for ResourcePaths {
+    fn default() -> Self {
+        let mut roots = Vec::new();
+        if let Some(dir) = std::env::var_os(RESOURCES_ENV_VAR).filter(|dir| !dir.is_empty()) {
+            roots.push(PathBuf::from(dir));
         }
+        roots.push(PathBuf::from(RESOURCES_DIR));
+        roots.push(Path::new("..").join(RESOURCES_DIR));
+        roots.push(Path::new("../..").join(RESOURCES_DIR));
+        Self { roots }
+    }
+}
+
+impl ResourcePaths {
+    pub fn new(roots: Vec<PathBuf>) -> Self {
+        Self { roots }
+    }
+
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    pub fn push_root(&mut self, root: impl Into<PathBuf>) {
+        self.roots.push(root.into());
+    }
+
+    // `relative` may start with `q3-resources/` or `../q3-resources/` as texture keys do. Each root
+    // is tried with the path as given, then with each of `exts` swapped in for its extension
+    pub fn resolve(&self, relative: &str, exts: &[&str]) -> Option<PathBuf> {
+        let relative = strip_resources_prefix(relative);
+        let candidates = extension_candidates(&relative, exts);
+        self.roots.iter().find_map(|root| {
+            candidates
+                .iter()
+                .map(|candidate| root.join(candidate))
+                .find(|path| path.exists())
+        })
+    }
+
+    // A directory such as a player model's under every root that has it, in search order
+    pub fn resolve_all(&self, relative: &str) -> Vec<PathBuf> {
+        let relative = strip_resources_prefix(relative);
+        self.roots
+            .iter()
+            .map(|root| root.join(&relative))
+            .filter(|path| path.exists())
+            .collect()
     }
+}
+
+// Built from the environment on first use
+pub fn resource_paths() -> &'static ResourcePaths {
+    RESOURCE_PATHS.get_or_init(ResourcePaths::default)
+}
+
+pub fn resolve(relative: &str, exts: &[&str]) -> Option<PathBuf> {
+    resource_paths().resolve(relative, exts)
+}
 
-    None
+// Texture keys and resolve() both go through this, so either spelling of a path matches
+pub fn strip_resources_prefix(path: &str) -> String {
+    let mut path = path.replace('\\', "/");
+    while let Some(rest) = path.strip_prefix("../").or_else(|| path.strip_prefix("./")) {
+        path = rest.to_string();
+    }
+    match path.strip_prefix(RESOURCES_DIR).and_then(|rest| rest.strip_prefix('/')) {
+        Some(rest) => rest.to_string(),
+        None => path,
+    }
+}
+
+pub fn extension_candidates(relative: &str, exts: &[&str]) -> Vec<PathBuf> {
+    let path = Path::new(relative);
+    let mut candidates = Vec::new();
+    if path.extension().is_some() || exts.is_empty() {
+        candidates.push(path.to_path_buf());
+    }
+    for ext in exts {
+        let candidate = path.with_extension(ext);
+        if !candidates.contains(&candidate) {
+            candidates.push(candidate);
+        }
+    }
+    candidates
 }
 
 pub fn find_q3_resource(relative_path: &str) -> Option<PathBuf> {
-    let q3_relative = format!("q3-resources/{}", relative_path);
-    find_resource(&q3_relative)
+    resolve(relative_path, &[])
 }
 
 pub fn find_model(model_name: &str, part: &str) -> Option<PathBuf> {
@@ -31,6 +113,3 @@ pub fn find_weapon_model(weapon_name: &str) -> Option<PathBuf> {
     let relative_path = format!("models/weapons2/{}/{}.md3", weapon_name, weapon_name);
     find_q3_resource(&relative_path)
 }
-
-
-
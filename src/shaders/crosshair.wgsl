@@ -9,9 +9,17 @@ struct VertexOutput {
 struct Uniforms {
     resolution: vec2<f32>,
     position: vec2<f32>,
+    color: vec4<f32>,
+    scale: f32,
+    textured: u32,
 }
 
 @group(0) @binding(0) var<uniform> uniforms: Uniforms;
+@group(0) @binding(1) var crosshair_texture: texture_2d<f32>;
+@group(0) @binding(2) var crosshair_sampler: sampler;
+
+// Q3 crosshair images are 32px across at scale 1
+const TEXTURE_SIZE: f32 = 32.0;
 
 @vertex
 fn vs_main(input: VertexInput) -> VertexOutput {
@@ -52,8 +60,18 @@ fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
     );
     
     let coord = frag_coord - uniforms.position;
+
+    let uv = coord / (TEXTURE_SIZE * uniforms.scale) + 0.5;
+    let texel = textureSampleLevel(crosshair_texture, crosshair_sampler, clamp(uv, vec2<f32>(0.0), vec2<f32>(1.0)), 0.0);
+    if (uniforms.textured != 0u) {
+        if (any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0))) {
+            return vec4<f32>(0.0);
+        }
+        return texel * uniforms.color;
+    }
     
-    let crosshair = get_crosshair(coord / 2.0);
+    let crosshair = get_crosshair(coord / (2.0 * uniforms.scale));
     
-    return crosshair;
+    // Tint the white fill and keep the dark outline
+    return vec4<f32>(crosshair.rgb * uniforms.color.rgb, crosshair.a * uniforms.color.a);
 }
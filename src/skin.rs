@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use crate::resource_path::find_q3_resource;
+
+pub const SKIN_PARTS: [&str; 3] = ["lower", "upper", "head"];
+
+// Mesh to texture assignments from a player model's `<part>_<skin>.skin` files, kept per part
+// since the same mesh name can appear in more than one of them
+#[derive(Clone, Debug, Default)]
+pub struct SkinSet {
+    pub name: String,
+    pub parts: HashMap<String, HashMap<String, String>>,
+    pub available: Vec<String>,
+}
+
+impl SkinSet {
+    pub fn load(model_dir: &Path, skin_name: &str) -> Self {
+        let mut parts = HashMap::new();
+        for part in SKIN_PARTS {
+            let mut candidates = vec![model_dir.join(format!("{}_{}.skin", part, skin_name))];
+            if skin_name == "default" {
+                candidates.push(model_dir.join(format!("{}.skin", part)));
+            }
+            if let Some(content) = candidates.iter().find_map(|path| std::fs::read_to_string(path).ok()) {
+                parts.insert(part.to_string(), parse_skin(&content));
+            }
+        }
+
+        Self {
+            name: skin_name.to_string(),
+            parts,
+            available: Self::available_skins(model_dir),
+        }
+    }
+
+    pub fn load_player(model_name: &str, skin_name: &str) -> Self {
+        match Self::player_dir(model_name) {
+            Some(dir) => Self::load(&dir, skin_name),
+            None => Self {
+                name: skin_name.to_string(),
+                ..Self::default()
+            },
+        }
+    }
+
+    pub fn player_dir(model_name: &str) -> Option<PathBuf> {
+        find_q3_resource(&format!("models/players/{}", model_name))
+    }
+
+    pub fn available_skins(model_dir: &Path) -> Vec<String> {
+        let mut skins: Vec<String> = Vec::new();
+        let entries = match std::fs::read_dir(model_dir) {
+            Ok(entries) => entries,
+            Err(_) => return skins,
+        };
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let skin = file_name.strip_suffix(".skin").and_then(|stem| {
+                SKIN_PARTS
+                    .iter()
+                    .find_map(|part| stem.strip_prefix(part).and_then(|rest| rest.strip_prefix('_')))
+            });
+            if let Some(skin) = skin {
+                if !skin.is_empty() && !skins.iter().any(|s| s == skin) {
+                    skins.push(skin.to_string());
+                }
+            }
+        }
+        skins.sort();
+        skins
+    }
+
+    pub fn texture(&self, part: &str, mesh_name: &str) -> Option<&String> {
+        self.parts.get(part)?.get(mesh_name)
+    }
+
+    pub fn has_part(&self, part: &str) -> bool {
+        self.parts.get(part).is_some_and(|textures| !textures.is_empty())
+    }
+}
+
+// `mesh,texture` lines; tag entries have an empty texture and are skipped
+pub fn parse_skin(content: &str) -> HashMap<String, String> {
+    let mut textures = HashMap::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+        let Some((mesh_name, texture_path)) = line.split_once(',') else {
+            continue;
+        };
+        let (mesh_name, texture_path) = (mesh_name.trim(), texture_path.trim());
+        if mesh_name.is_empty() || texture_path.is_empty() {
+            continue;
+        }
+        textures.insert(mesh_name.to_string(), resolve_texture_path(texture_path));
+    }
+    textures
+}
+
+pub fn resolve_texture_path(path: &str) -> String {
+    let path = path.trim_start_matches('/').replace('\\', "/");
+    if path.starts_with("q3-resources/") {
+        path
+    } else {
+        format!("q3-resources/{}", path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parts_keep_their_own_mesh_textures() {
+        let dir = std::env::temp_dir().join(format!("sas2_skin_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("lower_red.skin"), "l_legs,models/players/test/red_legs.tga\n").unwrap();
+        std::fs::write(dir.join("upper_red.skin"), "l_legs,models/players/test/red_torso.tga\ntag_head,\n").unwrap();
+        let skin_set = SkinSet::load(&dir, "red");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(skin_set.texture("lower", "l_legs").unwrap(), "q3-resources/models/players/test/red_legs.tga");
+        assert_eq!(skin_set.texture("upper", "l_legs").unwrap(), "q3-resources/models/players/test/red_torso.tga");
+        assert!(skin_set.texture("upper", "tag_head").is_none());
+        assert!(!skin_set.has_part("head"));
+        assert_eq!(skin_set.available, vec!["red".to_string()]);
+    }
+}